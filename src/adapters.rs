@@ -0,0 +1,239 @@
+use crate::diagnostic::Diagnostic;
+use crate::encoding::read_text;
+use crate::os::OS_KEYS;
+use crate::runner::CommandRunner;
+use crate::schema::suggest;
+use crate::sources::fetch_url;
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// Offline copy of the `configurationAttributes` of selected debug adapters (cpptools,
+/// CodeLLDB, debugpy, Go), laid out like an extension's package.json
+const BUILTIN: &str = include_str!("adapters.json");
+
+/// Keys VSCode accepts in every launch configuration, whatever its debugger type
+const COMMON_KEYS: [&str; 10] = [
+    "name",
+    "type",
+    "request",
+    "debugServer",
+    "preLaunchTask",
+    "postDebugTask",
+    "presentation",
+    "internalConsoleOptions",
+    "suppressMultipleSessionWarning",
+    "serverReadyAction",
+];
+
+/// Template keys mklaunch reads itself and never writes to launch.json
+const MKLAUNCH_KEYS: [&str; 3] = ["allowOverrides", "requireFromConfig", "rust"];
+
+/// Attributes debug adapters declare for their launch configurations, keyed by debugger
+/// type and request
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AdapterSchemas {
+    attributes: BTreeMap<(String, String), Map<String, Value>>,
+}
+
+impl AdapterSchemas {
+    /// The adapters mklaunch ships a copy of
+    pub fn builtin() -> Result<Self> {
+        let package = serde_json::from_str(BUILTIN).context("Invalid built-in adapter schemas")?;
+        Self::from_package(&package)
+    }
+
+    /// Reads an extension's package.json from a file, or downloads it with curl (run
+    /// through `runner`) when `source` is an https URL
+    pub fn read(
+        source: &str,
+        timeout: Option<Duration>,
+        runner: &dyn CommandRunner,
+    ) -> Result<Self> {
+        let text = if source.starts_with("https://") {
+            String::from_utf8(fetch_url(source, timeout, runner)?)
+                .with_context(|| format!("{} is not UTF-8 text", source))?
+        } else if source.contains("://") {
+            anyhow::bail!("Extension package {} must be downloaded over https", source);
+        } else {
+            read_text(Path::new(source))
+                .with_context(|| format!("Failed to read extension package {}", source))?
+        };
+        let package = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse extension package {}", source))?;
+        Self::from_package(&package).with_context(|| format!("In {}", source))
+    }
+
+    /// Collects `contributes.debuggers[].configurationAttributes` of a package.json
+    fn from_package(package: &Value) -> Result<Self> {
+        let debuggers = package
+            .pointer("/contributes/debuggers")
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow::anyhow!("No 'contributes.debuggers' array"))?;
+        let mut attributes = BTreeMap::new();
+        for debugger in debuggers {
+            let Some(debug_type) = debugger.get("type").and_then(Value::as_str) else {
+                continue;
+            };
+            let Some(requests) = debugger
+                .get("configurationAttributes")
+                .and_then(Value::as_object)
+            else {
+                continue;
+            };
+            for (request, schema) in requests {
+                let properties = schema
+                    .get("properties")
+                    .and_then(Value::as_object)
+                    .cloned()
+                    .unwrap_or_default();
+                attributes.insert((debug_type.to_string(), request.clone()), properties);
+            }
+        }
+        if attributes.is_empty() {
+            anyhow::bail!("No debugger declares configurationAttributes");
+        }
+        Ok(Self { attributes })
+    }
+
+    /// Adds the adapters of `other`, replacing every request of a debugger type both describe
+    pub fn extend(&mut self, other: Self) {
+        self.attributes
+            .retain(|(debug_type, _), _| !other.attributes.keys().any(|(t, _)| t == debug_type));
+        self.attributes.extend(other.attributes);
+    }
+
+    /// Unknown attributes (warnings) and values of the wrong type (errors) in the template
+    /// written at `pointer` in `path`, including its per-OS blocks. A template whose type and
+    /// request no adapter describes gets a single info saying it was not checked.
+    pub fn check_template(&self, path: &Path, pointer: &str, template: &Value) -> Vec<Diagnostic> {
+        let name = template.get("name").and_then(Value::as_str).unwrap_or("");
+        let debug_type = template.get("type").and_then(Value::as_str).unwrap_or("");
+        let request = template
+            .get("request")
+            .and_then(Value::as_str)
+            .unwrap_or("launch");
+        let Some(properties) = self
+            .attributes
+            .get(&(debug_type.to_string(), request.to_string()))
+        else {
+            let message = format!(
+                "Template '{}' was not checked: no schema is known for '{}' {} configurations; pass --package with the debug adapter's package.json",
+                name, debug_type, request
+            );
+            return vec![Diagnostic::at_pointer(path, pointer, message).info()];
+        };
+        let adapter = format!("{} {}", debug_type, request);
+
+        let Some(object) = template.as_object() else {
+            return Vec::new();
+        };
+        let blocks = OS_KEYS.iter().filter_map(|os| {
+            let block = object.get(*os)?.as_object()?;
+            Some((format!("{}/{}", pointer, os), block))
+        });
+        let mut diagnostics = Vec::new();
+        for (block_pointer, block) in std::iter::once((pointer.to_string(), object)).chain(blocks) {
+            for (key, value) in block {
+                if COMMON_KEYS.contains(&key.as_str())
+                    || MKLAUNCH_KEYS.contains(&key.as_str())
+                    || OS_KEYS.contains(&key.as_str())
+                {
+                    continue;
+                }
+                let key_pointer = format!("{}/{}", block_pointer, key);
+                match properties.get(key) {
+                    None => {
+                        let known: Vec<&str> = properties.keys().map(String::as_str).collect();
+                        let hint = match suggest(key, &known) {
+                            Some(known) => format!("; did you mean '{}'?", known),
+                            None => String::new(),
+                        };
+                        let message = format!(
+                            "Template '{}': '{}' is not a {} attribute{}",
+                            name, key, adapter, hint
+                        );
+                        diagnostics
+                            .push(Diagnostic::at_pointer(path, &key_pointer, message).warning());
+                    }
+                    Some(schema) => {
+                        if let Some(problem) = check_value(schema, value) {
+                            let message = format!(
+                                "Template '{}': {} attribute '{}' {}",
+                                name, adapter, key, problem
+                            );
+                            diagnostics.push(Diagnostic::at_pointer(path, &key_pointer, message));
+                        }
+                    }
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+/// Why `value` does not match the attribute `schema`, if it does not. A string made of one
+/// `${...}` variable matches any type, since VSCode substitutes it before launching.
+fn check_value(schema: &Value, value: &Value) -> Option<String> {
+    if value
+        .as_str()
+        .is_some_and(|s| s.starts_with("${") && s.ends_with('}'))
+    {
+        return None;
+    }
+    let types = expected_types(schema);
+    if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+        return Some(format!(
+            "must be {}, found {}",
+            types.join(" or "),
+            json_type(value)
+        ));
+    }
+    let allowed = schema.get("enum").and_then(Value::as_array)?;
+    if allowed.contains(value) {
+        return None;
+    }
+    let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+    Some(format!(
+        "must be one of {}, found {}",
+        allowed.join(", "),
+        value
+    ))
+}
+
+/// JSON types an attribute schema allows, from `type` or the branches of `anyOf`/`oneOf`;
+/// empty when it does not say
+fn expected_types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(single)) => vec![single.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => ["anyOf", "oneOf"]
+            .iter()
+            .filter_map(|key| schema.get(*key)?.as_array())
+            .flatten()
+            .flat_map(expected_types)
+            .collect(),
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" | "boolean" | "null" | "array" | "object" => json_type(value) == expected,
+        _ => true,
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
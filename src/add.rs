@@ -0,0 +1,372 @@
+use crate::atomic::write_atomic;
+use crate::diagnostic::span;
+use crate::encoding::read_text;
+use crate::generator::collect_config_files;
+use crate::order::{KeyLayout, Ordered};
+use crate::schema::{ConfigFile, Template, TemplateFile};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::{Map, Value, json};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Interactively creates a new config file in `configs_dir`, offering the templates of
+/// every manifest in `templates_paths`.
+/// Prompts are written to `output` and answers are read line by line from `input`.
+/// Returns the path of the written config file.
+pub fn add_config<R: BufRead, W: Write>(
+    templates_paths: &[PathBuf],
+    configs_dir: &Path,
+    input: &mut R,
+    output: &mut W,
+) -> Result<PathBuf> {
+    let templates = TemplateFile::from_paths(templates_paths)?;
+    let names: Vec<&str> = templates.names().collect();
+
+    let existing: Vec<String> = if configs_dir.exists() {
+        collect_config_files(configs_dir, false, &mut Vec::new())?
+            .into_iter()
+            .map(|(_, config)| config.name)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    writeln!(output, "Available templates:")?;
+    for (idx, name) in names.iter().enumerate() {
+        writeln!(output, "  {}) {}", idx + 1, name)?;
+    }
+
+    let extends = loop {
+        let answer = prompt(input, output, "Template [name or number]: ")?;
+        if let Ok(n) = answer.parse::<usize>()
+            && n >= 1
+            && n <= names.len()
+        {
+            break names[n - 1].to_string();
+        }
+        if names.contains(&answer.as_str()) {
+            break answer;
+        }
+        writeln!(output, "Unknown template '{}'", answer)?;
+    };
+
+    let name = loop {
+        let answer = prompt(input, output, "Configuration name: ")?;
+        if answer.is_empty() {
+            writeln!(output, "Configuration name must not be empty")?;
+        } else if existing.contains(&answer) {
+            writeln!(output, "Configuration name '{}' already exists", answer)?;
+        } else {
+            break answer;
+        }
+    };
+
+    let args_line = prompt(input, output, "Args (space-separated, empty for none): ")?;
+    let args: Vec<String> = args_line.split_whitespace().map(String::from).collect();
+
+    let config_path = configs_dir.join(format!("{}.json", file_stem_for(&name)));
+    if config_path.exists() {
+        anyhow::bail!("Config file already exists: {}", config_path.display());
+    }
+
+    let entry = ConfigFile {
+        name,
+        extends,
+        enabled: true,
+        args: Some(args),
+        ..Default::default()
+    };
+
+    fs::create_dir_all(configs_dir).with_context(|| {
+        format!(
+            "Failed to create configs directory: {}",
+            configs_dir.display()
+        )
+    })?;
+    ConfigFile::write_file(&config_path, &[entry])?;
+
+    Ok(config_path)
+}
+
+/// Debug adapter a template skeleton is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugAdapter {
+    /// C/C++ through the Microsoft C/C++ extension
+    Cppdbg,
+    /// CodeLLDB
+    Codelldb,
+    /// Python
+    Debugpy,
+    /// Node.js
+    Node,
+    /// Go through delve
+    Go,
+}
+
+impl DebugAdapter {
+    /// Template name offered by default
+    fn default_name(self) -> &'static str {
+        match self {
+            DebugAdapter::Cppdbg => "cpp",
+            DebugAdapter::Codelldb => "lldb",
+            DebugAdapter::Debugpy => "python",
+            DebugAdapter::Node => "node",
+            DebugAdapter::Go => "go",
+        }
+    }
+
+    fn default_program(self) -> &'static str {
+        match self {
+            DebugAdapter::Cppdbg | DebugAdapter::Codelldb => "${workspaceFolder}/build/app",
+            DebugAdapter::Debugpy => "${file}",
+            DebugAdapter::Node => "${workspaceFolder}/index.js",
+            DebugAdapter::Go => "${workspaceFolder}",
+        }
+    }
+
+    /// Template keys, in the order they are written, with the settings the adapter needs
+    /// in practice
+    fn skeleton(self, name: &str, program: &str) -> Vec<(&'static str, Value)> {
+        let mut keys = vec![
+            ("name", json!(name)),
+            ("type", json!(self.debug_type())),
+            ("request", json!("launch")),
+            ("program", json!(program)),
+        ];
+        match self {
+            DebugAdapter::Cppdbg => keys.extend([
+                ("cwd", json!("${workspaceFolder}")),
+                ("stopAtEntry", json!(false)),
+                ("MIMode", json!("gdb")),
+                (
+                    "setupCommands",
+                    json!([{
+                        "description": "Enable pretty-printing for gdb",
+                        "text": "-enable-pretty-printing",
+                        "ignoreFailures": true
+                    }]),
+                ),
+            ]),
+            DebugAdapter::Codelldb => keys.push(("cwd", json!("${workspaceFolder}"))),
+            DebugAdapter::Debugpy => keys.extend([
+                ("console", json!("integratedTerminal")),
+                ("justMyCode", json!(true)),
+            ]),
+            DebugAdapter::Node => keys.push(("skipFiles", json!(["<node_internals>/**"]))),
+            DebugAdapter::Go => keys.insert(3, ("mode", json!("auto"))),
+        }
+        keys
+    }
+
+    /// `type` of the adapter's launch configurations
+    fn debug_type(self) -> &'static str {
+        match self {
+            DebugAdapter::Cppdbg => "cppdbg",
+            DebugAdapter::Codelldb => "lldb",
+            DebugAdapter::Debugpy => "debugpy",
+            DebugAdapter::Node => "node",
+            DebugAdapter::Go => "go",
+        }
+    }
+}
+
+/// Interactively appends a template skeleton for `adapter` to the manifest at
+/// `templates_path`, creating the manifest if needed. The new template is inserted after
+/// the last one with the manifest's indentation, leaving the rest of the file untouched.
+/// Prompts are written to `output` and answers are read line by line from `input`.
+/// Returns the name of the new template.
+pub fn add_template<R: BufRead, W: Write>(
+    templates_path: &Path,
+    adapter: DebugAdapter,
+    input: &mut R,
+    output: &mut W,
+) -> Result<String> {
+    if templates_path.is_dir() {
+        anyhow::bail!(
+            "{} is a directory; pass the manifest to add the template to, e.g. --templates {}",
+            templates_path.display(),
+            templates_path.join("new.templates.json").display()
+        );
+    }
+    let existing = if templates_path.exists() {
+        Some(read_text(templates_path).with_context(|| {
+            format!(
+                "Failed to read templates manifest: {}",
+                templates_path.display()
+            )
+        })?)
+    } else {
+        None
+    };
+    let taken: Vec<String> = match &existing {
+        Some(_) => TemplateFile::from_path(templates_path)?
+            .names()
+            .map(str::to_string)
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let name = loop {
+        let answer = prompt(
+            input,
+            output,
+            &format!("Template name [{}]: ", adapter.default_name()),
+        )?;
+        let answer = if answer.is_empty() {
+            adapter.default_name().to_string()
+        } else {
+            answer
+        };
+        if taken.contains(&answer) {
+            writeln!(output, "Template '{}' already exists", answer)?;
+        } else {
+            break answer;
+        }
+    };
+    let program = prompt(
+        input,
+        output,
+        &format!("Program [{}]: ", adapter.default_program()),
+    )?;
+    let program = if program.is_empty() {
+        adapter.default_program()
+    } else {
+        program.as_str()
+    };
+
+    let keys = adapter.skeleton(&name, program);
+    let layout = KeyLayout {
+        keys: keys
+            .iter()
+            .map(|(key, _)| (key.to_string(), KeyLayout::default()))
+            .collect(),
+        items: Vec::new(),
+    };
+    let template: Map<String, Value> = keys
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+    let mut settings = template.clone();
+    settings.remove("name");
+    Template::from_value(Value::Object(settings))?;
+    let template = Value::Object(template);
+
+    let content = match existing {
+        Some(text) => insert_template(&text, &template, &layout)
+            .with_context(|| format!("Failed to update {}", templates_path.display()))?,
+        None => {
+            let manifest = json!({ "templates": [template] });
+            let layout = KeyLayout {
+                keys: vec![(
+                    "templates".to_string(),
+                    KeyLayout {
+                        keys: Vec::new(),
+                        items: vec![layout],
+                    },
+                )],
+                items: Vec::new(),
+            };
+            render(&manifest, &layout, "  ")? + "\n"
+        }
+    };
+    write_atomic(templates_path, content.as_bytes())?;
+    Ok(name)
+}
+
+/// `text` with `template` appended to its `templates` array, indented like the
+/// existing entries
+fn insert_template(text: &str, template: &Value, layout: &KeyLayout) -> Result<String> {
+    let (start, _) = span(text, "/templates")
+        .ok_or_else(|| anyhow::anyhow!("Templates manifest must contain a 'templates' array"))?;
+    // The `templates` key sits one level deep, so its indentation is the manifest's unit
+    let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    let unit = &text[line_start..start];
+    let unit = &unit[..unit.len() - unit.trim_start().len()];
+    let unit = if unit.is_empty() { "  " } else { unit };
+    let last = (0..)
+        .map_while(|idx| span(text, &format!("/templates/{}", idx)))
+        .last();
+    let (at, indent) = match last {
+        Some((item_start, item_end)) => (item_end, line_indent(text, item_start)),
+        None => (start + 1, ""),
+    };
+    let indent = if indent.is_empty() {
+        unit.repeat(2)
+    } else {
+        indent.to_string()
+    };
+
+    let rendered = render(template, layout, unit)?.replace('\n', &format!("\n{}", indent));
+
+    let separator = if last.is_some() { "," } else { "" };
+    let inserted = format!(
+        "{}{}\n{}{}{}",
+        &text[..at],
+        separator,
+        indent,
+        rendered,
+        &text[at..]
+    );
+    serde_json::from_str::<Value>(&inserted)
+        .context("Inserting the template produced invalid JSON")?;
+    Ok(inserted)
+}
+
+/// Pretty-prints `value` with keys in `layout` order, indenting by `unit`
+fn render(value: &Value, layout: &KeyLayout, unit: &str) -> Result<String> {
+    let mut rendered = Vec::new();
+    let formatter = PrettyFormatter::with_indent(unit.as_bytes());
+    Ordered {
+        value,
+        layout: Some(layout),
+    }
+    .serialize(&mut serde_json::Serializer::with_formatter(
+        &mut rendered,
+        formatter,
+    ))?;
+    Ok(String::from_utf8(rendered)?)
+}
+
+/// Leading whitespace of the line containing byte `offset`, if only whitespace precedes it
+fn line_indent(text: &str, offset: usize) -> &str {
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let before = &text[line_start..offset];
+    if before.chars().all(char::is_whitespace) {
+        before
+    } else {
+        ""
+    }
+}
+
+/// Writes `message` and reads one trimmed line; fails on end of input
+fn prompt<R: BufRead, W: Write>(input: &mut R, output: &mut W, message: &str) -> Result<String> {
+    write!(output, "{}", message)?;
+    output.flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        anyhow::bail!("Unexpected end of input");
+    }
+    Ok(line.trim().to_string())
+}
+
+/// Derives a file stem from a configuration name, e.g. "Debug (fast)" -> "debug-fast"
+pub(crate) fn file_stem_for(name: &str) -> String {
+    let mut stem = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            stem.push(c.to_ascii_lowercase());
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_end_matches('-');
+    if stem.is_empty() {
+        "config".to_string()
+    } else {
+        stem.to_string()
+    }
+}
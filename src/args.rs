@@ -0,0 +1,131 @@
+use crate::runner::{CommandRunner, CommandSpec};
+use anyhow::Context;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::path::Path;
+use std::time::Duration;
+
+/// Normalizes one `args` item into the strings passed to the debuggee.
+///
+/// - strings are kept as written
+/// - numbers and booleans become their JSON text (`3`, `true`)
+/// - `{ "flag": "--input", "value": "x" }` becomes `--input`, `x`; with
+///   `"separator": "="` it becomes the single argument `--input=x`. `value` may itself
+///   be a string, number or boolean and may be omitted for a bare flag.
+fn normalize_arg(item: &Value, out: &mut Vec<String>) -> Result<(), String> {
+    match item {
+        Value::String(s) => out.push(s.clone()),
+        Value::Number(_) | Value::Bool(_) => out.push(item.to_string()),
+        Value::Object(object) => {
+            let flag = match object.get("flag") {
+                Some(Value::String(flag)) => flag,
+                _ => return Err("arg objects need a string 'flag'".to_string()),
+            };
+            if let Some(key) = object
+                .keys()
+                .find(|k| !matches!(k.as_str(), "flag" | "value" | "separator"))
+            {
+                return Err(format!(
+                    "unknown key '{}' in arg object (expected flag, value, separator)",
+                    key
+                ));
+            }
+            let value = match object.get("value") {
+                None => None,
+                Some(Value::String(s)) => Some(s.clone()),
+                Some(v @ (Value::Number(_) | Value::Bool(_))) => Some(v.to_string()),
+                Some(_) => {
+                    return Err(format!(
+                        "value of '{}' must be a string, number or boolean",
+                        flag
+                    ));
+                }
+            };
+            let separator = match object.get("separator") {
+                None => None,
+                Some(Value::String(s)) => Some(s),
+                Some(_) => return Err("'separator' must be a string".to_string()),
+            };
+            match (value, separator) {
+                (Some(value), Some(separator)) => {
+                    out.push(format!("{}{}{}", flag, separator, value))
+                }
+                (Some(value), None) => out.extend([flag.clone(), value]),
+                (None, _) => out.push(flag.clone()),
+            }
+        }
+        Value::Null | Value::Array(_) => {
+            return Err(format!(
+                "unsupported arg {}; expected a string, number, boolean or {{\"flag\", \"value\"}} object",
+                item
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Normalizes a whole `args` array, naming the offending position on failure
+pub(crate) fn normalize_args(items: &[Value]) -> Result<Vec<String>, String> {
+    let mut out = Vec::with_capacity(items.len());
+    for (idx, item) in items.iter().enumerate() {
+        normalize_arg(item, &mut out).map_err(|e| format!("args[{}]: {}", idx, e))?;
+    }
+    Ok(out)
+}
+
+/// `deserialize_with` helper for required `args` arrays
+pub(crate) fn deserialize_args<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+    let items = Vec::<Value>::deserialize(d)?;
+    normalize_args(&items).map_err(serde::de::Error::custom)
+}
+
+/// `deserialize_with` helper for optional `args` arrays
+pub(crate) fn deserialize_optional_args<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<Option<Vec<String>>, D::Error> {
+    match Option::<Vec<Value>>::deserialize(d)? {
+        Some(items) => normalize_args(&items)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+/// Splits an `argsString` into words with POSIX shell quoting rules (via `shlex`): whitespace
+/// separates words, quotes keep them together and a backslash escapes the next character.
+/// Variables and globs are not expanded.
+pub(crate) fn split_command_line(line: &str) -> Result<Vec<String>, String> {
+    shlex::split(line).ok_or_else(|| "unterminated quote or trailing backslash".to_string())
+}
+
+/// Runs `command` through the platform shell in `cwd` and turns its stdout into args:
+/// a JSON array (normalized like `args`) or otherwise whitespace-separated words
+pub(crate) fn args_from_command(
+    command: &str,
+    cwd: &Path,
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> anyhow::Result<Vec<String>> {
+    let shell = if cfg!(windows) {
+        CommandSpec::new("cmd", ["/C", command])
+    } else {
+        CommandSpec::new("sh", ["-c", command])
+    };
+    let output = runner
+        .run(&shell.cwd(cwd).timeout(timeout))
+        .with_context(|| format!("argsFromCommand `{}` failed", command))?;
+    let stdout = String::from_utf8(output.stdout)
+        .with_context(|| format!("argsFromCommand `{}` printed invalid UTF-8", command))?;
+    let stdout = stdout.trim();
+    if stdout.starts_with('[') {
+        let items: Vec<Value> = serde_json::from_str(stdout).with_context(|| {
+            format!(
+                "argsFromCommand `{}` printed an invalid JSON array",
+                command
+            )
+        })?;
+        return normalize_args(&items)
+            .map_err(|e| anyhow::anyhow!("argsFromCommand `{}`: {}", command, e));
+    }
+    Ok(stdout.split_whitespace().map(str::to_string).collect())
+}
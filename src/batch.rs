@@ -0,0 +1,47 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory names never searched for workspaces, besides hidden ones
+const SKIPPED_DIRS: [&str; 2] = ["node_modules", "target"];
+
+/// Workspace folders under `dir` (including `dir` itself) that contain a `.mklaunch/`
+/// directory, sorted. Hidden directories, `node_modules` and `target` are not searched,
+/// and symlinked directories are not followed.
+pub fn find_workspaces(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        anyhow::bail!("Directory does not exist: {}", dir.display());
+    }
+
+    let mut workspaces = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        if dir.join(".mklaunch").is_dir() {
+            workspaces.push(dir.clone());
+        }
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+        {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if entry.file_type()?.is_dir()
+                && !name.starts_with('.')
+                && !SKIPPED_DIRS.contains(&name.as_ref())
+            {
+                dirs.push(entry.path());
+            }
+        }
+    }
+
+    workspaces.sort();
+    Ok(workspaces)
+}
+
+/// Nearest folder containing a `.mklaunch/` directory: `dir` itself or else its closest
+/// ancestor, the way git finds `.git/`
+pub fn find_workspace_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|d| d.join(".mklaunch").is_dir())
+        .map(Path::to_path_buf)
+}
@@ -0,0 +1,110 @@
+use crate::atomic::write_atomic;
+use crate::lock::hash_bytes;
+use crate::schema::LaunchAfter;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CACHE_VERSION: u32 = 6;
+
+/// Resolved configurations of each config file from a previous run, reused while the
+/// file and everything it was resolved from are unchanged
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct ResolveCache {
+    version: u32,
+    files: BTreeMap<String, CachedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CachedFile {
+    /// Hash of the file content, the inputs shared by all config files and, when an entry
+    /// uses `include`, every other config file
+    pub key: String,
+    pub includes: bool,
+    /// Set when an entry runs `argsFromCommand`, whose output can change at any time
+    #[serde(default)]
+    pub volatile: bool,
+    /// baseArgs and envFile files read while resolving, with their hashes
+    pub dependencies: BTreeMap<String, String>,
+    /// Number of entries in the file, enabled or not
+    pub entries: usize,
+    /// Number of enabled entries with a `baseArgs` file
+    pub base_args: usize,
+    pub configurations: Vec<Value>,
+    /// Template each configuration extends
+    pub templates: Vec<String>,
+    /// `launchAfter` of each configuration, which is not part of its JSON
+    pub launch_after: Vec<Option<LaunchAfter>>,
+    /// `perFile` of each configuration, which is not part of its JSON either
+    pub per_file: Vec<bool>,
+    /// Name of each configuration before `nameFormat`, when it has one
+    pub undecorated_names: Vec<Option<String>>,
+    pub warnings: Vec<String>,
+}
+
+impl ResolveCache {
+    /// Reads the cache at `path`; a missing, unreadable or outdated cache is empty
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|cache| cache.version == CACHE_VERSION)
+            .unwrap_or_default()
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let cache = Self {
+            version: CACHE_VERSION,
+            files: self.files.clone(),
+        };
+        write_atomic(path, (serde_json::to_string(&cache)? + "\n").as_bytes())
+            .context("Failed to write cache")
+    }
+
+    /// Cached result for `config_path` if its key, computed by `key` from whether the file
+    /// uses `include`, and all of its dependencies still match
+    pub fn lookup(&self, config_path: &Path, key: impl Fn(bool) -> String) -> Option<&CachedFile> {
+        let cached = self.files.get(&config_path.display().to_string())?;
+        let fresh = !cached.volatile
+            && cached.key == key(cached.includes)
+            && cached
+                .dependencies
+                .iter()
+                .all(|(path, hash)| *hash == hash_file(Path::new(path)));
+        fresh.then_some(cached)
+    }
+
+    pub fn insert(&mut self, config_path: &Path, cached: CachedFile) {
+        self.files.insert(config_path.display().to_string(), cached);
+    }
+
+    /// Drops files that are no longer config files
+    pub fn retain(&mut self, config_files: &[PathBuf]) {
+        self.files.retain(|path, _| {
+            config_files
+                .iter()
+                .any(|p| p.display().to_string() == *path)
+        });
+    }
+}
+
+/// Hash of a file's content, or `missing` when it cannot be read
+pub(crate) fn hash_file(path: &Path) -> String {
+    match fs::read(path) {
+        Ok(content) => hash_bytes(&content),
+        Err(_) => "missing".to_string(),
+    }
+}
+
+/// Combined hash of `parts`
+pub(crate) fn hash_parts<'a>(parts: impl IntoIterator<Item = &'a str>) -> String {
+    let mut joined = String::new();
+    for part in parts {
+        joined.push_str(part);
+        joined.push('\n');
+    }
+    hash_bytes(joined.as_bytes())
+}
@@ -0,0 +1,297 @@
+use crate::generator::workspace_path;
+use crate::runner::{CommandRunner, CommandSpec};
+use anyhow::{Context, Result};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Kind of cargo target a `cargo` block builds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CargoKind {
+    /// Unit or integration test binary (`cargo build --tests`)
+    Test,
+    /// Binary target (`cargo build --bins`)
+    Bin,
+    /// Example (`cargo build --examples`)
+    Example,
+}
+
+/// `cargo` block of a config entry: a target built at generation time whose executable
+/// becomes `program`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CargoTarget {
+    pub kind: CargoKind,
+    /// Package to build (`--package`), for workspaces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    /// Target name, needed when the build produces several executables of `kind`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Test name filter, passed to the test binary before the other args
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+}
+
+impl CargoTarget {
+    /// Arguments of the `cargo build` invocation producing the target
+    pub(crate) fn build_args(&self) -> Vec<String> {
+        let mut args = vec!["build".to_string()];
+        args.push(match (self.kind, &self.target) {
+            // Unit tests come from the lib and bin targets, which `--test` does not select;
+            // the test binary is picked by name among all of them instead
+            (CargoKind::Test, _) => "--tests".to_string(),
+            (CargoKind::Bin, Some(target)) => format!("--bin={}", target),
+            (CargoKind::Bin, None) => "--bins".to_string(),
+            (CargoKind::Example, Some(target)) => format!("--example={}", target),
+            (CargoKind::Example, None) => "--examples".to_string(),
+        });
+        args.extend(self.package.as_ref().map(|p| format!("--package={}", p)));
+        args.push("--message-format=json".to_string());
+        args
+    }
+
+    /// Executable of the target among the artifacts of its build
+    pub(crate) fn select<'a>(&self, artifacts: &'a [Artifact]) -> Result<&'a Path> {
+        let matching: Vec<&Artifact> = artifacts
+            .iter()
+            .filter(|a| match self.kind {
+                CargoKind::Test => a.test,
+                CargoKind::Bin => !a.test && a.kinds.iter().any(|k| k == "bin"),
+                CargoKind::Example => !a.test && a.kinds.iter().any(|k| k == "example"),
+            })
+            .filter(|a| self.target.as_ref().is_none_or(|t| *t == a.name))
+            .collect();
+        match matching.as_slice() {
+            [artifact] => Ok(&artifact.executable),
+            [] => anyhow::bail!(
+                "`cargo {}` built no {} executable{}",
+                self.build_args().join(" "),
+                self.kind_name(),
+                self.target
+                    .as_ref()
+                    .map(|t| format!(" named '{}'", t))
+                    .unwrap_or_default()
+            ),
+            several => {
+                let names: Vec<&str> = several.iter().map(|a| a.name.as_str()).collect();
+                anyhow::bail!(
+                    "`cargo {}` built several {} executables ({}); set 'target' to pick one",
+                    self.build_args().join(" "),
+                    self.kind_name(),
+                    names.join(", ")
+                )
+            }
+        }
+    }
+
+    fn kind_name(&self) -> &'static str {
+        match self.kind {
+            CargoKind::Test => "test",
+            CargoKind::Bin => "bin",
+            CargoKind::Example => "example",
+        }
+    }
+}
+
+/// `cargo` block of a config entry: CodeLLDB's own launch block when it has `args`, a target
+/// built by mklaunch otherwise
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum CargoBlock {
+    Lldb(LldbCargo),
+    Target(CargoTarget),
+}
+
+impl<'de> Deserialize<'de> for CargoBlock {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        if value.get("args").is_some() {
+            serde_json::from_value(value)
+                .map(CargoBlock::Lldb)
+                .map_err(D::Error::custom)
+        } else {
+            serde_json::from_value(value)
+                .map(CargoBlock::Target)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+/// CodeLLDB's `cargo` launch block: CodeLLDB runs `cargo <args>` when the session starts
+/// and debugs the executable it reports, so no `program` is needed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LldbCargo {
+    /// Cargo command line, e.g. `["build", "--bin=server"]`
+    pub args: Vec<String>,
+    /// Picks the executable when the build produces several
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<LldbCargoFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(rename = "problemMatcher", skip_serializing_if = "Option::is_none")]
+    pub problem_matcher: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LldbCargoFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Target kind, e.g. `bin`, `example`, `test` or `lib`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+}
+
+impl LldbCargo {
+    /// Checks a template's `cargo` block, which is otherwise copied as written
+    pub(crate) fn check_template(value: &Value) -> Result<()> {
+        let cargo: LldbCargo = serde_json::from_value(value.clone())
+            .map_err(|e| anyhow::anyhow!("Invalid 'cargo' block: {}", e))?;
+        if cargo.args.is_empty() {
+            anyhow::bail!("'cargo.args' must name a cargo subcommand, e.g. [\"build\"]");
+        }
+        Ok(())
+    }
+
+    /// Whether `args` starts with a cargo subcommand rather than an option
+    fn has_subcommand(&self) -> bool {
+        self.args.first().is_some_and(|arg| !arg.starts_with('-'))
+    }
+
+    /// The template's `cargo` block with this one merged in. Args starting with a
+    /// subcommand replace the template's, args made only of options are appended to them;
+    /// `env` is merged key by key and the other keys replace the template's.
+    pub(crate) fn merge_into(&self, template: Option<&Value>) -> Result<Value> {
+        let mut merged = match template {
+            Some(Value::Object(block)) => block.clone(),
+            _ => Map::new(),
+        };
+        let template_args = merged
+            .get("args")
+            .and_then(Value::as_array)
+            .filter(|_| !self.has_subcommand());
+        let args: Vec<Value> = match template_args {
+            Some(template_args) => template_args
+                .iter()
+                .cloned()
+                .chain(self.args.iter().map(|arg| Value::String(arg.clone())))
+                .collect(),
+            None if self.has_subcommand() => self
+                .args
+                .iter()
+                .map(|arg| Value::String(arg.clone()))
+                .collect(),
+            None => anyhow::bail!(
+                "cargo args {:?} have no subcommand to extend; start them with one, e.g. \"build\", or extend a template with a cargo block",
+                self.args
+            ),
+        };
+        merged.insert("args".to_string(), Value::Array(args));
+
+        if let Some(env) = &self.env {
+            let block = merged
+                .entry("env")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(block) = block {
+                for (key, value) in env {
+                    block.insert(key.clone(), Value::String(value.clone()));
+                }
+            }
+        }
+        if let Some(filter) = &self.filter {
+            merged.insert("filter".to_string(), serde_json::to_value(filter)?);
+        }
+        if let Some(cwd) = &self.cwd {
+            merged.insert("cwd".to_string(), Value::String(cwd.clone()));
+        }
+        if let Some(matcher) = &self.problem_matcher {
+            merged.insert("problemMatcher".to_string(), matcher.clone());
+        }
+        Ok(Value::Object(merged))
+    }
+}
+
+/// Executable reported by a `compiler-artifact` message of `cargo --message-format=json`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Artifact {
+    /// Target name, e.g. the crate or the integration test file stem
+    pub name: String,
+    /// Target kinds, e.g. `lib`, `bin`, `test`
+    pub kinds: Vec<String>,
+    /// Built with the test harness
+    pub test: bool,
+    pub executable: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    reason: String,
+    target: Option<MessageTarget>,
+    profile: Option<MessageProfile>,
+    executable: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct MessageTarget {
+    name: String,
+    kind: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct MessageProfile {
+    test: bool,
+}
+
+/// Runs `cargo` with `args` in `root` and returns the executables it built
+pub(crate) fn build(
+    root: &Path,
+    args: &[String],
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> Result<Vec<Artifact>> {
+    let output = runner.run(&CommandSpec::new("cargo", args).cwd(root).timeout(timeout))?;
+    let stdout = String::from_utf8(output.stdout).context("cargo printed invalid UTF-8")?;
+    Ok(parse_artifacts(&stdout))
+}
+
+/// Executables among the JSON messages printed by `cargo build --message-format=json`,
+/// one per line. Lines that are not artifact messages are skipped.
+pub(crate) fn parse_artifacts(output: &str) -> Vec<Artifact> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Message>(line).ok())
+        .filter(|m| m.reason == "compiler-artifact")
+        .filter_map(|m| {
+            let target = m.target?;
+            Some(Artifact {
+                name: target.name,
+                kinds: target.kind,
+                test: m.profile.is_some_and(|p| p.test),
+                executable: m.executable?,
+            })
+        })
+        .collect()
+}
+
+/// `program` value for `executable`: relative to `${workspaceFolder}` when it is inside
+/// `root`, absolute otherwise
+pub(crate) fn program_path(root: &Path, executable: &Path) -> String {
+    let root = if root.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        root
+    };
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    match executable.strip_prefix(&root) {
+        Ok(relative) => workspace_path(relative),
+        Err(_) => executable.display().to_string(),
+    }
+}
@@ -0,0 +1,197 @@
+use crate::encoding::read_text;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Executable target discovered through the CMake File API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CmakeTarget {
+    /// Configuration name: the target name, qualified by preset/build type when ambiguous
+    pub name: String,
+    /// Path of the built executable
+    pub artifact: PathBuf,
+    /// Target reply file describing the target
+    pub source: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Presets {
+    #[serde(rename = "configurePresets", default)]
+    configure_presets: Vec<ConfigurePreset>,
+}
+
+#[derive(Deserialize)]
+struct ConfigurePreset {
+    name: String,
+    #[serde(rename = "binaryDir")]
+    binary_dir: Option<String>,
+    #[serde(default)]
+    hidden: bool,
+}
+
+#[derive(Deserialize)]
+struct ReplyIndex {
+    objects: Vec<ReplyObject>,
+}
+
+#[derive(Deserialize)]
+struct ReplyObject {
+    kind: String,
+    #[serde(rename = "jsonFile")]
+    json_file: String,
+}
+
+#[derive(Deserialize)]
+struct Codemodel {
+    configurations: Vec<CodemodelConfiguration>,
+}
+
+#[derive(Deserialize)]
+struct CodemodelConfiguration {
+    name: String,
+    targets: Vec<CodemodelTarget>,
+}
+
+#[derive(Deserialize)]
+struct CodemodelTarget {
+    #[serde(rename = "jsonFile")]
+    json_file: String,
+}
+
+#[derive(Deserialize)]
+struct TargetReply {
+    name: String,
+    #[serde(rename = "type")]
+    target_type: String,
+    #[serde(default)]
+    artifacts: Vec<Artifact>,
+}
+
+#[derive(Deserialize)]
+struct Artifact {
+    path: PathBuf,
+}
+
+/// Enumerates executable targets from `CMakePresets.json` (every non-hidden configure
+/// preset with a `binaryDir`) or from a single build directory. Presets whose build
+/// directory CMake has not configured yet are skipped with a warning.
+pub(crate) fn discover(path: &Path, warnings: &mut Vec<String>) -> Result<Vec<CmakeTarget>> {
+    if path.is_dir() {
+        return discover_build_dir(path, None);
+    }
+
+    let content = read_text(path)
+        .with_context(|| format!("Failed to read CMake presets: {}", path.display()))?;
+    let presets: Presets = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse CMake presets: {}", path.display()))?;
+    let source_dir = path.parent().unwrap_or(Path::new(""));
+
+    let mut targets = Vec::new();
+    for preset in presets.configure_presets.iter().filter(|p| !p.hidden) {
+        let Some(binary_dir) = &preset.binary_dir else {
+            continue;
+        };
+        let binary_dir = binary_dir
+            .replace("${presetName}", &preset.name)
+            .replace("${sourceDir}/", "")
+            .replace("${sourceDir}", ".");
+        let build_dir = source_dir.join(binary_dir);
+        if !reply_dir(&build_dir).is_dir() {
+            warnings.push(format!(
+                "Skipped CMake preset '{}': {} is not configured; create an empty {} and run cmake --preset {}",
+                preset.name,
+                build_dir.display(),
+                query_file(&build_dir).display(),
+                preset.name
+            ));
+            continue;
+        }
+        targets.extend(discover_build_dir(&build_dir, Some(&preset.name))?);
+    }
+    Ok(targets)
+}
+
+/// Directory CMake writes the File API replies of `build_dir` to
+fn reply_dir(build_dir: &Path) -> PathBuf {
+    build_dir.join(".cmake/api/v1/reply")
+}
+
+/// Query file asking CMake for the codemodel of `build_dir`
+fn query_file(build_dir: &Path) -> PathBuf {
+    build_dir.join(".cmake/api/v1/query/codemodel-v2")
+}
+
+fn discover_build_dir(build_dir: &Path, preset: Option<&str>) -> Result<Vec<CmakeTarget>> {
+    let reply_dir = reply_dir(build_dir);
+    if !reply_dir.is_dir() {
+        anyhow::bail!(
+            "CMake File API reply directory not found: {}\nCreate an empty {} and re-run CMake",
+            reply_dir.display(),
+            query_file(build_dir).display()
+        );
+    }
+
+    // Index files are named with a timestamp, so the last one is the newest
+    let mut indexes: Vec<PathBuf> = fs::read_dir(&reply_dir)
+        .with_context(|| format!("Failed to read directory: {}", reply_dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|s| s.to_str())
+                .is_some_and(|s| s.starts_with("index-") && s.ends_with(".json"))
+        })
+        .collect();
+    indexes.sort();
+    let index_path = indexes
+        .pop()
+        .ok_or_else(|| anyhow::anyhow!("No index file in {}", reply_dir.display()))?;
+
+    let index: ReplyIndex = read_json(&index_path)?;
+    let codemodel_file = index
+        .objects
+        .iter()
+        .find(|o| o.kind == "codemodel")
+        .ok_or_else(|| anyhow::anyhow!("No codemodel reply in {}", index_path.display()))?;
+    let codemodel: Codemodel = read_json(&reply_dir.join(&codemodel_file.json_file))?;
+
+    let multi_config = codemodel.configurations.len() > 1;
+    let mut targets = Vec::new();
+    for configuration in &codemodel.configurations {
+        for target in &configuration.targets {
+            let source = reply_dir.join(&target.json_file);
+            let reply: TargetReply = read_json(&source)?;
+            if reply.target_type != "EXECUTABLE" {
+                continue;
+            }
+            let Some(artifact) = reply.artifacts.first() else {
+                continue;
+            };
+
+            let mut qualifiers: Vec<&str> = Vec::new();
+            qualifiers.extend(preset);
+            if multi_config {
+                qualifiers.push(&configuration.name);
+            }
+            let name = if qualifiers.is_empty() {
+                reply.name.clone()
+            } else {
+                format!("{} ({})", reply.name, qualifiers.join(", "))
+            };
+
+            targets.push(CmakeTarget {
+                name,
+                artifact: build_dir.join(&artifact.path),
+                source,
+            });
+        }
+    }
+    Ok(targets)
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T> {
+    let content = read_text(path)
+        .with_context(|| format!("Failed to read CMake reply: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse CMake reply: {}", path.display()))
+}
@@ -0,0 +1,106 @@
+use crate::diagnostic::Diagnostic;
+use crate::encoding::read_text;
+use crate::import::strip_jsonc;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Where the generated configurations are debugged from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    /// The checkout on this machine; inputs are used as written
+    #[default]
+    Host,
+    /// The dev container described by `.devcontainer/devcontainer.json`: host paths are
+    /// rewritten to the container's workspace folder and `<template>.devcontainer`
+    /// templates replace the templates they are named after
+    Devcontainer,
+}
+
+/// Suffix of templates used instead of the template they are named after in a dev container
+pub(crate) const TEMPLATE_SUFFIX: &str = "devcontainer";
+
+/// Dev container definition of a workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DevContainer {
+    /// Workspace root on this machine, canonicalized
+    pub host_root: PathBuf,
+    /// Where the workspace is mounted in the container
+    pub workspace_folder: String,
+}
+
+impl DevContainer {
+    /// Reads `.devcontainer/devcontainer.json` (or `.devcontainer.json`) under `root`;
+    /// `None` when the workspace has neither
+    pub fn find(root: &Path) -> Result<Option<Self>> {
+        let root = if root.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            root
+        };
+        let Some(path) = [".devcontainer/devcontainer.json", ".devcontainer.json"]
+            .iter()
+            .map(|file| root.join(file))
+            .find(|path| path.is_file())
+        else {
+            return Ok(None);
+        };
+        let content = read_text(&path)
+            .with_context(|| format!("Failed to read dev container file: {}", path.display()))?;
+        let value: Value = serde_json::from_str(&strip_jsonc(&content))
+            .map_err(|e| Diagnostic::from_json_error(&path, &e))
+            .context("Failed to parse dev container file")?;
+
+        let host_root = root
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve {}", root.display()))?;
+        let basename = host_root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let workspace_folder = match value.get("workspaceFolder") {
+            Some(Value::String(folder)) => {
+                folder.replace("${localWorkspaceFolderBasename}", &basename)
+            }
+            Some(_) => anyhow::bail!("'workspaceFolder' must be a string in {}", path.display()),
+            None => format!("/workspaces/{}", basename),
+        };
+        Ok(Some(Self {
+            host_root,
+            workspace_folder: workspace_folder.trim_end_matches('/').to_string(),
+        }))
+    }
+
+    /// `value` with each host workspace path in it, e.g. in `--data=/home/me/repo/data`,
+    /// moved to the container's workspace folder with `/` separators; `None` when it
+    /// contains none
+    pub fn rewrite(&self, value: &str) -> Option<String> {
+        let host = self.host_root.to_str()?;
+        if !value.contains(host) {
+            return None;
+        }
+        let mut out = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(idx) = rest.find(host) {
+            let (before, after) = rest.split_at(idx);
+            let after = &after[host.len()..];
+            out.push_str(before);
+            // `/repo` must not match `/repo-old`
+            if after.starts_with(|c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+                out.push_str(host);
+                rest = after;
+                continue;
+            }
+            let end = after
+                .find(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '"' | '\'' | ':'))
+                .unwrap_or(after.len());
+            out.push_str(&self.workspace_folder);
+            out.push_str(&after[..end].replace('\\', "/"));
+            rest = &after[end..];
+        }
+        out.push_str(rest);
+        (out != value).then_some(out)
+    }
+}
@@ -0,0 +1,464 @@
+use crate::encoding::read_text;
+use crate::order::KeyLayout;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Error tied to a location in an input file.
+/// Rendered as `path:line:column: message`; the CLI colors it when printing to a terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// File containing the offending value
+    pub path: PathBuf,
+    /// JSON pointer of the offending value (e.g. `/3/args/0`), when known
+    pub pointer: Option<String>,
+    /// 1-based line and column of the offending value, when it could be located
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+    pub severity: Severity,
+    /// Edit that resolves the problem, when there is an obvious one
+    pub fix: Option<Fix>,
+}
+
+/// Whether a diagnostic stops generation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    /// Accepted by generation, but likely a mistake (e.g. a misspelled key that is ignored)
+    Warning,
+    /// Not a problem, but something a check could not look at
+    Info,
+}
+
+/// Suggested edit for a diagnostic: the value at `pointer` replaced by `replacement`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// What the edit does, e.g. `Replace with 'cpp'`
+    pub title: String,
+    pub pointer: String,
+    /// JSON text of the new value
+    pub replacement: String,
+}
+
+impl Diagnostic {
+    /// Diagnostic for the value at `pointer` in `path`, located by re-reading the file
+    pub fn at_pointer(path: &Path, pointer: &str, message: impl Into<String>) -> Self {
+        let position = read_text(path)
+            .ok()
+            .and_then(|text| locate(&text, pointer).map(|offset| line_column(&text, offset)));
+        Self {
+            path: path.to_path_buf(),
+            pointer: Some(pointer.to_string()),
+            position,
+            message: message.into(),
+            severity: Severity::Error,
+            fix: None,
+        }
+    }
+
+    /// Diagnostic for a JSON syntax error, which carries its own position
+    pub fn from_json_error(path: &Path, error: &serde_json::Error) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            pointer: None,
+            position: (error.line() > 0).then(|| (error.line(), error.column())),
+            message: without_position(error),
+            severity: Severity::Error,
+            fix: None,
+        }
+    }
+
+    /// Diagnostic for `path` as a whole, for problems not tied to one value
+    pub fn for_file(path: &Path, message: impl Into<String>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            pointer: None,
+            position: None,
+            message: message.into(),
+            severity: Severity::Error,
+            fix: None,
+        }
+    }
+
+    /// This diagnostic as a warning
+    pub(crate) fn warning(mut self) -> Self {
+        self.severity = Severity::Warning;
+        self
+    }
+
+    /// This diagnostic as information that does not fail a check
+    pub(crate) fn info(mut self) -> Self {
+        self.severity = Severity::Info;
+        self
+    }
+
+    /// This diagnostic with a fix replacing the value at its pointer by `replacement`
+    pub(crate) fn with_fix(
+        mut self,
+        title: impl Into<String>,
+        replacement: &serde_json::Value,
+    ) -> Self {
+        self.fix = self.pointer.clone().map(|pointer| Fix {
+            title: title.into(),
+            pointer,
+            replacement: replacement.to_string(),
+        });
+        self
+    }
+
+    /// Diagnostic for a value at `pointer` that failed to deserialize as `T`.
+    /// The value's source text is parsed again so the error lands on the offending field;
+    /// otherwise the diagnostic points at the value itself.
+    pub(crate) fn from_value_error<T: DeserializeOwned>(
+        path: &Path,
+        pointer: &str,
+        error: &serde_json::Error,
+    ) -> Self {
+        let mut diagnostic = Self::at_pointer(path, pointer, error.to_string());
+        let Ok(text) = read_text(path) else {
+            return diagnostic;
+        };
+        let Some((start, end)) = span(&text, pointer) else {
+            return diagnostic;
+        };
+        if let Err(e) = serde_json::from_str::<T>(&text[start..end])
+            && e.line() > 0
+        {
+            let (line, column) = line_column(&text, start);
+            diagnostic.position = Some(if e.line() == 1 {
+                (line, column + e.column() - 1)
+            } else {
+                (line + e.line() - 1, e.column())
+            });
+        }
+        diagnostic
+    }
+
+    /// `path:line:column` (or just the path when the position is unknown)
+    pub fn location(&self) -> String {
+        match self.position {
+            Some((line, column)) => format!("{}:{}:{}", self.path.display(), line, column),
+            None => self.path.display().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "{}: {}", self.location(), self.message)?,
+            Severity::Warning => write!(f, "{}: warning: {}", self.location(), self.message)?,
+            Severity::Info => write!(f, "{}: info: {}", self.location(), self.message)?,
+        }
+        if let Some(pointer) = &self.pointer {
+            write!(f, " (at {})", pointer)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Diagnostics collected across input files, so every problem can be reported at once
+/// instead of stopping at the first. Returned as the error of a failed generation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.0.push(diagnostic);
+    }
+
+    /// Whether any diagnostic stops generation
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.0.iter().filter(|d| d.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.0.iter().filter(|d| d.severity == Severity::Warning)
+    }
+}
+
+impl std::ops::Deref for Diagnostics {
+    type Target = [Diagnostic];
+
+    fn deref(&self) -> &[Diagnostic] {
+        &self.0
+    }
+}
+
+impl From<Vec<Diagnostic>> for Diagnostics {
+    fn from(diagnostics: Vec<Diagnostic>) -> Self {
+        Self(diagnostics)
+    }
+}
+
+impl FromIterator<Diagnostic> for Diagnostics {
+    fn from_iter<I: IntoIterator<Item = Diagnostic>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Extend<Diagnostic> for Diagnostics {
+    fn extend<I: IntoIterator<Item = Diagnostic>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// One diagnostic per line
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, diagnostic) in self.0.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+/// serde_json's message without its trailing "at line N column M", which the location repeats
+fn without_position(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    match message.rfind(" at line ") {
+        Some(end) if error.line() > 0 => message[..end].to_string(),
+        _ => message,
+    }
+}
+
+/// Converts a byte offset into a 1-based (line, column) pair
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+/// Byte offset of the value addressed by the JSON `pointer` in `text`
+pub(crate) fn locate(text: &str, pointer: &str) -> Option<usize> {
+    let segments: Vec<String> = pointer
+        .split('/')
+        .skip(1)
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    let mut scanner = Scanner {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    scanner.find(&segments)
+}
+
+/// Key layout of the value addressed by the JSON `pointer` in `text`
+pub(crate) fn key_layout(text: &str, pointer: &str) -> Option<KeyLayout> {
+    let start = locate(text, pointer)?;
+    let mut scanner = Scanner {
+        bytes: text.as_bytes(),
+        pos: start,
+    };
+    scanner.layout()
+}
+
+/// Byte range of the value addressed by the JSON `pointer` in `text`
+pub(crate) fn span(text: &str, pointer: &str) -> Option<(usize, usize)> {
+    let start = locate(text, pointer)?;
+    let mut scanner = Scanner {
+        bytes: text.as_bytes(),
+        pos: start,
+    };
+    scanner.skip_value()?;
+    Some((start, scanner.pos))
+}
+
+/// Minimal JSON scanner that walks to a value without building a tree
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.skip_ws();
+        (self.peek()? == byte).then(|| self.pos += 1)
+    }
+
+    fn find(&mut self, segments: &[String]) -> Option<usize> {
+        self.skip_ws();
+        let Some((segment, rest)) = segments.split_first() else {
+            return Some(self.pos);
+        };
+
+        match self.peek()? {
+            b'{' => {
+                self.pos += 1;
+                loop {
+                    self.skip_ws();
+                    if self.peek()? == b'}' {
+                        return None;
+                    }
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    if key == *segment {
+                        return self.find(rest);
+                    }
+                    self.skip_value()?;
+                    self.skip_ws();
+                    match self.peek()? {
+                        b',' => self.pos += 1,
+                        _ => return None,
+                    }
+                }
+            }
+            b'[' => {
+                let index: usize = segment.parse().ok()?;
+                self.pos += 1;
+                for i in 0.. {
+                    self.skip_ws();
+                    if self.peek()? == b']' {
+                        return None;
+                    }
+                    if i == index {
+                        return self.find(rest);
+                    }
+                    self.skip_value()?;
+                    self.skip_ws();
+                    match self.peek()? {
+                        b',' => self.pos += 1,
+                        _ => return None,
+                    }
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads a string token and returns its raw contents (escapes are kept verbatim)
+    fn string(&mut self) -> Option<String> {
+        self.skip_ws();
+        if self.peek()? != b'"' {
+            return None;
+        }
+        self.pos += 1;
+        let start = self.pos;
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => break,
+                _ => self.pos += 1,
+            }
+        }
+        let raw = std::str::from_utf8(&self.bytes[start..self.pos]).ok()?;
+        self.pos += 1;
+        Some(raw.to_string())
+    }
+
+    /// Skips a value, recording the order of the keys of every object in it
+    fn layout(&mut self) -> Option<KeyLayout> {
+        self.skip_ws();
+        let mut layout = KeyLayout::default();
+        let close = match self.peek()? {
+            b'{' => b'}',
+            b'[' => b']',
+            _ => {
+                self.skip_value()?;
+                return Some(layout);
+            }
+        };
+        self.pos += 1;
+        loop {
+            self.skip_ws();
+            if self.peek()? == close {
+                self.pos += 1;
+                return Some(layout);
+            }
+            if close == b'}' {
+                let key = self.string()?;
+                self.expect(b':')?;
+                let child = self.layout()?;
+                layout.keys.push((key, child));
+            } else {
+                let item = self.layout()?;
+                layout.items.push(item);
+            }
+            self.skip_ws();
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b if b == close => {}
+                _ => return None,
+            }
+        }
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        self.skip_ws();
+        match self.peek()? {
+            b'"' => self.string().map(|_| ()),
+            open @ (b'{' | b'[') => {
+                let close = if open == b'{' { b'}' } else { b']' };
+                self.pos += 1;
+                loop {
+                    self.skip_ws();
+                    match self.peek()? {
+                        b if b == close => {
+                            self.pos += 1;
+                            return Some(());
+                        }
+                        b',' | b':' => self.pos += 1,
+                        _ => self.skip_value()?,
+                    }
+                }
+            }
+            _ => {
+                while self
+                    .peek()
+                    .is_some_and(|b| !matches!(b, b',' | b'}' | b']') && !b.is_ascii_whitespace())
+                {
+                    self.pos += 1;
+                }
+                Some(())
+            }
+        }
+    }
+}
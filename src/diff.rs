@@ -0,0 +1,98 @@
+use crate::generator::LaunchJson;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Difference in one configuration between two generated launch.json files
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigChange {
+    Added(String),
+    Removed(String),
+    /// Same name, different keys
+    Changed {
+        name: String,
+        keys: Vec<KeyChange>,
+    },
+}
+
+/// Top-level key of a configuration whose value differs; `None` when the key is absent
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyChange {
+    pub key: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+impl ConfigChange {
+    pub fn name(&self) -> &str {
+        match self {
+            ConfigChange::Added(name) | ConfigChange::Removed(name) => name,
+            ConfigChange::Changed { name, .. } => name,
+        }
+    }
+}
+
+impl fmt::Display for KeyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "(unset)".to_string(),
+        };
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.key,
+            show(&self.old),
+            show(&self.new)
+        )
+    }
+}
+
+/// Configurations added, removed or changed from `old` to `new`, matched by name and
+/// sorted by it
+pub fn diff_configs(old: &LaunchJson, new: &LaunchJson) -> Vec<ConfigChange> {
+    let old = by_name(old);
+    let mut new = by_name(new);
+
+    let mut changes = Vec::new();
+    for (name, old_config) in old {
+        let Some(new_config) = new.remove(&name) else {
+            changes.push(ConfigChange::Removed(name));
+            continue;
+        };
+        let keys = diff_keys(&old_config, &new_config);
+        if !keys.is_empty() {
+            changes.push(ConfigChange::Changed { name, keys });
+        }
+    }
+    changes.extend(new.into_keys().map(ConfigChange::Added));
+    changes.sort_by(|a, b| a.name().cmp(b.name()));
+    changes
+}
+
+/// Configurations of `launch` as JSON objects, keyed by name
+fn by_name(launch: &LaunchJson) -> BTreeMap<String, Map<String, Value>> {
+    launch
+        .configurations()
+        .iter()
+        .filter_map(|config| match serde_json::to_value(config) {
+            Ok(Value::Object(object)) => Some((config.name().to_string(), object)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Keys of either configuration whose values differ, in key order
+fn diff_keys(old: &Map<String, Value>, new: &Map<String, Value>) -> Vec<KeyChange> {
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .map(|key| KeyChange {
+            key: key.clone(),
+            old: old.get(key).cloned(),
+            new: new.get(key).cloned(),
+        })
+        .collect()
+}
@@ -0,0 +1,81 @@
+use crate::cmake;
+use crate::generator::workspace_path;
+use crate::runner::CommandRunner;
+use crate::{golang, npm, python};
+use anyhow::Result;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Kind of project scanned for targets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DiscoveryKind {
+    Cmake,
+    Python,
+    Go,
+    /// Only the listed package.json scripts become configurations
+    Npm {
+        scripts: Vec<String>,
+    },
+}
+
+impl DiscoveryKind {
+    /// Project kind as named in error messages
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiscoveryKind::Cmake => "CMake",
+            DiscoveryKind::Python => "Python",
+            DiscoveryKind::Go => "Go",
+            DiscoveryKind::Npm { .. } => "npm",
+        }
+    }
+}
+
+/// Project whose targets become configurations extending `template`
+#[derive(Debug, Clone)]
+pub(crate) struct DiscoverySource {
+    pub kind: DiscoveryKind,
+    pub path: PathBuf,
+    pub template: String,
+}
+
+/// Configuration found by scanning a project
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DiscoveredTarget {
+    pub name: String,
+    /// File the target was read from; reported in errors and tracked as an input
+    pub source: PathBuf,
+    /// Keys set on the resolved configuration; `None` removes the key from the template
+    pub keys: Vec<(&'static str, Option<Value>)>,
+    /// Args appended after the template's (empty) args
+    pub args: Vec<String>,
+}
+
+impl DiscoverySource {
+    /// Enumerates the targets of this project, running discovery tools through `runner`;
+    /// parts of the project that were skipped are reported in `warnings`
+    pub fn discover(
+        &self,
+        timeout: Option<Duration>,
+        runner: &dyn CommandRunner,
+        warnings: &mut Vec<String>,
+    ) -> Result<Vec<DiscoveredTarget>> {
+        match &self.kind {
+            DiscoveryKind::Cmake => Ok(cmake::discover(&self.path, warnings)?
+                .into_iter()
+                .map(|target| DiscoveredTarget {
+                    name: target.name,
+                    source: target.source,
+                    keys: vec![(
+                        "program",
+                        Some(Value::String(workspace_path(&target.artifact))),
+                    )],
+                    args: Vec::new(),
+                })
+                .collect()),
+            DiscoveryKind::Python => python::discover(&self.path),
+            DiscoveryKind::Go => golang::discover(&self.path, timeout, runner),
+            DiscoveryKind::Npm { scripts } => npm::discover(&self.path, scripts),
+        }
+    }
+}
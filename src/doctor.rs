@@ -0,0 +1,154 @@
+use crate::encoding::read_text;
+use crate::generator::{LaunchConfig, on_disk};
+use crate::import::strip_jsonc;
+use crate::os::TargetOs;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Outcome of the environment checks for one generated configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigHealth {
+    pub name: String,
+    /// What was checked, e.g. `program ./build/app`
+    pub checked: Vec<String>,
+    /// Checks that failed on this machine
+    pub problems: Vec<String>,
+}
+
+impl ConfigHealth {
+    /// Whether every check passed
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Labels of the tasks defined in `.vscode/tasks.json`; `None` when there is no such file
+pub(crate) struct Tasks {
+    path: PathBuf,
+    labels: Option<BTreeSet<String>>,
+}
+
+impl Tasks {
+    /// Reads the task labels of the workspace at `root`, ignoring comments and trailing commas
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join(".vscode").join("tasks.json");
+        if !path.exists() {
+            return Ok(Self { path, labels: None });
+        }
+        let content =
+            read_text(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let tasks: Value = serde_json::from_str(&strip_jsonc(&content))
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let labels = tasks
+            .get("tasks")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|task| task.get("label").or_else(|| task.get("taskName")))
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+        Ok(Self {
+            path,
+            labels: Some(labels),
+        })
+    }
+}
+
+/// Checks that the debugger, program, envFile and preLaunchTask referenced by `config`
+/// exist on this machine. Values in the host OS block take precedence over top-level ones,
+/// and values using variables other than `${workspaceFolder}` are skipped.
+pub(crate) fn check(config: &LaunchConfig, root: &Path, tasks: &Tasks) -> ConfigHealth {
+    let host_block = TargetOs::host().and_then(|os| config.get(os.key()));
+    let value = |key: &str| {
+        host_block
+            .as_ref()
+            .and_then(|block| block.get(key).cloned())
+            .or_else(|| config.get(key))
+            .and_then(|v| v.as_str().map(str::to_string))
+    };
+
+    let mut health = ConfigHealth {
+        name: config.name().to_string(),
+        checked: Vec::new(),
+        problems: Vec::new(),
+    };
+
+    if let Some(debugger) = value("miDebuggerPath") {
+        health.check_binary("miDebuggerPath", &debugger, root);
+    } else if let Some(mode) = value("MIMode") {
+        health.check_binary("MIMode", &mode, root);
+    }
+    for key in ["program", "envFile"] {
+        if let Some(path) = value(key) {
+            health.check_file(key, &path, root);
+        }
+    }
+    if let Some(task) = value("preLaunchTask") {
+        health.check_task(&task, tasks);
+    }
+    health
+}
+
+impl ConfigHealth {
+    /// A debugger given as a path must exist; a bare name must be found on `PATH`
+    fn check_binary(&mut self, key: &str, binary: &str, root: &Path) {
+        if binary.contains('/') || binary.contains('\\') {
+            self.check_file(key, binary, root);
+            return;
+        }
+        self.checked.push(format!("{} {}", key, binary));
+        if find_on_path(binary).is_none() {
+            self.problems
+                .push(format!("{} '{}' not found on PATH", key, binary));
+        }
+    }
+
+    fn check_file(&mut self, key: &str, path: &str, root: &Path) {
+        let local = on_disk(root, Path::new(path));
+        if local.to_string_lossy().contains("${") {
+            return;
+        }
+        self.checked.push(format!("{} {}", key, path));
+        if !local.exists() {
+            self.problems
+                .push(format!("{} not found: {}", key, local.display()));
+        }
+    }
+
+    /// Labels like `npm: build` come from task providers and cannot be checked
+    fn check_task(&mut self, task: &str, tasks: &Tasks) {
+        let provided = task
+            .split_once(": ")
+            .is_some_and(|(source, _)| !source.contains(char::is_whitespace));
+        match &tasks.labels {
+            Some(labels) if labels.contains(task) => {}
+            _ if provided => return,
+            Some(_) => self.problems.push(format!(
+                "preLaunchTask '{}' is not defined in {}",
+                task,
+                tasks.path.display()
+            )),
+            None => self.problems.push(format!(
+                "preLaunchTask '{}' needs {}, which does not exist",
+                task,
+                tasks.path.display()
+            )),
+        }
+        self.checked.push(format!("preLaunchTask {}", task));
+    }
+}
+
+fn find_on_path(binary: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).find_map(|dir| {
+        let candidate = dir.join(binary);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        let exe = candidate.with_extension("exe");
+        (cfg!(windows) && exe.is_file()).then_some(exe)
+    })
+}
@@ -0,0 +1,109 @@
+use crate::generator::LaunchJson;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+/// Banner written at the top of JSONC and JSON5 output
+const GENERATED_HEADER: &str =
+    "// Generated by mklaunch; do not edit. Changes are overwritten on the next run.\n";
+
+/// Syntax launch.json is written in. VSCode reads all three.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Strict JSON
+    #[default]
+    Json,
+    /// JSON with a do-not-edit header comment
+    Jsonc,
+    /// JSON5 restricted to what VSCode parses: header comment and trailing commas, with
+    /// keys kept quoted
+    Json5,
+}
+
+impl OutputFormat {
+    /// Renders pretty-printed JSON `text` in this format
+    pub(crate) fn render(self, text: &str) -> String {
+        match self {
+            OutputFormat::Json => text.to_string(),
+            OutputFormat::Jsonc => format!("{}{}", GENERATED_HEADER, text),
+            OutputFormat::Json5 => format!("{}{}", GENERATED_HEADER, to_json5(text)),
+        }
+    }
+}
+
+/// Rewrites JSON text as JSON5: every non-empty object and array gets a trailing comma.
+/// Keys stay quoted, since VSCode rejects unquoted ones in launch.json.
+fn to_json5(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                out.push(c);
+                while let Some(n) = chars.next() {
+                    out.push(n);
+                    match n {
+                        '\\' => out.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '}' | ']' => {
+                let last = out.trim_end().chars().last();
+                if !matches!(last, Some('{') | Some('[') | None) {
+                    let len = out.trim_end().len();
+                    out.insert(len, ',');
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// settings.json contents with `launch` set to the generated configurations.
+/// Other settings in `existing` are kept; comments are not supported.
+pub fn settings_json(launch: &LaunchJson, existing: Option<&str>) -> Result<String> {
+    let mut settings = match existing.map(str::trim) {
+        None | Some("") => Map::new(),
+        Some(text) => match serde_json::from_str(text)
+            .context("Failed to parse existing settings (comments are not supported)")?
+        {
+            Value::Object(map) => map,
+            _ => anyhow::bail!("Existing settings must be a JSON object"),
+        },
+    };
+    settings.insert("launch".to_string(), serde_json::to_value(launch)?);
+    serde_json::to_string_pretty(&settings).context("Failed to serialize settings")
+}
+
+/// Workspace snippets file (`.vscode/*.code-snippets`) with one snippet per configuration.
+/// Each snippet inserts the configuration into a launch.json `configurations` array.
+pub fn snippets(launch: &LaunchJson) -> Result<String> {
+    let mut snippets = Map::new();
+    for config in launch.configurations() {
+        let name = serde_json::to_value(config)?
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        // `$` starts a snippet variable, so `${workspaceFolder}` must be escaped
+        let body: Vec<String> = serde_json::to_string_pretty(config)?
+            .lines()
+            .map(|line| line.replace('$', "\\$"))
+            .collect();
+        snippets.insert(
+            name.clone(),
+            json!({
+                "scope": "json,jsonc",
+                "prefix": format!("launch: {}", name),
+                "description": format!("mklaunch configuration '{}'", name),
+                "body": body,
+            }),
+        );
+    }
+    serde_json::to_string_pretty(&snippets).context("Failed to serialize snippets")
+}
@@ -0,0 +1,35 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Reads a text file written as UTF-8 (with or without a byte order mark) or UTF-16
+/// (little or big endian, detected from the BOM or, without one, from the zero bytes
+/// around the first ASCII character), as Windows editors commonly save JSON
+pub(crate) fn read_text(path: &Path) -> io::Result<String> {
+    decode(&fs::read(path)?).map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+fn decode(bytes: &[u8]) -> Result<String, String> {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => utf8(rest),
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [first, 0, ..] if *first != 0 => utf16(bytes, u16::from_le_bytes),
+        [0, second, ..] if *second != 0 => utf16(bytes, u16::from_be_bytes),
+        _ => utf8(bytes),
+    }
+}
+
+fn utf8(bytes: &[u8]) -> Result<String, String> {
+    String::from_utf8(bytes.to_vec()).map_err(|e| format!("invalid UTF-8: {}", e))
+}
+
+fn utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Result<String, String> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err("invalid UTF-16: odd number of bytes".to_string());
+    }
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|e| format!("invalid UTF-16: {}", e))
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Keys of a configuration outside the typed ones: the keys of its template, shared with
+/// every other configuration resolved from it, under the keys the configuration set or
+/// removed itself. A template key is only copied when a configuration changes it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExtraKeys {
+    template: Arc<Map<String, Value>>,
+    /// Keys set by the configuration; `None` removes a template key
+    own: BTreeMap<String, Option<Value>>,
+}
+
+impl ExtraKeys {
+    pub fn shared(template: Arc<Map<String, Value>>) -> Self {
+        Self {
+            template,
+            own: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self.own.get(key) {
+            Some(value) => value.as_ref(),
+            None => self.template.get(key),
+        }
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The value at `key`, copied out of the template first when it comes from there
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        if !self.own.contains_key(key) {
+            let value = self.template.get(key)?.clone();
+            self.own.insert(key.to_string(), Some(value));
+        }
+        self.own.get_mut(key)?.as_mut()
+    }
+
+    /// The value at `key`, set to `default()` when there is none
+    pub fn get_or_insert_with(&mut self, key: &str, default: impl FnOnce() -> Value) -> &mut Value {
+        if !self.contains_key(key) {
+            self.own.insert(key.to_string(), Some(default()));
+        }
+        self.get_mut(key).expect("set above")
+    }
+
+    pub fn insert(&mut self, key: String, value: Value) {
+        self.own.insert(key, Some(value));
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let own = if self.template.contains_key(key) {
+            self.own.insert(key.to_string(), None)
+        } else {
+            self.own.remove(key)
+        };
+        match own {
+            Some(value) => value,
+            None => self.template.get(key).cloned(),
+        }
+    }
+
+    /// Entries in alphabetical key order
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        let template = self
+            .template
+            .iter()
+            .filter(|(key, _)| !self.own.contains_key(*key));
+        let own = self
+            .own
+            .iter()
+            .filter_map(|(key, value)| Some((key, value.as_ref()?)));
+        let mut entries: Vec<(&String, &Value)> = template.chain(own).collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries.into_iter()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.iter().map(|(key, _)| key)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExtraKeys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Map::deserialize(deserializer).map(|map| Self::shared(Arc::new(map)))
+    }
+}
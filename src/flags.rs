@@ -0,0 +1,134 @@
+use crate::diagnostic::Diagnostic;
+use crate::encoding::read_text;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+use std::path::Path;
+
+/// What to do with flags repeated in the resolved args of a configuration, e.g. when
+/// `baseArgs` and `args` both pass `--verbose`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgsDedupe {
+    /// Report each configuration with repeated flags as a warning
+    Warn,
+    /// Drop the repeats from the written args
+    Remove,
+}
+
+/// How a flag listed in the known-flags file may repeat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FlagRule {
+    /// A switch: later identical occurrences are repeats
+    Once,
+    /// Takes a value (`--port 80` or `--port=80`): only the last occurrence counts, as
+    /// later sources override earlier ones
+    Last,
+    /// May be given any number of times, like `-I <dir>`
+    Repeat,
+}
+
+/// Flags whose repetition rules are known, loaded from `flags.json` next to the templates
+/// manifest, e.g. `{ "--verbose": "once", "--port": "last", "-I": "repeat" }`.
+/// Flags not listed repeat only when the flag and the value following it are identical.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct KnownFlags {
+    rules: BTreeMap<String, FlagRule>,
+}
+
+/// Args of a configuration repeating an earlier flag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Repeat {
+    /// Flag as written, without an inline `=value`
+    pub flag: String,
+    /// Indices of the args to drop
+    pub range: Range<usize>,
+}
+
+impl KnownFlags {
+    /// Loads a known-flags file; a missing file lists no flags
+    pub fn from_path(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = read_text(path)
+            .with_context(|| format!("Failed to read known-flags file: {}", path.display()))?;
+        let rules = serde_json::from_str(&content)
+            .map_err(|e| Diagnostic::from_json_error(path, &e))
+            .context(
+                "Failed to parse known-flags file; expected an object of \"once\", \"last\" or \"repeat\" by flag",
+            )?;
+        Ok(Self { rules })
+    }
+
+    /// Args repeating an earlier flag, in arg order. Args after `--` are never repeats.
+    pub fn repeats(&self, args: &[String]) -> Vec<Repeat> {
+        // Occurrences of each flag (or flag and value) seen so far, by key
+        let mut seen: BTreeMap<String, Vec<Range<usize>>> = BTreeMap::new();
+        let mut drop: BTreeSet<(usize, usize)> = BTreeSet::new();
+        let mut flags: BTreeMap<usize, String> = BTreeMap::new();
+
+        let takes_next = |idx: usize| args.get(idx).is_some_and(|a| !a.starts_with('-'));
+        let mut idx = 0;
+        while idx < args.len() {
+            let arg = &args[idx];
+            if arg == "--" {
+                break;
+            }
+            if !arg.starts_with('-') || arg == "-" {
+                idx += 1;
+                continue;
+            }
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, _)) => (flag, true),
+                None => (arg.as_str(), false),
+            };
+            let (range, key) = match self.rules.get(flag) {
+                Some(FlagRule::Repeat) => {
+                    idx += if !inline && takes_next(idx + 1) { 2 } else { 1 };
+                    continue;
+                }
+                Some(FlagRule::Once) => (idx..idx + 1, arg.clone()),
+                Some(FlagRule::Last) => {
+                    let end = if !inline && takes_next(idx + 1) {
+                        idx + 2
+                    } else {
+                        idx + 1
+                    };
+                    (idx..end, flag.to_string())
+                }
+                None => {
+                    let end = if !inline && takes_next(idx + 1) {
+                        idx + 2
+                    } else {
+                        idx + 1
+                    };
+                    (idx..end, args[idx..end].join("\u{0}"))
+                }
+            };
+            let earlier = seen.entry(key).or_default();
+            if self.rules.get(flag) == Some(&FlagRule::Last) {
+                // Every earlier occurrence gives way to this one
+                for range in earlier.drain(..) {
+                    flags.insert(range.start, flag.to_string());
+                    drop.insert((range.start, range.end));
+                }
+            } else if !earlier.is_empty() {
+                flags.insert(range.start, flag.to_string());
+                drop.insert((range.start, range.end));
+                idx = range.end;
+                continue;
+            }
+            idx = range.end;
+            earlier.push(range);
+        }
+
+        drop.into_iter()
+            .map(|(start, end)| Repeat {
+                flag: flags[&start].clone(),
+                range: start..end,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,204 @@
+use crate::atomic::write_atomic;
+use crate::diagnostic::Diagnostic;
+use crate::encoding::read_text;
+use crate::generator::list_config_dir;
+use crate::schema::{CONFIG_KEYS, manifest_files};
+use crate::table::is_table;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Unit of indentation in formatted files
+const INDENT: &str = "  ";
+
+/// Arrays of scalars are kept on one line when the line stays within this many columns
+const MAX_WIDTH: usize = 100;
+
+/// Leading keys of a templates manifest
+const MANIFEST_KEYS: [&str; 3] = ["$schema", "schemaVersion", "templates"];
+
+/// Leading keys of a template, after which the launch settings follow alphabetically
+const TEMPLATE_KEYS: [&str; 8] = [
+    "name",
+    "type",
+    "request",
+    "program",
+    "stopAtEntry",
+    "allowOverrides",
+    "requireFromConfig",
+    "rust",
+];
+
+/// Leading keys of a config file written as an object
+const CONFIG_FILE_KEYS: [&str; 3] = ["$schema", "schemaVersion", "configurations"];
+
+/// What a JSON value is in an input file, which decides the order of its keys
+#[derive(Clone, Copy)]
+enum Shape {
+    Manifest,
+    Template,
+    ConfigFile,
+    Entry,
+    Other,
+}
+
+impl Shape {
+    fn leading_keys(self) -> &'static [&'static str] {
+        match self {
+            Shape::Manifest => &MANIFEST_KEYS,
+            Shape::Template => &TEMPLATE_KEYS,
+            Shape::ConfigFile => &CONFIG_FILE_KEYS,
+            Shape::Entry => &CONFIG_KEYS,
+            Shape::Other => &[],
+        }
+    }
+
+    /// Shape of the value under `key` (or of the items of an array under `key`)
+    fn child(self, key: &str) -> Shape {
+        match (self, key) {
+            (Shape::Manifest, "templates") => Shape::Template,
+            (Shape::ConfigFile, "configurations") => Shape::Entry,
+            (Shape::Entry, "variants" | "windows" | "linux" | "osx") => Shape::Entry,
+            _ => Shape::Other,
+        }
+    }
+}
+
+/// Rewrites the templates manifests and the JSON config files (fragments included) of
+/// every configs directory in
+/// their canonical layout: keys in the order the README documents them followed by the
+/// rest alphabetically, two-space indentation, short arrays of scalars on one line, LF
+/// line endings and UTF-8 without a byte order mark. With `check`, nothing is written.
+/// Returns the files that were (or, with `check`, would be) changed.
+pub fn format_inputs(
+    templates_paths: &[PathBuf],
+    configs_dirs: &[PathBuf],
+    check: bool,
+) -> Result<Vec<PathBuf>> {
+    let mut inputs = Vec::new();
+    for path in templates_paths {
+        for manifest in manifest_files(path)? {
+            inputs.push((manifest, Shape::Manifest));
+        }
+    }
+    for configs_dir in configs_dirs.iter().filter(|d| d.exists()) {
+        let (config_files, fragments) = list_config_dir(configs_dir, false)?;
+        for path in config_files.into_iter().chain(fragments) {
+            if !is_table(&path) {
+                inputs.push((path, Shape::ConfigFile));
+            }
+        }
+    }
+
+    let mut changed = Vec::new();
+    for (path, shape) in inputs {
+        let bytes =
+            std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let content =
+            read_text(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|e| Diagnostic::from_json_error(&path, &e))
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        let formatted = format_value(&value, shape);
+        if bytes != formatted.as_bytes() {
+            if !check {
+                write_atomic(&path, formatted.as_bytes())?;
+            }
+            changed.push(path);
+        }
+    }
+    Ok(changed)
+}
+
+/// Canonical text of a templates manifest holding `value`
+pub(crate) fn format_manifest(value: &Value) -> String {
+    format_value(value, Shape::Manifest)
+}
+
+/// Canonical text of a config file holding `value`, an array of entries
+pub(crate) fn format_config_file(value: &Value) -> String {
+    format_value(value, Shape::ConfigFile)
+}
+
+/// Canonical text of an input file holding `value`
+fn format_value(value: &Value, shape: Shape) -> String {
+    let shape = match (shape, value) {
+        // Config files are an array of entries, or a legacy single entry
+        (Shape::ConfigFile, Value::Array(_)) => Shape::Entry,
+        (Shape::ConfigFile, Value::Object(object)) if !object.contains_key("configurations") => {
+            Shape::Entry
+        }
+        _ => shape,
+    };
+    let mut out = String::new();
+    write_value(&mut out, value, shape, 0, 0);
+    out.push('\n');
+    out
+}
+
+/// Appends `value` to `out`; `depth` is the indentation level of its line and `column`
+/// the width of the line before it
+fn write_value(out: &mut String, value: &Value, shape: Shape, depth: usize, column: usize) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            let mut keys: Vec<&String> = shape
+                .leading_keys()
+                .iter()
+                .filter_map(|key| object.get_key_value(*key).map(|(k, _)| k))
+                .collect();
+            keys.extend(
+                object
+                    .keys()
+                    .filter(|key| !shape.leading_keys().contains(&key.as_str())),
+            );
+
+            out.push('{');
+            for (idx, key) in keys.into_iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                out.push('\n');
+                let prefix = format!(
+                    "{}{}: ",
+                    INDENT.repeat(depth + 1),
+                    Value::from(key.as_str())
+                );
+                out.push_str(&prefix);
+                write_value(out, &object[key], shape.child(key), depth + 1, prefix.len());
+            }
+            out.push('\n');
+            out.push_str(&INDENT.repeat(depth));
+            out.push('}');
+        }
+        Value::Array(items) if !items.is_empty() => {
+            let scalars = items
+                .iter()
+                .all(|item| !matches!(item, Value::Array(_) | Value::Object(_)));
+            if scalars {
+                let inline: Vec<String> = items.iter().map(Value::to_string).collect();
+                let inline = format!("[{}]", inline.join(", "));
+                // Room for the comma that may follow
+                if column + inline.len() < MAX_WIDTH {
+                    out.push_str(&inline);
+                    return;
+                }
+            }
+
+            out.push('[');
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                out.push('\n');
+                let indent = INDENT.repeat(depth + 1);
+                out.push_str(&indent);
+                write_value(out, item, shape, depth + 1, indent.len());
+            }
+            out.push('\n');
+            out.push_str(&INDENT.repeat(depth));
+            out.push(']');
+        }
+        _ => out.push_str(&value.to_string()),
+    }
+}
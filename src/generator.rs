@@ -1,48 +1,531 @@
-use crate::schema::{BaseArgsFile, ConfigFile, Template, TemplateFile};
+use crate::adapters::AdapterSchemas;
+use crate::args::{args_from_command, split_command_line};
+use crate::atomic::GenerationGuard;
+use crate::cache::{CachedFile, ResolveCache, hash_file, hash_parts};
+use crate::cargo::{Artifact, CargoBlock, CargoKind, CargoTarget, program_path};
+use crate::devcontainer::{self, DevContainer, Environment};
+use crate::diagnostic::{Diagnostic, Diagnostics};
+use crate::discover::{DiscoveredTarget, DiscoveryKind, DiscoverySource};
+use crate::doctor::{self, ConfigHealth, Tasks};
+use crate::emit::OutputFormat;
+use crate::extra::ExtraKeys;
+use crate::flags::{ArgsDedupe, KnownFlags};
+use crate::git::policy_warnings;
+use crate::import::strip_jsonc;
+use crate::include::{ArgsLayer, RawEntry, note_args_layer, resolve_includes};
+use crate::lint::{LintWarning, check_variables, duplicate_content, input_ids, lint};
+use crate::order::{KeyLayout, Ordered};
+use crate::os::{OS_KEYS, PATH_KEYS, PathStyle, TargetOs};
+use crate::provenance::{Explanation, Provenance};
+use crate::runner::{CommandRunner, ProcessRunner};
+use crate::schema::{
+    ArgSetLibrary, ArrayMerge, AttachOptions, BaseArgsFile, CONFIG_KEYS, ConfigFile, Console,
+    ContainerMapping, Defaults, DisabledList, EnvFile, LaunchAfter, Template, TemplateFile,
+    Templates, glob_match, manifest_files, suggest,
+};
+use crate::settings::Settings;
+use crate::sources::source_manifests;
+use crate::table::is_table;
+use crate::tokens::expand_name;
 use anyhow::{Context, Result};
-use serde::Serialize;
-use serde_json::{Map, Value};
-use std::collections::BTreeMap;
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::cell::RefCell;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::field;
 
 /// Launch configuration (template + overrides) serialized with ordered keys.
-/// Order: type, request, name, program, then other keys.
-#[derive(Debug, Serialize)]
+/// Order: type, request, name, program, args, stopAtEntry, then other keys (alphabetically,
+/// or in template order when generated with [`KeyOrder::Template`]).
+/// Deserializes from a launch.json configuration, so generated output can be read back.
+#[derive(Debug, Deserialize)]
 pub struct LaunchConfig {
     #[serde(rename = "type")]
     type_field: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
     request: Option<String>,
     name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
     program: Option<String>,
+    /// Hand-written configurations often leave out `args`
+    #[serde(default)]
     args: Vec<String>,
-    #[serde(rename = "stopAtEntry", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "stopAtEntry")]
     stop_at_entry: Option<bool>,
     #[serde(flatten)]
-    rest: Map<String, Value>,
+    rest: ExtraKeys,
+    /// Key order of the template, applied to `rest` when serializing
+    #[serde(skip)]
+    layout: Option<Arc<KeyLayout>>,
+    /// `launchAfter` of the entry, applied once every configuration is resolved
+    #[serde(skip)]
+    launch_after: Option<LaunchAfter>,
+    /// Set by `perFile` entries, which debug the file open in the editor
+    #[serde(skip)]
+    per_file: bool,
+    /// JSON pointer of the entry in its config file, for error messages
+    #[serde(skip)]
+    pointer: String,
+    /// Name before `nameFormat` decorated it, which `launchAfter` of other entries refers to
+    #[serde(skip)]
+    undecorated_name: Option<String>,
+}
+
+impl Serialize for LaunchConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("type", &self.type_field)?;
+        if let Some(request) = &self.request {
+            map.serialize_entry("request", request)?;
+        }
+        map.serialize_entry("name", &self.name)?;
+        if let Some(program) = &self.program {
+            map.serialize_entry("program", program)?;
+        }
+        map.serialize_entry("args", &self.args)?;
+        if let Some(stop_at_entry) = &self.stop_at_entry {
+            map.serialize_entry("stopAtEntry", stop_at_entry)?;
+        }
+        match &self.layout {
+            Some(layout) => {
+                for (key, value) in layout.order(self.rest.iter()) {
+                    let layout = layout.child(key);
+                    map.serialize_entry(key, &Ordered { value, layout })?;
+                }
+            }
+            None => {
+                for (key, value) in self.rest.iter() {
+                    map.serialize_entry(key, value)?;
+                }
+            }
+        }
+        map.end()
+    }
 }
 
+/// Keys backed by typed `LaunchConfig` fields rather than the free-form extra keys
+const TYPED_KEYS: [&str; 6] = ["type", "request", "name", "program", "args", "stopAtEntry"];
+
 impl LaunchConfig {
+    /// Display name in VSCode's launch dropdown
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Debugger type (`type` in launch.json), e.g. `cppdbg`
+    pub fn debug_type(&self) -> &str {
+        &self.type_field
+    }
+
+    /// `launch` or `attach`, when set
+    pub fn request(&self) -> Option<&str> {
+        self.request.as_deref()
+    }
+
+    pub fn program(&self) -> Option<&str> {
+        self.program.as_deref()
+    }
+
+    /// Resolved args: baseArgs, then argSets, then the entry's own args
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Whether the configuration debugs the file open in the editor (`perFile`)
+    pub fn is_per_file(&self) -> bool {
+        self.per_file
+    }
+
+    /// Value of any top-level key as it is serialized
+    pub fn get(&self, key: &str) -> Option<Value> {
+        match key {
+            "type" => Some(json!(self.type_field)),
+            "request" => self.request.as_ref().map(|r| json!(r)),
+            "name" => Some(json!(self.name)),
+            "program" => self.program.as_ref().map(|p| json!(p)),
+            "args" => Some(json!(self.args)),
+            "stopAtEntry" => self.stop_at_entry.map(|s| json!(s)),
+            _ => self.rest.get(key).cloned(),
+        }
+    }
+
+    /// Sets a key outside the typed ones (`type`, `request`, `name`, `program`, `args`,
+    /// `stopAtEntry`), returning its previous value
+    pub fn set_extra(&mut self, key: &str, value: Value) -> Result<Option<Value>> {
+        if TYPED_KEYS.contains(&key) {
+            anyhow::bail!(
+                "'{}' is not an extra key and cannot be set with set_extra",
+                key
+            );
+        }
+        let previous = self.rest.remove(key);
+        self.rest.insert(key.to_string(), value);
+        Ok(previous)
+    }
+
+    /// Removes a key outside the typed ones, returning its value
+    pub fn remove_extra(&mut self, key: &str) -> Option<Value> {
+        self.rest.remove(key)
+    }
+
+    /// Serialized top-level keys, in output order
+    pub(crate) fn keys(&self) -> Vec<String> {
+        let mut keys = vec!["type".to_string()];
+        if self.request.is_some() {
+            keys.push("request".to_string());
+        }
+        keys.push("name".to_string());
+        if self.program.is_some() {
+            keys.push("program".to_string());
+        }
+        keys.push("args".to_string());
+        if self.stop_at_entry.is_some() {
+            keys.push("stopAtEntry".to_string());
+        }
+        keys.extend(self.rest.keys().cloned());
+        keys
+    }
+
+    /// Sets a top-level key, routing the typed keys to their fields
+    pub(crate) fn set(&mut self, key: &str, value: Value) -> Result<()> {
+        fn string(value: Value) -> Result<String> {
+            match value {
+                Value::String(s) => Ok(s),
+                _ => anyhow::bail!("expected a string"),
+            }
+        }
+
+        match key {
+            "type" => self.type_field = string(value)?,
+            "request" => self.request = Some(string(value)?),
+            "name" => self.name = string(value)?,
+            "program" => self.program = Some(string(value)?),
+            "args" => self.args = serde_json::from_value(value)?,
+            "stopAtEntry" => {
+                self.stop_at_entry = Some(
+                    value
+                        .as_bool()
+                        .ok_or_else(|| anyhow::anyhow!("expected a boolean"))?,
+                )
+            }
+            _ => {
+                self.rest.insert(key.to_string(), value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the value at a dotted `path` (e.g. `env.RUST_LOG`), creating the objects
+    /// leading to it
+    pub(crate) fn set_path(&mut self, path: &str, value: Value) -> Result<()> {
+        let Some((key, rest)) = path.split_once('.') else {
+            return self.set(path, value);
+        };
+        if TYPED_KEYS.contains(&key) {
+            anyhow::bail!("'{}' has no nested keys", key);
+        }
+        let mut target = self
+            .rest
+            .get_or_insert_with(key, || Value::Object(Map::new()));
+        let mut walked = key.to_string();
+        let mut segments = rest.split('.').peekable();
+        while let Some(segment) = segments.next() {
+            let Value::Object(object) = target else {
+                anyhow::bail!("'{}' is not an object", walked);
+            };
+            if segments.peek().is_none() {
+                object.insert(segment.to_string(), value);
+                return Ok(());
+            }
+            target = object
+                .entry(segment)
+                .or_insert_with(|| Value::Object(Map::new()));
+            walked = format!("{}.{}", walked, segment);
+        }
+        Ok(())
+    }
+
+    /// Replaces every string of the configuration, at any depth, for which `f` returns a
+    /// new value
+    pub(crate) fn rewrite_strings(&mut self, f: &impl Fn(&str) -> Option<String>) {
+        fn walk(value: &mut Value, f: &impl Fn(&str) -> Option<String>) {
+            match value {
+                Value::String(s) => {
+                    if let Some(new) = f(s) {
+                        *s = new;
+                    }
+                }
+                Value::Array(items) => items.iter_mut().for_each(|item| walk(item, f)),
+                Value::Object(object) => object.values_mut().for_each(|item| walk(item, f)),
+                _ => {}
+            }
+        }
+        fn rewrites(value: &Value, f: &impl Fn(&str) -> Option<String>) -> bool {
+            match value {
+                Value::String(s) => f(s).is_some(),
+                Value::Array(items) => items.iter().any(|item| rewrites(item, f)),
+                Value::Object(object) => object.values().any(|item| rewrites(item, f)),
+                _ => false,
+            }
+        }
+        for s in self.program.iter_mut().chain(&mut self.args) {
+            if let Some(new) = f(s) {
+                *s = new;
+            }
+        }
+        // Only the values that change are copied out of the template
+        let changed: Vec<String> = self
+            .rest
+            .iter()
+            .filter(|(_, value)| rewrites(value, f))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in changed {
+            if let Some(value) = self.rest.get_mut(&key) {
+                walk(value, f);
+            }
+        }
+    }
+
+    /// Rewrites `program` and the other known path keys with `style`'s separators
+    pub(crate) fn normalize_paths(&mut self, style: PathStyle) {
+        if let Some(program) = &mut self.program {
+            *program = style.normalize(program);
+        }
+        for key in PATH_KEYS {
+            if let Some(Value::String(path)) = self.rest.get_mut(key) {
+                *path = style.normalize(path);
+            }
+        }
+    }
+
+    /// Removes a top-level key; `type`, `name` and `args` always stay
+    pub(crate) fn unset(&mut self, key: &str) {
+        match key {
+            "request" => self.request = None,
+            "program" => self.program = None,
+            "stopAtEntry" => self.stop_at_entry = None,
+            _ => {
+                self.rest.remove(key);
+            }
+        }
+    }
+
     /// Backward-compatible helper that delegates to `Resolver`.
+    /// Reads the manifest on every call; build a `Resolver` once to resolve many configs.
+    #[deprecated(note = "use Resolver::from_manifest_path once and call resolve on it")]
     pub fn from_template_and_config(
         templates_manifest: &Path,
         config: ConfigFile,
         template_override: Option<Value>,
     ) -> Result<Self> {
-        let resolver = Resolver::new(TemplateFile::from_path(templates_manifest)?);
-        resolver.resolve(config, template_override)
+        Resolver::from_manifest_path(templates_manifest)?.resolve(config, template_override)
     }
 }
+
 /// Resolves `ConfigFile` into `LaunchConfig` using templates manifest context.
-pub(crate) struct Resolver {
+/// The manifest is read once; a resolver can be cloned and reused for any number of configs.
+#[derive(Debug, Clone)]
+pub struct Resolver {
     templates: TemplateFile,
+    arg_sets: ArgSetLibrary,
+    flatten_os: Option<TargetOs>,
+    check_paths: bool,
+    check_programs: bool,
+    allow_missing_base_args: bool,
+    root: PathBuf,
+    key_order: KeyOrder,
+    defaults: Defaults,
+    group_root: Option<PathBuf>,
+    allow_exec: bool,
+    /// Suffix of templates preferred over the template an entry extends, e.g. `devcontainer`
+    /// for `cpp.devcontainer`
+    template_variant: Option<&'static str>,
+    dedupe_args: Option<ArgsDedupe>,
+    known_flags: KnownFlags,
+    runner: Arc<dyn CommandRunner>,
+    /// Timeout of the commands of entries without `timeoutMs`
+    command_timeout: Option<Duration>,
+    /// `nameFormat` of settings.json
+    name_format: Option<String>,
+    warnings: RefCell<Vec<String>>,
+    /// Executables built by each `cargo build` invocation, shared by the entries using it
+    cargo_builds: RefCell<BTreeMap<Vec<String>, Vec<Artifact>>>,
 }
 
 impl Resolver {
-    pub fn new(templates: TemplateFile) -> Self {
-        Self { templates }
+    /// Resolver for the templates of a manifest, or of every `*.templates.json` manifest in
+    /// a directory, with default options
+    pub fn from_manifest_path(path: &Path) -> Result<Self> {
+        Ok(Self::new(TemplateFile::from_path(path)?))
+    }
+
+    pub(crate) fn new(templates: TemplateFile) -> Self {
+        Self {
+            templates,
+            arg_sets: ArgSetLibrary::default(),
+            flatten_os: None,
+            check_paths: false,
+            check_programs: false,
+            allow_missing_base_args: false,
+            root: PathBuf::new(),
+            key_order: KeyOrder::Alphabetical,
+            defaults: Defaults::default(),
+            group_root: None,
+            allow_exec: false,
+            template_variant: None,
+            dedupe_args: None,
+            known_flags: KnownFlags::default(),
+            runner: Arc::new(ProcessRunner),
+            command_timeout: None,
+            name_format: None,
+            warnings: RefCell::new(Vec::new()),
+            cargo_builds: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Treats every missing `baseArgs` file as an empty arg list, as `baseArgsOptional` does
+    pub fn with_allow_missing_base_args(mut self, allow: bool) -> Self {
+        self.allow_missing_base_args = allow;
+        self
+    }
+
+    /// Reads workspace-relative `baseArgs`, `envFile` and `cwd` paths from `root`
+    /// instead of the current directory
+    pub fn with_root(mut self, root: PathBuf) -> Self {
+        self.root = root;
+        self
+    }
+
+    /// Merges `defaults` into every template, the template's own keys taking precedence
+    pub(crate) fn with_defaults(mut self, defaults: Defaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Groups configurations without a `group` by the subdirectory of `configs_dir` their
+    /// config file is in
+    pub fn with_group_by_dir(mut self, configs_dir: Option<PathBuf>) -> Self {
+        self.group_root = configs_dir;
+        self
+    }
+
+    /// Runs the `argsFromCommand` of configurations; without it they are an error
+    pub fn with_allow_exec(mut self, allow: bool) -> Self {
+        self.allow_exec = allow;
+        self
+    }
+
+    /// Reports or drops flags repeated in the resolved args of a configuration
+    pub fn with_dedupe_args(mut self, mode: Option<ArgsDedupe>) -> Self {
+        self.dedupe_args = mode;
+        self
+    }
+
+    /// Runs `argsFromCommand` shells and cargo builds through `runner` instead of as child
+    /// processes
+    pub fn with_command_runner(mut self, runner: Arc<dyn CommandRunner>) -> Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Kills the commands of entries without their own `timeoutMs` after `timeout`
+    pub fn with_command_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.command_timeout = timeout;
+        self
+    }
+
+    /// Decorates every name resolved by `resolve_entry` with `format`, where `{name}` is the
+    /// name after token expansion and the other name tokens are available, e.g.
+    /// `[{template}] {name}`
+    pub fn with_name_format(mut self, format: Option<String>) -> Self {
+        self.name_format = format;
+        self
+    }
+
+    /// Timeout of the commands run for `config`
+    fn timeout_for(&self, config: &ConfigFile) -> Option<Duration> {
+        config
+            .timeout_ms
+            .map(Duration::from_millis)
+            .or(self.command_timeout)
+    }
+
+    /// Repetition rules of the flags named in a known-flags file
+    pub(crate) fn with_known_flags(mut self, known_flags: KnownFlags) -> Self {
+        self.known_flags = known_flags;
+        self
+    }
+
+    /// Orders the keys of resolved configurations as in their template
+    pub fn with_key_order(mut self, key_order: KeyOrder) -> Self {
+        self.key_order = key_order;
+        self
+    }
+
+    /// Uses `<template>.<variant>` instead of `<template>` wherever the manifests define it
+    pub(crate) fn with_template_variant(mut self, variant: Option<&'static str>) -> Self {
+        self.template_variant = variant;
+        self
+    }
+
+    /// Name of the template used for entries extending `extends`
+    fn template_name(&self, extends: &str) -> String {
+        if let Some(variant) = self.template_variant {
+            let name = format!("{}.{}", extends, variant);
+            if self.templates.get(&name).is_ok() {
+                return name;
+            }
+        }
+        extends.to_string()
+    }
+
+    /// Key layout attached to configurations extending `template`
+    fn layout(&self, template: &str) -> Option<Arc<KeyLayout>> {
+        match self.key_order {
+            KeyOrder::Template => self
+                .templates
+                .get(&self.template_name(template))
+                .ok()?
+                .layout
+                .clone(),
+            KeyOrder::Alphabetical => None,
+        }
+    }
+
+    /// Non-fatal problems found while resolving, in the order they were found
+    pub fn take_warnings(&self) -> Vec<String> {
+        self.warnings.take()
+    }
+
+    /// Fails when a resolved `cwd` is not an existing directory
+    pub fn with_check_paths(mut self, check_paths: bool) -> Self {
+        self.check_paths = check_paths;
+        self
+    }
+
+    /// Fails when a resolved `program` is not an existing file
+    pub fn with_check_programs(mut self, check_programs: bool) -> Self {
+        self.check_programs = check_programs;
+        self
+    }
+
+    /// Merges the override block for `os` into the top level and drops all OS blocks
+    pub fn with_flatten_os(mut self, os: Option<TargetOs>) -> Self {
+        self.flatten_os = os;
+        self
+    }
+
+    /// Uses `arg_sets` to resolve `argSets` references
+    pub(crate) fn with_arg_sets(mut self, arg_sets: ArgSetLibrary) -> Self {
+        self.arg_sets = arg_sets;
+        self
     }
 
     /// Build a configuration from templates dir and ConfigFile.
@@ -52,23 +535,229 @@ impl Resolver {
         config: ConfigFile,
         template_override: Option<Value>,
     ) -> Result<LaunchConfig> {
-        let tmpl = match template_override {
-            Some(v) => Template::from_value(v)?,
-            None => self.templates.get(&config.extends)?.clone(),
+        let mut provenance = Provenance::default();
+        self.resolve_traced(config, template_override, "config", &mut provenance)
+    }
+
+    /// Resolves a config entry loaded from `source` and expands name tokens:
+    /// `{file}` (config file stem), `{template}` (extended template) and `{args[N]}`,
+    /// then applies the name format
+    pub fn resolve_entry(&self, source: &Path, config: ConfigFile) -> Result<LaunchConfig> {
+        let _span =
+            tracing::trace_span!("resolve", config = %config.name, source = %source.display())
+                .entered();
+        let mut provenance = Provenance::default();
+        self.resolve_entry_traced(source, config, &mut provenance)
+    }
+
+    /// `resolve_entry` that also records where each value came from
+    pub fn resolve_entry_traced(
+        &self,
+        source: &Path,
+        mut config: ConfigFile,
+        provenance: &mut Provenance,
+    ) -> Result<LaunchConfig> {
+        if config.group.is_none()
+            && !config.per_file
+            && let Some(root) = &self.group_root
+        {
+            config.group = dir_group(root, source);
+        }
+        let mut vars = BTreeMap::new();
+        vars.insert("template", config.extends.clone());
+        if let Some(stem) = source.file_stem().and_then(|s| s.to_str()) {
+            vars.insert("file", stem.to_string());
+        }
+
+        let config_label = format!("config {}", source.display());
+        let mut launch = self.resolve_traced(config, None, &config_label, provenance)?;
+        let expanded = expand_name(&launch.name, &vars, &launch.args)?;
+        if expanded != launch.name {
+            provenance.set_key("name", format!("{} (name tokens)", config_label));
+            launch.name = expanded;
+        }
+        if let Some(format) = &self.name_format {
+            vars.insert("name", launch.name.clone());
+            let decorated = expand_name(format, &vars, &launch.args)
+                .context("Failed to apply nameFormat of settings.json")?;
+            launch.undecorated_name = Some(std::mem::replace(&mut launch.name, decorated));
+            provenance.set_key("name", format!("{} (nameFormat)", config_label));
+        }
+        Ok(launch)
+    }
+
+    fn resolve_traced(
+        &self,
+        config: ConfigFile,
+        template_override: Option<Value>,
+        config_label: &str,
+        provenance: &mut Provenance,
+    ) -> Result<LaunchConfig> {
+        let (tmpl, template_label) = match template_override {
+            Some(v) => (
+                Arc::new(Template::from_value(v)?),
+                "template override".to_string(),
+            ),
+            None => {
+                let name = self.template_name(&config.extends);
+                (
+                    Arc::clone(self.templates.get(&name)?),
+                    format!("template '{}'", name),
+                )
+            }
+        };
+        self.build_from_template(config, tmpl, config_label, &template_label, provenance)
+    }
+
+    /// `program` built by the `cargo` block of entry `name`, and the test filter to pass
+    /// as its first argument
+    fn cargo_program(
+        &self,
+        name: &str,
+        cargo: &CargoTarget,
+        timeout: Option<Duration>,
+    ) -> Result<(String, Option<String>)> {
+        if cargo.filter.is_some() && cargo.kind != CargoKind::Test {
+            anyhow::bail!(
+                "'{}' sets a cargo filter, which only test targets take",
+                name
+            );
+        }
+        let args = cargo.build_args();
+        if !self.allow_exec {
+            anyhow::bail!(
+                "'{}' uses cargo; pass --allow-exec to run `cargo {}`",
+                name,
+                args.join(" ")
+            );
+        }
+        let mut builds = self.cargo_builds.borrow_mut();
+        let artifacts = match builds.entry(args) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let artifacts =
+                    crate::cargo::build(&self.root, entry.key(), timeout, self.runner.as_ref())
+                        .with_context(|| {
+                            format!("Failed to build the cargo target of '{}'", name)
+                        })?;
+                entry.insert(artifacts)
+            }
         };
-        Self::build_from_template(config, tmpl)
+        let executable = cargo
+            .select(artifacts)
+            .with_context(|| format!("Failed to find the cargo target of '{}'", name))?;
+        Ok((program_path(&self.root, executable), cargo.filter.clone()))
     }
 
-    fn build_from_template(config: ConfigFile, tmpl: Template) -> Result<LaunchConfig> {
-        // Build args: baseArgs (if any) + args (if any). Always present (can be empty)
+    fn build_from_template(
+        &self,
+        mut config: ConfigFile,
+        tmpl: Arc<Template>,
+        config_label: &str,
+        template_label: &str,
+        provenance: &mut Provenance,
+    ) -> Result<LaunchConfig> {
+        tmpl.check_contract(&config, template_label)?;
+        if config.per_file {
+            config
+                .group
+                .get_or_insert_with(|| PER_FILE_GROUP.to_string());
+        }
+
+        // Build args: baseArgs + argSets + args (each optional). Always present (can be empty)
         let mut args: Vec<String> = Vec::new();
+        let program = match &config.cargo {
+            Some(_) if config.program.is_some() => {
+                anyhow::bail!("'{}' cannot set both program and cargo", config.name)
+            }
+            Some(CargoBlock::Target(cargo)) => {
+                let (program, filter) =
+                    self.cargo_program(&config.name, cargo, self.timeout_for(&config))?;
+                if let Some(filter) = filter {
+                    provenance.push_args([&filter], "cargo filter");
+                    args.push(filter);
+                }
+                Some(program)
+            }
+            // CodeLLDB builds the target itself when the session starts
+            Some(CargoBlock::Lldb(_)) => None,
+            None => config.program.clone().or_else(|| tmpl.program.clone()),
+        };
         if let Some(base_path) = &config.base_args {
-            let base = BaseArgsFile::from_path(base_path)?;
-            args.extend(base.args);
+            let local = on_disk(&self.root, base_path);
+            if !local.exists() && (self.allow_missing_base_args || config.base_args_optional) {
+                self.warnings.borrow_mut().push(format!(
+                    "baseArgs file {} of '{}' does not exist; using no base args",
+                    local.display(),
+                    config.name
+                ));
+            } else {
+                let base = BaseArgsFile::from_path(&local)?;
+                provenance.push_args(&base.args, &format!("baseArgs {}", base_path.display()));
+                args.extend(base.args);
+            }
+        }
+        for name in config.arg_sets.iter().flatten() {
+            let set = self.arg_sets.get(name)?;
+            provenance.push_args(set, &format!("argSets '{}'", name));
+            args.extend_from_slice(set);
         }
         if let Some(extra) = &config.args {
+            provenance.push_args(extra, &format!("args in {}", config_label));
             args.extend(extra.clone());
         }
+        if let Some(line) = &config.args_string {
+            if config.args.is_some() {
+                anyhow::bail!(
+                    "'{}' cannot set both args and argsString in {}",
+                    config.name,
+                    config_label
+                );
+            }
+            let words = split_command_line(line).map_err(|e| {
+                anyhow::anyhow!("'{}' has an invalid argsString: {}", config.name, e)
+            })?;
+            provenance.push_args(&words, &format!("argsString in {}", config_label));
+            args.extend(words);
+        }
+        if let Some(command) = &config.args_from_command {
+            if !self.allow_exec {
+                anyhow::bail!(
+                    "'{}' uses argsFromCommand; pass --allow-exec to run `{}`",
+                    config.name,
+                    command
+                );
+            }
+            let output = args_from_command(
+                command,
+                &self.root,
+                self.timeout_for(&config),
+                self.runner.as_ref(),
+            )?;
+            provenance.push_args(&output, &format!("argsFromCommand `{}`", command));
+            args.extend(output);
+        }
+
+        if let Some(mode) = self.dedupe_args {
+            let repeats = self.known_flags.repeats(&args);
+            if !repeats.is_empty() {
+                let mut flags: Vec<&str> = repeats.iter().map(|r| r.flag.as_str()).collect();
+                flags.dedup();
+                match mode {
+                    ArgsDedupe::Warn => self.warnings.borrow_mut().push(format!(
+                        "'{}' repeats {} in its args",
+                        config.name,
+                        flags.join(", ")
+                    )),
+                    ArgsDedupe::Remove => {
+                        for repeat in repeats.into_iter().rev() {
+                            args.drain(repeat.range.clone());
+                            provenance.remove_args(repeat.range);
+                        }
+                    }
+                }
+            }
+        }
 
         // Sanity check: templates must not provide args (enforced at parse time)
         debug_assert!(
@@ -76,150 +765,2825 @@ impl Resolver {
             "Template rest must not contain 'args'"
         );
 
-        Ok(LaunchConfig {
-            type_field: tmpl.type_field,
-            request: tmpl.request,
-            name: config.name,
-            program: tmpl.program,
+        // The template's keys are shared; only the ones the entry changes are copied
+        let mut launch = LaunchConfig {
+            type_field: tmpl.type_field.clone(),
+            request: tmpl.request.clone(),
+            name: config.name.clone(),
+            program,
             args,
             stop_at_entry: tmpl.stop_at_entry,
-            rest: tmpl.rest.clone(),
-        })
+            rest: ExtraKeys::shared(tmpl.rest.clone()),
+            layout: match self.key_order {
+                KeyOrder::Template => tmpl.layout.clone(),
+                KeyOrder::Alphabetical => None,
+            },
+            launch_after: config.launch_after.clone(),
+            per_file: config.per_file,
+            pointer: config.pointer.clone(),
+            undecorated_name: None,
+        };
+        self.apply_defaults(&mut launch, provenance)?;
+        provenance.set_key("name", config_label);
+        match &config.cargo {
+            Some(CargoBlock::Target(_)) => {
+                provenance.set_key("program", format!("cargo in {}", config_label))
+            }
+            Some(CargoBlock::Lldb(cargo)) => {
+                if launch.type_field != "lldb" {
+                    anyhow::bail!(
+                        "'{}' sets CodeLLDB's cargo block (with args), but {} has type '{}'",
+                        config.name,
+                        template_label,
+                        launch.type_field
+                    );
+                }
+                let source = if launch.rest.contains_key("cargo") {
+                    format!("{} + cargo in {}", template_label, config_label)
+                } else {
+                    format!("cargo in {}", config_label)
+                };
+                let merged = cargo
+                    .merge_into(launch.rest.get("cargo"))
+                    .with_context(|| format!("In the cargo block of '{}'", config.name))?;
+                launch.rest.insert("cargo".to_string(), merged);
+                provenance.set_key("cargo", source);
+            }
+            None if config.program.is_some() => provenance.set_key("program", config_label),
+            None => {}
+        }
+        provenance.set_key("args", "resolved args (see per-argument sources)");
+
+        for key in config.unset.iter().flatten() {
+            if matches!(key.as_str(), "type" | "name" | "args") {
+                anyhow::bail!("'{}' cannot unset '{}'", config.name, key);
+            }
+            launch.unset(key);
+            provenance.remove_key(key);
+        }
+
+        for (key, merge) in config.array_merge.iter().flatten() {
+            apply_array_merge(&mut launch.rest, key, merge)?;
+            let mode = match merge {
+                ArrayMerge::Append(_) => "append",
+                ArrayMerge::Prepend(_) => "prepend",
+                ArrayMerge::Replace(_) => "replace",
+            };
+            provenance.set_key(
+                key,
+                format!(
+                    "{} + arrayMerge {} in {}",
+                    template_label, mode, config_label
+                ),
+            );
+        }
+        if let Some(attach) = &config.attach {
+            let key = apply_attach(&mut launch, attach);
+            for key in ["request", key] {
+                provenance.set_key(key, format!("attach in {}", config_label));
+            }
+        }
+        if let Some(core_dump) = &config.core_dump {
+            if config.attach.is_some() {
+                anyhow::bail!("'{}' cannot set both attach and coreDump", config.name);
+            }
+            let path = find_core_dump(&self.root, core_dump)?;
+            for key in apply_core_dump(&mut launch, &path)? {
+                provenance.set_key(key, format!("coreDump {}", path));
+            }
+        }
+        if let Some(container) = &config.container {
+            for key in apply_container(&mut launch, container)? {
+                provenance.set_key(
+                    key,
+                    format!("{} + container in {}", template_label, config_label),
+                );
+            }
+        }
+        if let Some(env_file) = &config.env_file {
+            apply_env_file(&mut launch, env_file.path(), &self.root)?;
+            let key = if launch.rest.contains_key("envFile") {
+                "envFile"
+            } else {
+                "environment"
+            };
+            provenance.set_key(key, format!("envFile {}", env_file.path().display()));
+        }
+        if let Some(cwd) = &config.cwd {
+            launch
+                .rest
+                .insert("cwd".to_string(), Value::String(cwd.clone()));
+            provenance.set_key("cwd", config_label);
+        }
+        for (key, task) in [
+            ("preLaunchTask", &config.pre_launch_task),
+            ("postDebugTask", &config.post_debug_task),
+        ] {
+            match task {
+                Some(Some(task)) => {
+                    launch
+                        .rest
+                        .insert(key.to_string(), Value::String(task.clone()));
+                    provenance.set_key(key, config_label);
+                }
+                Some(None) => {
+                    launch.unset(key);
+                    provenance.remove_key(key);
+                }
+                None => {}
+            }
+        }
+        for key in apply_console(&mut launch, &config)? {
+            provenance.set_key(key, config_label);
+        }
+        for key in apply_debugger(&mut launch, &config)? {
+            provenance.set_key(key, config_label);
+        }
+        for (key, object) in config.object_overrides() {
+            merge_object(&mut launch, key, object);
+            provenance.set_key(key, format!("{} + {}", template_label, config_label));
+        }
+        if apply_presentation(&mut launch, &config) {
+            provenance.set_key(
+                "presentation",
+                format!("{} + {}", template_label, config_label),
+            );
+        }
+
+        for (key, block) in config.os_blocks() {
+            merge_object(&mut launch, key, block);
+            provenance.set_key(key, format!("{} + {}", template_label, config_label));
+        }
+        if let Some(os) = self.flatten_os {
+            for (key, _) in flatten_os_block(&mut launch, os)? {
+                provenance.set_key(&key, format!("{} block (flattened)", os));
+            }
+        }
+        if config.per_file {
+            check_per_file(&launch)?;
+        } else {
+            if self.check_paths
+                && let Some(Value::String(cwd)) = launch.rest.get("cwd")
+            {
+                check_cwd(&self.root, cwd)?;
+            }
+            if self.check_programs
+                && let Some(program) = &launch.program
+            {
+                check_program(&self.root, program)?;
+            }
+        }
+
+        for key in launch.keys() {
+            provenance.default_key(&key, template_label);
+        }
+        Ok(launch)
     }
 }
 
-#[derive(Debug, Serialize)]
-pub struct LaunchJson {
-    version: String,
-    configurations: Vec<LaunchConfig>,
+impl Resolver {
+    /// Fills keys the template left unset in `launch` from the defaults file
+    fn apply_defaults(&self, launch: &mut LaunchConfig, provenance: &mut Provenance) -> Result<()> {
+        let source = format!("defaults {}", self.defaults.path.display());
+        for (key, value) in &self.defaults.values {
+            let applied = match key.as_str() {
+                "request" if launch.request.is_none() => {
+                    launch.request = Some(defaults_string(key, value, &self.defaults)?);
+                    true
+                }
+                "program" if launch.program.is_none() => {
+                    launch.program = Some(defaults_string(key, value, &self.defaults)?);
+                    true
+                }
+                "stopAtEntry" if launch.stop_at_entry.is_none() => {
+                    launch.stop_at_entry = Some(value.as_bool().ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "'stopAtEntry' in {} must be a boolean",
+                            self.defaults.path.display()
+                        )
+                    })?);
+                    true
+                }
+                "request" | "program" | "stopAtEntry" => false,
+                _ if !launch.rest.contains_key(key) => {
+                    launch.rest.insert(key.clone(), value.clone());
+                    true
+                }
+                _ => false,
+            };
+            if applied {
+                provenance.set_key(key, &source);
+            }
+        }
+        Ok(())
+    }
 }
 
-impl LaunchJson {
-    pub fn configurations(&self) -> &[LaunchConfig] {
-        &self.configurations
+fn defaults_string(key: &str, value: &Value, defaults: &Defaults) -> Result<String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("'{}' in {} must be a string", key, defaults.path.display()))
+}
+
+/// Writes `group`, `order` and `hidden` into the `presentation` object, keeping the
+/// template's other presentation keys. Returns whether anything was set.
+fn apply_presentation(launch: &mut LaunchConfig, config: &ConfigFile) -> bool {
+    let values = [
+        ("group", config.group.clone().map(Value::String)),
+        ("order", config.order.map(Value::from)),
+        ("hidden", config.hidden.map(Value::Bool)),
+    ];
+    if values.iter().all(|(_, v)| v.is_none()) {
+        return false;
+    }
+    let mut presentation = match launch.rest.remove("presentation") {
+        Some(Value::Object(object)) => object,
+        _ => Map::new(),
+    };
+    for (key, value) in values {
+        if let Some(value) = value {
+            presentation.insert(key.to_string(), value);
+        }
     }
+    launch
+        .rest
+        .insert("presentation".to_string(), Value::Object(presentation));
+    true
 }
 
-/// Main generator for creating VSCode launch.json from templates and configs
-pub struct Generator {
-    templates_path: PathBuf,
-    configs_dir: PathBuf,
+/// Subdirectory of `root` containing `source`, with `/` separators; `None` for files
+/// directly in `root` or outside it
+fn dir_group(root: &Path, source: &Path) -> Option<String> {
+    let dir = source.strip_prefix(root).ok()?.parent()?;
+    let parts: Vec<String> = dir
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
 }
 
-impl Generator {
-    /// Creates a new generator instance with explicit templates/configs directories
-    pub fn new(templates_path: PathBuf, configs_dir: PathBuf) -> Self {
-        Self {
-            templates_path,
-            configs_dir,
+/// Applies the block for `os` over the top-level keys and removes every OS block.
+/// Returns the keys that were overridden.
+fn flatten_os_block(launch: &mut LaunchConfig, os: TargetOs) -> Result<Vec<(String, Value)>> {
+    let mut block = None;
+    for key in OS_KEYS {
+        let removed = launch.rest.remove(key);
+        if key == os.key() {
+            block = removed;
         }
     }
 
-    /// Main generation process - reads configs, merges with templates, and returns LaunchJson
-    pub fn generate(&self) -> Result<LaunchJson> {
-        if !self.templates_path.exists() {
-            anyhow::bail!(
-                "Templates manifest does not exist: {}",
-                self.templates_path.display()
-            );
+    let Some(Value::Object(block)) = block else {
+        return Ok(Vec::new());
+    };
+    let overrides: Vec<(String, Value)> = block.into_iter().collect();
+    for (key, value) in &overrides {
+        if let Some(base @ Value::Object(_)) = launch.rest.get_mut(key) {
+            deep_merge(base, value.clone());
+            continue;
         }
+        launch
+            .set(key, strip_replace(value.clone()))
+            .with_context(|| format!("Invalid '{}' in '{}' override block", key, os))?;
+    }
+    Ok(overrides)
+}
 
-        let configs = collect_config_files(&self.configs_dir)?;
+/// Deep-merges `object` into the object `launch` has at `key`, or sets it when there is none
+fn merge_object(launch: &mut LaunchConfig, key: &str, object: &Map<String, Value>) {
+    let mut merged = match launch.rest.remove(key) {
+        Some(base @ Value::Object(_)) => base,
+        _ => Value::Object(Map::new()),
+    };
+    deep_merge(&mut merged, Value::Object(object.clone()));
+    launch.rest.insert(key.to_string(), merged);
+}
 
-        if configs.is_empty() {
-            anyhow::bail!(
-                "No configuration entries found in: {}",
-                self.configs_dir.display()
-            );
+/// Key of an override object that, set to `true`, replaces the object under it instead of
+/// being merged into it
+const REPLACE_KEY: &str = "$replace";
+
+/// Merges `overlay` into `base`: objects key by key at every depth, anything else by
+/// replacement. An overlay object with `"$replace": true` replaces the base object whole.
+fn deep_merge(base: &mut Value, overlay: Value) {
+    let mut overlay = overlay;
+    let replace = match &mut overlay {
+        Value::Object(object) => object.remove(REPLACE_KEY) == Some(Value::Bool(true)),
+        _ => false,
+    };
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) if !replace => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base.insert(key, strip_replace(value));
+                    }
+                }
+            }
         }
+        (base, overlay) => *base = strip_replace(overlay),
+    }
+}
 
-        // Filter out disabled configurations before validation
-        let enabled_configs: Vec<_> = configs
-            .into_iter()
-            .filter(|(_, config)| config.enabled)
-            .collect();
+/// `value` without the `$replace` markers of its objects
+fn strip_replace(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .filter(|(key, _)| key != REPLACE_KEY)
+                .map(|(key, value)| (key, strip_replace(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(strip_replace).collect()),
+        value => value,
+    }
+}
 
-        if enabled_configs.is_empty() {
-            anyhow::bail!(
-                "No enabled configuration entries found in: {}",
-                self.configs_dir.display()
-            );
-        }
+/// Combines config values with the template array at `key` (missing arrays count as empty)
+fn apply_array_merge(rest: &mut ExtraKeys, key: &str, merge: &ArrayMerge) -> Result<()> {
+    let template_items = match rest.remove(key) {
+        Some(Value::Array(items)) => items,
+        None => Vec::new(),
+        Some(_) => anyhow::bail!(
+            "arrayMerge target '{}' is not an array in the template",
+            key
+        ),
+    };
 
-        validate_unique_names(&enabled_configs)?;
+    let merged = match merge {
+        ArrayMerge::Append(values) => template_items.into_iter().chain(values.clone()).collect(),
+        ArrayMerge::Prepend(values) => values.iter().cloned().chain(template_items).collect(),
+        ArrayMerge::Replace(values) => values.clone(),
+    };
+    rest.insert(key.to_string(), Value::Array(merged));
+    Ok(())
+}
 
-        let mut configurations: Vec<LaunchConfig> = Vec::new();
-        let resolver = Resolver::new(TemplateFile::from_path(&self.templates_path)?);
+/// Turns a resolved configuration into an attach request.
+/// CodeLLDB (`lldb`) attaches by `program` name or `pid`; other adapters use `processId`.
+/// Returns the process selection key that was written.
+fn apply_attach(launch: &mut LaunchConfig, attach: &AttachOptions) -> &'static str {
+    launch.request = Some("attach".to_string());
 
-        for (config_path, config) in enabled_configs {
-            let merged = resolver
-                .resolve(config, None)
-                .with_context(|| format!("Error processing config: {}", config_path.display()))?;
-            configurations.push(merged);
+    if launch.type_field == "lldb" {
+        match (attach.pid, &attach.process_name) {
+            (Some(pid), _) => {
+                launch.rest.insert("pid".to_string(), Value::from(pid));
+            }
+            (None, Some(name)) => {
+                launch.program = Some(name.clone());
+                return "program";
+            }
+            (None, None) => {
+                launch
+                    .rest
+                    .insert("pid".to_string(), Value::from("${command:pickMyProcess}"));
+            }
         }
+        return "pid";
+    }
 
-        // Sort configurations by display name to stabilize order
-        configurations.sort_by(|a, b| a.name.cmp(&b.name));
-
-        let launch_json = LaunchJson {
-            version: "0.2.0".to_string(),
-            configurations,
-        };
+    let process_id = match attach.pid {
+        Some(pid) => Value::from(pid),
+        None => Value::from("${command:pickProcess}"),
+    };
+    launch.rest.insert("processId".to_string(), process_id);
+    "processId"
+}
 
-        Ok(launch_json)
+/// Points a resolved configuration at a core file, in the form its debugger type expects:
+/// `coreDumpPath` for cppdbg/cppvsdbg, a custom request creating the target from the core
+/// for CodeLLDB (`lldb`) and `mode: "core"` for Go. Returns the keys that were written.
+fn apply_core_dump(launch: &mut LaunchConfig, path: &str) -> Result<Vec<&'static str>> {
+    match launch.type_field.as_str() {
+        "cppdbg" | "cppvsdbg" => {
+            launch
+                .rest
+                .insert("coreDumpPath".to_string(), Value::from(path));
+            Ok(vec!["coreDumpPath"])
+        }
+        "lldb" => {
+            let mut command = format!("target create -c \"{}\"", path);
+            if let Some(program) = &launch.program {
+                command.push_str(&format!(" \"{}\"", program));
+            }
+            launch.request = Some("custom".to_string());
+            launch
+                .rest
+                .insert("targetCreateCommands".to_string(), json!([command]));
+            launch
+                .rest
+                .insert("processCreateCommands".to_string(), json!([]));
+            Ok(vec![
+                "request",
+                "targetCreateCommands",
+                "processCreateCommands",
+            ])
+        }
+        "go" => {
+            launch.rest.insert("mode".to_string(), Value::from("core"));
+            launch
+                .rest
+                .insert("coreFilePath".to_string(), Value::from(path));
+            Ok(vec!["mode", "coreFilePath"])
+        }
+        other => anyhow::bail!("coreDump is not supported for debugger type '{}'", other),
     }
 }
 
-/// Collects all JSON config entries from `configs_dir` in alphabetical order of file path
-pub(crate) fn collect_config_files(configs_dir: &Path) -> Result<Vec<(PathBuf, ConfigFile)>> {
-    if !configs_dir.exists() {
-        anyhow::bail!("Config directory does not exist: {}", configs_dir.display());
+/// Writes `container` as the path mapping key of the debugger type, merged with the
+/// template's mappings. Returns the keys written.
+fn apply_container(
+    launch: &mut LaunchConfig,
+    container: &ContainerMapping,
+) -> Result<Vec<&'static str>> {
+    let (local, remote) = (&container.local_root, &container.remote_root);
+    let (key, mapping) = match launch.type_field.as_str() {
+        "cppdbg" | "cppvsdbg" => ("sourceFileMap", json!({ remote: local })),
+        "lldb" => ("sourceMap", json!({ remote: local })),
+        "debugpy" | "python" => (
+            "pathMappings",
+            json!([{ "localRoot": local, "remoteRoot": remote }]),
+        ),
+        "go" => ("substitutePath", json!([{ "from": local, "to": remote }])),
+        "chrome" | "pwa-chrome" | "msedge" | "pwa-msedge" => (
+            "sourceMapPathOverrides",
+            json!({ format!("{}/*", remote): format!("{}/*", local) }),
+        ),
+        "node" | "pwa-node" => {
+            launch
+                .rest
+                .insert("localRoot".to_string(), Value::from(local.as_str()));
+            launch
+                .rest
+                .insert("remoteRoot".to_string(), Value::from(remote.as_str()));
+            return Ok(vec!["localRoot", "remoteRoot"]);
+        }
+        other => anyhow::bail!("container is not supported for debugger type '{}'", other),
+    };
+    match (launch.rest.get_mut(key), mapping) {
+        (Some(Value::Array(items)), Value::Array(mapping)) => items.extend(mapping),
+        (Some(existing @ Value::Object(_)), mapping @ Value::Object(_)) => {
+            deep_merge(existing, mapping)
+        }
+        (_, mapping) => {
+            launch.rest.insert(key.to_string(), mapping);
+        }
     }
+    Ok(vec![key])
+}
 
-    let mut config_files: Vec<PathBuf> = Vec::new();
-
-    for entry in fs::read_dir(configs_dir).with_context(|| {
-        format!(
-            "Failed to read configs directory: {}",
-            configs_dir.display()
-        )
-    })? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-            config_files.push(path);
+/// Writes `console`, `externalConsole` and `justMyCode` as the keys the debugger type
+/// reads. Returns the keys written.
+fn apply_console(launch: &mut LaunchConfig, config: &ConfigFile) -> Result<Vec<&'static str>> {
+    let console = match (config.console, config.external_console) {
+        (Some(_), Some(_)) => anyhow::bail!(
+            "'{}' cannot set both console and externalConsole",
+            config.name
+        ),
+        (Some(console), None) => Some(console),
+        (None, Some(true)) => Some(Console::ExternalTerminal),
+        (None, Some(false)) => Some(Console::IntegratedTerminal),
+        (None, None) => None,
+    };
+    let debug_type = launch.type_field.clone();
+    let mut keys = Vec::new();
+    if let Some(console) = console {
+        let (key, value) = match (debug_type.as_str(), console) {
+            ("cppdbg", Console::InternalConsole) => anyhow::bail!(
+                "'{}': cppdbg has no internal console; use integratedTerminal or externalTerminal",
+                config.name
+            ),
+            ("cppdbg", console) => (
+                "externalConsole",
+                Value::Bool(console == Console::ExternalTerminal),
+            ),
+            ("lldb", console) => (
+                "terminal",
+                Value::from(match console {
+                    Console::InternalConsole => "console",
+                    Console::IntegratedTerminal => "integrated",
+                    Console::ExternalTerminal => "external",
+                }),
+            ),
+            ("cppvsdbg" | "debugpy" | "python" | "node" | "pwa-node" | "go" | "coreclr", _) => {
+                ("console", serde_json::to_value(console)?)
+            }
+            (other, _) => {
+                anyhow::bail!("console is not supported for debugger type '{}'", other)
+            }
+        };
+        launch.rest.insert(key.to_string(), value);
+        keys.push(key);
+    }
+    if let Some(just_my_code) = config.just_my_code {
+        if !matches!(debug_type.as_str(), "debugpy" | "python" | "coreclr") {
+            anyhow::bail!(
+                "justMyCode is not supported for debugger type '{}'",
+                debug_type
+            );
         }
+        launch
+            .rest
+            .insert("justMyCode".to_string(), Value::Bool(just_my_code));
+        keys.push("justMyCode");
     }
+    Ok(keys)
+}
 
-    config_files.sort();
-
-    // Load after collecting all paths
-    let mut configs: Vec<(PathBuf, ConfigFile)> = Vec::new();
-    for config_path in config_files.into_iter() {
-        let entries = ConfigFile::from_path(&config_path)?;
-        for config in entries {
-            configs.push((config_path.clone(), config));
+/// Writes `stopAtEntry`, `MIMode` and `miDebuggerPath`, which only the C/C++ debugger types
+/// read. Returns the keys written.
+fn apply_debugger(launch: &mut LaunchConfig, config: &ConfigFile) -> Result<Vec<&'static str>> {
+    let debug_type = launch.type_field.clone();
+    let mut keys = Vec::new();
+    if let Some(stop_at_entry) = config.stop_at_entry {
+        if !matches!(debug_type.as_str(), "cppdbg" | "cppvsdbg") {
+            anyhow::bail!(
+                "stopAtEntry is not supported for debugger type '{}'",
+                debug_type
+            );
         }
+        launch.stop_at_entry = Some(stop_at_entry);
+        keys.push("stopAtEntry");
     }
-    Ok(configs)
+    let mi_keys = [
+        (
+            "MIMode",
+            config.mi_mode.map(serde_json::to_value).transpose()?,
+        ),
+        (
+            "miDebuggerPath",
+            config.mi_debugger_path.clone().map(Value::from),
+        ),
+    ];
+    for (key, value) in mi_keys {
+        let Some(value) = value else { continue };
+        if debug_type != "cppdbg" {
+            anyhow::bail!(
+                "{} is not supported for debugger type '{}'",
+                key,
+                debug_type
+            );
+        }
+        launch.rest.insert(key.to_string(), value);
+        keys.push(key);
+    }
+    Ok(keys)
+}
+
+/// Whether a `coreDump` value is a pattern resolved at generation time
+fn is_core_pattern(core_dump: &str) -> bool {
+    file_part(core_dump).1.contains(['*', '?'])
+}
+
+/// `core_dump` as a workspace path. When its file name is a `*`/`?` pattern, the most
+/// recently modified matching file in its directory (read from the workspace root) is used.
+fn find_core_dump(root: &Path, core_dump: &str) -> Result<String> {
+    if !is_core_pattern(core_dump) {
+        return Ok(workspace_path(Path::new(core_dump)));
+    }
+    let (dir, pattern) = file_part(core_dump);
+    let local = on_disk(root, Path::new(if dir.is_empty() { "." } else { dir }));
+    let newest = fs::read_dir(&local)
+        .with_context(|| format!("Failed to read core dump directory: {}", local.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|entry| glob_match(pattern, &entry.file_name().to_string_lossy()))
+        .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok());
+    let Some(newest) = newest else {
+        anyhow::bail!("No core file matches '{}' in {}", pattern, local.display());
+    };
+    let name = newest.file_name().to_string_lossy().into_owned();
+    let path = if dir.is_empty() {
+        name
+    } else {
+        format!("{}/{}", dir, name)
+    };
+    Ok(workspace_path(Path::new(&path)))
+}
+
+/// Splits a path as written into its directory (empty for none) and file name
+fn file_part(path: &str) -> (&str, &str) {
+    match path.rfind(['/', '\\']) {
+        Some(idx) => (&path[..idx], &path[idx + 1..]),
+        None => ("", path),
+    }
+}
+
+const WORKSPACE_FOLDER: &str = "${workspaceFolder}";
+
+/// Validates that `envFile` exists and emits it in the form the debugger type understands.
+/// cppdbg has no `envFile` support, so the variables are merged into `environment` instead.
+fn apply_env_file(launch: &mut LaunchConfig, path: &Path, root: &Path) -> Result<()> {
+    let local = on_disk(root, path);
+    if !local.is_file() {
+        anyhow::bail!("envFile does not exist: {}", local.display());
+    }
+
+    if launch.type_field != "cppdbg" {
+        launch
+            .rest
+            .insert("envFile".to_string(), Value::from(workspace_path(path)));
+        return Ok(());
+    }
+
+    let mut environment = match launch.rest.remove("environment") {
+        Some(Value::Array(items)) => items,
+        None => Vec::new(),
+        Some(_) => anyhow::bail!("Template 'environment' must be an array to merge envFile"),
+    };
+    for (name, value) in EnvFile::from_path(&local)?.vars {
+        // envFile values override template entries of the same name
+        environment.retain(|e| e.get("name").and_then(Value::as_str) != Some(name.as_str()));
+        environment.push(json!({ "name": name, "value": value }));
+    }
+    launch
+        .rest
+        .insert("environment".to_string(), Value::Array(environment));
+    Ok(())
+}
+
+/// Filesystem path for a workspace-relative path, stripping a leading `${workspaceFolder}`
+fn local_path(path: &Path) -> PathBuf {
+    match path.strip_prefix(WORKSPACE_FOLDER) {
+        Ok(rest) if rest.as_os_str().is_empty() => PathBuf::from("."),
+        Ok(rest) => rest.to_path_buf(),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Where a workspace path is found on disk when the workspace folder is `root`
+pub(crate) fn on_disk(root: &Path, path: &Path) -> PathBuf {
+    root.join(local_path(path))
+}
+
+/// Validates that `cwd` is an existing directory, relative paths being taken from the
+/// workspace root. Values using variables other than `${workspaceFolder}` are skipped.
+fn check_cwd(root: &Path, cwd: &str) -> Result<()> {
+    let local = on_disk(root, Path::new(cwd));
+    if local.to_string_lossy().contains("${") {
+        return Ok(());
+    }
+    if !local.is_dir() {
+        anyhow::bail!("cwd does not exist: {}", local.display());
+    }
+    Ok(())
+}
+
+/// Validates that `program` is an existing file, relative paths being taken from the
+/// workspace root. Values using variables other than `${workspaceFolder}` are skipped.
+fn check_program(root: &Path, program: &str) -> Result<()> {
+    let local = on_disk(root, Path::new(program));
+    if local.to_string_lossy().contains("${") {
+        return Ok(());
+    }
+    if !local.is_file() {
+        anyhow::bail!("program does not exist: {}", local.display());
+    }
+    Ok(())
+}
+
+/// Fails when a `perFile` configuration uses none of the variables naming the file open
+/// in the editor, so it would debug the same program whichever file is open
+fn check_per_file(launch: &LaunchConfig) -> Result<()> {
+    let text = serde_json::to_string(launch)?;
+    if FILE_VARIABLES
+        .iter()
+        .any(|var| text.contains(&format!("${{{}}}", var)))
+    {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "'{}' sets perFile but uses none of {}",
+        launch.name,
+        FILE_VARIABLES.map(|var| format!("${{{}}}", var)).join(", ")
+    )
+}
+
+/// Path as VSCode should see it: relative paths are anchored at `${workspaceFolder}`
+pub(crate) fn workspace_path(path: &Path) -> String {
+    if path.is_absolute() || path.starts_with(WORKSPACE_FOLDER) {
+        return path.display().to_string();
+    }
+    let path = path.strip_prefix(".").unwrap_or(path);
+    format!("{}/{}", WORKSPACE_FOLDER, path.display())
+}
+
+/// VSCode variables derived from the file open in the editor
+const FILE_VARIABLES: [&str; 8] = [
+    "file",
+    "relativeFile",
+    "relativeFileDirname",
+    "fileBasename",
+    "fileBasenameNoExtension",
+    "fileExtname",
+    "fileDirname",
+    "fileDirnameBasename",
+];
+
+/// Picker group of `perFile` configurations without a `group`
+const PER_FILE_GROUP: &str = "Current file";
+
+/// launch.json format version written unless settings.json sets `launchVersion`
+pub(crate) const LAUNCH_VERSION: &str = "0.2.0";
+
+/// Top-level key written by `--stamp`
+const GENERATED_BY: &str = "generatedBy";
+
+#[derive(Debug, Serialize)]
+pub struct LaunchJson {
+    version: String,
+    configurations: Vec<LaunchConfig>,
+    /// Other top-level keys, such as `inputs` or `compounds`
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+    #[serde(skip)]
+    warnings: Vec<String>,
+    #[serde(skip)]
+    errors: Diagnostics,
+    #[serde(skip)]
+    summary: GenerationSummary,
+}
+
+/// Output of `Generator::resolve_all`
+struct Resolved {
+    configurations: Vec<LaunchConfig>,
+    warnings: Vec<String>,
+    /// Failures skipped by `keep_going`
+    errors: Diagnostics,
+    summary: GenerationSummary,
+}
+
+/// Counts describing a generation run, printed by `--stats` and `--verbose`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GenerationSummary {
+    /// Templates loaded from every manifest
+    pub templates: usize,
+    /// Config files read from the configs directory
+    pub files: usize,
+    pub enabled: usize,
+    pub disabled: usize,
+    /// Configurations added by `--from-*` discovery
+    pub discovered: usize,
+    /// Enabled configurations dropped as duplicates by `--on-duplicate skip` or `--dedupe`
+    pub skipped: usize,
+    /// Enabled configurations whose `baseArgs` file was read
+    pub base_args: usize,
+    pub warnings: usize,
+    /// Enabled entries and discovered targets skipped by `keep_going` because they failed
+    /// to resolve
+    pub failed: usize,
+}
+
+impl LaunchJson {
+    pub fn configurations(&self) -> &[LaunchConfig] {
+        &self.configurations
+    }
+
+    /// Non-fatal problems found during generation, such as skipped optional `baseArgs`
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Config files and entries skipped by `keep_going` because of an error, one diagnostic
+    /// each; empty unless some configurations are missing from this launch.json
+    pub fn errors(&self) -> &Diagnostics {
+        &self.errors
+    }
+
+    /// What went into this launch.json
+    pub fn summary(&self) -> &GenerationSummary {
+        &self.summary
+    }
+
+    /// Carries the top-level keys of an existing launch.json other than `version` and
+    /// `configurations`, such as hand-maintained `inputs` or `compounds`, into this one.
+    /// Keys this launch.json already sets from `launchKeys` are not replaced, except that
+    /// `compounds` with other names are appended to the generated ones.
+    /// Comments, trailing commas and unquoted keys in `existing` are allowed. Returns the
+    /// keys carried over.
+    pub fn preserve_keys_from(&mut self, existing: &str) -> Result<Vec<String>> {
+        if existing.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let Value::Object(object) = serde_json::from_str(&strip_jsonc(existing))
+            .context("Failed to parse the existing launch.json")?
+        else {
+            anyhow::bail!("The existing launch.json must be a JSON object");
+        };
+        let mut kept = Vec::new();
+        for (key, value) in object {
+            if key == "compounds" && self.extra.contains_key(&key) {
+                // Hand-written compounds are kept next to the ones from `launchAfter`
+                if let Value::Array(compounds) = value
+                    && merge_compounds(&mut self.extra, compounds)
+                {
+                    kept.push(key);
+                }
+                continue;
+            }
+            // A stale stamp is never carried over; `--stamp` writes a fresh one
+            if !matches!(key.as_str(), "version" | "configurations" | GENERATED_BY)
+                && !self.extra.contains_key(&key)
+            {
+                kept.push(key.clone());
+                self.extra.insert(key, value);
+            }
+        }
+        Ok(kept)
+    }
+
+    /// Names of the configurations in an existing launch.json (or settings.json, under
+    /// `launch`) that this one does not generate, in file order; replacing the file loses
+    /// them. Unnamed configurations are listed as `(unnamed)`. Comments, trailing commas and
+    /// unquoted keys in `existing` are allowed.
+    pub fn unmanaged_in(&self, existing: &str) -> Result<Vec<String>> {
+        if existing.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let object: Value = serde_json::from_str(&strip_jsonc(existing))
+            .context("Failed to parse the existing launch.json")?;
+        let configurations = object
+            .get("configurations")
+            .or_else(|| object.pointer("/launch/configurations"))
+            .and_then(Value::as_array);
+        let generated: BTreeSet<&str> = self.configurations.iter().map(|c| c.name()).collect();
+        Ok(configurations
+            .into_iter()
+            .flatten()
+            .map(|config| match config.get("name").and_then(Value::as_str) {
+                Some(name) => name.to_string(),
+                None => "(unnamed)".to_string(),
+            })
+            .filter(|name| !generated.contains(name.as_str()))
+            .collect())
+    }
+
+    /// launch.json contents as written by mklaunch
+    pub fn to_pretty_string(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize launch.json")
+    }
+
+    /// launch.json contents in `format`
+    pub fn to_string_as(&self, format: OutputFormat) -> Result<String> {
+        Ok(format.render(&self.to_pretty_string()?))
+    }
+}
+
+/// Appends `compounds` to the `compounds` array of `extra`, skipping names it already
+/// has. Returns whether any was added.
+fn merge_compounds(extra: &mut Map<String, Value>, compounds: Vec<Value>) -> bool {
+    let existing = extra
+        .entry("compounds")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    let Value::Array(existing) = existing else {
+        return false;
+    };
+    let mut added = false;
+    for compound in compounds {
+        if !existing
+            .iter()
+            .any(|c| c.get("name") == compound.get("name"))
+        {
+            existing.push(compound);
+            added = true;
+        }
+    }
+    added
+}
+
+/// What to do when two enabled configurations resolve to the same name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateNames {
+    /// Fail generation, listing the files involved
+    #[default]
+    Error,
+    /// Rename later configurations to `Name (2)`, `Name (3)`, ... with a warning
+    Suffix,
+    /// Drop later configurations with a warning
+    Skip,
+}
+
+/// Order of the keys after the fixed `type`/`request`/`name`/`program`/`args`/`stopAtEntry`
+/// prefix, in the configuration itself and in nested objects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyOrder {
+    /// Sorted by key; identical output regardless of how inputs are formatted
+    #[default]
+    Alphabetical,
+    /// As written in the template, keys added by configs following alphabetically
+    Template,
+}
+
+/// How entries are selected by several `--tag`s
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    /// Entries with at least one of the tags
+    #[default]
+    Any,
+    /// Entries with every tag
+    All,
+}
+
+/// Generator settings. New knobs are added here so `Generator` construction stays stable.
+#[derive(Debug, Clone)]
+pub struct GeneratorOptions {
+    templates_path: PathBuf,
+    extra_templates: Vec<PathBuf>,
+    configs_dir: PathBuf,
+    args_dir: Option<PathBuf>,
+    defaults_path: Option<PathBuf>,
+    group_by_dir: bool,
+    group_by_template: bool,
+    allow_exec: bool,
+    discovery: Vec<DiscoverySource>,
+    flatten_os: Option<TargetOs>,
+    check_paths: bool,
+    check_programs: bool,
+    dedupe: bool,
+    dedupe_args: Option<ArgsDedupe>,
+    command_runner: Arc<dyn CommandRunner>,
+    command_timeout: Option<Duration>,
+    keep_going: bool,
+    allow_missing_base_args: bool,
+    strict_config: bool,
+    cache: bool,
+    stamp: bool,
+    root: Option<PathBuf>,
+    duplicate_names: DuplicateNames,
+    key_order: KeyOrder,
+    output_format: OutputFormat,
+    tags: Vec<String>,
+    tag_match: TagMatch,
+    path_style: Option<PathStyle>,
+    environment: Environment,
+    overrides: Vec<(String, Value)>,
+    override_names: Vec<String>,
+    extra_configs: Vec<PathBuf>,
+    overlay_configs: Vec<PathBuf>,
+}
+
+impl GeneratorOptions {
+    /// Options for the given templates manifest and configs directory, with defaults for the rest
+    pub fn new(templates_path: PathBuf, configs_dir: PathBuf) -> Self {
+        Self {
+            templates_path,
+            extra_templates: Vec::new(),
+            configs_dir,
+            args_dir: None,
+            defaults_path: None,
+            group_by_dir: false,
+            group_by_template: false,
+            allow_exec: false,
+            discovery: Vec::new(),
+            flatten_os: None,
+            check_paths: false,
+            check_programs: false,
+            dedupe: false,
+            dedupe_args: None,
+            command_runner: Arc::new(ProcessRunner),
+            command_timeout: None,
+            keep_going: false,
+            allow_missing_base_args: false,
+            strict_config: false,
+            cache: false,
+            stamp: false,
+            root: None,
+            duplicate_names: DuplicateNames::Error,
+            key_order: KeyOrder::Alphabetical,
+            output_format: OutputFormat::Json,
+            tags: Vec::new(),
+            tag_match: TagMatch::Any,
+            path_style: None,
+            environment: Environment::Host,
+            overrides: Vec::new(),
+            override_names: Vec::new(),
+            extra_configs: Vec::new(),
+            overlay_configs: Vec::new(),
+        }
+    }
+
+    /// Directory containing named arg sets [default: `args/` next to the templates manifest]
+    pub fn args_dir(mut self, args_dir: PathBuf) -> Self {
+        self.args_dir = Some(args_dir);
+        self
+    }
+
+    /// Syntax of the launch.json written by `generate_to`
+    pub fn output_format(mut self, format: OutputFormat) -> Self {
+        self.output_format = format;
+        self
+    }
+
+    /// Reads templates from another manifest, or from every `*.templates.json` manifest of a
+    /// directory. Template names must be unique across all manifests.
+    pub fn templates(mut self, path: PathBuf) -> Self {
+        self.extra_templates.push(path);
+        self
+    }
+
+    /// Layers another configs directory over the previous ones: its entries are merged key
+    /// by key over an earlier entry with the same name, or added when the name is new.
+    /// A missing overlay directory is skipped, so per-developer overlays are optional.
+    pub fn configs(mut self, path: PathBuf) -> Self {
+        self.overlay_configs.push(path);
+        self
+    }
+
+    /// Defaults file merged into every configuration [default: `defaults.json` next to the
+    /// templates manifest]; the file is optional
+    pub fn defaults_path(mut self, path: PathBuf) -> Self {
+        self.defaults_path = Some(path);
+        self
+    }
+
+    /// Sets `presentation.group` of configurations without a `group` to the subdirectory of
+    /// the configs directory their config file is in
+    pub fn group_by_dir(mut self, enabled: bool) -> Self {
+        self.group_by_dir = enabled;
+        self
+    }
+
+    /// Sets `presentation.group` of enabled config entries without a `group` to the template
+    /// they extend, and `presentation.order` of those without an `order` to their position
+    /// in the group, in file order. Takes precedence over `group_by_dir`.
+    pub fn group_by_template(mut self, enabled: bool) -> Self {
+        self.group_by_template = enabled;
+        self
+    }
+
+    /// Runs the shell commands named by `argsFromCommand`; without it such entries fail
+    pub fn allow_exec(mut self, allow: bool) -> Self {
+        self.allow_exec = allow;
+        self
+    }
+
+    /// Adds one configuration per executable target found through the CMake File API.
+    /// `path` is a `CMakePresets.json` or a build directory; each configuration extends
+    /// `template` with `program` set to the target's output path.
+    pub fn from_cmake(mut self, path: PathBuf, template: String) -> Self {
+        self.discovery.push(DiscoverySource {
+            kind: DiscoveryKind::Cmake,
+            path,
+            template,
+        });
+        self
+    }
+
+    /// Adds debugpy configurations for the Python project at `path` (a directory or its
+    /// `pyproject.toml`): console scripts in module mode, top-level scripts with a
+    /// `__main__` guard in program mode, and one `pytest` run per test file. Each extends
+    /// `template`.
+    pub fn from_python(mut self, path: PathBuf, template: String) -> Self {
+        self.discovery.push(DiscoverySource {
+            kind: DiscoveryKind::Python,
+            path,
+            template,
+        });
+        self
+    }
+
+    /// Flattens the `windows`/`linux`/`osx` override block for `os` into each configuration
+    /// instead of passing the blocks through
+    pub fn flatten_os(mut self, os: TargetOs) -> Self {
+        self.flatten_os = Some(os);
+        self
+    }
+
+    /// Fails generation when a configuration's `cwd` is not an existing directory
+    /// (relative to the current directory, taken as the workspace root)
+    pub fn check_paths(mut self, check_paths: bool) -> Self {
+        self.check_paths = check_paths;
+        self
+    }
+
+    /// Fails generation when a configuration's `program` is not an existing file, after
+    /// anchoring relative paths and `${workspaceFolder}` at the workspace root
+    pub fn check_programs(mut self, check_programs: bool) -> Self {
+        self.check_programs = check_programs;
+        self
+    }
+
+    /// Drops configurations identical to an earlier one (by name order) apart from their name
+    pub fn dedupe(mut self, dedupe: bool) -> Self {
+        self.dedupe = dedupe;
+        self
+    }
+
+    /// Reports (`Warn`) or drops (`Remove`) flags repeated in the resolved args of a
+    /// configuration, following the rules of `flags.json` next to the templates manifest
+    pub fn dedupe_args(mut self, mode: Option<ArgsDedupe>) -> Self {
+        self.dedupe_args = mode;
+        self
+    }
+
+    /// Runs the external commands of a generation (`argsFromCommand`, cargo builds and
+    /// discovery tools such as `go list`) through `runner`; `NoExec` refuses them all
+    pub fn command_runner(mut self, runner: Arc<dyn CommandRunner>) -> Self {
+        self.command_runner = runner;
+        self
+    }
+
+    /// Kills external commands running longer than `timeout`; entries can set their own
+    /// with `timeoutMs`
+    pub fn command_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.command_timeout = timeout;
+        self
+    }
+
+    /// Skips config files that fail to parse and entries that fail to resolve, collecting
+    /// their errors in `LaunchJson::errors` instead of failing generation
+    pub fn keep_going(mut self, keep_going: bool) -> Self {
+        self.keep_going = keep_going;
+        self
+    }
+
+    /// Treats missing `baseArgs` files as empty arg lists, reporting each as a warning
+    pub fn allow_missing_base_args(mut self, allow: bool) -> Self {
+        self.allow_missing_base_args = allow;
+        self
+    }
+
+    /// Rejects unknown keys in config entries, suggesting the closest known key
+    pub fn strict_config(mut self, strict: bool) -> Self {
+        self.strict_config = strict;
+        self
+    }
+
+    /// Handling of configurations sharing a name; later ones (in file order, then discovered
+    /// targets) are renamed or dropped
+    pub fn duplicate_names(mut self, policy: DuplicateNames) -> Self {
+        self.duplicate_names = policy;
+        self
+    }
+
+    /// Order of the keys in generated configurations
+    pub fn key_order(mut self, key_order: KeyOrder) -> Self {
+        self.key_order = key_order;
+        self
+    }
+
+    /// Workspace folder that relative `baseArgs`, `envFile` and `cwd` paths are read from
+    /// [default: the current directory]
+    pub fn root(mut self, root: PathBuf) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    /// Adds one delve configuration per `main` package of the Go module at `path`, found with
+    /// `go list -json ./...`. Each extends `template` with `program` set to the package directory.
+    pub fn from_go(mut self, path: PathBuf, template: String) -> Self {
+        self.discovery.push(DiscoverySource {
+            kind: DiscoveryKind::Go,
+            path,
+            template,
+        });
+        self
+    }
+
+    /// Adds one Node configuration per allowed script of the package.json at `path` (or in
+    /// the directory `path`) and of its workspace packages, running `npm run <script>`.
+    /// Each extends `template`; scripts missing from every package are an error.
+    pub fn from_npm(mut self, path: PathBuf, template: String, scripts: Vec<String>) -> Self {
+        self.discovery.push(DiscoverySource {
+            kind: DiscoveryKind::Npm { scripts },
+            path,
+            template,
+        });
+        self
+    }
+
+    /// Reuses the resolved configurations of config files that did not change since the
+    /// previous cached run, stored in `cache/` next to the templates manifest
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Adds a top-level `generatedBy` key recording the mklaunch version and a hash of
+    /// every input file
+    pub fn stamp(mut self, stamp: bool) -> Self {
+        self.stamp = stamp;
+        self
+    }
+
+    /// Only generates config entries carrying these tags (any or all of them, per
+    /// `tag_match`); the others count as disabled. No tags selects every entry.
+    pub fn tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Whether an entry needs any or all of the `tags`
+    pub fn tag_match(mut self, tag_match: TagMatch) -> Self {
+        self.tag_match = tag_match;
+        self
+    }
+
+    /// Rewrites the separators of `program`, `cwd`, `miDebuggerPath` and other known path
+    /// keys of every configuration [default: paths are written as resolved]
+    pub fn path_style(mut self, style: PathStyle) -> Self {
+        self.path_style = Some(style);
+        self
+    }
+
+    /// Where the configurations are debugged from. `Environment::Devcontainer` requires a
+    /// `.devcontainer/devcontainer.json` in the workspace root.
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Sets `key` to `value` in the resolved configurations, after everything else.
+    /// `key` may be a dotted path into nested objects, such as `env.RUST_LOG`.
+    pub fn set(mut self, key: String, value: Value) -> Self {
+        self.overrides.push((key, value));
+        self
+    }
+
+    /// Limits `set` to configurations whose name matches one of the `*`/`?` patterns
+    /// [default: every configuration]
+    pub fn override_names(mut self, patterns: Vec<String>) -> Self {
+        self.override_names = patterns;
+        self
+    }
+
+    /// Reads config entries from `path` in addition to the configs directory, e.g. a
+    /// machine-local file kept out of version control. Its entries may `include` entries of
+    /// the configs directory and the other way around.
+    pub fn extra_config(mut self, path: PathBuf) -> Self {
+        self.extra_configs.push(path);
+        self
+    }
+}
+
+/// Main generator for creating VSCode launch.json from templates and configs
+pub struct Generator {
+    templates_path: PathBuf,
+    extra_templates: Vec<PathBuf>,
+    configs_dir: PathBuf,
+    args_dir: PathBuf,
+    defaults_path: PathBuf,
+    group_by_dir: bool,
+    group_by_template: bool,
+    allow_exec: bool,
+    discovery: Vec<DiscoverySource>,
+    flatten_os: Option<TargetOs>,
+    check_paths: bool,
+    check_programs: bool,
+    dedupe: bool,
+    dedupe_args: Option<ArgsDedupe>,
+    command_runner: Arc<dyn CommandRunner>,
+    command_timeout: Option<Duration>,
+    keep_going: bool,
+    allow_missing_base_args: bool,
+    strict_config: bool,
+    cache: bool,
+    stamp: bool,
+    root: PathBuf,
+    duplicate_names: DuplicateNames,
+    key_order: KeyOrder,
+    output_format: OutputFormat,
+    tags: Vec<String>,
+    tag_match: TagMatch,
+    path_style: Option<PathStyle>,
+    environment: Environment,
+    overrides: Vec<(String, Value)>,
+    override_names: Vec<String>,
+    extra_configs: Vec<PathBuf>,
+    overlay_configs: Vec<PathBuf>,
+}
+
+impl Generator {
+    /// Creates a new generator instance with explicit templates/configs directories
+    /// and default options
+    pub fn new(templates_path: PathBuf, configs_dir: PathBuf) -> Self {
+        Self::with_options(GeneratorOptions::new(templates_path, configs_dir))
+    }
+
+    /// Creates a new generator instance from `options`
+    pub fn with_options(options: GeneratorOptions) -> Self {
+        let args_dir = options.args_dir.unwrap_or_else(|| {
+            options
+                .templates_path
+                .parent()
+                .map(|p| p.join("args"))
+                .unwrap_or_else(|| PathBuf::from("args"))
+        });
+        let defaults_path = options.defaults_path.unwrap_or_else(|| {
+            options
+                .templates_path
+                .parent()
+                .map(|p| p.join("defaults.json"))
+                .unwrap_or_else(|| PathBuf::from("defaults.json"))
+        });
+        Self {
+            templates_path: options.templates_path,
+            extra_templates: options.extra_templates,
+            configs_dir: options.configs_dir,
+            args_dir,
+            defaults_path,
+            group_by_dir: options.group_by_dir,
+            group_by_template: options.group_by_template,
+            allow_exec: options.allow_exec,
+            discovery: options.discovery,
+            flatten_os: options.flatten_os,
+            check_paths: options.check_paths,
+            check_programs: options.check_programs,
+            dedupe: options.dedupe,
+            dedupe_args: options.dedupe_args,
+            command_runner: options.command_runner,
+            command_timeout: options.command_timeout,
+            keep_going: options.keep_going,
+            allow_missing_base_args: options.allow_missing_base_args,
+            strict_config: options.strict_config,
+            cache: options.cache,
+            stamp: options.stamp,
+            root: options.root.unwrap_or_default(),
+            duplicate_names: options.duplicate_names,
+            key_order: options.key_order,
+            output_format: options.output_format,
+            tags: options.tags,
+            tag_match: options.tag_match,
+            path_style: options.path_style,
+            environment: options.environment,
+            overrides: options.overrides,
+            override_names: options.override_names,
+            extra_configs: options.extra_configs,
+            overlay_configs: options.overlay_configs,
+        }
+    }
+
+    /// Overrides the directory containing named arg sets
+    #[deprecated(note = "use GeneratorOptions::args_dir and Generator::with_options")]
+    pub fn with_args_dir(mut self, args_dir: PathBuf) -> Self {
+        self.args_dir = args_dir;
+        self
+    }
+
+    fn resolver(&self) -> Result<Resolver> {
+        let settings = Settings::from_path(&Settings::path_for(&self.templates_path))?;
+        Ok(Resolver::new(self.template_file()?)
+            .with_arg_sets(ArgSetLibrary::from_dir(&self.args_dir)?)
+            .with_defaults(Defaults::from_path(&self.defaults_path)?)
+            .with_flatten_os(self.flatten_os)
+            .with_check_paths(self.check_paths)
+            .with_check_programs(self.check_programs)
+            .with_allow_missing_base_args(self.allow_missing_base_args)
+            .with_root(self.root.clone())
+            .with_key_order(self.key_order)
+            .with_group_by_dir(self.group_by_dir.then(|| self.configs_dir.clone()))
+            .with_allow_exec(self.allow_exec)
+            .with_dedupe_args(self.dedupe_args)
+            .with_command_runner(self.command_runner.clone())
+            .with_command_timeout(self.command_timeout)
+            .with_known_flags(KnownFlags::from_path(&self.flags_path())?)
+            .with_template_variant(
+                (self.environment == Environment::Devcontainer)
+                    .then_some(devcontainer::TEMPLATE_SUFFIX),
+            )
+            .with_name_format(settings.name_format))
+    }
+
+    /// Config entries from the configs directory, which is optional when discovering targets.
+    /// Upgrade messages for outdated config files are added to `warnings`.
+    fn collect_configs(&self, warnings: &mut Vec<String>) -> Result<Vec<(PathBuf, ConfigFile)>> {
+        self.read_configs(warnings, None)
+    }
+
+    /// `collect_configs`, skipping config files and entries that fail to parse when
+    /// `errors` is given and adding their errors to it
+    fn read_configs(
+        &self,
+        warnings: &mut Vec<String>,
+        errors: Option<&mut Diagnostics>,
+    ) -> Result<Vec<(PathBuf, ConfigFile)>> {
+        let span = tracing::debug_span!(
+            "collect_configs",
+            dir = %self.configs_dir.display(),
+            entries = field::Empty,
+            enabled = field::Empty
+        )
+        .entered();
+        let disabled = DisabledList::from_path(&self.disabled_path())?;
+        let config_files = self.config_files()?.0;
+        let mut configs = read_config_files(
+            &config_files,
+            &self.overlay_configs,
+            self.strict_config,
+            warnings,
+            errors,
+        )?;
+        for (_, config) in &mut configs {
+            self.filter_entry(&disabled, config);
+        }
+        self.apply_template_groups(configs.iter_mut().map(|(_, config)| config));
+        span.record("entries", configs.len());
+        span.record("enabled", configs.iter().filter(|(_, c)| c.enabled).count());
+        Ok(configs)
+    }
+
+    /// Config files of the configs directory and its overlays, in layer order, followed by
+    /// the extra config files, and the include fragments of every layer. A missing configs
+    /// directory is only an error when neither discovery nor extra config files can provide
+    /// entries.
+    fn config_files(&self) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+        let (mut files, mut fragments) = if !self.configs_dir.exists()
+            && (!self.discovery.is_empty() || !self.extra_configs.is_empty())
+        {
+            (Vec::new(), Vec::new())
+        } else {
+            list_config_dir(&self.configs_dir, self.group_by_dir)?
+        };
+        for dir in self.overlay_configs.iter().filter(|d| d.is_dir()) {
+            let (overlay_files, overlay_fragments) = list_config_dir(dir, self.group_by_dir)?;
+            files.extend(overlay_files);
+            fragments.extend(overlay_fragments);
+        }
+        for path in &self.extra_configs {
+            if !path.is_file() {
+                anyhow::bail!("Extra config file does not exist: {}", path.display());
+            }
+            if !files.contains(path) {
+                files.push(path.clone());
+            }
+        }
+        Ok((files, fragments))
+    }
+
+    /// With `group_by_template`, groups enabled entries without a `group` under their
+    /// template and numbers those without an `order` within their group, in the given order
+    fn apply_template_groups<'a>(&self, configs: impl Iterator<Item = &'a mut ConfigFile>) {
+        if !self.group_by_template {
+            return;
+        }
+        let mut counts: BTreeMap<String, i64> = BTreeMap::new();
+        for config in configs.filter(|c| c.enabled && !c.per_file) {
+            let group = config.group.get_or_insert_with(|| config.extends.clone());
+            let count = counts.entry(group.clone()).or_default();
+            *count += 1;
+            config.order.get_or_insert(*count);
+        }
+    }
+
+    /// Turns `config` off when the disabled list names it or the tag filter leaves it out
+    fn filter_entry(&self, disabled: &DisabledList, config: &mut ConfigFile) {
+        disabled.apply(config);
+        let tagged = |tag: &String| config.tags.contains(tag);
+        let selected = match self.tag_match {
+            TagMatch::Any => self.tags.iter().any(tagged),
+            TagMatch::All => self.tags.iter().all(tagged),
+        };
+        if !self.tags.is_empty() && !selected {
+            config.enabled = false;
+        }
+    }
+
+    /// Config entries as written (names before token expansion), with `enabled` cleared
+    /// for entries the disabled list or tag filter turns off
+    pub fn entries(&self) -> Result<Vec<ConfigFile>> {
+        let configs = self.collect_configs(&mut Vec::new())?;
+        Ok(configs.into_iter().map(|(_, config)| config).collect())
+    }
+
+    /// Targets of every discovery source, paired with the source they came from
+    fn discover_targets(
+        &self,
+        warnings: &mut Vec<String>,
+    ) -> Result<Vec<(&DiscoverySource, DiscoveredTarget)>> {
+        let mut targets = Vec::new();
+        for source in &self.discovery {
+            let found =
+                source.discover(self.command_timeout, self.command_runner.as_ref(), warnings)?;
+            targets.extend(found.into_iter().map(|t| (source, t)));
+        }
+        Ok(targets)
+    }
+
+    /// Templates of every manifest this generator reads, including fetched `templateSources`
+    pub fn templates(&self) -> Result<Templates> {
+        Templates::from_paths(&self.template_paths()?)
+    }
+
+    fn template_file(&self) -> Result<TemplateFile> {
+        let span = tracing::debug_span!(
+            "load_templates",
+            manifests = field::Empty,
+            templates = field::Empty
+        )
+        .entered();
+        let paths = self.template_paths()?;
+        span.record("manifests", paths.len());
+        let templates = TemplateFile::from_paths(&paths)?;
+        let settings = Settings::from_path(&Settings::path_for(&self.templates_path))?;
+        templates.check_forbidden_keys(&settings.forbid_template_keys)?;
+        span.record("templates", templates.names().count());
+        Ok(templates)
+    }
+
+    /// Templates manifests (or manifest directories), the main one first, then the fetched
+    /// `templateSources` of the settings file
+    fn template_paths(&self) -> Result<Vec<PathBuf>> {
+        let settings = Settings::from_path(&Settings::path_for(&self.templates_path))?;
+        let mut paths: Vec<PathBuf> = std::iter::once(&self.templates_path)
+            .chain(&self.extra_templates)
+            .cloned()
+            .collect();
+        paths.extend(source_manifests(&self.templates_path, &settings)?);
+        Ok(paths)
+    }
+
+    /// Every templates manifest read, with directories expanded
+    fn template_manifests(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for path in self.template_paths()? {
+            files.extend(manifest_files(&path)?);
+        }
+        Ok(files)
+    }
+
+    /// Waits for other mklaunch runs on the same inputs to finish and keeps them waiting
+    /// until the returned guard is dropped. The advisory lock is taken on `generate.lock`
+    /// next to the templates manifest.
+    pub fn guard(&self) -> Result<GenerationGuard> {
+        let path = self
+            .templates_path
+            .parent()
+            .map(|p| p.join("generate.lock"))
+            .unwrap_or_else(|| PathBuf::from("generate.lock"));
+        GenerationGuard::acquire(&path)
+    }
+
+    /// Location of the list of configurations to disable: `disabled.json` next to the
+    /// templates manifest
+    pub fn disabled_path(&self) -> PathBuf {
+        self.templates_path
+            .parent()
+            .map(|p| p.join("disabled.json"))
+            .unwrap_or_else(|| PathBuf::from("disabled.json"))
+    }
+
+    /// Location of the known-flags file read by `dedupe_args`: `flags.json` next to the
+    /// templates manifest
+    pub fn flags_path(&self) -> PathBuf {
+        self.templates_path
+            .parent()
+            .map(|p| p.join("flags.json"))
+            .unwrap_or_else(|| PathBuf::from("flags.json"))
+    }
+
+    /// Problems with how `output` is kept in git, checked against `gitPolicy` in
+    /// settings.json; empty outside a git work tree
+    pub fn git_warnings(&self, output: &Path) -> Result<Vec<String>> {
+        let settings = Settings::from_path(&Settings::path_for(&self.templates_path))?;
+        Ok(policy_warnings(
+            output,
+            settings.git_policy,
+            self.command_timeout,
+            self.command_runner.as_ref(),
+        ))
+    }
+
+    /// Location of the generation lock file: `lock.json` next to the templates manifest
+    pub fn lock_path(&self) -> PathBuf {
+        self.templates_path
+            .parent()
+            .map(|p| p.join("lock.json"))
+            .unwrap_or_else(|| PathBuf::from("lock.json"))
+    }
+
+    /// Location of the resolve cache: `cache/resolved.json` next to the templates manifest
+    pub fn cache_path(&self) -> PathBuf {
+        self.templates_path
+            .parent()
+            .map(|p| p.join("cache"))
+            .unwrap_or_else(|| PathBuf::from("cache"))
+            .join("resolved.json")
+    }
+
+    /// Reports templates no enabled config extends, arg set files nothing references,
+    /// configurations identical to another one apart from their name, and unknown `${...}`
+    /// variables, including `${input:id}` ids missing from the `inputs` of settings.json
+    pub fn lint(&self) -> Result<Vec<LintWarning>> {
+        let templates = self.template_file()?;
+        let arg_sets = ArgSetLibrary::from_dir(&self.args_dir)?;
+        let configs = self.collect_configs(&mut Vec::new())?;
+        let mut warnings = lint(&templates, &arg_sets, &configs, &self.root);
+        for source in &self.discovery {
+            warnings.retain(|w| *w != LintWarning::UnusedTemplate(source.template.clone()));
+        }
+
+        let configurations = self.resolve_all()?.configurations;
+        for (duplicate, original) in duplicate_content(&configurations) {
+            warnings.push(LintWarning::DuplicateContent {
+                name: configurations[duplicate].name.clone(),
+                duplicate_of: configurations[original].name.clone(),
+            });
+        }
+
+        let settings = Settings::from_path(&Settings::path_for(&self.templates_path))?;
+        let inputs = input_ids(settings.launch_keys.get("inputs"));
+        warnings.extend(check_variables(&configurations, &inputs));
+        Ok(warnings)
+    }
+
+    /// Checks the keys of every template against the `configurationAttributes` of its debug
+    /// adapter: the built-in copy, replaced per debugger type by the extension package.json
+    /// files (or https URLs) in `packages`. Unknown attributes are warnings, values of the
+    /// wrong type errors, and templates of a type no schema describes get an info.
+    pub fn lint_templates(&self, packages: &[String]) -> Result<Diagnostics> {
+        let mut schemas = AdapterSchemas::builtin()?;
+        for package in packages {
+            schemas.extend(AdapterSchemas::read(
+                package,
+                self.command_timeout,
+                self.command_runner.as_ref(),
+            )?);
+        }
+        let templates = self.template_file()?;
+        let mut diagnostics = Diagnostics::new();
+        for (name, value, origin) in templates.entries() {
+            for diagnostic in schemas.check_template(origin, templates.pointer(name), value) {
+                diagnostics.push(diagnostic);
+            }
+        }
+        Ok(diagnostics)
+    }
+
+    /// Checks every enabled config entry against the merged template set without resolving
+    /// it. Every entry that fails to parse or extends an unknown template is reported, the
+    /// latter with the closest template name.
+    pub fn validate(&self) -> Result<Diagnostics> {
+        self.validate_files(None)
+    }
+
+    /// Checks the entries of one config file like `validate`, for editors linting a file as
+    /// it is saved. `include`s may still name entries of the other files in the configs
+    /// directory. Problems loading the templates are reported on the file instead of failing,
+    /// and keys mklaunch does not know (ignored unless `strict_config` is set) are reported
+    /// as warnings.
+    pub fn validate_file(&self, path: &Path) -> Result<Diagnostics> {
+        self.validate_files(Some(path))
+    }
+
+    /// Diagnostics of every config file, or only of `only`
+    fn validate_files(&self, only: Option<&Path>) -> Result<Diagnostics> {
+        let mut diagnostics = Diagnostics::new();
+        let templates = match (self.template_file(), only) {
+            (Ok(templates), _) => Some(templates),
+            (Err(err), Some(path)) => {
+                diagnostics.push(into_diagnostic(err, path, ""));
+                None
+            }
+            (Err(err), None) => return Err(err),
+        };
+        if only.is_none()
+            && let Some(templates) = &templates
+            && !Settings::from_path(&Settings::path_for(&self.templates_path))?
+                .allow_any_template_name
+        {
+            diagnostics.extend(templates.check_names());
+        }
+        let names: Option<Vec<&str>> = templates.as_ref().map(|t| t.names().collect());
+        let mut files = match only {
+            Some(_) if !self.configs_dir.exists() => self.extra_configs.clone(),
+            _ => self.config_files()?.0,
+        };
+        if let Some(path) = only
+            && !files.iter().any(|f| f == path)
+        {
+            files.push(path.to_path_buf());
+        }
+        let reported = |path: &Path| only.is_none_or(|only| only == path);
+
+        let mut entries: Vec<RawEntry> = Vec::new();
+        for path in files {
+            match ConfigFile::read_entries(&path, &mut Vec::new()) {
+                Ok(read) => entries.extend(read),
+                Err(err) if reported(&path) => diagnostics.push(into_diagnostic(err, &path, "")),
+                Err(_) => {}
+            }
+        }
+        let entries = match (layer_entries(entries, &self.overlay_configs), only) {
+            (Ok(entries), _) => entries,
+            (Err(err), Some(path)) => {
+                diagnostics.push(into_diagnostic(err, path, ""));
+                return Ok(diagnostics);
+            }
+            (Err(err), None) => return Err(err),
+        };
+        let resolved = match (resolve_includes(&entries), only) {
+            (Ok(resolved), _) => resolved,
+            (Err(err), Some(path)) => {
+                diagnostics.push(into_diagnostic(err, path, ""));
+                return Ok(diagnostics);
+            }
+            (Err(err), None) => return Err(err),
+        };
+        let disabled = DisabledList::from_path(&self.disabled_path())?;
+
+        for (value, entry) in resolved.into_iter().zip(&entries) {
+            if !reported(&entry.path) {
+                continue;
+            }
+            if only.is_some()
+                && !self.strict_config
+                && let Value::Object(object) = &entry.value
+            {
+                for key in object.keys().filter(|k| !CONFIG_KEYS.contains(&k.as_str())) {
+                    let pointer = format!("{}/{}", entry.pointer, key);
+                    let message =
+                        format!("Unknown key '{}' in configuration entry is ignored", key);
+                    let diagnostic = match suggest(key, &CONFIG_KEYS) {
+                        Some(known) => Diagnostic::at_pointer(
+                            &entry.path,
+                            &pointer,
+                            format!("{}; did you mean '{}'?", message, known),
+                        ),
+                        None => Diagnostic::at_pointer(&entry.path, &pointer, message),
+                    };
+                    diagnostics.push(diagnostic.warning());
+                }
+            }
+            let configs = match ConfigFile::from_entry(value, entry, self.strict_config) {
+                Ok(configs) => configs,
+                Err(err) => {
+                    diagnostics.push(into_diagnostic(err, &entry.path, &entry.pointer));
+                    continue;
+                }
+            };
+            let Some(names) = &names else {
+                continue;
+            };
+            for mut config in configs {
+                self.filter_entry(&disabled, &mut config);
+                if !config.enabled || names.contains(&config.extends.as_str()) {
+                    continue;
+                }
+                // `extends` may be inherited through `include`
+                let pointer = match entry.value.get("extends") {
+                    Some(_) => format!("{}/extends", entry.pointer),
+                    None => entry.pointer.clone(),
+                };
+                let suggestion = suggest(&config.extends, names);
+                let hint = match suggestion {
+                    Some(name) => format!("; did you mean '{}'?", name),
+                    None => String::new(),
+                };
+                let mut diagnostic = Diagnostic::at_pointer(
+                    &entry.path,
+                    &pointer,
+                    format!(
+                        "'{}' extends unknown template '{}'{}",
+                        config.name, config.extends, hint
+                    ),
+                );
+                if let Some(name) = suggestion
+                    && entry.value.get("extends").is_some()
+                {
+                    diagnostic =
+                        diagnostic.with_fix(format!("Replace with '{}'", name), &json!(name));
+                }
+                diagnostics.push(diagnostic);
+            }
+        }
+        Ok(diagnostics)
+    }
+
+    /// Checks that the debuggers, programs, envFiles and preLaunchTasks referenced by the
+    /// generated configurations exist on this machine
+    pub fn doctor(&self) -> Result<Vec<ConfigHealth>> {
+        let tasks = Tasks::load(&self.root)?;
+        let configurations = self.resolve_all()?.configurations;
+        Ok(configurations
+            .iter()
+            .map(|config| doctor::check(config, &self.root, &tasks))
+            .collect())
+    }
+
+    /// Resolves the enabled configuration named `name` (after token expansion) and records
+    /// where each of its values came from
+    pub fn explain(&self, name: &str) -> Result<Explanation> {
+        let resolver = self.resolver()?;
+
+        for (source, config) in self.collect_configs(&mut Vec::new())? {
+            if !config.enabled {
+                continue;
+            }
+            let mut provenance = Provenance::default();
+            let launch = resolver
+                .resolve_entry_traced(&source, config, &mut provenance)
+                .with_context(|| format!("Error processing config: {}", source.display()))?;
+            if launch.name == name {
+                return Ok(Explanation {
+                    source,
+                    config: launch,
+                    provenance,
+                });
+            }
+        }
+
+        anyhow::bail!("No enabled configuration named '{}' found", name)
+    }
+
+    /// Every file the generated output depends on: the settings file, the templates
+    /// manifests, the defaults file, config files, arg set files, and the baseArgs/envFile
+    /// files referenced by enabled configs
+    pub fn input_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let settings = Settings::path_for(&self.templates_path);
+        if settings.exists() {
+            files.push(settings);
+        }
+        files.extend(self.template_manifests()?);
+        if self.defaults_path.exists() {
+            files.push(self.defaults_path.clone());
+        }
+        if self.disabled_path().exists() {
+            files.push(self.disabled_path());
+        }
+        if self.dedupe_args.is_some() && self.flags_path().exists() {
+            files.push(self.flags_path());
+        }
+        files.extend(
+            ArgSetLibrary::from_dir(&self.args_dir)?
+                .files()
+                .iter()
+                .cloned(),
+        );
+
+        for source in &self.discovery {
+            if source.path.is_file() {
+                files.push(source.path.clone());
+            }
+        }
+        let mut sources: Vec<PathBuf> = self
+            .discover_targets(&mut Vec::new())?
+            .into_iter()
+            .map(|(_, t)| t.source)
+            .collect();
+        sources.dedup();
+        files.extend(sources);
+
+        let (config_files, fragments) = self.config_files()?;
+        files.extend(config_files);
+        files.extend(fragments);
+        let configs = self.collect_configs(&mut Vec::new())?;
+
+        for (_, config) in configs.iter().filter(|(_, c)| c.enabled) {
+            // Missing optional baseArgs files are skipped during generation
+            if let Some(base_args) = &config.base_args
+                && on_disk(&self.root, base_args).exists()
+            {
+                files.push(on_disk(&self.root, base_args));
+            }
+            if let Some(env_file) = &config.env_file {
+                files.push(on_disk(&self.root, env_file.path()));
+            }
+        }
+        Ok(files)
+    }
+
+    /// Generates launch.json and writes its contents to `writer`
+    pub fn generate_to<W: Write>(&self, mut writer: W) -> Result<LaunchJson> {
+        let launch = self.generate()?;
+        writer
+            .write_all(launch.to_string_as(self.output_format)?.as_bytes())
+            .context("Failed to write launch.json")?;
+        Ok(launch)
+    }
+
+    /// Main generation process - reads configs, merges with templates, and returns LaunchJson
+    pub fn generate(&self) -> Result<LaunchJson> {
+        let span = tracing::info_span!(
+            "generate",
+            configurations = field::Empty,
+            warnings = field::Empty
+        )
+        .entered();
+        let Resolved {
+            mut configurations,
+            warnings,
+            errors,
+            mut summary,
+        } = self.resolve_all()?;
+        for warning in &warnings {
+            tracing::warn!("{}", warning);
+        }
+
+        if self.dedupe {
+            // Indices ascend, so removing from the back keeps the remaining ones valid
+            for (duplicate, _) in duplicate_content(&configurations).into_iter().rev() {
+                configurations.remove(duplicate);
+                summary.skipped += 1;
+            }
+        }
+        summary.warnings = warnings.len();
+        let compounds = apply_launch_after(&mut configurations)?;
+
+        let settings = Settings::from_path(&Settings::path_for(&self.templates_path))?;
+        let mut extra = settings.launch_keys;
+        if !compounds.is_empty() {
+            merge_compounds(&mut extra, compounds);
+        }
+        if self.stamp {
+            extra.insert(GENERATED_BY.to_string(), self.generated_by()?);
+        }
+        span.record("configurations", configurations.len());
+        span.record("warnings", warnings.len());
+        let launch_json = LaunchJson {
+            version: settings
+                .launch_version
+                .unwrap_or_else(|| LAUNCH_VERSION.to_string()),
+            configurations,
+            extra,
+            warnings,
+            errors,
+            summary,
+        };
+
+        Ok(launch_json)
+    }
+
+    /// `generatedBy` value: the tool version and a hash of the paths and contents of every
+    /// input file, which changes exactly when regenerating could change the output
+    fn generated_by(&self) -> Result<Value> {
+        let inputs: Vec<String> = self
+            .input_files()?
+            .iter()
+            .map(|path| format!("{} {}", path.display(), hash_file(path)))
+            .collect();
+        Ok(json!({
+            "tool": "mklaunch",
+            "version": env!("CARGO_PKG_VERSION"),
+            "inputsHash": hash_parts(inputs.iter().map(String::as_str))
+        }))
+    }
+
+    /// Every enabled configuration, resolved and sorted by name, with resolution warnings,
+    /// the errors skipped by `keep_going` and counts of what was read
+    fn resolve_all(&self) -> Result<Resolved> {
+        let resolver = self.resolver()?;
+        let mut summary = GenerationSummary {
+            templates: resolver.templates.names().count(),
+            ..Default::default()
+        };
+
+        let mut warnings = Vec::new();
+        let discovered = self.discover_targets(&mut warnings)?;
+        let devcontainer = self.devcontainer()?;
+
+        // Failing files and entries are collected so all of them are reported at once
+        let settings = Settings::from_path(&Settings::path_for(&self.templates_path))?;
+        if !settings.allow_any_template_name {
+            for warning in resolver.templates.check_names() {
+                warnings.push(format!("{}: {}", warning.location(), warning.message));
+            }
+        }
+        let mut errors = Diagnostics::new();
+        let (entries, mut resolved) = if self.cache {
+            self.resolve_cached(&resolver, &mut warnings, &mut errors, &mut summary)?
+        } else {
+            let configs = self.read_configs(&mut warnings, Some(&mut errors))?;
+            summary.files = self.config_files()?.0.len();
+            let entries = configs.len();
+            let mut resolved: Vec<(PathBuf, LaunchConfig)> = Vec::new();
+            // Disabled configurations are skipped before validation
+            for (config_path, config) in configs.into_iter().filter(|(_, c)| c.enabled) {
+                if config.base_args.is_some() {
+                    summary.base_args += 1;
+                }
+                let merged = resolver
+                    .resolve_entry(&config_path, config)
+                    .with_context(|| format!("Error processing config: {}", config_path.display()));
+                match recover(merged, &config_path, Some(&mut errors))? {
+                    Some(merged) => resolved.push((config_path, merged)),
+                    None => summary.failed += 1,
+                }
+            }
+            (entries, resolved)
+        };
+        let discovered_count = discovered.len();
+        let mut targets: Vec<(PathBuf, LaunchConfig)> = Vec::new();
+        let mut failed_targets = 0;
+        for (source, target) in discovered {
+            let path = target.source.clone();
+            let config = resolve_target(&resolver, source, target);
+            match recover(config, &path, Some(&mut errors))? {
+                Some(config) => targets.push((path, config)),
+                None => failed_targets += 1,
+            }
+        }
+        if !self.keep_going && !errors.is_empty() {
+            return Err(errors.into());
+        }
+        summary.enabled = resolved.len();
+        summary.disabled = entries - resolved.len() - summary.failed;
+        summary.discovered = discovered_count;
+        summary.failed += failed_targets;
+
+        if resolved.is_empty() && targets.is_empty() && !errors.is_empty() {
+            anyhow::bail!("No configuration could be generated:\n{}", errors);
+        }
+        if entries == 0 && discovered_count == 0 {
+            anyhow::bail!(
+                "No configuration entries found in: {}",
+                self.configs_dir.display()
+            );
+        }
+        if resolved.is_empty() && discovered_count == 0 {
+            anyhow::bail!(
+                "No enabled configuration entries found in: {}",
+                self.configs_dir.display()
+            );
+        }
+        resolved.extend(targets);
+        if let Some(devcontainer) = &devcontainer {
+            for (_, config) in &mut resolved {
+                config.rewrite_strings(&|s| devcontainer.rewrite(s));
+            }
+        }
+        if let Some(style) = self.path_style {
+            for (_, config) in &mut resolved {
+                config.normalize_paths(style);
+            }
+        }
+        if !self.overrides.is_empty() {
+            for pattern in &self.override_names {
+                if !resolved.iter().any(|(_, c)| glob_match(pattern, &c.name)) {
+                    warnings.push(format!("--only '{}' matches no configuration", pattern));
+                }
+            }
+            for (_, config) in &mut resolved {
+                self.apply_overrides(config)?;
+            }
+        }
+
+        // Names may contain tokens, so uniqueness is checked on the expanded names
+        let case_sensitive = settings.case_sensitive_names;
+        match self.duplicate_names {
+            DuplicateNames::Error => validate_unique_names(
+                resolved
+                    .iter()
+                    .map(|(p, c)| (p.as_path(), c.pointer.as_str(), c.name.as_str())),
+                case_sensitive,
+            )?,
+            policy => {
+                let before = resolved.len();
+                resolve_duplicate_names(&mut resolved, policy, case_sensitive, &mut warnings);
+                summary.skipped = before - resolved.len();
+            }
+        }
+
+        let mut configurations: Vec<LaunchConfig> = resolved.into_iter().map(|(_, c)| c).collect();
+
+        // Sort configurations by display name to stabilize order, perFile ones last
+        configurations.sort_by(|a, b| (a.per_file, &a.name).cmp(&(b.per_file, &b.name)));
+
+        warnings.extend(resolver.take_warnings());
+        Ok(Resolved {
+            configurations,
+            warnings,
+            errors,
+            summary,
+        })
+    }
+
+    /// Dev container definition of the workspace when generating for one
+    fn devcontainer(&self) -> Result<Option<DevContainer>> {
+        if self.environment != Environment::Devcontainer {
+            return Ok(None);
+        }
+        match DevContainer::find(&self.root)? {
+            Some(devcontainer) => Ok(Some(devcontainer)),
+            None => anyhow::bail!(
+                "No .devcontainer/devcontainer.json found in the workspace root {}",
+                Path::new(".").join(&self.root).display()
+            ),
+        }
+    }
+
+    /// Applies the `set` overrides to `config` when its name matches `override_names`
+    fn apply_overrides(&self, config: &mut LaunchConfig) -> Result<()> {
+        if !self.override_names.is_empty()
+            && !self
+                .override_names
+                .iter()
+                .any(|pattern| glob_match(pattern, &config.name))
+        {
+            return Ok(());
+        }
+        for (key, value) in &self.overrides {
+            config
+                .set_path(key, value.clone())
+                .with_context(|| format!("Failed to set '{}' in '{}'", key, config.name))?;
+        }
+        Ok(())
+    }
+
+    /// Resolves enabled config entries one at a time, in file order, followed by discovered
+    /// targets. Templates are loaded, config files parsed and targets discovered up front;
+    /// each configuration is only resolved when the iterator reaches it, so consumers can
+    /// stop early or filter without building the whole `LaunchJson`. Unlike `generate`, the
+    /// configurations are not sorted or checked for duplicate names, `launchAfter` is not
+    /// applied, `--cache` is not used and warnings are dropped.
+    pub fn resolve_iter(&self) -> Result<impl Iterator<Item = Result<LaunchConfig>> + '_> {
+        let resolver = Rc::new(self.resolver()?);
+        let configs = self.collect_configs(&mut Vec::new())?;
+        let discovered = self.discover_targets(&mut Vec::new())?;
+        let devcontainer = self.devcontainer()?;
+
+        let entries = {
+            let resolver = Rc::clone(&resolver);
+            configs
+                .into_iter()
+                .filter(|(_, config)| config.enabled)
+                .map(move |(path, config)| {
+                    resolver
+                        .resolve_entry(&path, config)
+                        .with_context(|| format!("Error processing config: {}", path.display()))
+                })
+        };
+        let targets = discovered
+            .into_iter()
+            .map(move |(source, target)| resolve_target(&resolver, source, target));
+        let style = self.path_style;
+        Ok(entries.chain(targets).map(move |config| {
+            let mut config = config?;
+            if let Some(devcontainer) = &devcontainer {
+                config.rewrite_strings(&|s| devcontainer.rewrite(s));
+            }
+            if let Some(style) = style {
+                config.normalize_paths(style);
+            }
+            self.apply_overrides(&mut config)?;
+            Ok(config)
+        }))
+    }
+
+    /// Resolves the enabled entries of each config file, reusing the cached configurations
+    /// of files whose content and inputs are unchanged. Returns the number of entries
+    /// (enabled or not) and the resolved configurations. Files with errors skipped by
+    /// `keep_going` are not cached.
+    fn resolve_cached(
+        &self,
+        resolver: &Resolver,
+        warnings: &mut Vec<String>,
+        errors: &mut Diagnostics,
+        summary: &mut GenerationSummary,
+    ) -> Result<(usize, Vec<(PathBuf, LaunchConfig)>)> {
+        let (config_files, fragments) = self.config_files()?;
+        summary.files = config_files.len();
+
+        // Inputs every config file is resolved against, and the options affecting resolution
+        let mut shared: Vec<String> = vec![format!(
+            "{} {} {:?} {:?} {:?} {} {} {} {} {:?} {:?}",
+            env!("CARGO_PKG_VERSION"),
+            self.root.display(),
+            self.environment,
+            self.dedupe_args,
+            self.flatten_os,
+            self.allow_missing_base_args,
+            self.strict_config,
+            self.group_by_dir,
+            self.group_by_template,
+            self.tags,
+            self.tag_match
+        )];
+        let disabled_path = self.disabled_path();
+        let flags_path = self.flags_path();
+        let settings_path = Settings::path_for(&self.templates_path);
+        for path in self
+            .template_manifests()?
+            .iter()
+            .chain([
+                &self.defaults_path,
+                &disabled_path,
+                &flags_path,
+                &settings_path,
+            ])
+            .chain(resolver.arg_sets.files())
+            .chain(&fragments)
+        {
+            shared.push(format!("{} {}", path.display(), hash_file(path)));
+        }
+        let shared = hash_parts(shared.iter().map(String::as_str));
+
+        let contents: Vec<String> = config_files
+            .iter()
+            .map(|path| format!("{} {}", path.display(), hash_file(path)))
+            .collect();
+        let all_configs = hash_parts(contents.iter().map(String::as_str));
+        let key = |idx: usize, includes: bool| {
+            let all = if includes { all_configs.as_str() } else { "" };
+            hash_parts([contents[idx].as_str(), shared.as_str(), all])
+        };
+
+        let cache_path = self.cache_path();
+        let mut cache = ResolveCache::load(&cache_path);
+        let hits: Vec<Option<CachedFile>> = config_files
+            .iter()
+            .enumerate()
+            .map(|(idx, path)| cache.lookup(path, |includes| key(idx, includes)).cloned())
+            .collect();
+        let fresh = hits.iter().filter(|hit| hit.is_some()).count();
+        tracing::debug!(fresh, stale = hits.len() - fresh, "looked up config files");
+
+        // Any change means parsing every file, since includes may reference entries in any of them
+        let mut parsed: Vec<(Vec<ConfigFile>, bool, Vec<String>)> = Vec::new();
+        let mut broken = BTreeSet::new();
+        if hits.iter().any(Option::is_none) {
+            let mut entries: Vec<RawEntry> = Vec::new();
+            for (idx, path) in config_files.iter().enumerate() {
+                let mut file_warnings = Vec::new();
+                let file_entries = ConfigFile::read_entries(path, &mut file_warnings)
+                    .with_context(|| format!("Skipped config file: {}", path.display()));
+                let Some(file_entries) = recover(file_entries, path, Some(&mut *errors))? else {
+                    broken.insert(idx);
+                    parsed.push((Vec::new(), false, file_warnings));
+                    continue;
+                };
+                // Template group orders and overlays depend on the entries of every file,
+                // like includes
+                let includes = self.group_by_template
+                    || !self.overlay_configs.is_empty()
+                    || file_entries
+                        .iter()
+                        .any(|e| e.value.get("include").is_some());
+                parsed.push((Vec::new(), includes, file_warnings));
+                entries.extend(file_entries);
+            }
+            let entries = layer_entries(entries, &self.overlay_configs)?;
+            let values = resolve_includes(&entries)?;
+            let disabled = DisabledList::from_path(&disabled_path)?;
+            for (value, entry) in values.into_iter().zip(&entries) {
+                let idx = config_files.iter().position(|p| *p == entry.path);
+                let idx = idx.expect("entries come from listed files");
+                let configs = ConfigFile::from_entry(value, entry, self.strict_config);
+                let Some(configs) = recover(configs, &entry.path, Some(&mut *errors))? else {
+                    broken.insert(idx);
+                    continue;
+                };
+                let file = &mut parsed[idx].0;
+                for mut config in configs {
+                    self.filter_entry(&disabled, &mut config);
+                    file.push(config);
+                }
+            }
+            self.apply_template_groups(parsed.iter_mut().flat_map(|(configs, ..)| configs));
+        }
+
+        let mut resolved: Vec<(PathBuf, LaunchConfig)> = Vec::new();
+        let mut entries = 0;
+        let parsed_any = !parsed.is_empty();
+        let mut parsed = parsed.into_iter();
+        for (idx, (config_path, hit)) in config_files.iter().zip(hits).enumerate() {
+            let file = parsed.next();
+            let cached = match (hit, file) {
+                (Some(cached), _) => cached,
+                (None, Some((configs, includes, file_warnings))) => {
+                    let failed = summary.failed;
+                    let mut cached = self.resolve_file(
+                        resolver,
+                        config_path,
+                        configs,
+                        file_warnings,
+                        errors,
+                        summary,
+                    )?;
+                    cached.key = key(idx, includes);
+                    cached.includes = includes;
+                    if summary.failed == failed && !broken.contains(&idx) {
+                        cache.insert(config_path, cached.clone());
+                    }
+                    cached
+                }
+                (None, None) => unreachable!("files are parsed when any is not cached"),
+            };
+
+            entries += cached.entries;
+            summary.base_args += cached.base_args;
+            warnings.extend(cached.warnings);
+            let configurations = cached.configurations.into_iter().zip(&cached.templates);
+            let configurations = configurations
+                .zip(cached.launch_after)
+                .zip(cached.per_file)
+                .zip(cached.undecorated_names);
+            for ((((value, template), launch_after), per_file), undecorated_name) in configurations
+            {
+                let mut launch: LaunchConfig = serde_json::from_value(value)
+                    .with_context(|| format!("Invalid cache: {}", cache_path.display()))?;
+                // Key order, launchAfter, perFile and the undecorated name are not part of
+                // the cached JSON
+                launch.layout = resolver.layout(template);
+                launch.launch_after = launch_after;
+                launch.per_file = per_file;
+                launch.undecorated_name = undecorated_name;
+                if self.check_paths
+                    && !per_file
+                    && let Some(Value::String(cwd)) = launch.rest.get("cwd")
+                {
+                    check_cwd(&self.root, cwd).with_context(|| {
+                        format!("Error processing config: {}", config_path.display())
+                    })?;
+                }
+                if self.check_programs
+                    && !per_file
+                    && let Some(program) = &launch.program
+                {
+                    check_program(&self.root, program).with_context(|| {
+                        format!("Error processing config: {}", config_path.display())
+                    })?;
+                }
+                resolved.push((config_path.clone(), launch));
+            }
+        }
+
+        if !parsed_any {
+            return Ok((entries, resolved));
+        }
+        cache.retain(&config_files);
+        cache.write(&cache_path)?;
+        Ok((entries, resolved))
+    }
+
+    /// Resolves the enabled entries of one config file into a cache record; the caller
+    /// fills in its key. Entries skipped by `keep_going` are counted in `summary.failed`.
+    fn resolve_file(
+        &self,
+        resolver: &Resolver,
+        config_path: &Path,
+        configs: Vec<ConfigFile>,
+        mut warnings: Vec<String>,
+        errors: &mut Diagnostics,
+        summary: &mut GenerationSummary,
+    ) -> Result<CachedFile> {
+        let entries = configs.len();
+        let mut dependencies = BTreeMap::new();
+        let mut configurations = Vec::new();
+        let mut templates = Vec::new();
+        let mut launch_after = Vec::new();
+        let mut per_file = Vec::new();
+        let mut undecorated_names = Vec::new();
+        let mut volatile = false;
+        let mut base_args = 0;
+        for config in configs.into_iter().filter(|c| c.enabled) {
+            let template = config.extends.clone();
+            volatile |= config.args_from_command.is_some()
+                || matches!(config.cargo, Some(CargoBlock::Target(_)))
+                || config.core_dump.as_deref().is_some_and(is_core_pattern);
+            base_args += usize::from(config.base_args.is_some());
+            let files = config
+                .base_args
+                .as_deref()
+                .into_iter()
+                .chain(config.env_file.as_ref().map(|e| e.path()))
+                .map(|path| on_disk(&self.root, path));
+            for path in files {
+                let hash = hash_file(&path);
+                dependencies.insert(path.display().to_string(), hash);
+            }
+            let launch = resolver
+                .resolve_entry(config_path, config)
+                .with_context(|| format!("Error processing config: {}", config_path.display()));
+            let Some(launch) = recover(launch, config_path, Some(&mut *errors))? else {
+                summary.failed += 1;
+                continue;
+            };
+            templates.push(template);
+            configurations.push(serde_json::to_value(&launch)?);
+            launch_after.push(launch.launch_after);
+            per_file.push(launch.per_file);
+            undecorated_names.push(launch.undecorated_name);
+        }
+        warnings.extend(resolver.take_warnings());
+
+        Ok(CachedFile {
+            key: String::new(),
+            includes: false,
+            volatile,
+            dependencies,
+            entries,
+            base_args,
+            configurations,
+            templates,
+            launch_after,
+            per_file,
+            undecorated_names,
+            warnings,
+        })
+    }
+}
+
+/// Resolves a discovered target as an entry extending its source's template, then applies
+/// the keys the discovery set or removed
+fn resolve_target(
+    resolver: &Resolver,
+    source: &DiscoverySource,
+    target: DiscoveredTarget,
+) -> Result<LaunchConfig> {
+    let config = ConfigFile {
+        name: target.name,
+        extends: source.template.clone(),
+        enabled: true,
+        args: (!target.args.is_empty()).then_some(target.args),
+        ..Default::default()
+    };
+    let context = || {
+        format!(
+            "Error processing {} target: {}",
+            source.kind.label(),
+            target.source.display()
+        )
+    };
+    let mut merged = resolver
+        .resolve_entry(&target.source, config)
+        .with_context(context)?;
+    for (key, value) in target.keys {
+        match value {
+            Some(value) => merged.set(key, value).with_context(context)?,
+            None => merged.unset(key),
+        }
+    }
+    Ok(merged)
+}
+
+/// Checks one config file with [`Generator::validate_file`], for editor integrations.
+/// The inputs are found from the file's location: the nearest parent directory holding a
+/// `templates.json` is taken as the `.mklaunch` directory, with its `configs/` directory
+/// (falling back to the file's own directory when there is none). Failures are returned
+/// as diagnostics on the file.
+pub fn validate_config_file(path: &Path) -> Diagnostics {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let base = dir
+        .ancestors()
+        .find(|d| d.join("templates.json").is_file())
+        .unwrap_or(dir);
+    let configs_dir = match base.join("configs") {
+        configs if configs.is_dir() && path.starts_with(&configs) => configs,
+        _ => dir.to_path_buf(),
+    };
+    Generator::new(base.join("templates.json"), configs_dir)
+        .validate_file(path)
+        .unwrap_or_else(|err| Diagnostics::from(vec![into_diagnostic(err, path, "")]))
+}
+
+/// The located error in `err`, or one at `pointer` in `path` carrying its message
+fn into_diagnostic(err: anyhow::Error, path: &Path, pointer: &str) -> Diagnostic {
+    match err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<Diagnostic>())
+    {
+        Some(diagnostic) => diagnostic.clone(),
+        None => Diagnostic::at_pointer(path, pointer, format!("{:#}", err)),
+    }
+}
+
+/// Collects all JSON config entries from `configs_dir` in alphabetical order of file path.
+/// Files starting with `_` are include fragments and not config files themselves.
+/// Upgrade messages for outdated config files are added to `warnings`.
+pub(crate) fn collect_config_files(
+    configs_dir: &Path,
+    strict: bool,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<(PathBuf, ConfigFile)>> {
+    read_config_files(
+        &list_config_dir(configs_dir, false)?.0,
+        &[],
+        strict,
+        warnings,
+        None,
+    )
+}
+
+/// Reads the entries of `config_files` in order, layering the entries of files in the
+/// `overlays` directories and resolving includes across all of them. With `errors`, files
+/// and entries that fail to parse are skipped and their errors added to it.
+fn read_config_files(
+    config_files: &[PathBuf],
+    overlays: &[PathBuf],
+    strict: bool,
+    warnings: &mut Vec<String>,
+    mut errors: Option<&mut Diagnostics>,
+) -> Result<Vec<(PathBuf, ConfigFile)>> {
+    // Load after collecting all paths; includes may reference entries in any file
+    let mut entries: Vec<RawEntry> = Vec::new();
+    for config_path in config_files {
+        let file_entries = ConfigFile::read_entries(config_path, warnings)
+            .with_context(|| format!("Skipped config file: {}", config_path.display()));
+        entries.extend(
+            recover(file_entries, config_path, errors.as_deref_mut())?
+                .into_iter()
+                .flatten(),
+        );
+    }
+    let entries = layer_entries(entries, overlays)?;
+    let resolved = resolve_includes(&entries)?;
+
+    let mut configs: Vec<(PathBuf, ConfigFile)> = Vec::new();
+    for (value, entry) in resolved.into_iter().zip(&entries) {
+        let parsed = ConfigFile::from_entry(value, entry, strict);
+        for config in recover(parsed, &entry.path, errors.as_deref_mut())?
+            .into_iter()
+            .flatten()
+        {
+            configs.push((entry.path.clone(), config));
+        }
+    }
+    Ok(configs)
+}
+
+/// `result`'s value; with `errors`, a failure is added to it as a diagnostic on `path`
+/// and skipped instead of returned
+fn recover<T>(
+    result: Result<T>,
+    path: &Path,
+    errors: Option<&mut Diagnostics>,
+) -> Result<Option<T>> {
+    match (result, errors) {
+        (Ok(value), _) => Ok(Some(value)),
+        (Err(err), Some(errors)) => {
+            let located = err
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<Diagnostic>());
+            errors.push(match located {
+                Some(diagnostic) => diagnostic.clone(),
+                None => {
+                    // The diagnostic names the file, so contexts that only name it again
+                    // ("Error processing config: <path>") are left out
+                    let file = path.display().to_string();
+                    let message = err
+                        .chain()
+                        .map(ToString::to_string)
+                        .filter(|message| !message.ends_with(&file))
+                        .collect::<Vec<_>>()
+                        .join(": ");
+                    if message.is_empty() {
+                        Diagnostic::for_file(path, format!("{:#}", err))
+                    } else {
+                        Diagnostic::for_file(path, message)
+                    }
+                }
+            });
+            Ok(None)
+        }
+        (Err(err), None) => Err(err),
+    }
+}
+
+/// Merges each entry read from an `overlays` directory over the earlier entry with the same
+/// name, key by key, keeping the earlier entry's place; overlay entries with new names are
+/// kept as they are
+fn layer_entries(entries: Vec<RawEntry>, overlays: &[PathBuf]) -> Result<Vec<RawEntry>> {
+    let name = |value: &Value| {
+        value
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let mut layered: Vec<(RawEntry, ArgsLayer)> = Vec::new();
+    for entry in entries {
+        let mut args_layer = None;
+        if let Value::Object(object) = &entry.value {
+            note_args_layer(&mut args_layer, object, &entry.path.display().to_string())?;
+        }
+        if overlays.iter().any(|dir| entry.path.starts_with(dir))
+            && let Some(overlay_name) = name(&entry.value)
+            && let Some((base, base_args)) = layered
+                .iter_mut()
+                .find(|(e, _)| name(&e.value).as_ref() == Some(&overlay_name))
+            && let (Value::Object(base), Value::Object(overlay)) = (&mut base.value, &entry.value)
+        {
+            let label = format!("overlay {}", entry.path.display());
+            note_args_layer(base_args, overlay, &label)
+                .with_context(|| format!("In '{}'", overlay_name))?;
+            base.extend(overlay.clone());
+            continue;
+        }
+        layered.push((entry, args_layer));
+    }
+    Ok(layered.into_iter().map(|(entry, _)| entry).collect())
+}
+
+/// Lists `*.json` files in `configs_dir` as (config files, `_` fragment files), sorted.
+/// `*.csv` and `*.tsv` tables are always config files. With `recursive` (`--group-by-dir`),
+/// subdirectories are listed too; symlinked directories are never followed, so a link
+/// back up the tree cannot loop.
+pub(crate) fn list_config_dir(
+    configs_dir: &Path,
+    recursive: bool,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    if !configs_dir.exists() {
+        anyhow::bail!("Config directory does not exist: {}", configs_dir.display());
+    }
+
+    let mut config_files: Vec<PathBuf> = Vec::new();
+    let mut fragments: Vec<PathBuf> = Vec::new();
+    let mut dirs = vec![configs_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read configs directory: {}", dir.display()))?
+        {
+            let entry = entry?;
+            let path = entry.path();
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+
+            // `file_type` does not follow symlinks, unlike `Path::is_dir`
+            if entry.file_type()?.is_dir() {
+                if recursive && !file_name.starts_with('.') {
+                    dirs.push(path);
+                }
+            } else if path.is_file() && is_table(&path) {
+                config_files.push(path);
+            } else if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
+                if file_name.starts_with('_') {
+                    fragments.push(path);
+                } else {
+                    config_files.push(path);
+                }
+            }
+        }
+    }
+
+    config_files.sort();
+    fragments.sort();
+    Ok((config_files, fragments))
+}
+
+/// Applies the `launchAfter` of each configuration: a plain name becomes a compound
+/// starting both configurations, and a `pattern` becomes a `serverReadyAction` on the
+/// configuration started first. Returns the compounds, sorted by name.
+fn apply_launch_after(configurations: &mut [LaunchConfig]) -> Result<Vec<Value>> {
+    let mut compounds = Vec::new();
+    for idx in 0..configurations.len() {
+        let Some(launch_after) = configurations[idx].launch_after.clone() else {
+            continue;
+        };
+        let name = configurations[idx].name.clone();
+        let first = launch_after_target(configurations, idx, launch_after.name())?;
+        let first_name = configurations[first].name.clone();
+        match launch_after {
+            LaunchAfter::Name(_) => compounds.push(json!({
+                "name": format!("{} + {}", first_name, name),
+                "configurations": [first_name, name],
+                "stopAll": true
+            })),
+            LaunchAfter::Ready {
+                name: target,
+                pattern,
+            } => {
+                let first = &mut configurations[first];
+                if first.rest.contains_key("serverReadyAction") {
+                    anyhow::bail!(
+                        "'{}' has launchAfter '{}' with a pattern, but '{}' already has a serverReadyAction",
+                        name,
+                        target,
+                        first_name
+                    );
+                }
+                first.rest.insert(
+                    "serverReadyAction".to_string(),
+                    json!({ "action": "startDebugging", "pattern": pattern, "name": name }),
+                );
+            }
+        }
+    }
+    compounds.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+    Ok(compounds)
+}
+
+/// Index of the configuration `launchAfter` of `configurations[idx]` names: the one whose
+/// name before `nameFormat` is `target`, or else the one named `target` in launch.json
+fn launch_after_target(configurations: &[LaunchConfig], idx: usize, target: &str) -> Result<usize> {
+    let name = &configurations[idx].name;
+    let others = || {
+        configurations
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| *i != idx)
+    };
+    let undecorated: Vec<(usize, &LaunchConfig)> = others()
+        .filter(|(_, c)| c.undecorated_name.as_deref() == Some(target))
+        .collect();
+    match undecorated.as_slice() {
+        [(first, _)] => return Ok(*first),
+        [] => {}
+        several => {
+            let names: Vec<String> = several
+                .iter()
+                .map(|(_, c)| format!("'{}'", c.name))
+                .collect();
+            anyhow::bail!(
+                "'{}' has launchAfter '{}', which nameFormat turned into {}; use one of those names",
+                name,
+                target,
+                names.join(", ")
+            );
+        }
+    }
+    others()
+        .find(|(_, c)| c.name == target)
+        .map(|(first, _)| first)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "'{}' has launchAfter '{}', which is not another enabled configuration",
+                name,
+                target
+            )
+        })
+}
+
+/// Key two configuration names share when they count as the same name
+fn name_key(name: &str, case_sensitive: bool) -> String {
+    if case_sensitive {
+        name.to_string()
+    } else {
+        name.to_lowercase()
+    }
+}
+
+/// Renames (`Suffix`) or drops (`Skip`) every configuration whose name an earlier one
+/// already uses, reporting each in `warnings`
+fn resolve_duplicate_names(
+    resolved: &mut Vec<(PathBuf, LaunchConfig)>,
+    policy: DuplicateNames,
+    case_sensitive: bool,
+    warnings: &mut Vec<String>,
+) {
+    let key = |name: &str| name_key(name, case_sensitive);
+    let mut taken: BTreeSet<String> = resolved.iter().map(|(_, c)| key(&c.name)).collect();
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    resolved.retain_mut(|(path, config)| {
+        if seen.insert(key(&config.name)) {
+            return true;
+        }
+        if policy == DuplicateNames::Skip {
+            warnings.push(format!(
+                "Skipped duplicate configuration '{}' from {}",
+                config.name,
+                path.display()
+            ));
+            return false;
+        }
+        let renamed = (2..)
+            .map(|n| format!("{} ({})", config.name, n))
+            .find(|name| !taken.contains(&key(name)))
+            .expect("unbounded suffixes");
+        warnings.push(format!(
+            "Renamed duplicate configuration '{}' from {} to '{}'",
+            config.name,
+            path.display(),
+            renamed
+        ));
+        taken.insert(key(&renamed));
+        seen.insert(key(&renamed));
+        config.name = renamed;
+        true
+    });
 }
 
-/// Validates that all configuration names are unique across files
-pub(crate) fn validate_unique_names(configs: &[(PathBuf, ConfigFile)]) -> Result<()> {
-    let mut name_to_files: BTreeMap<&str, Vec<&Path>> = BTreeMap::new();
+/// Validates that all configuration names are unique across files; unless `case_sensitive`,
+/// names differing only in case count as the same name. Entries are `(file, JSON pointer of
+/// the entry, name)`; the pointer is empty for configurations no entry wrote.
+pub(crate) fn validate_unique_names<'a>(
+    entries: impl IntoIterator<Item = (&'a Path, &'a str, &'a str)>,
+    case_sensitive: bool,
+) -> Result<()> {
+    let mut name_to_files: BTreeMap<String, Vec<(&Path, &str, &str)>> = BTreeMap::new();
 
-    for (path, config) in configs {
-        name_to_files.entry(&config.name).or_default().push(path);
+    for (path, pointer, name) in entries {
+        name_to_files
+            .entry(name_key(name, case_sensitive))
+            .or_default()
+            .push((path, pointer, name));
     }
 
-    for (name, files) in name_to_files {
-        if files.len() > 1 {
-            let file_list: Vec<String> = files
+    for entries in name_to_files.into_values() {
+        if entries.len() > 1 {
+            let file_list: Vec<String> = entries
                 .iter()
-                .map(|p| format!("  - {}", p.display()))
+                .map(|(p, pointer, _)| match *pointer {
+                    "" => format!("  - {}", p.display()),
+                    pointer => format!("  - {} (at {})", p.display(), pointer),
+                })
                 .collect();
+            let name = entries[0].2;
 
+            if let Some((_, _, other)) = entries.iter().find(|(_, _, n)| *n != name) {
+                anyhow::bail!(
+                    "Configuration names '{}' and '{}' differ only in case, which VSCode does not always tell apart; found in:\n{}\nSet caseSensitiveNames in settings.json to allow this.",
+                    name,
+                    other,
+                    file_list.join("\n")
+                );
+            }
             anyhow::bail!(
                 "Duplicate configuration name '{}' found in:\n{}\nEach configuration must have a unique name.",
                 name,
@@ -0,0 +1,202 @@
+use crate::atomic::write_atomic;
+use crate::runner::{CommandRunner, CommandSpec};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Whether the generated launch.json belongs in the repository, from `gitPolicy` in
+/// settings.json
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum GitPolicy {
+    /// launch.json is committed and must not be gitignored
+    Commit,
+    /// launch.json is gitignored and must not be tracked
+    Ignore,
+}
+
+/// How a file relates to the git repository containing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct GitStatus {
+    pub tracked: bool,
+    /// Matched by a `.gitignore` pattern, whether or not the file is tracked
+    pub ignored: bool,
+}
+
+/// Runs git in `dir` and returns its stdout
+fn git(
+    dir: &Path,
+    args: &[&str],
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> Result<Vec<u8>> {
+    let command = CommandSpec::new("git", args.iter().copied())
+        .cwd(dir)
+        .timeout(timeout);
+    Ok(runner.run(&command)?.stdout)
+}
+
+/// Runs git in `dir` and returns whether it succeeded; false as well when git cannot run
+fn git_succeeds(
+    dir: &Path,
+    args: &[&str],
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> bool {
+    git(dir, args, timeout, runner).is_ok()
+}
+
+/// Directory of `path` to run git in, and its file name
+fn split(path: &Path) -> Option<(&Path, &str)> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    Some((dir, path.file_name()?.to_str()?))
+}
+
+/// Git status of `path`; `None` outside a work tree, when its directory does not exist
+/// or when git cannot run
+pub(crate) fn git_status(
+    path: &Path,
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> Option<GitStatus> {
+    let (dir, file) = split(path)?;
+    let succeeds = |args: &[&str]| git_succeeds(dir, args, timeout, runner);
+    if !dir.is_dir() || !succeeds(&["rev-parse", "--is-inside-work-tree"]) {
+        return None;
+    }
+    Some(GitStatus {
+        tracked: succeeds(&["ls-files", "--error-unmatch", "--", file]),
+        ignored: succeeds(&["check-ignore", "-q", "--no-index", "--", file]),
+    })
+}
+
+/// Problems with how `output` is kept in git: a mismatch with `policy` when one is set,
+/// and otherwise a file that is both tracked and gitignored
+pub(crate) fn policy_warnings(
+    output: &Path,
+    policy: Option<GitPolicy>,
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> Vec<String> {
+    let Some(status) = git_status(output, timeout, runner) else {
+        return Vec::new();
+    };
+    let path = output.display();
+    let mut warnings = Vec::new();
+    match policy {
+        Some(GitPolicy::Commit) => {
+            if status.ignored {
+                warnings.push(format!(
+                    "{} is gitignored, but settings.json sets gitPolicy \"commit\"; remove it from .gitignore",
+                    path
+                ));
+            } else if !status.tracked {
+                warnings.push(format!(
+                    "{} is not committed yet (gitPolicy \"commit\"); add it with `git add {}`",
+                    path, path
+                ));
+            }
+        }
+        Some(GitPolicy::Ignore) => {
+            if !status.ignored {
+                warnings.push(format!(
+                    "{} is not gitignored, but settings.json sets gitPolicy \"ignore\"; rerun with --gitignore to add it",
+                    path
+                ));
+            }
+            if status.tracked {
+                warnings.push(format!(
+                    "{} is tracked by git, but settings.json sets gitPolicy \"ignore\"; untrack it with `git rm --cached {}`",
+                    path, path
+                ));
+            }
+        }
+        None if status.tracked && status.ignored => warnings.push(format!(
+            "{} is tracked by git although .gitignore matches it; set gitPolicy in settings.json to choose one",
+            path
+        )),
+        None => {}
+    }
+    warnings
+}
+
+/// Appends `output` to the `.gitignore` at the top of its work tree, unless a pattern
+/// already matches it. Returns the `.gitignore` written, or `None` when nothing changed.
+/// git runs through `runner`.
+pub fn gitignore_output(
+    output: &Path,
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> Result<Option<PathBuf>> {
+    let (dir, _) = split(output).context("Output path has no file name")?;
+    let outside = || {
+        format!(
+            "{} is not inside a git work tree",
+            dir.canonicalize().unwrap_or(dir.to_path_buf()).display()
+        )
+    };
+    // Run first so that git failing to run (or --no-exec) is reported as such
+    let top = git(dir, &["rev-parse", "--show-toplevel"], timeout, runner).with_context(outside)?;
+    let status = git_status(output, timeout, runner).with_context(outside)?;
+    if status.ignored {
+        return Ok(None);
+    }
+
+    let top = PathBuf::from(String::from_utf8_lossy(&top).trim());
+    let output = output
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", output.display()))?;
+    let relative = output
+        .strip_prefix(top.canonicalize().unwrap_or(top.clone()))
+        .with_context(|| format!("{} is outside {}", output.display(), top.display()))?;
+    let pattern: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+
+    let gitignore = top.join(".gitignore");
+    let mut content = if gitignore.exists() {
+        fs::read_to_string(&gitignore)
+            .with_context(|| format!("Failed to read {}", gitignore.display()))?
+    } else {
+        String::new()
+    };
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("/{}\n", pattern.join("/")));
+    write_atomic(&gitignore, content.as_bytes())?;
+    Ok(Some(gitignore))
+}
+
+/// Writes the files under `dir` as of git revision `git_ref` into `into`, keeping their
+/// paths relative to `dir`. Returns the number of files written. git runs through `runner`.
+pub fn export_dir(
+    git_ref: &str,
+    dir: &Path,
+    into: &Path,
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> Result<usize> {
+    let git = |args: &[&str]| git(dir, args, timeout, runner);
+
+    let listing = git(&["ls-tree", "-r", "-z", "--name-only", git_ref, "--", "."])?;
+    let mut written = 0;
+    for name in listing.split(|b| *b == 0).filter(|n| !n.is_empty()) {
+        let name = String::from_utf8_lossy(name);
+        let content = git(&["show", &format!("{}:./{}", git_ref, name)])?;
+        let path = into.join(name.as_ref());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        written += 1;
+    }
+    Ok(written)
+}
@@ -0,0 +1,111 @@
+use crate::discover::DiscoveredTarget;
+use crate::generator::workspace_path;
+use crate::runner::{CommandRunner, CommandSpec};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Package as printed by `go list -json`
+#[derive(Debug, Deserialize)]
+struct Package {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "ImportPath")]
+    import_path: String,
+    #[serde(rename = "Dir")]
+    dir: PathBuf,
+    #[serde(rename = "GoFiles", default)]
+    go_files: Vec<String>,
+    #[serde(rename = "Module")]
+    module: Option<Module>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Module {
+    #[serde(rename = "Path")]
+    path: String,
+}
+
+/// Enumerates the `main` packages of the Go module at `path` with `go list -json ./...`
+pub(crate) fn discover(
+    path: &Path,
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> Result<Vec<DiscoveredTarget>> {
+    let dir = if path.is_file() {
+        path.parent().unwrap_or(Path::new(""))
+    } else {
+        path
+    };
+    let command = CommandSpec::new("go", ["list", "-json", "./..."])
+        .cwd(dir)
+        .timeout(timeout);
+    let output = runner
+        .run(&command)
+        .with_context(|| format!("Failed to list the Go packages in {}", dir.display()))?;
+    let stdout = String::from_utf8(output.stdout).context("`go list` printed invalid UTF-8")?;
+    parse_go_list(&stdout, &std::env::current_dir()?)
+}
+
+/// Turns the concatenated JSON objects printed by `go list -json` into one delve target per
+/// `main` package. Configurations are named after the package directory, qualified by the
+/// path within the module when two commands share a directory name. Package directories
+/// inside `workspace` are written relative to `${workspaceFolder}`.
+pub(crate) fn parse_go_list(output: &str, workspace: &Path) -> Result<Vec<DiscoveredTarget>> {
+    let mut commands = Vec::new();
+    for package in serde_json::Deserializer::from_str(output).into_iter::<Package>() {
+        let package = package.context("Failed to parse `go list -json` output")?;
+        if package.name == "main" {
+            commands.push(package);
+        }
+    }
+
+    let short_name = |package: &Package| {
+        package
+            .import_path
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for package in &commands {
+        *counts.entry(short_name(package)).or_default() += 1;
+    }
+
+    let workspace = workspace
+        .canonicalize()
+        .unwrap_or_else(|_| workspace.to_path_buf());
+    let mut targets = Vec::new();
+    for package in commands {
+        let mut name = short_name(&package);
+        if counts[&name] > 1 {
+            let module = package.module.as_ref().map(|m| m.path.as_str());
+            name = module
+                .and_then(|m| package.import_path.strip_prefix(m))
+                .map(|p| p.trim_start_matches('/').to_string())
+                .filter(|p| !p.is_empty())
+                .unwrap_or_else(|| package.import_path.clone());
+        }
+
+        let program = match package.dir.strip_prefix(&workspace) {
+            Ok(relative) if relative.as_os_str().is_empty() => "${workspaceFolder}".to_string(),
+            Ok(relative) => workspace_path(relative),
+            Err(_) => package.dir.display().to_string(),
+        };
+        let source = match package.go_files.first() {
+            Some(file) => package.dir.join(file),
+            None => package.dir.clone(),
+        };
+        targets.push(DiscoveredTarget {
+            name,
+            source,
+            keys: vec![("program", Some(Value::String(program)))],
+            args: Vec::new(),
+        });
+    }
+    Ok(targets)
+}
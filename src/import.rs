@@ -0,0 +1,298 @@
+use crate::add::file_stem_for;
+use crate::atomic::write_atomic;
+use crate::encoding::read_text;
+use crate::format::{format_config_file, format_manifest};
+use crate::generator::LAUNCH_VERSION;
+use crate::schema::SCHEMA_VERSION;
+use crate::settings::Settings;
+use anyhow::{Context, Result};
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files written by [`import_launch`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    /// Names of the templates written to the manifest
+    pub templates: Vec<String>,
+    /// Config file holding one entry per imported configuration
+    pub config_file: PathBuf,
+    pub configurations: usize,
+    /// settings.json written with the top-level keys of launch.json besides
+    /// `configurations` (`compounds`, `inputs`, ...) under `launchKeys`
+    pub settings_file: Option<PathBuf>,
+    /// Top-level keys that could not be carried over
+    pub warnings: Vec<String>,
+}
+
+/// Whether a config entry can set `key` to `value` itself
+fn entry_can_set(key: &str, value: &Value) -> bool {
+    match key {
+        "args" => true,
+        "cwd" => value.is_string(),
+        "windows" | "linux" | "osx" => value.is_object(),
+        _ => false,
+    }
+}
+
+/// Factors the configurations of an existing launch.json into a templates manifest and
+/// a single config file `imported.json` in `configs_dir`. Configurations that agree on all
+/// keys an entry cannot set share a template, named after `type` (and `request` unless it
+/// is `launch`); keys with the same value across the group go into the template and the
+/// rest (`args`, `cwd`, per-OS blocks) into each entry. Other top-level keys of launch.json
+/// go into `launchKeys` (and a `version` other than the default into `launchVersion`) of a
+/// new settings.json next to the manifest.
+pub fn import_launch(
+    launch_path: &Path,
+    templates_path: &Path,
+    configs_dir: &Path,
+) -> Result<Import> {
+    let content = read_text(launch_path)
+        .with_context(|| format!("Failed to read launch.json: {}", launch_path.display()))?;
+    let launch: Value = serde_json::from_str(&strip_jsonc(&content))
+        .with_context(|| format!("Failed to parse launch.json: {}", launch_path.display()))?;
+    let configurations = launch
+        .get("configurations")
+        .and_then(Value::as_array)
+        .ok_or_else(|| {
+            anyhow::anyhow!("{} has no 'configurations' array", launch_path.display())
+        })?;
+
+    let config_file = configs_dir.join("imported.json");
+    for path in [templates_path, config_file.as_path()] {
+        if path.exists() {
+            anyhow::bail!("Refusing to overwrite existing {}", path.display());
+        }
+    }
+
+    let mut settings = Map::new();
+    let mut launch_keys = Map::new();
+    for (key, value) in launch.as_object().into_iter().flatten() {
+        match key.as_str() {
+            "configurations" => {}
+            "version" if value == LAUNCH_VERSION => {}
+            "version" => {
+                settings.insert("launchVersion".to_string(), value.clone());
+            }
+            _ => {
+                launch_keys.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    if !launch_keys.is_empty() {
+        settings.insert("launchKeys".to_string(), Value::Object(launch_keys));
+    }
+    let settings_path = Settings::path_for(templates_path);
+    let mut warnings = Vec::new();
+    if !settings.is_empty() && settings_path.exists() {
+        warnings.push(format!(
+            "{} already exists; add {} to it by hand",
+            settings_path.display(),
+            settings
+                .iter()
+                .map(|(key, value)| match value {
+                    Value::Object(keys) => keys
+                        .keys()
+                        .map(|k| format!("'{}.{}'", key, k))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    _ => format!("'{}'", key),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        settings.clear();
+    }
+
+    // Configurations can share a template when they agree on every key an entry cannot set
+    let mut groups: BTreeMap<String, Vec<Vec<&Map<String, Value>>>> = BTreeMap::new();
+    for (idx, config) in configurations.iter().enumerate() {
+        let object = config
+            .as_object()
+            .ok_or_else(|| anyhow::anyhow!("Configuration at index {} must be an object", idx))?;
+        let config_type = object.get("type").and_then(Value::as_str).ok_or_else(|| {
+            anyhow::anyhow!("Configuration at index {} has no string 'type'", idx)
+        })?;
+        if object.get("name").and_then(Value::as_str).is_none() {
+            anyhow::bail!("Configuration at index {} has no string 'name'", idx);
+        }
+        if let Some(args) = object.get("args")
+            && !args
+                .as_array()
+                .is_some_and(|a| a.iter().all(Value::is_string))
+        {
+            anyhow::bail!(
+                "Configuration at index {} has 'args' that is not an array of strings",
+                idx
+            );
+        }
+        let base = match object.get("request").and_then(Value::as_str) {
+            None | Some("launch") => config_type.to_string(),
+            Some(request) => format!("{}-{}", config_type, request),
+        };
+
+        let variants = groups.entry(base).or_default();
+        match variants
+            .iter_mut()
+            .find(|v| template_part(v[0]) == template_part(object))
+        {
+            Some(variant) => variant.push(object),
+            None => variants.push(vec![object]),
+        }
+    }
+
+    let mut templates: Vec<Value> = Vec::new();
+    let mut template_names: Vec<String> = Vec::new();
+    let mut entries: Vec<Value> = Vec::new();
+    for (base, variants) in &groups {
+        for members in variants {
+            let name = if variants.len() == 1 {
+                base.clone()
+            } else {
+                let first = members[0]["name"].as_str().unwrap_or_default();
+                format!("{}-{}", base, file_stem_for(first))
+            };
+            let common = common_keys(members);
+
+            for member in members {
+                let mut entry = Map::new();
+                entry.insert("name".to_string(), member["name"].clone());
+                entry.insert("extends".to_string(), json!(name));
+                entry.insert("enabled".to_string(), json!(true));
+                for (key, value) in member.iter() {
+                    if key != "name" && !common.contains_key(key) {
+                        entry.insert(key.clone(), value.clone());
+                    }
+                }
+                entries.push(Value::Object(entry));
+            }
+
+            let mut template = Map::new();
+            template.insert("name".to_string(), json!(name));
+            template.extend(common);
+            templates.push(Value::Object(template));
+            template_names.push(name);
+        }
+    }
+
+    if let Some(parent) = templates_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::create_dir_all(configs_dir)?;
+    let manifest = json!({ "schemaVersion": SCHEMA_VERSION, "templates": templates });
+    write_atomic(templates_path, format_manifest(&manifest).as_bytes()).with_context(|| {
+        format!(
+            "Failed to write templates manifest: {}",
+            templates_path.display()
+        )
+    })?;
+    let imported = entries.len();
+    write_atomic(
+        &config_file,
+        format_config_file(&Value::Array(entries)).as_bytes(),
+    )
+    .with_context(|| format!("Failed to write config file: {}", config_file.display()))?;
+    let settings_file = if settings.is_empty() {
+        None
+    } else {
+        let content = serde_json::to_string_pretty(&settings)? + "\n";
+        write_atomic(&settings_path, content.as_bytes()).with_context(|| {
+            format!("Failed to write settings file: {}", settings_path.display())
+        })?;
+        Some(settings_path)
+    };
+
+    Ok(Import {
+        templates: template_names,
+        config_file,
+        configurations: imported,
+        settings_file,
+        warnings,
+    })
+}
+
+/// Keys of `config` that only a template can set
+fn template_part(config: &Map<String, Value>) -> Map<String, Value> {
+    config
+        .iter()
+        .filter(|(key, value)| *key != "name" && !entry_can_set(key, value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Keys with the same value in every member, except `name` and `args`
+fn common_keys(members: &[&Map<String, Value>]) -> Map<String, Value> {
+    let Some((first, rest)) = members.split_first() else {
+        return Map::new();
+    };
+    first
+        .iter()
+        .filter(|(key, _)| *key != "name" && *key != "args")
+        .filter(|(key, value)| rest.iter().all(|m| m.get(*key) == Some(*value)))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Removes `//` and `/* */` comments and trailing commas, which VSCode allows in its JSON files,
+/// and quotes unquoted object keys, so hand-written JSON5 parses as well
+pub(crate) fn strip_jsonc(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+    let mut in_string = false;
+    while let Some((idx, c)) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next().map(|(_, n)| n)),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek().map(|&(_, n)| n)) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => while chars.next_if(|&(_, n)| n != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for (_, n) in chars.by_ref() {
+                    if prev == '*' && n == '/' {
+                        break;
+                    }
+                    prev = n;
+                }
+            }
+            (']' | '}', _) => {
+                // Drop a trailing comma before the closing bracket
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(',') {
+                    out.truncate(trimmed - 1);
+                }
+                out.push(c);
+            }
+            (c, _) if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+                let mut end = idx + c.len_utf8();
+                while let Some((i, n)) =
+                    chars.next_if(|&(_, n)| n.is_ascii_alphanumeric() || n == '_' || n == '$')
+                {
+                    end = i + n.len_utf8();
+                }
+                let word = &text[idx..end];
+                // `true`, `false` and `null` are never followed by a colon
+                if text[end..].trim_start().starts_with(':') {
+                    out.push('"');
+                    out.push_str(word);
+                    out.push('"');
+                } else {
+                    out.push_str(word);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
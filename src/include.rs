@@ -0,0 +1,159 @@
+use crate::encoding::read_text;
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Config entry as written, before includes are merged and the schema is applied
+#[derive(Debug, Clone)]
+pub(crate) struct RawEntry {
+    pub path: PathBuf,
+    pub index: usize,
+    /// JSON pointer of the entry within its file
+    pub pointer: String,
+    pub value: Value,
+}
+
+/// Keys an entry never inherits from the entries or fragments it includes
+const NOT_INHERITED: [&str; 3] = ["name", "enabled", "variants"];
+
+/// Keys of which a resolved entry may only have one
+const ARGS_KEYS: [&str; 2] = ["args", "argsString"];
+
+/// Which of `args` and `argsString` the layers merged so far set, and the layer that set it
+pub(crate) type ArgsLayer = Option<(&'static str, String)>;
+
+/// Records which of `args` and `argsString` the layer `label` sets, failing with both layers
+/// named when an earlier one set the other key. A layer setting both is left to the
+/// resolved entry's own check.
+pub(crate) fn note_args_layer(
+    set: &mut ArgsLayer,
+    layer: &Map<String, Value>,
+    label: &str,
+) -> Result<()> {
+    for key in ARGS_KEYS {
+        if !layer.contains_key(key) {
+            continue;
+        }
+        match set {
+            Some((other, other_label)) if *other != key && !layer.contains_key(*other) => {
+                anyhow::bail!(
+                    "{} sets {}, but {} sets {}; an entry can only use one of them",
+                    label,
+                    key,
+                    other_label,
+                    other
+                )
+            }
+            _ => *set = Some((key, label.to_string())),
+        }
+    }
+    Ok(())
+}
+
+/// Merges every entry over the entries and fragment files named by its `include` key.
+/// An include is either the name of another entry in `entries` or, when it ends in `.json`,
+/// a fragment file (a JSON object) relative to the including file.
+/// Includes apply in order, later ones and the entry's own keys taking precedence.
+pub(crate) fn resolve_includes(entries: &[RawEntry]) -> Result<Vec<Value>> {
+    let mut by_name: BTreeMap<&str, Vec<&RawEntry>> = BTreeMap::new();
+    for entry in entries {
+        if let Some(name) = entry.value.get("name").and_then(Value::as_str) {
+            by_name.entry(name).or_default().push(entry);
+        }
+    }
+
+    entries
+        .iter()
+        .map(|entry| {
+            let Value::Object(object) = &entry.value else {
+                return Ok(entry.value.clone());
+            };
+            let mut stack = vec![entry_key(&entry.value, entry)];
+            expand(object.clone(), &entry.path, &by_name, &mut stack)
+                .map(Value::Object)
+                .with_context(|| {
+                    format!(
+                        "Failed to resolve includes of entry at index {} in {}",
+                        entry.index,
+                        entry.path.display()
+                    )
+                })
+        })
+        .collect()
+}
+
+fn entry_key(value: &Value, entry: &RawEntry) -> String {
+    match value.get("name").and_then(Value::as_str) {
+        Some(name) => format!("'{}'", name),
+        None => format!("entry {} in {}", entry.index, entry.path.display()),
+    }
+}
+
+fn expand(
+    mut object: Map<String, Value>,
+    source: &Path,
+    by_name: &BTreeMap<&str, Vec<&RawEntry>>,
+    stack: &mut Vec<String>,
+) -> Result<Map<String, Value>> {
+    let includes = match object.remove("include") {
+        None => return Ok(object),
+        Some(Value::String(s)) => vec![s],
+        Some(Value::Array(items)) => items
+            .into_iter()
+            .map(|v| match v {
+                Value::String(s) => Ok(s),
+                _ => anyhow::bail!("'include' items must be strings"),
+            })
+            .collect::<Result<_>>()?,
+        Some(_) => anyhow::bail!("'include' must be a string or an array of strings"),
+    };
+
+    let mut merged = Map::new();
+    let mut args_layer: ArgsLayer = None;
+    for include in includes {
+        let (target, target_source, key) = if include.ends_with(".json") {
+            let path = source.parent().unwrap_or(Path::new("")).join(&include);
+            let content = read_text(&path)
+                .with_context(|| format!("Failed to read include fragment: {}", path.display()))?;
+            let value: Value = serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse include fragment: {}", path.display()))?;
+            let key = path.display().to_string();
+            (value, path, key)
+        } else {
+            let target = match by_name.get(include.as_str()).map(Vec::as_slice) {
+                Some([target]) => target,
+                Some(_) => anyhow::bail!("Included entry name '{}' is ambiguous", include),
+                None => anyhow::bail!("Included entry '{}' not found", include),
+            };
+            (
+                target.value.clone(),
+                target.path.clone(),
+                format!("'{}'", include),
+            )
+        };
+
+        if stack.contains(&key) {
+            anyhow::bail!("Include cycle: {} -> {}", stack.join(" -> "), key);
+        }
+        let Value::Object(target) = target else {
+            anyhow::bail!("Included {} must be a JSON object", key);
+        };
+
+        stack.push(key);
+        let expanded = expand(target, &target_source, by_name, stack)?;
+        let key = stack.pop().expect("pushed above");
+        note_args_layer(&mut args_layer, &expanded, &format!("include {}", key))?;
+
+        for (k, v) in expanded {
+            if !NOT_INHERITED.contains(&k.as_str()) {
+                merged.insert(k, v);
+            }
+        }
+    }
+
+    let label = stack.last().expect("the entry itself is on the stack");
+    note_args_layer(&mut args_layer, &object, label)?;
+    merged.extend(object);
+    Ok(merged)
+}
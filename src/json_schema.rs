@@ -0,0 +1,475 @@
+use crate::schema::{OVERRIDE_KEYS, SCHEMA_VERSION};
+use serde_json::{Value, json};
+
+const DRAFT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// JSON Schema describing a config file: an array of configuration entries, or an object
+/// with `schemaVersion` and a `configurations` array
+pub fn configs_schema() -> Value {
+    let entries = configs_array();
+    json!({
+        "$schema": DRAFT,
+        "title": "mklaunch configuration entries",
+        "oneOf": [
+            entries,
+            {
+                "type": "object",
+                "required": ["configurations"],
+                "properties": {
+                    "$schema": { "type": "string" },
+                    "schemaVersion": schema_version(),
+                    "configurations": entries
+                }
+            }
+        ]
+    })
+}
+
+fn schema_version() -> Value {
+    json!({
+        "type": "integer",
+        "minimum": 1,
+        "maximum": SCHEMA_VERSION,
+        "description": "Input format version"
+    })
+}
+
+fn launch_after() -> Value {
+    json!({
+        "description": "Configuration started first: joined in a compound, or starting this one through its serverReadyAction when its output matches pattern",
+        "oneOf": [
+            { "type": "string" },
+            {
+                "type": "object",
+                "required": ["name", "pattern"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "pattern": { "type": "string" }
+                }
+            }
+        ]
+    })
+}
+
+fn cargo() -> Value {
+    json!({
+        "oneOf": [
+            {
+                "type": "object",
+                "description": "Cargo target built at generation time whose executable becomes program; requires --allow-exec",
+                "required": ["kind"],
+                "additionalProperties": false,
+                "properties": {
+                    "kind": { "type": "string", "enum": ["test", "bin", "example"] },
+                    "package": { "type": "string" },
+                    "target": { "type": "string" },
+                    "filter": { "type": "string", "description": "Test name filter passed as the first argument" }
+                }
+            },
+            lldb_cargo("CodeLLDB cargo block merged into the template's: args starting with a subcommand replace the template's cargo args, options only are appended")
+        ]
+    })
+}
+
+/// CodeLLDB's own `cargo` launch block
+fn lldb_cargo(description: &str) -> Value {
+    json!({
+        "type": "object",
+        "description": description,
+        "required": ["args"],
+        "additionalProperties": false,
+        "properties": {
+            "args": { "type": "array", "items": { "type": "string" } },
+            "filter": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "name": { "type": "string" },
+                    "kind": { "type": "string" }
+                }
+            },
+            "env": { "type": "object", "additionalProperties": { "type": "string" } },
+            "cwd": { "type": "string" },
+            "problemMatcher": { "type": ["string", "array"] }
+        }
+    })
+}
+
+fn console() -> Value {
+    json!({
+        "enum": ["internalConsole", "integratedTerminal", "externalTerminal"],
+        "description": "Where the debuggee's input and output go, written as console, terminal (lldb) or externalConsole (cppdbg)"
+    })
+}
+
+fn container() -> Value {
+    json!({
+        "type": "object",
+        "description": "Source roots on this machine and in the container, written as sourceFileMap, sourceMap, pathMappings, substitutePath, sourceMapPathOverrides or localRoot/remoteRoot",
+        "required": ["remoteRoot"],
+        "additionalProperties": false,
+        "properties": {
+            "localRoot": { "type": "string", "description": "Defaults to ${workspaceFolder}" },
+            "remoteRoot": { "type": "string" }
+        }
+    })
+}
+
+fn configs_array() -> Value {
+    let mut properties = entry_properties();
+    if let (Some(properties), Value::Object(overrides)) =
+        (properties.as_object_mut(), override_properties())
+    {
+        properties.extend(overrides);
+    }
+    json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "required": ["name", "enabled"],
+            "properties": properties
+        }
+    })
+}
+
+/// Entry keys other than the ones replacing template values
+fn entry_properties() -> Value {
+    json!({
+        "$schema": {
+            "type": "string",
+            "description": "JSON Schema used by editors; ignored by mklaunch"
+        },
+        "include": {
+            "description": "Entry names or fragment files (*.json, relative to this file) merged under this entry",
+            "oneOf": [
+                { "type": "string" },
+                { "type": "array", "items": { "type": "string" } }
+            ]
+        },
+        "name": {
+            "type": "string",
+            "description": "Unique configuration name displayed in VSCode"
+        },
+        "variants": {
+            "type": "array",
+            "description": "Extra configurations cloned from this entry, e.g. a Release build next to Debug",
+            "items": {
+                "type": "object",
+                "required": ["nameSuffix"],
+                "additionalProperties": false,
+                "properties": {
+                    "nameSuffix": { "type": "string", "minLength": 1 },
+                    "overrides": {
+                        "type": "object",
+                        "not": {
+                            "anyOf": [
+                                { "required": ["name"] },
+                                { "required": ["include"] },
+                                { "required": ["variants"] }
+                            ]
+                        }
+                    }
+                }
+            }
+        },
+        "extends": {
+            "type": "string",
+            "pattern": "^[^/\\\\]+$",
+            "description": "Template name defined in templates.json (required unless included)"
+        },
+        "enabled": {
+            "type": "boolean",
+            "description": "Whether this configuration is enabled"
+        },
+        "tags": {
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "Free-form labels selected with --tag and shown by mklaunch list"
+        },
+        "perFile": {
+            "type": "boolean",
+            "description": "Debug the file open in the editor through ${file} and related variables; skips program checks and is listed last"
+        },
+        "baseArgs": {
+            "type": "string",
+            "description": "Path to a JSON file containing { \"args\": [...] }; prepended to args"
+        },
+        "baseArgsOptional": {
+            "type": "boolean",
+            "description": "Treat a missing baseArgs file as an empty arg list (with a warning)"
+        },
+        "argSets": {
+            "type": "array",
+            "items": { "type": "string", "pattern": "^[^/\\\\]+$" },
+            "description": "Names of arg sets in .mklaunch/args/, appended after baseArgs"
+        },
+        "args": {
+            "type": "array",
+            "items": {
+                "oneOf": [
+                    { "type": ["string", "number", "boolean"] },
+                    {
+                        "type": "object",
+                        "required": ["flag"],
+                        "additionalProperties": false,
+                        "properties": {
+                            "flag": { "type": "string" },
+                            "value": { "type": ["string", "number", "boolean"] },
+                            "separator": { "type": "string" }
+                        }
+                    }
+                ]
+            },
+            "description": "Additional args appended after baseArgs and argSets; numbers, booleans and {flag, value} objects become strings"
+        },
+        "arrayMerge": {
+            "type": "object",
+            "description": "Per-key merge into template arrays, e.g. { \"skipFiles\": { \"append\": [...] } }",
+            "additionalProperties": {
+                "type": "object",
+                "minProperties": 1,
+                "maxProperties": 1,
+                "properties": {
+                    "append": { "type": "array" },
+                    "prepend": { "type": "array" },
+                    "replace": { "type": "array" }
+                },
+                "additionalProperties": false
+            }
+        },
+        "unset": {
+            "type": "array",
+            "items": { "type": "string", "not": { "enum": ["type", "name", "args"] } },
+            "description": "Keys inherited from the template or defaults file to remove, e.g. [\"preLaunchTask\"]"
+        },
+        "argsString": {
+            "type": "string",
+            "description": "Args as one command line, e.g. \"--input 'my data.txt' --threads 4\", split with shell quoting rules; cannot be combined with args"
+        },
+        "argsFromCommand": {
+            "type": "string",
+            "description": "Shell command whose stdout (a JSON array or whitespace-separated words) is appended to args; requires --allow-exec"
+        },
+        "timeoutMs": {
+            "type": "integer",
+            "minimum": 1,
+            "description": "Milliseconds argsFromCommand and the cargo build may run before they are killed"
+        },
+        "group": {
+            "type": "string",
+            "description": "Picker group, written to presentation.group"
+        },
+        "order": {
+            "type": "integer",
+            "description": "Position within the picker group, written to presentation.order"
+        },
+        "hidden": {
+            "type": "boolean",
+            "description": "Hide the configuration from the picker (presentation.hidden)"
+        },
+        "envFile": {
+            "description": "Dotenv file; passed through as envFile, or expanded into environment for cppdbg",
+            "oneOf": [
+                { "type": "string" },
+                {
+                    "type": "object",
+                    "required": ["path"],
+                    "properties": {
+                        "path": { "type": "string" },
+                        "relativeTo": { "type": "string", "enum": ["workspace", "config"] }
+                    }
+                }
+            ]
+        },
+        "env": {
+            "type": "object",
+            "description": "Environment variables, merged key by key into the template's env; \"$replace\": true replaces it"
+        },
+        "presentation": {
+            "type": "object",
+            "description": "Picker presentation, merged key by key into the template's; \"$replace\": true replaces it"
+        },
+        "sourceFileMap": {
+            "type": "object",
+            "description": "Source path mapping, merged key by key into the template's; \"$replace\": true replaces it"
+        },
+        "windows": {
+            "type": "object",
+            "description": "Windows override block, merged over the template's block"
+        },
+        "linux": {
+            "type": "object",
+            "description": "Linux override block, merged over the template's block"
+        },
+        "osx": {
+            "type": "object",
+            "description": "macOS override block, merged over the template's block"
+        },
+        "attach": {
+            "type": "object",
+            "description": "Generate an attach request instead of launch",
+            "properties": {
+                "processName": { "type": "string" },
+                "pid": { "type": "integer", "minimum": 0 }
+            }
+        },
+        "coreDump": {
+            "type": "string",
+            "description": "Core file to debug post-mortem; a * or ? pattern in the file name picks the newest match"
+        },
+        "cargo": cargo(),
+        "container": container(),
+        "launchAfter": launch_after()
+    })
+}
+
+/// Entry keys replacing the template's value of a launch.json key
+fn override_properties() -> Value {
+    json!({
+    "program": {
+        "type": "string",
+        "description": "Program to debug, replacing the template's program"
+    },
+    "cwd": {
+        "type": "string",
+        "description": "Working directory, replacing the template's cwd"
+    },
+    "preLaunchTask": {
+        "type": ["string", "null"],
+        "description": "Task run before debugging, replacing the template's; null removes it"
+    },
+    "postDebugTask": {
+        "type": ["string", "null"],
+        "description": "Task run after debugging, replacing the template's; null removes it"
+    },
+    "console": console(),
+    "externalConsole": {
+        "type": "boolean",
+        "description": "Run the debuggee in an external terminal (true) or the integrated one (false)"
+    },
+    "justMyCode": {
+        "type": "boolean",
+        "description": "Step into user code only (debugpy and coreclr)"
+    },
+    "stopAtEntry": {
+        "type": "boolean",
+        "description": "Stop at the program's entry point (cppdbg and cppvsdbg)"
+    },
+    "MIMode": {
+        "enum": ["gdb", "lldb"],
+        "description": "Debugger cppdbg drives"
+    },
+    "miDebuggerPath": {
+        "type": "string",
+        "description": "Path of the gdb or lldb executable cppdbg runs, replacing the template's"
+    }
+    })
+}
+
+fn server_ready_action() -> Value {
+    json!({
+        "type": "object",
+        "description": "Action taken when the program prints a line matching pattern",
+        "additionalProperties": false,
+        "properties": {
+            "action": {
+                "enum": ["openExternally", "debugWithChrome", "debugWithEdge", "startDebugging"]
+            },
+            "pattern": { "type": "string", "minLength": 1 },
+            "uriFormat": { "type": "string" },
+            "webRoot": { "type": "string" },
+            "name": { "type": "string" },
+            "config": { "type": "object" },
+            "killOnServerStop": { "type": "boolean" }
+        }
+    })
+}
+
+/// JSON Schema describing the templates manifest
+pub fn templates_schema() -> Value {
+    json!({
+        "$schema": DRAFT,
+        "title": "mklaunch templates manifest",
+        "type": "object",
+        "required": ["templates"],
+        "properties": {
+            "schemaVersion": schema_version(),
+            "templates": {
+                "type": "array",
+                "minItems": 1,
+                "items": {
+                    "type": "object",
+                    "required": ["name", "type"],
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "Template name referenced by config 'extends'"
+                        },
+                        "type": {
+                            "type": "string",
+                            "description": "Debugger type, e.g. cppdbg, lldb"
+                        },
+                        "request": {
+                            "type": "string",
+                            "enum": ["launch", "attach"]
+                        },
+                        "program": { "type": "string" },
+                        "stopAtEntry": { "type": "boolean" },
+                        "serverReadyAction": server_ready_action(),
+                        "debugServer": {
+                            "type": ["integer", "string"],
+                            "pattern": "^[0-9]+$",
+                            "minimum": 1,
+                            "maximum": 65535,
+                            "description": "Port of a debug adapter started separately; digit strings become numbers"
+                        },
+                        "windows": { "type": "object" },
+                        "linux": { "type": "object" },
+                        "osx": { "type": "object" },
+                        "allowOverrides": {
+                            "type": "array",
+                            "items": { "type": "string", "enum": OVERRIDE_KEYS },
+                            "description": "Config keys that entries extending this template may use"
+                        },
+                        "requireFromConfig": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Config keys that entries extending this template must set"
+                        },
+                        "cargo": lldb_cargo("CodeLLDB builds the target with `cargo <args>` and debugs its executable; lldb templates without a program only"),
+                        "rust": {
+                            "description": "CodeLLDB helper: adds sourceLanguages [\"rust\"] and, without a program, a cargo build",
+                            "oneOf": [
+                                { "type": "boolean" },
+                                {
+                                    "type": "object",
+                                    "properties": {
+                                        "bin": { "type": "string" },
+                                        "package": { "type": "string" }
+                                    },
+                                    "additionalProperties": false
+                                }
+                            ]
+                        }
+                    },
+                    "not": {
+                        "required": ["args"],
+                        "description": "Templates must not define 'args'; use config files to set args"
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// VSCode `settings.json` snippet associating input files with schema files
+pub fn vscode_settings(file_match: &str, schema_url: &str) -> Value {
+    json!({
+        "json.schemas": [
+            {
+                "fileMatch": [file_match],
+                "url": schema_url
+            }
+        ]
+    })
+}
@@ -1,9 +1,80 @@
+mod adapters;
+mod add;
+mod args;
+mod atomic;
+mod batch;
+mod cache;
+mod cargo;
+mod cmake;
+mod devcontainer;
+mod diagnostic;
+mod diff;
+mod discover;
+mod doctor;
+mod emit;
+mod encoding;
+mod extra;
+mod flags;
+mod format;
 pub mod generator;
+mod git;
+mod golang;
+mod import;
+mod include;
+mod json_schema;
+mod lint;
+mod lock;
+mod log;
+mod migrate;
+mod npm;
+mod order;
+mod os;
+mod provenance;
+mod python;
+mod report;
+mod runner;
 mod schema;
+mod settings;
+mod sources;
+mod table;
+mod toggle;
+mod tokens;
 
 // Re-export public APIs
-pub use generator::{Generator, LaunchConfig, LaunchJson};
-pub use schema::ConfigFile;
+pub use add::{DebugAdapter, add_config, add_template};
+pub use atomic::{GenerationGuard, write_atomic};
+pub use batch::{find_workspace_root, find_workspaces};
+pub use devcontainer::Environment;
+pub use diagnostic::{Diagnostic, Diagnostics, Fix, Severity};
+pub use diff::{ConfigChange, KeyChange, diff_configs};
+pub use doctor::ConfigHealth;
+pub use emit::{OutputFormat, settings_json, snippets};
+pub use flags::ArgsDedupe;
+pub use format::format_inputs;
+pub use generator::{
+    DuplicateNames, GenerationSummary, Generator, GeneratorOptions, KeyOrder, LaunchConfig,
+    LaunchJson, Resolver, TagMatch, validate_config_file,
+};
+pub use git::{export_dir, gitignore_output};
+pub use import::{Import, import_launch};
+pub use json_schema::{configs_schema, templates_schema, vscode_settings};
+pub use lint::LintWarning;
+pub use lock::{LockFile, LockStatus};
+pub use log::{LogFormat, LogLevel, init_logging};
+pub use migrate::{Migration, migrate};
+pub use os::{PathStyle, TargetOs};
+pub use provenance::{Explanation, Provenance};
+pub use report::{
+    DiagnosticReport, DoctorReport, EntryReport, FixReport, HealthReport, ListReport,
+    REPORT_VERSION, ValidateReport,
+};
+pub use runner::{CommandOutput, CommandRunner, CommandSpec, NoExec, ProcessRunner};
+pub use schema::{
+    AttachOptions, ConfigFile, Console, ContainerMapping, MiMode, SCHEMA_VERSION, Templates,
+};
+pub use settings::{OptionDefaults, OutputFilter, OutputTarget, Settings};
+pub use sources::{SyncedSource, sync_templates};
+pub use toggle::toggle_enabled;
 
 #[cfg(test)]
 mod tests {
@@ -85,9 +156,9 @@ mod tests {
             name: "Dummy".to_string(),
             extends: "cpp".to_string(),
             enabled: true,
-            base_args: None,
-            args: None,
+            ..Default::default()
         };
+        #[allow(deprecated)]
         let doc = LaunchConfig::from_template_and_config(&templates_manifest, config, None)?;
         let v = serde_json::to_value(doc)?;
         assert_eq!(v["type"], "cppdbg");
@@ -96,6 +167,74 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_resolver_reads_manifest_once() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        setup_test_files(&temp_dir)?;
+        let templates_manifest = temp_dir.path().join(".mklaunch/templates.json");
+        let resolver = Resolver::from_manifest_path(&templates_manifest)?;
+        fs::remove_file(&templates_manifest)?;
+
+        let reused = resolver.clone();
+        for (resolver, name) in [(&resolver, "A"), (&reused, "B")] {
+            let config = ConfigFile {
+                name: name.to_string(),
+                extends: "cpp".to_string(),
+                enabled: true,
+                ..Default::default()
+            };
+            let launch = resolver.resolve(config, None)?;
+            assert_eq!((launch.name(), launch.debug_type()), (name, "cppdbg"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_log_line_formats() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let logged = |level, format| {
+            let buffer = Buffer::default();
+            let writer = buffer.clone();
+            let subscriber = crate::log::subscriber(level, format, move || writer.clone());
+            tracing::subscriber::with_default(subscriber, || {
+                let _span = tracing::info_span!("resolve", config = "Debug").entered();
+                tracing::debug!(fresh = 2, "looked up");
+            });
+            String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap()
+        };
+
+        let text = logged(LogLevel::Debug, LogFormat::Text);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", text);
+        assert!(lines[0].ends_with("DEBUG resolve{config=\"Debug\"}: looked up fresh=2"));
+        assert!(lines[1].contains(" INFO resolve{config=\"Debug\"}: close time.busy="));
+
+        assert_eq!(logged(LogLevel::Info, LogFormat::Text).lines().count(), 1);
+
+        let json = logged(LogLevel::Debug, LogFormat::Json);
+        let event: serde_json::Value = serde_json::from_str(json.lines().next().unwrap()).unwrap();
+        assert_eq!(event["level"], "DEBUG");
+        assert_eq!(event["message"], "looked up");
+        assert_eq!(event["fresh"], 2);
+        assert_eq!(
+            event["span"],
+            json!({ "name": "resolve", "config": "Debug" })
+        );
+    }
+
     #[test]
     fn test_load_template_not_found() {
         let temp_dir = TempDir::new().unwrap();
@@ -105,9 +244,9 @@ mod tests {
             name: "Dummy".to_string(),
             extends: "nonexistent".to_string(),
             enabled: true,
-            base_args: None,
-            args: None,
+            ..Default::default()
         };
+        #[allow(deprecated)]
         let result = LaunchConfig::from_template_and_config(&templates_manifest, config, None);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
@@ -188,8 +327,8 @@ mod tests {
             name: "Test Config".to_string(),
             extends: "cpp".to_string(),
             enabled: true,
-            base_args: None,
             args: Some(vec!["--test".to_string()]),
+            ..Default::default()
         };
 
         // Local helper: resolve using Resolver with in-memory template
@@ -210,24 +349,27 @@ mod tests {
             name: "Test".to_string(),
             extends: "cpp".to_string(),
             enabled: true,
-            base_args: None,
-            args: None,
+            ..Default::default()
         };
 
         let config2 = ConfigFile {
             name: "Test".to_string(), // Duplicate name
             extends: "cpp".to_string(),
             enabled: true,
-            base_args: None,
-            args: None,
+            ..Default::default()
         };
 
-        let configs = vec![
+        let configs = [
             (std::path::PathBuf::from("config1.json"), config1),
             (std::path::PathBuf::from("config2.json"), config2),
         ];
 
-        let result = crate::generator::validate_unique_names(&configs);
+        let result = crate::generator::validate_unique_names(
+            configs
+                .iter()
+                .map(|(p, c)| (p.as_path(), c.pointer.as_str(), c.name.as_str())),
+            false,
+        );
         assert!(result.is_err());
         assert!(
             result
@@ -239,12 +381,55 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_template_name_rules() {
+        use crate::schema::is_valid_template_name;
+        for name in ["cpp", "cpp-release_2", "cpp.devcontainer"] {
+            assert!(is_valid_template_name(name), "{}", name);
+        }
+        for name in ["", "Cpp", "cpp release", "cpp.release", ".devcontainer"] {
+            assert!(!is_valid_template_name(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_extra_keys_share_the_template() {
+        use crate::extra::ExtraKeys;
+        use std::sync::Arc;
+        let template = Arc::new(
+            json!({ "cwd": "/t", "env": { "A": "1" }, "setupCommands": [1, 2] })
+                .as_object()
+                .cloned()
+                .unwrap(),
+        );
+        let mut keys = ExtraKeys::shared(template.clone());
+        keys.get_mut("env").unwrap()["B"] = json!("2");
+        keys.insert("args".to_string(), json!([]));
+        assert_eq!(keys.remove("cwd"), Some(json!("/t")));
+        assert!(!keys.contains_key("cwd"));
+        assert_eq!(keys.remove("cwd"), None);
+
+        let entries: Vec<(&str, &serde_json::Value)> =
+            keys.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        assert_eq!(
+            entries,
+            [
+                ("args", &json!([])),
+                ("env", &json!({ "A": "1", "B": "2" })),
+                ("setupCommands", &json!([1, 2])),
+            ]
+        );
+        // The template itself is left untouched
+        assert_eq!(template["cwd"], "/t");
+        assert_eq!(template["env"], json!({ "A": "1" }));
+    }
+
     #[test]
     fn test_collect_config_files() -> anyhow::Result<()> {
         let temp_dir = TempDir::new()?;
         setup_test_files(&temp_dir)?;
         let configs_dir = temp_dir.path().join(".mklaunch/configs");
-        let entries = crate::generator::collect_config_files(&configs_dir)?;
+        let entries = crate::generator::collect_config_files(&configs_dir, false, &mut Vec::new())?;
         assert_eq!(entries.len(), 2);
         // No ordering guarantee here anymore; just assert files exist
         let mut names: Vec<_> = entries
@@ -448,4 +633,593 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_add_config_writes_entry() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        setup_test_files(&temp_dir)?;
+        let base = temp_dir.path().join(".mklaunch");
+
+        // Unknown template and duplicate name are re-prompted
+        let mut input = std::io::Cursor::new("gdb\n1\nBasic Test\nNew Run (fast)\n--fast -n 3\n");
+        let mut output = Vec::new();
+        let path = add_config(
+            &[base.join("templates.json")],
+            &base.join("configs"),
+            &mut input,
+            &mut output,
+        )?;
+
+        assert_eq!(path.file_name().unwrap(), "new-run-fast.json");
+        let configs = ConfigFile::from_path(&path)?;
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs[0].name, "New Run (fast)");
+        assert_eq!(configs[0].extends, "cpp");
+        assert_eq!(configs[0].args.as_ref().unwrap(), &["--fast", "-n", "3"]);
+
+        let prompts = String::from_utf8(output)?;
+        assert!(prompts.contains("Unknown template 'gdb'"));
+        assert!(prompts.contains("'Basic Test' already exists"));
+
+        let launch = create_test_generator(&temp_dir).generate()?;
+        assert_eq!(launch.configurations().len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_file_round_trip() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("debug.json");
+        fs::write(
+            &path,
+            r#"[{"osx": {"cwd": "/tmp"}, "name": "Debug", "extends": "cpp", "enabled": true,
+                "baseArgs": "args.json", "preLaunchTask": null, "tags": ["fast"],
+                "arrayMerge": {"skipFiles": {"append": ["<node_internals>/**"]}},
+                "attach": {"processName": "app"}, "args": ["-v", 3]}]"#,
+        )?;
+
+        let configs = ConfigFile::from_path(&path)?;
+        let written = ConfigFile::to_file_string(&configs)?;
+        // Canonical key order and spelling; defaults are left out
+        let name = written.find("\"name\"").unwrap();
+        assert!(name < written.find("\"baseArgs\"").unwrap());
+        assert!(
+            written.find("\"preLaunchTask\": null").unwrap() < written.find("\"osx\"").unwrap()
+        );
+        assert!(!written.contains("baseArgsOptional"));
+        assert!(!written.contains("\"pid\""));
+        assert!(written.contains("\"3\""));
+
+        ConfigFile::write_file(&path, &configs)?;
+        assert_eq!(fs::read_to_string(&path)?, written);
+        let reread = ConfigFile::from_path(&path)?;
+        assert_eq!(
+            serde_json::to_value(&reread)?,
+            serde_json::to_value(&configs)?
+        );
+        assert_eq!(reread[0].pre_launch_task, Some(None));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_toggle_enabled_rewrites_flags_in_place() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let configs_dir = temp_dir.path().join("configs");
+        fs::create_dir_all(&configs_dir)?;
+        let original = "[\n  {\"name\": \"A\", \"extends\": \"cpp\", \"enabled\": true},\n  {\"name\": \"B\",   \"extends\": \"cpp\", \"enabled\": false}\n]\n";
+        fs::write(configs_dir.join("a.json"), original)?;
+        fs::write(
+            configs_dir.join("b.json"),
+            r#"[{"name": "C", "extends": "cpp", "enabled": true}]"#,
+        )?;
+
+        // Bad selections are re-prompted; toggling twice restores a flag
+        let mut input = std::io::Cursor::new("9\n1-2\n3\n3\ns\n");
+        let mut output = Vec::new();
+        let written = toggle_enabled(std::slice::from_ref(&configs_dir), &mut input, &mut output)?;
+
+        assert_eq!(written, vec![configs_dir.join("a.json")]);
+        assert_eq!(
+            fs::read_to_string(configs_dir.join("a.json"))?,
+            original
+                .replace("\"enabled\": true", "\"enabled\": TRUE")
+                .replace("\"enabled\": false", "\"enabled\": true")
+                .replace("TRUE", "false")
+        );
+        let prompts = String::from_utf8(output)?;
+        assert!(prompts.contains("  1) [x] A  "));
+        assert!(prompts.contains("Unknown selection '9'"));
+
+        // Quitting leaves every file alone
+        let mut input = std::io::Cursor::new("a\nq\n");
+        assert!(toggle_enabled(&[configs_dir], &mut input, &mut Vec::new())?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_template_keeps_manifest_formatting() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let manifest = temp_dir.path().join("templates.json");
+        let original = "{\n    // not valid JSON, kept as is\n}";
+        let existing = "{\n    \"templates\": [\n        {\"name\": \"cpp\", \"type\": \"cppdbg\"}\n    ]\n}\n";
+        fs::write(&manifest, existing)?;
+
+        // A taken name is re-prompted; an empty program keeps the default
+        let mut input = std::io::Cursor::new("cpp\n\n\n");
+        let mut output = Vec::new();
+        let name = add_template(&manifest, DebugAdapter::Node, &mut input, &mut output)?;
+        assert_eq!(name, "node");
+        assert!(String::from_utf8(output)?.contains("Template 'cpp' already exists"));
+
+        let text = fs::read_to_string(&manifest)?;
+        assert!(text.starts_with("{\n    \"templates\": [\n        {\"name\": \"cpp\", \"type\": \"cppdbg\"},\n        {\n            \"name\": \"node\",\n"));
+        assert!(text.ends_with("        }\n    ]\n}\n"));
+        let templates = Templates::from_path(&manifest)?;
+        assert_eq!(
+            templates.get("node").unwrap()["program"],
+            json!("${workspaceFolder}/index.js")
+        );
+
+        let fresh = temp_dir.path().join("new/templates.json");
+        let mut input = std::io::Cursor::new("\n./a.out\n");
+        add_template(&fresh, DebugAdapter::Cppdbg, &mut input, &mut Vec::new())?;
+        let templates = Templates::from_path(&fresh)?;
+        assert_eq!(templates.get("cpp").unwrap()["program"], json!("./a.out"));
+
+        fs::write(&manifest, original)?;
+        let mut input = std::io::Cursor::new("\n\n");
+        assert!(add_template(&manifest, DebugAdapter::Go, &mut input, &mut Vec::new()).is_err());
+        assert_eq!(fs::read_to_string(&manifest)?, original);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_schemas_describe_inputs() {
+        let configs = configs_schema();
+        let entries = &configs["oneOf"][0];
+        assert_eq!(entries["type"], "array");
+        // `extends` may come from an included entry or fragment
+        assert_eq!(entries["items"]["required"], json!(["name", "enabled"]));
+        let properties: Vec<&str> = entries["items"]["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut known = crate::schema::CONFIG_KEYS.to_vec();
+        known.sort();
+        assert_eq!(properties, known);
+        let versioned = &configs["oneOf"][1]["properties"];
+        assert_eq!(versioned["configurations"], *entries);
+        assert_eq!(versioned["schemaVersion"]["maximum"], SCHEMA_VERSION);
+
+        let templates = templates_schema();
+        let item = &templates["properties"]["templates"]["items"];
+        assert_eq!(item["required"], json!(["name", "type"]));
+        assert_eq!(item["not"]["required"], json!(["args"]));
+
+        let settings = vscode_settings(".mklaunch/configs/*.json", "./configs.schema.json");
+        assert_eq!(settings["json.schemas"][0]["url"], "./configs.schema.json");
+    }
+
+    #[test]
+    fn test_lock_file_detects_changes() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        setup_test_files(&temp_dir)?;
+        let generator = create_test_generator(&temp_dir);
+        let output = temp_dir.path().join(".vscode/launch.json");
+        fs::create_dir_all(output.parent().unwrap())?;
+
+        let content = generator.generate()?.to_pretty_string()?;
+        fs::write(&output, &content)?;
+        LockFile::new(&generator.input_files()?, &output, content.as_bytes())?
+            .write(&generator.lock_path())?;
+
+        let lock = LockFile::from_path(&generator.lock_path())?.unwrap();
+        let inputs = generator.input_files()?;
+        assert_eq!(inputs.len(), 3);
+        assert_eq!(lock.check(&inputs, &output)?, LockStatus::UpToDate);
+
+        fs::write(&output, "{}")?;
+        assert_eq!(lock.check(&inputs, &output)?, LockStatus::OutputModified);
+
+        let config_path = temp_dir.path().join(".mklaunch/configs/01-basic.json");
+        write_json(
+            &config_path,
+            &json!([{ "name": "Changed", "extends": "cpp", "enabled": true }]),
+        )?;
+        assert_eq!(lock.check(&inputs, &output)?, LockStatus::InputsChanged);
+
+        // A partial output is recorded without its inputs
+        fs::write(&output, &content)?;
+        let lock = LockFile::outputs_only(&[(&output, content.as_bytes())])?;
+        assert_eq!(lock.check_output(&output), LockStatus::UpToDate);
+        assert_eq!(
+            lock.check(&generator.input_files()?, &output)?,
+            LockStatus::InputsChanged
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_file_with_several_outputs() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        setup_test_files(&temp_dir)?;
+        let generator = create_test_generator(&temp_dir);
+        let first = temp_dir.path().join("first.json");
+        let second = temp_dir.path().join("second.json");
+        fs::write(&first, "{}")?;
+        fs::write(&second, "[]")?;
+        LockFile::with_outputs(
+            &generator.input_files()?,
+            &[(&first, b"{}"), (&second, b"[]")],
+        )?
+        .write(&generator.lock_path())?;
+
+        let lock = LockFile::from_path(&generator.lock_path())?.unwrap();
+        let inputs = generator.input_files()?;
+        let outputs = [first.as_path(), second.as_path()];
+        assert_eq!(lock.check_outputs(&inputs, &outputs)?, LockStatus::UpToDate);
+        assert_eq!(lock.check_output(&second), LockStatus::UpToDate);
+        // An output the lock does not list was never generated
+        assert_eq!(
+            lock.check_outputs(&inputs, &outputs[..1])?,
+            LockStatus::InputsChanged
+        );
+        assert_eq!(
+            lock.check_output(&temp_dir.path().join("launch.json")),
+            LockStatus::NotRecorded
+        );
+
+        fs::write(&second, "[1]")?;
+        assert_eq!(
+            lock.check_outputs(&inputs, &outputs)?,
+            LockStatus::OutputModified
+        );
+
+        // A lock in the format of older versions is ignored rather than rejected
+        write_json(
+            generator.lock_path(),
+            &json!({ "version": 1, "inputs": {}, "output": { "path": "x", "hash": "y" } }),
+        )?;
+        assert!(LockFile::from_path(&generator.lock_path())?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generator_options_args_dir() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        setup_test_files(&temp_dir)?;
+        let base = temp_dir.path().join(".mklaunch");
+        let shared_args = temp_dir.path().join("shared-args");
+        fs::create_dir_all(&shared_args)?;
+        write_json(shared_args.join("common.json"), &json!({ "args": ["-v"] }))?;
+        write_json(
+            base.join("configs/01-basic.json"),
+            &json!([{ "name": "Basic Test", "extends": "cpp", "enabled": true, "argSets": ["common"] }]),
+        )?;
+
+        let options = GeneratorOptions::new(base.join("templates.json"), base.join("configs"))
+            .args_dir(shared_args);
+        let launch = Generator::with_options(options).generate()?;
+        let v = serde_json::to_value(&launch.configurations()[0])?;
+        assert_eq!(v["args"], json!(["-v"]));
+
+        // The default args directory does not contain the set
+        assert!(create_test_generator(&temp_dir).generate().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_reports_provenance() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        setup_test_files(&temp_dir)?;
+        let base_args = temp_dir.path().join("base.json");
+        write_json(&base_args, &json!({ "args": ["--base"] }))?;
+        let config_path = temp_dir.path().join(".mklaunch/configs/01-basic.json");
+        write_json(
+            &config_path,
+            &json!([{
+                "name": "Basic Test",
+                "extends": "cpp",
+                "enabled": true,
+                "baseArgs": base_args,
+                "args": ["--test"],
+                "arrayMerge": { "environment": { "append": [{ "name": "A", "value": "1" }] } }
+            }]),
+        )?;
+
+        let explanation = create_test_generator(&temp_dir).explain("Basic Test")?;
+        assert_eq!(explanation.source, config_path);
+
+        let keys = explanation.provenance.keys();
+        assert_eq!(keys["type"], "template 'cpp'");
+        assert_eq!(keys["MIMode"], "template 'cpp'");
+        assert_eq!(keys["name"], format!("config {}", config_path.display()));
+        assert!(keys["environment"].contains("arrayMerge append"));
+
+        let args = explanation.provenance.args();
+        assert_eq!(
+            args[0],
+            (
+                "--base".to_string(),
+                format!("baseArgs {}", base_args.display())
+            )
+        );
+        assert_eq!(args[1].0, "--test");
+        assert!(args[1].1.starts_with("args in config"));
+
+        assert!(create_test_generator(&temp_dir).explain("Missing").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagnostic_locates_offending_value() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("bad.json");
+        fs::write(
+            &config_path,
+            "[\n  {\n    \"name\": \"A\",\n    \"extends\": \"cpp\",\n    \"enabled\": true,\n    \"argSets\": [\"ok\", \"../x\"]\n  }\n]\n",
+        )?;
+
+        let err = ConfigFile::from_path(&config_path).unwrap_err();
+        let diagnostic = err.downcast_ref::<Diagnostic>().expect("diagnostic");
+        assert_eq!(diagnostic.pointer.as_deref(), Some("/0/argSets/1"));
+        assert_eq!(diagnostic.position, Some((6, 23)));
+
+        fs::write(
+            &config_path,
+            "[\n  {\"name\": \"A\",\n   \"extends\": 3, \"enabled\": true}\n]",
+        )?;
+        let err = ConfigFile::from_path(&config_path).unwrap_err();
+        let diagnostic = err
+            .chain()
+            .find_map(|e| e.downcast_ref::<Diagnostic>())
+            .expect("diagnostic");
+        assert_eq!(diagnostic.position.map(|(line, _)| line), Some(3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_to_writer() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        setup_test_files(&temp_dir)?;
+        let generator = create_test_generator(&temp_dir);
+
+        let mut buffer = Vec::new();
+        let launch = generator.generate_to(&mut buffer)?;
+
+        assert_eq!(String::from_utf8(buffer)?, launch.to_pretty_string()?);
+        assert_eq!(launch.configurations().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_launch_config_accessors_and_round_trip() -> anyhow::Result<()> {
+        let mut config: LaunchConfig = serde_json::from_value(json!({
+            "type": "cppdbg",
+            "request": "launch",
+            "name": "Debug",
+            "program": "app",
+            "args": ["-v"],
+            "MIMode": "gdb"
+        }))?;
+
+        assert_eq!(config.name(), "Debug");
+        assert_eq!(config.debug_type(), "cppdbg");
+        assert_eq!(config.args(), ["-v"]);
+        assert_eq!(config.get("MIMode"), Some(json!("gdb")));
+        assert_eq!(config.get("stopAtEntry"), None);
+
+        assert_eq!(
+            config.set_extra("MIMode", json!("lldb"))?,
+            Some(json!("gdb"))
+        );
+        assert!(config.set_extra("name", json!("Other")).is_err());
+        assert_eq!(config.remove_extra("missing"), None);
+
+        let value = serde_json::to_value(&config)?;
+        assert_eq!(value["MIMode"], "lldb");
+        let back: LaunchConfig = serde_json::from_value(value.clone())?;
+        assert_eq!(serde_json::to_value(&back)?, value);
+
+        let attach: LaunchConfig = serde_json::from_value(json!({
+            "type": "cppdbg",
+            "request": "attach",
+            "name": "Attach",
+            "processId": "${command:pickProcess}"
+        }))?;
+        assert!(attach.args().is_empty());
+        assert_eq!(serde_json::to_value(&attach)?["args"], json!([]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_go_list_main_packages() -> anyhow::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path().canonicalize()?;
+        let package = |name: &str, import_path: &str, dir: &str| {
+            json!({
+                "Name": name,
+                "ImportPath": import_path,
+                "Dir": root.join(dir),
+                "GoFiles": ["main.go"],
+                "Module": { "Path": "example.com/app" }
+            })
+            .to_string()
+        };
+        let output = [
+            package("main", "example.com/app/cmd/server", "cmd/server"),
+            package("util", "example.com/app/internal/util", "internal/util"),
+            package("main", "example.com/app/cmd/tool", "cmd/tool"),
+            package("main", "example.com/app/tools/tool", "tools/tool"),
+        ]
+        .join("\n");
+
+        let targets = crate::golang::parse_go_list(&output, &root)?;
+        let names: Vec<&str> = targets.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["server", "cmd/tool", "tools/tool"]);
+        assert_eq!(targets[0].source, root.join("cmd/server/main.go"));
+        assert_eq!(
+            targets[0].keys,
+            [("program", Some(json!("${workspaceFolder}/cmd/server")))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cargo_target_selection() -> anyhow::Result<()> {
+        use crate::cargo::{CargoTarget, parse_artifacts};
+
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path().canonicalize()?;
+        let artifact = |name: &str, kind: &str, test: bool, executable: Option<&str>| {
+            json!({
+                "reason": "compiler-artifact",
+                "target": { "name": name, "kind": [kind] },
+                "profile": { "test": test },
+                "executable": executable.map(|e| root.join(e))
+            })
+            .to_string()
+        };
+        let output = [
+            artifact("foo", "lib", false, None),
+            artifact("foo", "lib", true, Some("target/debug/deps/foo-1a2b")),
+            artifact(
+                "parser",
+                "test",
+                true,
+                Some("target/debug/deps/parser-3c4d"),
+            ),
+            artifact("foo", "bin", false, Some("target/debug/foo")),
+            json!({ "reason": "build-finished", "success": true }).to_string(),
+        ]
+        .join("\n");
+        let artifacts = parse_artifacts(&output);
+        assert_eq!(artifacts.len(), 3);
+
+        let target = |value: serde_json::Value| -> anyhow::Result<CargoTarget> {
+            Ok(serde_json::from_value(value)?)
+        };
+        let tests = target(json!({ "kind": "test", "package": "foo", "filter": "parser::" }))?;
+        assert_eq!(
+            tests.build_args(),
+            ["build", "--tests", "--package=foo", "--message-format=json"]
+        );
+        let err = tests.select(&artifacts).unwrap_err().to_string();
+        assert!(
+            err.contains("several test executables (foo, parser)"),
+            "{}",
+            err
+        );
+
+        let unit = target(json!({ "kind": "test", "target": "foo" }))?;
+        let executable = unit.select(&artifacts)?;
+        assert_eq!(
+            crate::cargo::program_path(&root, executable),
+            "${workspaceFolder}/target/debug/deps/foo-1a2b"
+        );
+        let bin = target(json!({ "kind": "bin" }))?;
+        assert_eq!(bin.select(&artifacts)?, root.join("target/debug/foo"));
+        let example = target(json!({ "kind": "example", "target": "demo" }))?;
+        let err = example.select(&artifacts).unwrap_err().to_string();
+        assert!(
+            err.contains("no example executable named 'demo'"),
+            "{}",
+            err
+        );
+        assert!(target(json!({ "kind": "test", "bogus": 1 })).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_suggest_known_key() {
+        let keys = crate::schema::CONFIG_KEYS;
+        assert_eq!(crate::schema::suggest("arg", &keys), Some("args"));
+        assert_eq!(crate::schema::suggest("enable", &keys), Some("enabled"));
+        assert_eq!(crate::schema::suggest("BaseArgs", &keys), Some("baseArgs"));
+        assert_eq!(crate::schema::suggest("programs", &keys), Some("program"));
+        assert_eq!(crate::schema::suggest("debugger", &keys), None);
+    }
+
+    #[test]
+    fn test_split_command_line() {
+        use crate::args::split_command_line;
+
+        let split = |line: &str| split_command_line(line).unwrap();
+        assert_eq!(
+            split("  --input data.txt\t--threads 4 "),
+            ["--input", "data.txt", "--threads", "4"]
+        );
+        assert_eq!(
+            split(r#"--name 'my file.txt' --msg "say \"hi\" $HOME" a\ b '' x"y"z"#),
+            [
+                "--name",
+                "my file.txt",
+                "--msg",
+                "say \"hi\" $HOME",
+                "a b",
+                "",
+                "xyz"
+            ]
+        );
+        assert_eq!(
+            split(
+                r#"'\n' "\n" \\ --a \
+--b"#
+            ),
+            ["\\n", "\\n", "\\", "--a", "--b"]
+        );
+        assert!(split("").is_empty());
+        for line in ["--input 'data", r#"--input "data"#, "--input \\"] {
+            assert_eq!(
+                split_command_line(line).unwrap_err(),
+                "unterminated quote or trailing backslash"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rust_template_helper() -> anyhow::Result<()> {
+        use crate::schema::Template;
+
+        let tmpl = Template::from_value(json!({
+            "type": "lldb",
+            "rust": { "bin": "server", "package": "app" }
+        }))?;
+        assert_eq!(tmpl.rest.get("sourceLanguages"), Some(&json!(["rust"])));
+        assert_eq!(
+            tmpl.rest.get("cargo"),
+            Some(&json!({
+                "args": ["build", "--bin=server", "--package=app"],
+                "filter": { "name": "server", "kind": "bin" }
+            }))
+        );
+        assert!(tmpl.rest.get("rust").is_none());
+
+        let tmpl = Template::from_value(json!({
+            "type": "lldb",
+            "program": "${workspaceFolder}/target/debug/app",
+            "sourceLanguages": ["rust", "c"],
+            "rust": true
+        }))?;
+        assert_eq!(
+            tmpl.rest.get("sourceLanguages"),
+            Some(&json!(["rust", "c"]))
+        );
+        assert!(tmpl.rest.get("cargo").is_none());
+
+        let err = Template::from_value(json!({ "type": "cppdbg", "rust": true })).unwrap_err();
+        assert!(err.to_string().contains("only applies to CodeLLDB"));
+        Ok(())
+    }
 }
@@ -0,0 +1,251 @@
+use crate::devcontainer::TEMPLATE_SUFFIX;
+use crate::generator::{LaunchConfig, on_disk};
+use crate::schema::{ArgSetLibrary, ConfigFile, TemplateFile, suggest};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Non-fatal finding about dead entries in the mklaunch inputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintWarning {
+    /// Template that no enabled config extends
+    UnusedTemplate(String),
+    /// File in the args directory that no enabled config references via `argSets` or `baseArgs`
+    OrphanArgsFile(PathBuf),
+    /// Enabled configuration resolving to the same JSON as another one apart from its name
+    DuplicateContent { name: String, duplicate_of: String },
+    /// `${...}` variable VSCode does not define, with the closest known one
+    UnknownVariable {
+        name: String,
+        variable: String,
+        suggestion: Option<String>,
+    },
+    /// `${input:id}` reference without a matching `inputs` entry
+    UnknownInput { name: String, input: String },
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintWarning::UnusedTemplate(name) => {
+                write!(
+                    f,
+                    "Template '{}' is not extended by any enabled config",
+                    name
+                )
+            }
+            LintWarning::OrphanArgsFile(path) => write!(
+                f,
+                "Args file {} is not referenced by any enabled config",
+                path.display()
+            ),
+            LintWarning::DuplicateContent { name, duplicate_of } => write!(
+                f,
+                "Configuration '{}' is identical to '{}' apart from its name",
+                name, duplicate_of
+            ),
+            LintWarning::UnknownVariable {
+                name,
+                variable,
+                suggestion,
+            } => {
+                write!(
+                    f,
+                    "Configuration '{}' uses unknown variable '${{{}}}'",
+                    name, variable
+                )?;
+                match suggestion {
+                    Some(known) => write!(f, "; did you mean '${{{}}}'?", known),
+                    None => Ok(()),
+                }
+            }
+            LintWarning::UnknownInput { name, input } => write!(
+                f,
+                "Configuration '{}' uses '${{input:{}}}', which no inputs entry defines",
+                name, input
+            ),
+        }
+    }
+}
+
+/// Template a `<template>.devcontainer` variant stands in for with `--env devcontainer`;
+/// other names are returned as they are
+fn variant_base(name: &str) -> &str {
+    name.strip_suffix(TEMPLATE_SUFFIX)
+        .and_then(|n| n.strip_suffix('.'))
+        .unwrap_or(name)
+}
+
+/// Reports unused templates and orphan arg set files for the enabled `configs`
+pub(crate) fn lint(
+    templates: &TemplateFile,
+    arg_sets: &ArgSetLibrary,
+    configs: &[(PathBuf, ConfigFile)],
+    root: &Path,
+) -> Vec<LintWarning> {
+    let enabled: Vec<&ConfigFile> = configs
+        .iter()
+        .map(|(_, c)| c)
+        .filter(|c| c.enabled)
+        .collect();
+
+    let extended: BTreeSet<&str> = enabled.iter().map(|c| c.extends.as_str()).collect();
+    let mut warnings: Vec<LintWarning> = templates
+        .names()
+        .filter(|name| !extended.contains(variant_base(name)))
+        .map(|name| LintWarning::UnusedTemplate(name.to_string()))
+        .collect();
+
+    let referenced_sets: BTreeSet<&str> = enabled
+        .iter()
+        .flat_map(|c| c.arg_sets.iter().flatten())
+        .map(String::as_str)
+        .collect();
+    // baseArgs may point into the args directory by path; compare canonical forms
+    let referenced_paths: BTreeSet<PathBuf> = enabled
+        .iter()
+        .filter_map(|c| c.base_args.as_ref())
+        .filter_map(|p| fs::canonicalize(on_disk(root, p)).ok())
+        .collect();
+
+    for path in arg_sets.files() {
+        let by_name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .is_some_and(|stem| referenced_sets.contains(stem));
+        let by_path = fs::canonicalize(path).is_ok_and(|p| referenced_paths.contains(&p));
+        if !by_name && !by_path {
+            warnings.push(LintWarning::OrphanArgsFile(path.clone()));
+        }
+    }
+
+    warnings
+}
+
+/// Predefined VSCode variables, used as `${name}`
+const VARIABLES: [&str; 19] = [
+    "userHome",
+    "workspaceFolder",
+    "workspaceFolderBasename",
+    "file",
+    "fileWorkspaceFolder",
+    "relativeFile",
+    "relativeFileDirname",
+    "fileBasename",
+    "fileBasenameNoExtension",
+    "fileExtname",
+    "fileDirname",
+    "fileDirnameBasename",
+    "cwd",
+    "lineNumber",
+    "selectedText",
+    "execPath",
+    "defaultBuildTask",
+    "pathSeparator",
+    "/",
+];
+
+/// Prefixes of `${prefix:argument}` variables
+const VARIABLE_PREFIXES: [&str; 5] = ["env", "config", "command", "input", "workspaceFolder"];
+
+/// Reports `${...}` variables in the string values of `configs` that VSCode does not
+/// define, and `${input:id}` references whose id is not in `inputs`
+pub(crate) fn check_variables(
+    configs: &[LaunchConfig],
+    inputs: &BTreeSet<String>,
+) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+    for config in configs {
+        let Ok(value) = serde_json::to_value(config) else {
+            continue;
+        };
+        let mut seen = BTreeSet::new();
+        let mut strings = Vec::new();
+        collect_strings(&value, &mut strings);
+        for variable in strings.into_iter().flat_map(variables) {
+            if !seen.insert(variable) {
+                continue;
+            }
+            let warning = match variable.split_once(':') {
+                Some(("input", id)) if !inputs.contains(id) => LintWarning::UnknownInput {
+                    name: config.name().to_string(),
+                    input: id.to_string(),
+                },
+                Some((prefix, _)) if VARIABLE_PREFIXES.contains(&prefix) => continue,
+                None if VARIABLES.contains(&variable) => continue,
+                Some((prefix, _)) => LintWarning::UnknownVariable {
+                    name: config.name().to_string(),
+                    variable: variable.to_string(),
+                    suggestion: suggest(prefix, &VARIABLE_PREFIXES)
+                        .map(|p| format!("{}:{}", p, &variable[prefix.len() + 1..])),
+                },
+                None => LintWarning::UnknownVariable {
+                    name: config.name().to_string(),
+                    variable: variable.to_string(),
+                    suggestion: suggest(variable, &VARIABLES).map(str::to_string),
+                },
+            };
+            warnings.push(warning);
+        }
+    }
+    warnings
+}
+
+/// `id`s of the `inputs` entries of a launch.json `inputs` array
+pub(crate) fn input_ids(inputs: Option<&Value>) -> BTreeSet<String> {
+    inputs
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|input| input.get("id").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect()
+}
+
+fn collect_strings<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => out.push(s),
+        Value::Array(items) => items.iter().for_each(|v| collect_strings(v, out)),
+        Value::Object(map) => map.values().for_each(|v| collect_strings(v, out)),
+        _ => {}
+    }
+}
+
+/// Names inside the `${...}` variables of `text`
+fn variables(text: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        found.push(&rest[start + 2..start + 2 + len]);
+        rest = &rest[start + 3 + len..];
+    }
+    found
+}
+
+/// Pairs of (duplicate, original) indices of configurations whose JSON is identical
+/// apart from `name`; the original is the first occurrence. `perFile` configurations are
+/// never reported.
+pub(crate) fn duplicate_content(configs: &[LaunchConfig]) -> Vec<(usize, usize)> {
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    let mut duplicates = Vec::new();
+    for (idx, config) in configs.iter().enumerate().filter(|(_, c)| !c.is_per_file()) {
+        let Ok(serde_json::Value::Object(mut value)) = serde_json::to_value(config) else {
+            continue;
+        };
+        value.remove("name");
+        // Object keys serialize in sorted order, so equal content gives equal strings
+        let content = serde_json::Value::Object(value).to_string();
+        match seen.get(&content) {
+            Some(&original) => duplicates.push((idx, original)),
+            None => {
+                seen.insert(content, idx);
+            }
+        }
+    }
+    duplicates
+}
@@ -0,0 +1,149 @@
+use crate::atomic::write_atomic;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LOCK_VERSION: u32 = 2;
+
+/// Generation manifest recording hashes of every input and of each file produced from them
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockFile {
+    version: u32,
+    inputs: BTreeMap<String, String>,
+    /// One entry per file written, e.g. each of the `outputs` of settings.json
+    outputs: Vec<LockedOutput>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct LockedOutput {
+    path: String,
+    hash: String,
+}
+
+/// Result of comparing a lock file against the current inputs and output
+#[derive(Debug, PartialEq, Eq)]
+pub enum LockStatus {
+    /// Inputs and output match the lock; regeneration can be skipped
+    UpToDate,
+    /// At least one input was added, removed or modified
+    InputsChanged,
+    /// launch.json was edited (or deleted) since it was generated
+    OutputModified,
+    /// The output is not listed in the lock, e.g. it was never generated
+    NotRecorded,
+}
+
+impl LockFile {
+    /// Records `inputs` as they are on disk and `content` as written to `output`
+    pub fn new(inputs: &[PathBuf], output: &Path, content: &[u8]) -> Result<Self> {
+        Self::with_outputs(inputs, &[(output, content)])
+    }
+
+    /// Records `inputs` as they are on disk and every output with the content written to it
+    pub fn with_outputs(inputs: &[PathBuf], outputs: &[(&Path, &[u8])]) -> Result<Self> {
+        Ok(Self {
+            version: LOCK_VERSION,
+            inputs: hash_inputs(inputs)?,
+            outputs: outputs
+                .iter()
+                .map(|(path, content)| LockedOutput {
+                    path: path.display().to_string(),
+                    hash: hash_bytes(content),
+                })
+                .collect(),
+        })
+    }
+
+    /// Records the content written to every output but no inputs, so `check` reports
+    /// changed inputs until a complete run records them. For outputs written while some
+    /// config entries failed
+    pub fn outputs_only(outputs: &[(&Path, &[u8])]) -> Result<Self> {
+        Ok(Self {
+            inputs: BTreeMap::new(),
+            ..Self::with_outputs(&[], outputs)?
+        })
+    }
+
+    /// Reads a lock file; returns `None` when it does not exist or was written by a
+    /// version of mklaunch with another lock format
+    pub fn from_path(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read lock file: {}", path.display()))?;
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse lock file: {}", path.display()))?;
+        if value.get("version").and_then(serde_json::Value::as_u64) != Some(LOCK_VERSION.into()) {
+            return Ok(None);
+        }
+        let lock = serde_json::from_value(value)
+            .with_context(|| format!("Failed to parse lock file: {}", path.display()))?;
+        Ok(Some(lock))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write_atomic(
+            path,
+            (serde_json::to_string_pretty(self)? + "\n").as_bytes(),
+        )
+        .context("Failed to write lock file")
+    }
+
+    /// Compares the lock with the current state of `inputs` and `output`
+    pub fn check(&self, inputs: &[PathBuf], output: &Path) -> Result<LockStatus> {
+        self.check_outputs(inputs, &[output])
+    }
+
+    /// Compares the lock with the current state of `inputs` and of every one of `outputs`,
+    /// which must be exactly the outputs recorded
+    pub fn check_outputs(&self, inputs: &[PathBuf], outputs: &[&Path]) -> Result<LockStatus> {
+        let mut paths: Vec<String> = outputs.iter().map(|p| p.display().to_string()).collect();
+        let mut locked: Vec<String> = self.outputs.iter().map(|o| o.path.clone()).collect();
+        paths.sort();
+        locked.sort();
+        if paths != locked || self.inputs != hash_inputs(inputs)? {
+            return Ok(LockStatus::InputsChanged);
+        }
+        Ok(outputs
+            .iter()
+            .map(|output| self.check_output(output))
+            .find(|status| *status != LockStatus::UpToDate)
+            .unwrap_or(LockStatus::UpToDate))
+    }
+
+    /// Whether `output` is recorded in the lock and still has the content recorded there
+    pub fn check_output(&self, output: &Path) -> LockStatus {
+        let path = output.display().to_string();
+        let Some(locked) = self.outputs.iter().find(|o| o.path == path) else {
+            return LockStatus::NotRecorded;
+        };
+        match fs::read(output) {
+            Ok(content) if hash_bytes(&content) == locked.hash => LockStatus::UpToDate,
+            _ => LockStatus::OutputModified,
+        }
+    }
+}
+
+fn hash_inputs(inputs: &[PathBuf]) -> Result<BTreeMap<String, String>> {
+    inputs
+        .iter()
+        .map(|path| {
+            let content = fs::read(path)
+                .with_context(|| format!("Failed to read input: {}", path.display()))?;
+            Ok((path.display().to_string(), hash_bytes(&content)))
+        })
+        .collect()
+}
+
+/// 64-bit FNV-1a; stable across platforms and toolchains, unlike `DefaultHasher`
+pub(crate) fn hash_bytes(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("fnv1a64:{:016x}", hash)
+}
@@ -0,0 +1,64 @@
+use tracing::Subscriber;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// Severity of a log event; a logger set to one level prints it and every more severe one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// How log events are written to stderr
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `LEVEL span{key=value ...}: message`
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Installs a `tracing` subscriber printing mklaunch's events and spans up to `level` on
+/// stderr, each span when it closes with its duration. Logging is off until this is
+/// called, and only the first call (or a subscriber the application set itself) takes effect.
+pub fn init_logging(level: LogLevel, format: LogFormat) {
+    let _ = tracing::subscriber::set_global_default(subscriber(level, format, std::io::stderr));
+}
+
+/// The subscriber `init_logging` installs, writing to `writer`
+pub(crate) fn subscriber<W>(
+    level: LogLevel,
+    format: LogFormat,
+    writer: W,
+) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_target(false)
+        .with_ansi(false)
+        .with_writer(writer);
+    match format {
+        LogFormat::Text => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(builder.json().flatten_event(true).finish()),
+    }
+}
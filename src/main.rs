@@ -1,54 +1,1592 @@
-use anyhow::Result;
-use clap::Parser;
-use mklaunch::Generator;
+use anyhow::{Context, Result};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use mklaunch::{
+    ArgsDedupe, CommandRunner, ConfigChange, ConfigHealth, DebugAdapter, Diagnostic, Diagnostics,
+    DoctorReport, DuplicateNames, Environment, Generator, GeneratorOptions, KeyOrder, LaunchJson,
+    ListReport, LockFile, LockStatus, LogFormat, LogLevel, NoExec, OutputFilter, OutputFormat,
+    PathStyle, ProcessRunner, Settings, Severity, TagMatch, TargetOs, ValidateReport,
+};
 use serde_json::to_string_pretty;
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Command line interface for VSCode launch.json generator
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 #[command(name = "mklaunch")]
 #[command(about = "Generate VSCode launch.json from template and config files")]
 struct Cli {
-    /// Templates manifest path
-    #[arg(long, default_value = ".mklaunch/templates.json")]
-    templates: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    /// Configs directory path
-    #[arg(long, default_value = ".mklaunch/configs")]
-    configs: PathBuf,
+    /// Templates manifest path, or a directory of *.templates.json manifests; repeat to merge several
+    #[arg(long, global = true, default_value = ".mklaunch/templates.json")]
+    templates: Vec<PathBuf>,
 
-    /// Output file path for generated launch.json
-    #[arg(short, long, default_value = ".vscode/launch.json")]
+    /// Configs directory path; repeat to layer overlays (e.g. .mklaunch/configs.local) whose entries override same-named ones
+    #[arg(long, global = true, default_value = ".mklaunch/configs")]
+    configs: Vec<PathBuf>,
+
+    /// Workspace folder that relative baseArgs, envFile and cwd paths are read from [default: current directory]
+    #[arg(long, global = true, value_name = "PATH")]
+    root: Option<PathBuf>,
+
+    /// Named arg sets directory path [default: args/ next to the templates manifest]
+    #[arg(long, global = true)]
+    args_dir: Option<PathBuf>,
+
+    /// Keys merged into every configuration [default: defaults.json next to the templates manifest]
+    #[arg(long, global = true, value_name = "PATH")]
+    defaults: Option<PathBuf>,
+
+    /// Add a configuration per executable target from a CMakePresets.json or build directory
+    #[arg(long, global = true, value_name = "PATH", requires = "cmake_template")]
+    from_cmake: Option<PathBuf>,
+
+    /// Template extended by configurations generated with --from-cmake
+    #[arg(long, global = true, value_name = "NAME")]
+    cmake_template: Option<String>,
+
+    /// Add debugpy configurations for console scripts, __main__ scripts and pytest files of a Python project
+    #[arg(long, global = true, value_name = "PATH")]
+    from_python: Option<PathBuf>,
+
+    /// Template extended by configurations generated with --from-python
+    #[arg(long, global = true, value_name = "NAME", default_value = "python")]
+    python_template: String,
+
+    /// Add a delve configuration per main package of a Go module (uses `go list`)
+    #[arg(long, global = true, value_name = "PATH")]
+    from_go: Option<PathBuf>,
+
+    /// Template extended by configurations generated with --from-go
+    #[arg(long, global = true, value_name = "NAME", default_value = "go")]
+    go_template: String,
+
+    /// Add a Node configuration running `npm run <script>` per allowed script of a package.json and its workspaces
+    #[arg(long, global = true, value_name = "PATH", requires = "npm_scripts")]
+    from_npm: Option<PathBuf>,
+
+    /// Comma-separated scripts to generate configurations for with --from-npm
+    #[arg(long, global = true, value_name = "NAMES", value_delimiter = ',')]
+    npm_scripts: Vec<String>,
+
+    /// Template extended by configurations generated with --from-npm
+    #[arg(long, global = true, value_name = "NAME", default_value = "node")]
+    npm_template: String,
+
+    /// Merge the windows/linux/osx override block for the host OS into each configuration
+    #[arg(long, global = true)]
+    flatten_os: bool,
+
+    /// Fail when a configuration's cwd is not an existing directory
+    #[arg(long, global = true, overrides_with = "no_check_paths")]
+    check_paths: bool,
+
+    /// Turn off --check-paths enabled in settings.json
+    #[arg(long, global = true, hide = true, overrides_with = "check_paths")]
+    no_check_paths: bool,
+
+    /// Fail when a configuration's program is not an existing file
+    #[arg(long, global = true, overrides_with = "no_check_programs")]
+    check_programs: bool,
+
+    /// Turn off --check-programs enabled in settings.json
+    #[arg(long, global = true, hide = true, overrides_with = "check_programs")]
+    no_check_programs: bool,
+
+    /// Output file path for generated launch.json; --emit settings and --emit workspace default to settings.json and launch.code-snippets in the same folder
+    #[arg(short, long, global = true, default_value = ".vscode/launch.json")]
     output: PathBuf,
 
+    /// Output format: launch.json, the `launch` key of settings.json, or workspace snippets
+    #[arg(long, global = true, value_enum, default_value = "launch")]
+    emit: EmitKind,
+
+    /// Syntax of the generated launch.json (--emit launch only)
+    #[arg(long, global = true, value_enum, default_value = "json")]
+    output_format: OutputFormatArg,
+
+    /// Keep top-level keys of the existing launch.json besides version and configurations (e.g. inputs, compounds)
+    #[arg(long, global = true, overrides_with = "no_preserve_keys")]
+    preserve_keys: bool,
+
+    /// Turn off --preserve-keys enabled in settings.json
+    #[arg(long, global = true, hide = true, overrides_with = "preserve_keys")]
+    no_preserve_keys: bool,
+
+    /// Add the output file to the .gitignore at the top of its git work tree unless it is already ignored
+    #[arg(long, global = true)]
+    gitignore: bool,
+
+    /// Use .mklaunch/ and .vscode/ of the current directory instead of the nearest folder above it that has a .mklaunch directory
+    #[arg(long, global = true)]
+    no_discover: bool,
+
     /// Enable verbose output
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     verbose: bool,
+
+    /// Print counts of templates, config files, entries and warnings after generation
+    #[arg(long, global = true)]
+    stats: bool,
+
+    /// Log generation phases (template loading, config collection, resolution) to stderr at this level and above
+    #[arg(long, global = true, value_enum, value_name = "LEVEL")]
+    log_level: Option<LogLevelArg>,
+
+    /// Format of --log-level output
+    #[arg(long, global = true, value_enum, default_value = "text")]
+    log_format: LogFormatArg,
+
+    /// Skip generation when inputs and output match the lock file
+    #[arg(long, global = true)]
+    if_changed: bool,
+
+    /// Warn about unused templates, orphan args files, duplicate configurations and unknown ${...} variables after generation
+    #[arg(long, global = true, overrides_with = "no_lint")]
+    lint: bool,
+
+    /// Turn off --lint enabled in settings.json
+    #[arg(long, global = true, hide = true, overrides_with = "lint")]
+    no_lint: bool,
+
+    /// Treat missing baseArgs files as empty arg lists instead of failing
+    #[arg(long, global = true)]
+    allow_missing_base_args: bool,
+
+    /// Reject unknown keys in config entries instead of ignoring them
+    #[arg(long, global = true, overrides_with = "no_strict_config")]
+    strict_config: bool,
+
+    /// Turn off --strict-config enabled in settings.json
+    #[arg(long, global = true, hide = true, overrides_with = "strict_config")]
+    no_strict_config: bool,
+
+    /// Run the shell commands of argsFromCommand entries and the builds of cargo entries
+    #[arg(long, global = true)]
+    allow_exec: bool,
+
+    /// Refuse to run any external command (argsFromCommand, cargo builds, discovery tools, git, curl), even with --allow-exec
+    #[arg(long, global = true)]
+    no_exec: bool,
+
+    /// Kill external commands running longer than this many milliseconds; entries can set their own timeoutMs
+    #[arg(long, global = true, value_name = "MS")]
+    command_timeout: Option<u64>,
+
+    /// Drop configurations identical to another one apart from their name
+    #[arg(long, global = true, overrides_with = "no_dedupe")]
+    dedupe: bool,
+
+    /// Turn off --dedupe enabled in settings.json
+    #[arg(long, global = true, hide = true, overrides_with = "dedupe")]
+    no_dedupe: bool,
+
+    /// Report or drop flags repeated in a configuration's args, following .mklaunch/flags.json
+    #[arg(long, global = true, value_enum)]
+    dedupe_args: Option<DedupeArgs>,
+
+    /// Skip config files and entries that fail, write the rest and report every error at the end
+    #[arg(long, global = true, overrides_with = "no_keep_going")]
+    keep_going: bool,
+
+    /// Turn off --keep-going enabled in settings.json
+    #[arg(long, global = true, hide = true, overrides_with = "keep_going")]
+    no_keep_going: bool,
+
+    /// What to do when two enabled configurations share a name
+    #[arg(long, global = true, value_enum, default_value = "error")]
+    on_duplicate: OnDuplicate,
+
+    /// Read configs subdirectories and set presentation.group of configurations without a group to theirs
+    #[arg(long, global = true)]
+    group_by_dir: bool,
+
+    /// Set presentation.group and presentation.order of configurations without them from their template and file order
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "KEY",
+        conflicts_with = "group_by_dir"
+    )]
+    group_by: Option<GroupByArg>,
+
+    /// Order of configuration keys after type/request/name/program/args/stopAtEntry
+    #[arg(long, global = true, value_enum, default_value = "alphabetical")]
+    key_order: KeyOrderArg,
+
+    /// Where the configurations are debugged from
+    #[arg(long = "env", global = true, value_enum, default_value = "host")]
+    environment: EnvironmentArg,
+
+    /// Only re-resolve config files that changed since the last cached run
+    #[arg(long, global = true)]
+    cache: bool,
+
+    /// Record the mklaunch version and a hash of the inputs under a top-level generatedBy key
+    #[arg(long, global = true)]
+    stamp: bool,
+
+    /// Only generate config entries tagged TAG; repeat to select several tags
+    #[arg(long = "tag", global = true, value_name = "TAG")]
+    tags: Vec<String>,
+
+    /// Whether entries need any or all of the --tag tags
+    #[arg(long, global = true, value_enum, default_value = "any")]
+    tag_match: TagMatchArg,
+
+    /// Rewrite separators in program, cwd, miDebuggerPath and other path keys [default: leave as resolved]
+    #[arg(long, global = true, value_enum, value_name = "STYLE")]
+    path_style: Option<PathStyleArg>,
+
+    /// Set KEY (a dotted path such as env.RUST_LOG) to VALUE, read as JSON or else as a string, in generated configurations; repeat for several keys
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE")]
+    sets: Vec<String>,
+
+    /// Only apply --set to configurations whose name matches PATTERN (* and ? wildcards); repeat for several patterns
+    #[arg(long, global = true, value_name = "PATTERN", requires = "sets")]
+    only: Vec<String>,
+
+    /// Also read config entries from FILE, e.g. machine-local configs kept out of git; repeat for several files. MKLAUNCH_EXTRA_CONFIG adds more, separated like PATH
+    #[arg(long = "extra-config", global = true, value_name = "FILE")]
+    extra_configs: Vec<PathBuf>,
+
+    /// Replace an existing output even if it has configurations mklaunch does not generate
+    #[arg(long, global = true)]
+    force: bool,
+
+    /// Files to write instead of --output, from `outputs` in settings.json
+    #[arg(skip)]
+    outputs: Vec<(PathBuf, OutputFilter)>,
+}
+
+#[derive(Subcommand, Clone)]
+enum Command {
+    /// Interactively create a new config file and regenerate launch.json
+    Add,
+    /// Append a template skeleton for a debug adapter to the templates manifest
+    NewTemplate {
+        /// Debug adapter the template is for
+        #[arg(value_enum)]
+        adapter: AdapterArg,
+    },
+    /// Print every config entry with its status and tags, without generating
+    List {
+        /// Print a JSON report instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Interactively enable or disable config entries and regenerate launch.json
+    Toggle,
+    /// Report unused templates, orphan args files, duplicate configurations and unknown ${...} variables without generating
+    Lint,
+    /// Check template attributes against the launch.json schemas of their debug adapters
+    LintTemplates {
+        /// Extension package.json (file or http(s) URL) whose debuggers replace the built-in schemas; repeat for several
+        #[arg(long = "package", value_name = "FILE|URL")]
+        packages: Vec<String>,
+    },
+    /// Factor an existing launch.json into a templates manifest and a config file
+    Import {
+        /// launch.json to import
+        launch: PathBuf,
+    },
+    /// Rewrite the templates manifest and config files in their canonical layout
+    Fmt {
+        /// List the files that are not formatted and fail instead of rewriting them
+        #[arg(long)]
+        check: bool,
+    },
+    /// Convert legacy per-file templates and single-object configs, keeping backups
+    Migrate,
+    /// Check every enabled config entry against the templates, reporting all problems at once
+    Validate {
+        /// Print a JSON report instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check that debuggers, programs, envFiles and preLaunchTasks referenced by configurations exist
+    Doctor {
+        /// Print a JSON report instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Generate launch.json in every folder under DIR that has a .mklaunch directory
+    Batch {
+        /// Directory tree to search
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+    },
+    /// Fetch the templateSources listed in settings.json into .mklaunch/sources/
+    SyncTemplates {
+        /// Fetch the latest revisions instead of the ones pinned in sources.lock.json
+        #[arg(long)]
+        update: bool,
+    },
+    /// Print the configurations added, removed or changed since a git revision
+    DiffConfigs {
+        /// Revision whose .mklaunch inputs are compared with the working tree
+        #[arg(value_name = "GIT_REF")]
+        git_ref: String,
+    },
+    /// Print a resolved configuration with the source of each value
+    Explain {
+        /// Configuration name as it appears in launch.json
+        name: String,
+    },
+    /// Print a JSON Schema describing mklaunch input files
+    Schema {
+        /// Which input format to describe
+        #[arg(long, value_enum)]
+        what: SchemaKind,
+
+        /// Write the schema to this path instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// Also print a VSCode settings.json snippet referencing the written schema
+        #[arg(long, requires = "out")]
+        settings: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum EmitKind {
+    Launch,
+    Settings,
+    Workspace,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AdapterArg {
+    /// C/C++ (Microsoft C/C++ extension)
+    Cppdbg,
+    /// CodeLLDB
+    Codelldb,
+    /// Python
+    Debugpy,
+    /// Node.js
+    Node,
+    /// Go (delve)
+    Go,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormatArg {
+    /// Strict JSON
+    Json,
+    /// JSON with a do-not-edit header comment
+    Jsonc,
+    /// JSON5 with a header comment and trailing commas
+    Json5,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DedupeArgs {
+    /// Warn about each configuration with repeated flags
+    Warn,
+    /// Drop the repeats from the written args
+    Remove,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OnDuplicate {
+    /// Fail generation
+    Error,
+    /// Rename later ones to "Name (2)", "Name (3)", ...
+    Suffix,
+    /// Drop later ones
+    Skip,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TagMatchArg {
+    /// Entries with at least one of the tags
+    Any,
+    /// Entries with every tag
+    All,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PathStyleArg {
+    /// Forward slashes
+    Posix,
+    /// Backslashes
+    Windows,
+    /// The style of the OS mklaunch runs on
+    Auto,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GroupByArg {
+    /// Group by the template each configuration extends
+    Template,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LogLevelArg {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LogFormatArg {
+    /// One human-readable line per event
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum KeyOrderArg {
+    /// Sorted by key, in nested objects too
+    Alphabetical,
+    /// As written in the template; keys added by configs follow alphabetically
+    Template,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum EnvironmentArg {
+    /// This checkout; inputs are used as written
+    Host,
+    /// The dev container of .devcontainer/devcontainer.json
+    Devcontainer,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SchemaKind {
+    Configs,
+    Templates,
 }
 
 /// Main entry point - parses CLI arguments and generates launch.json
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+fn main() -> ExitCode {
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    if let Some(level) = cli.log_level {
+        let level = match level {
+            LogLevelArg::Error => LogLevel::Error,
+            LogLevelArg::Warn => LogLevel::Warn,
+            LogLevelArg::Info => LogLevel::Info,
+            LogLevelArg::Debug => LogLevel::Debug,
+            LogLevelArg::Trace => LogLevel::Trace,
+        };
+        let format = match cli.log_format {
+            LogFormatArg::Text => LogFormat::Text,
+            LogFormatArg::Json => LogFormat::Json,
+        };
+        mklaunch::init_logging(level, format);
+    }
+
+    if !cli.no_discover && !matches!(cli.command, Some(Command::Batch { .. })) {
+        discover_workspace(&mut cli, &matches);
+    }
 
-    let generator = Generator::new(cli.templates, cli.configs);
+    let result = if matches!(cli.command, Some(Command::Batch { .. })) {
+        run(&cli, &matches)
+    } else {
+        apply_settings(&mut cli, &matches).and_then(|()| run(&cli, &matches))
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            report(&err);
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    let launch = generator.generate()?;
+/// Points the default --templates, --configs, --output and --root at the nearest folder
+/// above the current directory that has a `.mklaunch/` directory. Paths given on the
+/// command line stay relative to the current directory.
+fn discover_workspace(cli: &mut Cli, matches: &ArgMatches) {
+    let Ok(cwd) = std::env::current_dir() else {
+        return;
+    };
+    let Some(workspace) = mklaunch::find_workspace_root(&cwd) else {
+        return;
+    };
+    if workspace == cwd {
+        return;
+    }
 
-    // Ensure output directory exists and write file
-    if let Some(parent) = cli.output.parent() {
-        fs::create_dir_all(parent)?;
+    if is_default(matches, "templates") {
+        cli.templates = cli.templates.iter().map(|p| workspace.join(p)).collect();
+    }
+    if is_default(matches, "configs") {
+        cli.configs = cli.configs.iter().map(|p| workspace.join(p)).collect();
+    }
+    if is_default(matches, "output") {
+        cli.output = workspace.join(&cli.output);
+    }
+    if cli.root.is_none() {
+        cli.root = Some(workspace);
+    }
+}
+
+/// Whether option `id` was left at its default value rather than given on the command line
+fn is_default(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::DefaultValue)
+}
+
+/// Fills options not given on the command line from `options` in the settings file next
+/// to the first templates manifest
+fn apply_settings(cli: &mut Cli, matches: &ArgMatches) -> Result<()> {
+    let path = Settings::path_for(&cli.templates[0]);
+    let settings = Settings::from_path(&path)?;
+    // Output paths are relative to the folder containing .mklaunch/, where the default
+    // output lives too
+    let workspace = path
+        .parent()
+        .and_then(Path::parent)
+        .unwrap_or(Path::new(""));
+    // The configured outputs are launch.json files; settings.json and snippets get their
+    // own file next to the default one instead of replacing it
+    let launch_output = is_default(matches, "output") && matches!(cli.emit, EmitKind::Launch);
+    match cli.emit {
+        _ if !is_default(matches, "output") => {}
+        EmitKind::Launch => {}
+        EmitKind::Settings => cli.output.set_file_name("settings.json"),
+        EmitKind::Workspace => cli.output.set_file_name("launch.code-snippets"),
+    }
+    if launch_output {
+        cli.outputs = settings
+            .outputs
+            .iter()
+            .map(|target| (workspace.join(&target.path), settings.output_filter(target)))
+            .collect();
+    }
+    let options = settings.options;
+
+    if let Some(output) = options.output
+        && launch_output
+    {
+        cli.output = workspace.join(output);
+    }
+    if let Some(format) = options.output_format
+        && is_default(matches, "output_format")
+    {
+        cli.output_format = match format {
+            OutputFormat::Json => OutputFormatArg::Json,
+            OutputFormat::Jsonc => OutputFormatArg::Jsonc,
+            OutputFormat::Json5 => OutputFormatArg::Json5,
+        };
+    }
+    if let Some(order) = options.key_order
+        && is_default(matches, "key_order")
+    {
+        cli.key_order = match order {
+            KeyOrder::Alphabetical => KeyOrderArg::Alphabetical,
+            KeyOrder::Template => KeyOrderArg::Template,
+        };
+    }
+    if let Some(policy) = options.on_duplicate
+        && is_default(matches, "on_duplicate")
+    {
+        cli.on_duplicate = match policy {
+            DuplicateNames::Error => OnDuplicate::Error,
+            DuplicateNames::Suffix => OnDuplicate::Suffix,
+            DuplicateNames::Skip => OnDuplicate::Skip,
+        };
+    }
+    // A switch on in settings.json stays on unless its --no- flag is the last one given
+    cli.strict_config |= options.strict_config && !cli.no_strict_config;
+    cli.check_paths |= options.check_paths && !cli.no_check_paths;
+    cli.check_programs |= options.check_programs && !cli.no_check_programs;
+    cli.keep_going |= options.keep_going && !cli.no_keep_going;
+    cli.dedupe |= options.dedupe && !cli.no_dedupe;
+    cli.preserve_keys |= options.preserve_keys && !cli.no_preserve_keys;
+    cli.lint |= options.lint && !cli.no_lint;
+    Ok(())
+}
+
+fn run(cli: &Cli, matches: &ArgMatches) -> Result<()> {
+    match &cli.command {
+        None => generate(cli),
+        Some(Command::Add) => {
+            let stdin = io::stdin();
+            let path = mklaunch::add_config(
+                &cli.templates,
+                &cli.configs[0],
+                &mut stdin.lock(),
+                &mut io::stdout(),
+            )?;
+            println!("Created {}", path.display());
+            generate(cli)
+        }
+        Some(Command::NewTemplate { adapter }) => {
+            let adapter = match adapter {
+                AdapterArg::Cppdbg => DebugAdapter::Cppdbg,
+                AdapterArg::Codelldb => DebugAdapter::Codelldb,
+                AdapterArg::Debugpy => DebugAdapter::Debugpy,
+                AdapterArg::Node => DebugAdapter::Node,
+                AdapterArg::Go => DebugAdapter::Go,
+            };
+            let stdin = io::stdin();
+            let name = mklaunch::add_template(
+                &cli.templates[0],
+                adapter,
+                &mut stdin.lock(),
+                &mut io::stdout(),
+            )?;
+            println!(
+                "Added template '{}' to {}",
+                name,
+                cli.templates[0].display()
+            );
+            Ok(())
+        }
+        Some(Command::List { json }) => list(cli, *json),
+        Some(Command::Toggle) => {
+            let stdin = io::stdin();
+            let written =
+                mklaunch::toggle_enabled(&cli.configs, &mut stdin.lock(), &mut io::stdout())?;
+            if written.is_empty() {
+                println!("No changes");
+                return Ok(());
+            }
+            for path in &written {
+                println!("Updated {}", path.display());
+            }
+            generate(cli)
+        }
+        Some(Command::Lint) => {
+            let warnings = generator(cli)?.lint()?;
+            for warning in &warnings {
+                println!("warning: {}", warning);
+            }
+            if cli.verbose {
+                println!("{} lint warnings", warnings.len());
+            }
+            Ok(())
+        }
+        Some(Command::LintTemplates { packages }) => {
+            let diagnostics = generator(cli)?.lint_templates(packages)?;
+            if diagnostics.iter().any(|d| d.severity != Severity::Info) {
+                return Err(diagnostics.into());
+            }
+            for info in diagnostics.iter() {
+                eprintln!("{}", info);
+            }
+            if cli.verbose {
+                println!("No problems found");
+            }
+            Ok(())
+        }
+        Some(Command::Validate { json }) => validate(cli, *json),
+        Some(Command::Doctor { json }) => doctor(cli, *json),
+        Some(Command::Batch { dir }) => batch(cli, matches, dir),
+        Some(Command::SyncTemplates { update }) => {
+            for source in mklaunch::sync_templates(
+                &cli.templates[0],
+                *update,
+                command_timeout(cli),
+                command_runner(cli).as_ref(),
+            )? {
+                match &source.rev {
+                    Some(rev) => println!(
+                        "Fetched '{}' at {} into {}",
+                        source.name,
+                        rev,
+                        source.path.display()
+                    ),
+                    None => println!("Fetched '{}' into {}", source.name, source.path.display()),
+                }
+            }
+            Ok(())
+        }
+        Some(Command::Import { launch }) => {
+            let import = mklaunch::import_launch(launch, &cli.templates[0], &cli.configs[0])?;
+            println!(
+                "Imported {} configurations into {} using {} templates ({})",
+                import.configurations,
+                import.config_file.display(),
+                import.templates.len(),
+                import.templates.join(", ")
+            );
+            if let Some(settings) = &import.settings_file {
+                println!("Wrote the other top-level keys to {}", settings.display());
+            }
+            for warning in &import.warnings {
+                eprintln!("warning: {}", warning);
+            }
+            Ok(())
+        }
+        Some(Command::Fmt { check }) => {
+            let changed = mklaunch::format_inputs(&cli.templates, &cli.configs, *check)?;
+            for path in &changed {
+                if *check {
+                    println!("Not formatted: {}", path.display());
+                } else {
+                    println!("Formatted {}", path.display());
+                }
+            }
+            if *check && !changed.is_empty() {
+                anyhow::bail!("{} files are not formatted", changed.len());
+            }
+            Ok(())
+        }
+        Some(Command::Migrate) => {
+            let migrations = mklaunch::migrate(&cli.templates[0], &cli.configs[0])?;
+            if migrations.is_empty() {
+                println!("Nothing to migrate");
+            }
+            for migration in &migrations {
+                println!(
+                    "Migrated {} (backup: {})",
+                    migration.path.display(),
+                    migration.backup.display()
+                );
+            }
+            Ok(())
+        }
+        Some(Command::DiffConfigs { git_ref }) => diff_configs(cli, git_ref),
+        Some(Command::Explain { name }) => explain(cli, name),
+        Some(Command::Schema {
+            what,
+            out,
+            settings,
+        }) => schema(cli, *what, out.as_deref(), *settings),
+    }
+}
+
+/// Prints an error chain to stderr, showing the file location of any diagnostic in it.
+/// A collection of diagnostics is printed one diagnostic after another.
+/// Colored when stderr is a terminal and `NO_COLOR` is unset.
+fn report(err: &anyhow::Error) {
+    let color = io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let paint = |code: &str, text: &str| {
+        if color {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    };
+    let locate = |d: &Diagnostic| {
+        let pointer = d
+            .pointer
+            .as_ref()
+            .map(|p| format!(" (at {})", p))
+            .unwrap_or_default();
+        eprintln!("  {} {}{}", paint("1;34", "-->"), d.location(), pointer);
+    };
+
+    if let Some(diagnostics) = err.downcast_ref::<Diagnostics>() {
+        for d in diagnostics {
+            let label = match d.severity {
+                Severity::Error => paint("1;31", "error:"),
+                Severity::Warning => paint("1;33", "warning:"),
+                Severity::Info => paint("1;36", "info:"),
+            };
+            eprintln!("{} {}", label, paint("1", &d.message));
+            locate(d);
+        }
+        let problems = diagnostics
+            .iter()
+            .filter(|d| d.severity != Severity::Info)
+            .count();
+        if problems > 1 {
+            eprintln!(
+                "{} {}",
+                paint("1;31", "error:"),
+                paint("1", &format!("{} problems found", problems))
+            );
+        }
+        return;
+    }
+
+    for (idx, cause) in err.chain().enumerate() {
+        let (message, diagnostic) = match cause.downcast_ref::<Diagnostic>() {
+            Some(d) => (d.message.clone(), Some(d)),
+            None => (cause.to_string(), None),
+        };
+        if idx == 0 {
+            eprintln!("{} {}", paint("1;31", "error:"), paint("1", &message));
+        } else {
+            eprintln!("  {} {}", paint("1;34", "caused by:"), message);
+        }
+        if let Some(d) = diagnostic {
+            locate(d);
+        }
+    }
+}
+
+/// Generates every workspace found under `dir`, resolving relative input and output
+/// paths against each one, and fails when any of them failed
+fn batch(cli: &Cli, matches: &ArgMatches, dir: &Path) -> Result<()> {
+    let workspaces = mklaunch::find_workspaces(dir)?;
+    if workspaces.is_empty() {
+        anyhow::bail!("No .mklaunch directories found under {}", dir.display());
+    }
+
+    let mut failed = 0;
+    for workspace in &workspaces {
+        let mut sub = cli.clone();
+        sub.command = None;
+        sub.templates = cli.templates.iter().map(|p| workspace.join(p)).collect();
+        sub.configs = cli.configs.iter().map(|p| workspace.join(p)).collect();
+        sub.output = workspace.join(&cli.output);
+        sub.root = Some(
+            cli.root
+                .as_ref()
+                .map_or(workspace.clone(), |r| workspace.join(r)),
+        );
+        sub.args_dir = cli.args_dir.as_ref().map(|p| workspace.join(p));
+        sub.defaults = cli.defaults.as_ref().map(|p| workspace.join(p));
+        sub.from_cmake = cli.from_cmake.as_ref().map(|p| workspace.join(p));
+        sub.from_python = cli.from_python.as_ref().map(|p| workspace.join(p));
+        sub.from_go = cli.from_go.as_ref().map(|p| workspace.join(p));
+        sub.from_npm = cli.from_npm.as_ref().map(|p| workspace.join(p));
+        sub.extra_configs = cli
+            .extra_configs
+            .iter()
+            .map(|p| workspace.join(p))
+            .collect();
+
+        match apply_settings(&mut sub, matches).and_then(|()| generate(&sub)) {
+            Ok(()) => println!("ok    {}", workspace.display()),
+            Err(err) => {
+                failed += 1;
+                println!("FAIL  {}", workspace.display());
+                report(&err);
+            }
+        }
+    }
+
+    println!(
+        "{} of {} workspaces generated",
+        workspaces.len() - failed,
+        workspaces.len()
+    );
+    if failed > 0 {
+        anyhow::bail!("{} of {} workspaces failed", failed, workspaces.len());
+    }
+    Ok(())
+}
+
+/// Prints one row per config entry (or a JSON report), in file order
+fn list(cli: &Cli, json: bool) -> Result<()> {
+    let entries = generator(cli)?.entries()?;
+    if json {
+        println!("{}", to_string_pretty(&ListReport::new(&entries))?);
+        return Ok(());
+    }
+    let width = entries
+        .iter()
+        .map(|e| e.name.len())
+        .chain(["CONFIGURATION".len()])
+        .max()
+        .unwrap_or(0);
+    println!("{:width$}  STATUS    TAGS", "CONFIGURATION", width = width);
+    for entry in &entries {
+        let status = if entry.enabled { "enabled" } else { "disabled" };
+        let row = format!(
+            "{:width$}  {:8}  {}",
+            entry.name,
+            status,
+            entry.tags.join(", "),
+            width = width
+        );
+        println!("{}", row.trim_end());
     }
-    let mut f = fs::File::create(&cli.output)?;
-    f.write_all(to_string_pretty(&launch)?.as_bytes())?;
+    Ok(())
+}
 
-    if cli.verbose {
+/// Prints one row per configuration (or a JSON report) and fails when any check failed
+fn doctor(cli: &Cli, json: bool) -> Result<()> {
+    let report = generator(cli)?.doctor()?;
+    if json {
+        println!("{}", to_string_pretty(&DoctorReport::new(&report))?);
+    } else {
+        print_health(&report);
+    }
+
+    let failing = report.iter().filter(|h| !h.is_ok()).count();
+    if failing > 0 {
+        anyhow::bail!(
+            "{} of {} configurations have problems",
+            failing,
+            report.len()
+        );
+    }
+    Ok(())
+}
+
+fn print_health(report: &[ConfigHealth]) {
+    let width = report
+        .iter()
+        .map(|h| h.name.len())
+        .chain(["CONFIGURATION".len()])
+        .max()
+        .unwrap_or(0);
+    println!("{:width$}  STATUS  DETAILS", "CONFIGURATION", width = width);
+    for health in report {
+        let (status, details) = if health.is_ok() {
+            ("ok", format!("{} checks passed", health.checked.len()))
+        } else {
+            ("FAIL", health.problems.join("; "))
+        };
         println!(
-            "Generated launch.json with {} configurations",
-            launch.configurations().len()
+            "{:width$}  {:6}  {}",
+            health.name,
+            status,
+            details,
+            width = width
         );
     }
+}
 
+/// Prints every problem found in the config entries (or a JSON report), then fails if
+/// there were any
+fn validate(cli: &Cli, json: bool) -> Result<()> {
+    let diagnostics = generator(cli)?.validate()?;
+    if json {
+        println!("{}", to_string_pretty(&ValidateReport::new(&diagnostics))?);
+    } else {
+        for diagnostic in &diagnostics {
+            report(&anyhow::Error::new(diagnostic.clone()));
+        }
+        if cli.verbose && diagnostics.is_empty() {
+            println!("No problems found");
+        }
+    }
+    if !diagnostics.is_empty() {
+        anyhow::bail!("{} problems found", diagnostics.len());
+    }
     Ok(())
 }
+
+/// Generates launch.json from the inputs at `git_ref` and from the working tree, and prints
+/// one line per configuration added (`+`), removed (`-`) or changed (`~`), the latter
+/// followed by its changed keys
+fn diff_configs(cli: &Cli, git_ref: &str) -> Result<()> {
+    let inputs = match cli.templates[0].parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let snapshot = std::env::temp_dir().join(format!("mklaunch-diff-{}", std::process::id()));
+    let changes = (|| {
+        if mklaunch::export_dir(
+            git_ref,
+            inputs,
+            &snapshot,
+            command_timeout(cli),
+            command_runner(cli).as_ref(),
+        )? == 0
+        {
+            anyhow::bail!("{} has no files at {}", inputs.display(), git_ref);
+        }
+        // Inputs outside the .mklaunch directory, such as baseArgs files, are read from
+        // the working tree
+        let rebase = |path: &PathBuf| match path.strip_prefix(inputs) {
+            Ok(relative) => snapshot.join(relative),
+            Err(_) => path.clone(),
+        };
+        let mut old = cli.clone();
+        old.templates = cli.templates.iter().map(rebase).collect();
+        old.configs = cli.configs.iter().map(rebase).collect();
+        old.args_dir = cli.args_dir.as_ref().map(rebase);
+        old.defaults = cli.defaults.as_ref().map(rebase);
+        old.cache = false;
+        let before = generator(&old)?
+            .generate()
+            .with_context(|| format!("Failed to generate launch.json at {}", git_ref))?;
+        let after = generator(cli)?.generate()?;
+        Ok(mklaunch::diff_configs(&before, &after))
+    })();
+    let _ = fs::remove_dir_all(&snapshot);
+    let changes = changes?;
+
+    for change in &changes {
+        match change {
+            ConfigChange::Added(name) => println!("+ {}", name),
+            ConfigChange::Removed(name) => println!("- {}", name),
+            ConfigChange::Changed { name, keys } => {
+                println!("~ {}", name);
+                for key in keys {
+                    println!("    {}", key);
+                }
+            }
+        }
+    }
+    if changes.is_empty() {
+        println!("No configuration changed since {}", git_ref);
+    }
+    Ok(())
+}
+
+/// Prints the resolved configuration followed by per-key and per-argument sources
+fn explain(cli: &Cli, name: &str) -> Result<()> {
+    let explanation = generator(cli)?.explain(name)?;
+    let provenance = &explanation.provenance;
+
+    println!(
+        "Configuration '{}' from {}\n",
+        name,
+        explanation.source.display()
+    );
+    println!("{}\n", to_string_pretty(&explanation.config)?);
+
+    println!("Keys:");
+    let width = provenance.keys().keys().map(String::len).max().unwrap_or(0);
+    for (key, source) in provenance.keys() {
+        println!("  {:width$}  {}", key, source, width = width);
+    }
+
+    if !provenance.args().is_empty() {
+        println!("\nArgs:");
+        let quoted: Vec<String> = provenance
+            .args()
+            .iter()
+            .map(|(arg, _)| to_string_pretty(arg))
+            .collect::<Result<_, _>>()?;
+        let width = quoted.iter().map(String::len).max().unwrap_or(0);
+        for (idx, (arg, (_, source))) in quoted.iter().zip(provenance.args()).enumerate() {
+            println!("  [{}] {:width$}  {}", idx, arg, source, width = width);
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints or writes the JSON Schema for the selected input format
+fn schema(cli: &Cli, what: SchemaKind, out: Option<&Path>, settings: bool) -> Result<()> {
+    let (schema, file_match) = match what {
+        SchemaKind::Configs => (
+            mklaunch::configs_schema(),
+            cli.configs[0].join("*.json").display().to_string(),
+        ),
+        SchemaKind::Templates => (
+            mklaunch::templates_schema(),
+            if cli.templates[0].is_dir() {
+                cli.templates[0]
+                    .join("*.templates.json")
+                    .display()
+                    .to_string()
+            } else {
+                cli.templates[0].display().to_string()
+            },
+        ),
+    };
+
+    let Some(out) = out else {
+        println!("{}", to_string_pretty(&schema)?);
+        return Ok(());
+    };
+
+    if let Some(parent) = out.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(out, to_string_pretty(&schema)? + "\n")?;
+
+    if settings {
+        // VSCode resolves relative schema URLs against the workspace folder
+        let url = if out.is_relative() && !out.starts_with(".") {
+            format!("./{}", out.display())
+        } else {
+            out.display().to_string()
+        };
+        let snippet = mklaunch::vscode_settings(&file_match, &url);
+        println!("{}", to_string_pretty(&snippet)?);
+    } else if cli.verbose {
+        println!("Wrote schema to {}", out.display());
+    }
+
+    Ok(())
+}
+
+/// Runs external commands as child processes, or refuses them with `--no-exec`
+fn command_runner(cli: &Cli) -> Arc<dyn CommandRunner> {
+    if cli.no_exec {
+        Arc::new(NoExec)
+    } else {
+        Arc::new(ProcessRunner)
+    }
+}
+
+fn command_timeout(cli: &Cli) -> Option<Duration> {
+    cli.command_timeout.map(Duration::from_millis)
+}
+
+/// Builds a generator from the command line options
+fn generator(cli: &Cli) -> Result<Generator> {
+    let mut options = GeneratorOptions::new(cli.templates[0].clone(), cli.configs[0].clone());
+    for path in &cli.templates[1..] {
+        options = options.templates(path.clone());
+    }
+    for path in &cli.configs[1..] {
+        options = options.configs(path.clone());
+    }
+    if let Some(root) = &cli.root {
+        options = options.root(root.clone());
+    }
+    options = options.command_runner(command_runner(cli));
+    if let Some(args_dir) = &cli.args_dir {
+        options = options.args_dir(args_dir.clone());
+    }
+    if let Some(defaults) = &cli.defaults {
+        options = options.defaults_path(defaults.clone());
+    }
+    if let (Some(path), Some(template)) = (&cli.from_cmake, &cli.cmake_template) {
+        options = options.from_cmake(path.clone(), template.clone());
+    }
+    if let Some(path) = &cli.from_python {
+        options = options.from_python(path.clone(), cli.python_template.clone());
+    }
+    if let Some(path) = &cli.from_go {
+        options = options.from_go(path.clone(), cli.go_template.clone());
+    }
+    if let Some(path) = &cli.from_npm {
+        options = options.from_npm(
+            path.clone(),
+            cli.npm_template.clone(),
+            cli.npm_scripts.clone(),
+        );
+    }
+    if let Some(style) = cli.path_style {
+        options = options.path_style(match style {
+            PathStyleArg::Posix => PathStyle::Posix,
+            PathStyleArg::Windows => PathStyle::Windows,
+            PathStyleArg::Auto => PathStyle::host(),
+        });
+    }
+    for set in &cli.sets {
+        let (key, value) = set
+            .split_once('=')
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("--set expects KEY=VALUE, found '{}'", set))?;
+        let value = serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::from(value));
+        options = options.set(key.to_string(), value);
+    }
+    if !cli.only.is_empty() {
+        options = options.override_names(cli.only.clone());
+    }
+    let from_env = std::env::var_os("MKLAUNCH_EXTRA_CONFIG");
+    let from_env = from_env.iter().flat_map(std::env::split_paths);
+    for path in cli.extra_configs.iter().cloned().chain(from_env) {
+        if !path.as_os_str().is_empty() {
+            options = options.extra_config(path);
+        }
+    }
+    if cli.flatten_os {
+        let os = TargetOs::host().ok_or_else(|| {
+            anyhow::anyhow!("--flatten-os: VSCode has no override block for this OS")
+        })?;
+        options = options.flatten_os(os);
+    }
+    options = options
+        .check_paths(cli.check_paths)
+        .check_programs(cli.check_programs)
+        .dedupe(cli.dedupe)
+        .command_timeout(command_timeout(cli))
+        .dedupe_args(cli.dedupe_args.map(|mode| match mode {
+            DedupeArgs::Warn => ArgsDedupe::Warn,
+            DedupeArgs::Remove => ArgsDedupe::Remove,
+        }))
+        .keep_going(cli.keep_going)
+        .allow_missing_base_args(cli.allow_missing_base_args)
+        .strict_config(cli.strict_config)
+        .cache(cli.cache)
+        .stamp(cli.stamp)
+        .group_by_dir(cli.group_by_dir)
+        .group_by_template(matches!(cli.group_by, Some(GroupByArg::Template)))
+        .allow_exec(cli.allow_exec)
+        .duplicate_names(match cli.on_duplicate {
+            OnDuplicate::Error => DuplicateNames::Error,
+            OnDuplicate::Suffix => DuplicateNames::Suffix,
+            OnDuplicate::Skip => DuplicateNames::Skip,
+        })
+        .tags(cli.tags.clone())
+        .tag_match(match cli.tag_match {
+            TagMatchArg::Any => TagMatch::Any,
+            TagMatchArg::All => TagMatch::All,
+        })
+        .key_order(match cli.key_order {
+            KeyOrderArg::Alphabetical => KeyOrder::Alphabetical,
+            KeyOrderArg::Template => KeyOrder::Template,
+        })
+        .environment(match cli.environment {
+            EnvironmentArg::Host => Environment::Host,
+            EnvironmentArg::Devcontainer => Environment::Devcontainer,
+        })
+        .output_format(output_format(cli));
+    Ok(Generator::with_options(options))
+}
+
+fn output_format(cli: &Cli) -> OutputFormat {
+    match cli.output_format {
+        OutputFormatArg::Json => OutputFormat::Json,
+        OutputFormatArg::Jsonc => OutputFormat::Jsonc,
+        OutputFormatArg::Json5 => OutputFormat::Json5,
+    }
+}
+
+/// Generates launch.json and writes it to the configured output path
+fn generate(cli: &Cli) -> Result<()> {
+    if !cli.outputs.is_empty() {
+        return generate_outputs(cli);
+    }
+    let generator = generator(cli)?;
+    let _guard = generator.guard()?;
+
+    let lock_path = generator.lock_path();
+    let lock = LockFile::from_path(&lock_path)?;
+
+    if cli.if_changed
+        && let Some(lock) = &lock
+        // Unreadable inputs fall through so generation reports the real error
+        && let Ok(LockStatus::UpToDate) = generator
+            .input_files()
+            .and_then(|inputs| lock.check(&inputs, &cli.output))
+    {
+        if cli.verbose {
+            println!("{} is up to date", cli.output.display());
+        }
+        return Ok(());
+    }
+
+    let (launch, content) = render(cli, &generator)?;
+
+    // An output unchanged since the last run holds nothing but generated configurations
+    if !lock
+        .as_ref()
+        .is_some_and(|lock| lock.check_output(&cli.output) == LockStatus::UpToDate)
+    {
+        confirm_overwrite(cli, &cli.output, &launch)?;
+    }
+
+    if let Some(lock) = &lock
+        && cli.output.exists()
+        && lock.check_output(&cli.output) == LockStatus::OutputModified
+    {
+        eprintln!(
+            "warning: {} was modified since it was last generated; overwriting",
+            cli.output.display()
+        );
+    }
+
+    mklaunch::write_atomic(&cli.output, &content)?;
+
+    // A partial launch.json must not make --if-changed skip the next run, but is recorded
+    // so the next run does not take it for a hand-edited file
+    if launch.errors().is_empty() {
+        LockFile::new(&generator.input_files()?, &cli.output, &content)?.write(&lock_path)?;
+    } else {
+        LockFile::outputs_only(&[(&cli.output, &content)])?.write(&lock_path)?;
+    }
+
+    if cli.gitignore
+        && let Some(gitignore) = mklaunch::gitignore_output(
+            &cli.output,
+            command_timeout(cli),
+            command_runner(cli).as_ref(),
+        )?
+    {
+        println!("Added {} to {}", cli.output.display(), gitignore.display());
+    }
+
+    for warning in launch.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+    for warning in generator.git_warnings(&cli.output)? {
+        eprintln!("warning: {}", warning);
+    }
+
+    if cli.verbose || cli.stats {
+        print_stats(&cli.output, &launch);
+    }
+
+    if !launch.errors().is_empty() {
+        for error in launch.errors() {
+            eprintln!("error: {}", error);
+        }
+        anyhow::bail!(
+            "{} config file(s) or entries failed and were left out of {}",
+            launch.errors().len(),
+            cli.output.display()
+        );
+    }
+
+    if cli.lint {
+        for warning in generator.lint()? {
+            eprintln!("warning: {}", warning);
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints how many configurations went into `output` and how they were generated
+fn print_stats(output: &Path, launch: &LaunchJson) {
+    println!(
+        "Generated {} with {} configurations",
+        output.display(),
+        launch.configurations().len()
+    );
+    let summary = launch.summary();
+    println!("  templates loaded:     {}", summary.templates);
+    println!("  config files scanned: {}", summary.files);
+    println!(
+        "  entries:              {} enabled, {} disabled",
+        summary.enabled, summary.disabled
+    );
+    if summary.discovered > 0 {
+        println!("  discovered targets:   {}", summary.discovered);
+    }
+    println!("  duplicates skipped:   {}", summary.skipped);
+    println!("  baseArgs resolved:    {}", summary.base_args);
+    println!("  warnings:             {}", summary.warnings);
+    if summary.failed > 0 {
+        println!("  failed (skipped):     {}", summary.failed);
+    }
+}
+
+/// Refuses to replace `output` when it has configurations `launch` does not generate,
+/// unless `--force` is given or the user agrees at a terminal
+fn confirm_overwrite(cli: &Cli, output: &Path, launch: &LaunchJson) -> Result<()> {
+    if cli.force || !output.exists() {
+        return Ok(());
+    }
+    let existing = fs::read_to_string(output)?;
+    let lost = launch.unmanaged_in(&existing).with_context(|| {
+        format!(
+            "Failed to read the configurations of {}; pass --force to replace it",
+            output.display()
+        )
+    })?;
+    if lost.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!(
+        "{} has configurations mklaunch does not generate:",
+        output.display()
+    );
+    for name in &lost {
+        eprintln!("  {}", name);
+    }
+    if io::stdin().is_terminal() {
+        eprint!("Overwrite and lose them? [y/N] ");
+        let mut line = String::new();
+        io::stdin().read_line(&mut line)?;
+        if matches!(line.trim(), "y" | "Y" | "yes") {
+            return Ok(());
+        }
+    }
+    anyhow::bail!(
+        "Refusing to overwrite {}; pass --force to replace it anyway",
+        output.display()
+    )
+}
+
+/// launch.json, settings.json or snippets for `cli`, as written to `cli.output`
+fn render(cli: &Cli, generator: &Generator) -> Result<(LaunchJson, Vec<u8>)> {
+    if !matches!(cli.emit, EmitKind::Launch) && !matches!(cli.output_format, OutputFormatArg::Json)
+    {
+        anyhow::bail!("--output-format only applies to --emit launch");
+    }
+
+    let mut content = Vec::new();
+    let launch = match cli.emit {
+        EmitKind::Launch if cli.preserve_keys && cli.output.exists() => {
+            let mut launch = generator.generate()?;
+            let existing = fs::read_to_string(&cli.output)?;
+            let kept = launch
+                .preserve_keys_from(&existing)
+                .with_context(|| format!("Failed to preserve keys of {}", cli.output.display()))?;
+            if cli.verbose && !kept.is_empty() {
+                println!("Preserved top-level keys: {}", kept.join(", "));
+            }
+            content = launch.to_string_as(output_format(cli))?.into_bytes();
+            launch
+        }
+        EmitKind::Launch => generator.generate_to(&mut content)?,
+        EmitKind::Settings => {
+            let launch = generator.generate()?;
+            let existing = if cli.output.exists() {
+                Some(fs::read_to_string(&cli.output)?)
+            } else {
+                None
+            };
+            content = mklaunch::settings_json(&launch, existing.as_deref())
+                .with_context(|| format!("Failed to update {}", cli.output.display()))?
+                .into_bytes();
+            launch
+        }
+        EmitKind::Workspace => {
+            let launch = generator.generate()?;
+            content = mklaunch::snippets(&launch)?.into_bytes();
+            launch
+        }
+    };
+    Ok((launch, content))
+}
+
+/// Generates every file listed under `outputs` in settings.json, each with its own
+/// filters, and records all of them in one lock file. Nothing is written unless all of
+/// them could be generated, but each file is replaced on its own: a failure while writing
+/// can leave the earlier outputs already replaced. With --keep-going, entries that fail
+/// are left out of every output and reported once all of them are written.
+fn generate_outputs(cli: &Cli) -> Result<()> {
+    let base = generator(cli)?;
+    let _guard = base.guard()?;
+    let lock_path = base.lock_path();
+    let lock = LockFile::from_path(&lock_path)?;
+
+    let mut targets = Vec::new();
+    for (path, filter) in &cli.outputs {
+        let mut target = cli.clone();
+        target.outputs.clear();
+        target.output = path.clone();
+        if !filter.tags.is_empty() {
+            target.tags = filter.tags.clone();
+        }
+        if let Some(tag_match) = filter.tag_match {
+            target.tag_match = match tag_match {
+                TagMatch::Any => TagMatchArg::Any,
+                TagMatch::All => TagMatchArg::All,
+            };
+        }
+        if let Some(format) = filter.output_format {
+            target.output_format = match format {
+                OutputFormat::Json => OutputFormatArg::Json,
+                OutputFormat::Jsonc => OutputFormatArg::Jsonc,
+                OutputFormat::Json5 => OutputFormatArg::Json5,
+            };
+        }
+        if let Some(environment) = filter.env {
+            target.environment = match environment {
+                Environment::Host => EnvironmentArg::Host,
+                Environment::Devcontainer => EnvironmentArg::Devcontainer,
+            };
+        }
+        let generator = generator(&target)?;
+        targets.push((target, generator));
+    }
+    let paths: Vec<&Path> = cli.outputs.iter().map(|(path, _)| path.as_path()).collect();
+
+    if cli.if_changed
+        && let Some(lock) = &lock
+        // Unreadable inputs fall through so generation reports the real error
+        && let Ok(LockStatus::UpToDate) = input_files(targets.iter().map(|(_, generator)| generator))
+            .and_then(|inputs| lock.check_outputs(&inputs, &paths))
+    {
+        if cli.verbose {
+            for path in &paths {
+                println!("{} is up to date", path.display());
+            }
+        }
+        return Ok(());
+    }
+
+    let mut rendered = Vec::new();
+    for (target, generator) in targets {
+        let path = &target.output;
+        let (launch, content) = render(&target, &generator)
+            .with_context(|| format!("Failed to generate {}", path.display()))?;
+        // An output unchanged since the last run holds nothing but generated configurations
+        if !lock
+            .as_ref()
+            .is_some_and(|lock| lock.check_output(path) == LockStatus::UpToDate)
+        {
+            confirm_overwrite(&target, path, &launch)?;
+        }
+        rendered.push((target, generator, launch, content));
+    }
+
+    for (target, generator, launch, content) in &rendered {
+        if let Some(lock) = &lock
+            && target.output.exists()
+            && lock.check_output(&target.output) == LockStatus::OutputModified
+        {
+            eprintln!(
+                "warning: {} was modified since it was last generated; overwriting",
+                target.output.display()
+            );
+        }
+        mklaunch::write_atomic(&target.output, content)?;
+        if target.gitignore
+            && let Some(gitignore) = mklaunch::gitignore_output(
+                &target.output,
+                command_timeout(target),
+                command_runner(target).as_ref(),
+            )?
+        {
+            println!(
+                "Added {} to {}",
+                target.output.display(),
+                gitignore.display()
+            );
+        }
+        for warning in launch.warnings() {
+            eprintln!("warning: {}", warning);
+        }
+        for warning in generator.git_warnings(&target.output)? {
+            eprintln!("warning: {}", warning);
+        }
+        if cli.verbose || cli.stats {
+            print_stats(&target.output, launch);
+        }
+    }
+
+    let written: Vec<(&Path, &[u8])> = rendered
+        .iter()
+        .map(|(target, _, _, content)| (target.output.as_path(), content.as_slice()))
+        .collect();
+    // With --keep-going, outputs sharing a config file report its errors once
+    let mut errors: Vec<String> = Vec::new();
+    for (_, _, launch, _) in &rendered {
+        for error in launch.errors() {
+            let error = error.to_string();
+            if !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+    }
+    // Partial outputs must not make --if-changed skip the next run
+    if errors.is_empty() {
+        let inputs = input_files(rendered.iter().map(|(_, generator, _, _)| generator))?;
+        LockFile::with_outputs(&inputs, &written)?.write(&lock_path)?;
+    } else {
+        LockFile::outputs_only(&written)?.write(&lock_path)?;
+        for error in &errors {
+            eprintln!("error: {}", error);
+        }
+        anyhow::bail!(
+            "{} config file(s) or entries failed and were left out of {}",
+            errors.len(),
+            paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    if cli.lint {
+        for warning in base.lint()? {
+            eprintln!("warning: {}", warning);
+        }
+    }
+    Ok(())
+}
+
+/// Every input any of the `targets` reads, each listed once
+fn input_files<'a>(generators: impl Iterator<Item = &'a Generator>) -> Result<Vec<PathBuf>> {
+    let mut inputs = Vec::new();
+    for generator in generators {
+        inputs.extend(generator.input_files()?);
+    }
+    inputs.sort();
+    inputs.dedup();
+    Ok(inputs)
+}
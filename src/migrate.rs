@@ -0,0 +1,131 @@
+use crate::encoding::read_text;
+use crate::generator::list_config_dir;
+use crate::schema::SCHEMA_VERSION;
+use crate::table::is_table;
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Input rewritten by [`migrate`] together with the backup of its previous form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// File written in the current format
+    pub path: PathBuf,
+    /// Copy of the legacy file, or the renamed legacy templates directory
+    pub backup: PathBuf,
+}
+
+/// Converts schema version 1 inputs to the current format in place:
+/// a per-file `templates/` directory next to `templates_path` becomes the manifest
+/// (the directory is kept as `templates.bak`), and single-object config files in
+/// `configs_dir` are wrapped in an array (the original is kept as `<file>.bak`).
+/// Returns the migrated files; an empty list means everything was already current.
+pub fn migrate(templates_path: &Path, configs_dir: &Path) -> Result<Vec<Migration>> {
+    let mut migrations = Vec::new();
+
+    let legacy_dir = templates_path.with_file_name("templates");
+    if !templates_path.exists() && legacy_dir.is_dir() {
+        migrations.push(migrate_templates(templates_path, &legacy_dir)?);
+    }
+
+    if configs_dir.exists() {
+        for config_path in list_config_dir(configs_dir, false)?.0 {
+            if let Some(migration) = migrate_config(&config_path)? {
+                migrations.push(migration);
+            }
+        }
+    }
+
+    Ok(migrations)
+}
+
+/// Builds the manifest from one template per file, named after the file stem
+fn migrate_templates(templates_path: &Path, legacy_dir: &Path) -> Result<Migration> {
+    let mut files: Vec<PathBuf> = fs::read_dir(legacy_dir)
+        .with_context(|| format!("Failed to read directory: {}", legacy_dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().and_then(|s| s.to_str()) == Some("json"))
+        .collect();
+    files.sort();
+
+    let mut templates = Vec::new();
+    for path in &files {
+        let content = read_text(path)
+            .with_context(|| format!("Failed to read template: {}", path.display()))?;
+        let Value::Object(mut template) = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse template: {}", path.display()))?
+        else {
+            anyhow::bail!("Template {} must be a JSON object", path.display());
+        };
+        if !template.contains_key("name") {
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default();
+            template.insert("name".to_string(), Value::String(stem.to_string()));
+        }
+        templates.push(Value::Object(template));
+    }
+    if templates.is_empty() {
+        anyhow::bail!("No template files found in {}", legacy_dir.display());
+    }
+
+    let backup = legacy_dir.with_file_name("templates.bak");
+    if backup.exists() {
+        anyhow::bail!("Backup already exists: {}", backup.display());
+    }
+
+    let manifest = json!({ "schemaVersion": SCHEMA_VERSION, "templates": templates });
+    fs::write(
+        templates_path,
+        serde_json::to_string_pretty(&manifest)? + "\n",
+    )
+    .with_context(|| {
+        format!(
+            "Failed to write templates manifest: {}",
+            templates_path.display()
+        )
+    })?;
+    fs::rename(legacy_dir, &backup)
+        .with_context(|| format!("Failed to rename {}", legacy_dir.display()))?;
+
+    Ok(Migration {
+        path: templates_path.to_path_buf(),
+        backup,
+    })
+}
+
+/// Wraps a legacy single-object config file in an array; other files are left alone
+fn migrate_config(config_path: &Path) -> Result<Option<Migration>> {
+    if is_table(config_path) {
+        return Ok(None);
+    }
+    let content = read_text(config_path)
+        .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+    let value: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse config JSON: {}", config_path.display()))?;
+    if !value.is_object() || value.get("configurations").is_some() {
+        return Ok(None);
+    }
+
+    let mut backup = config_path.as_os_str().to_owned();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+    if backup.exists() {
+        anyhow::bail!("Backup already exists: {}", backup.display());
+    }
+
+    fs::write(&backup, &content)
+        .with_context(|| format!("Failed to write backup: {}", backup.display()))?;
+    fs::write(
+        config_path,
+        serde_json::to_string_pretty(&json!([value]))? + "\n",
+    )
+    .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
+
+    Ok(Some(Migration {
+        path: config_path.to_path_buf(),
+        backup,
+    }))
+}
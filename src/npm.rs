@@ -0,0 +1,131 @@
+use crate::discover::DiscoveredTarget;
+use crate::encoding::read_text;
+use crate::generator::workspace_path;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+struct PackageJson {
+    name: Option<String>,
+    #[serde(default)]
+    scripts: BTreeMap<String, String>,
+    workspaces: Option<Workspaces>,
+}
+
+/// `workspaces` as an array of patterns, or Yarn's `{ "packages": [...] }`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Workspaces {
+    Patterns(Vec<String>),
+    Yarn { packages: Vec<String> },
+}
+
+/// Enumerates the scripts named in `allowlist` of the package at `path` (a directory or
+/// its `package.json`) and of its workspace packages. Each becomes a target running
+/// `npm run <script>`; workspace packages also set `cwd` to the package directory.
+pub(crate) fn discover(path: &Path, allowlist: &[String]) -> Result<Vec<DiscoveredTarget>> {
+    let manifest = if path.is_dir() {
+        path.join("package.json")
+    } else {
+        path.to_path_buf()
+    };
+    let root = read_package(&manifest)?;
+    let root_dir = manifest.parent().unwrap_or(Path::new(""));
+
+    let mut packages = vec![(manifest.clone(), None, root.scripts)];
+    let patterns = match root.workspaces {
+        Some(Workspaces::Patterns(patterns)) | Some(Workspaces::Yarn { packages: patterns }) => {
+            patterns
+        }
+        None => Vec::new(),
+    };
+    for dir in workspace_dirs(root_dir, &patterns)? {
+        let manifest = dir.join("package.json");
+        let package = read_package(&manifest)?;
+        let label = package.name.unwrap_or_else(|| {
+            dir.strip_prefix(root_dir)
+                .unwrap_or(&dir)
+                .display()
+                .to_string()
+        });
+        packages.push((manifest, Some((label, dir)), package.scripts));
+    }
+
+    let mut targets = Vec::new();
+    for script in allowlist {
+        let before = targets.len();
+        for (manifest, workspace, scripts) in &packages {
+            if !scripts.contains_key(script) {
+                continue;
+            }
+            let mut keys = vec![
+                ("runtimeExecutable", Some(json!("npm"))),
+                ("runtimeArgs", Some(json!(["run", script]))),
+                ("program", None),
+            ];
+            let name = match workspace {
+                Some((label, dir)) => {
+                    keys.push(("cwd", Some(Value::String(workspace_path(dir)))));
+                    format!("npm run {} ({})", script, label)
+                }
+                None => format!("npm run {}", script),
+            };
+            targets.push(DiscoveredTarget {
+                name,
+                source: manifest.clone(),
+                keys,
+                args: Vec::new(),
+            });
+        }
+        if targets.len() == before {
+            anyhow::bail!(
+                "npm script '{}' not found in {} or its workspaces",
+                script,
+                manifest.display()
+            );
+        }
+    }
+    Ok(targets)
+}
+
+fn read_package(manifest: &Path) -> Result<PackageJson> {
+    let content =
+        read_text(manifest).with_context(|| format!("Failed to read {}", manifest.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", manifest.display()))
+}
+
+/// Directories matched by workspace patterns: plain paths, or paths ending in `/*` for every
+/// subdirectory containing a package.json. Negated (`!`) patterns exclude directories.
+fn workspace_dirs(root: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for pattern in patterns.iter().filter(|p| !p.starts_with('!')) {
+        match pattern.strip_suffix("/*").or(pattern.strip_suffix("/**")) {
+            Some(parent) => {
+                let parent = root.join(parent);
+                let entries = fs::read_dir(&parent)
+                    .with_context(|| format!("Failed to read directory: {}", parent.display()))?;
+                let mut matched: Vec<PathBuf> = entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.join("package.json").is_file())
+                    .collect();
+                matched.sort();
+                dirs.extend(matched);
+            }
+            None => dirs.push(root.join(pattern)),
+        }
+    }
+
+    let excluded: Vec<PathBuf> = patterns
+        .iter()
+        .filter_map(|p| p.strip_prefix('!'))
+        .map(|p| root.join(p))
+        .collect();
+    dirs.retain(|d| !excluded.contains(d));
+    dirs.dedup();
+    Ok(dirs)
+}
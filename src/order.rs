@@ -0,0 +1,78 @@
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::{Map, Value};
+
+/// Key order of a JSON value as written in its source file, including the order inside
+/// nested objects and array items
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct KeyLayout {
+    pub keys: Vec<(String, KeyLayout)>,
+    pub items: Vec<KeyLayout>,
+}
+
+impl KeyLayout {
+    /// Layout of the value under `key`
+    pub fn child(&self, key: &str) -> Option<&KeyLayout> {
+        self.keys.iter().find(|(k, _)| k == key).map(|(_, l)| l)
+    }
+
+    /// Entries of `object`: keys of this layout first in layout order, then the others
+    /// alphabetically
+    pub fn ordered<'a>(&self, object: &'a Map<String, Value>) -> Vec<(&'a String, &'a Value)> {
+        self.order(object.iter())
+    }
+
+    /// `entries`, given in alphabetical order, with the keys of this layout first in
+    /// layout order
+    pub fn order<'a>(
+        &self,
+        entries: impl Iterator<Item = (&'a String, &'a Value)>,
+    ) -> Vec<(&'a String, &'a Value)> {
+        let mut entries: Vec<(&String, &Value)> = entries.collect();
+        // Stable, so keys outside the layout keep their alphabetical order
+        entries.sort_by_key(|(key, _)| {
+            self.keys
+                .iter()
+                .position(|(k, _)| k == *key)
+                .unwrap_or(usize::MAX)
+        });
+        entries
+    }
+}
+
+/// Serializes `value` with object keys ordered by `layout` where one is known
+pub(crate) struct Ordered<'a> {
+    pub value: &'a Value,
+    pub layout: Option<&'a KeyLayout>,
+}
+
+impl Serialize for Ordered<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(layout) = self.layout else {
+            return self.value.serialize(serializer);
+        };
+        match self.value {
+            Value::Object(object) => {
+                let mut map = serializer.serialize_map(Some(object.len()))?;
+                for (key, value) in layout.ordered(object) {
+                    let value = Ordered {
+                        value,
+                        layout: layout.child(key),
+                    };
+                    map.serialize_entry(key, &value)?;
+                }
+                map.end()
+            }
+            Value::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for (idx, value) in items.iter().enumerate() {
+                    seq.serialize_element(&Ordered {
+                        value,
+                        layout: layout.items.get(idx),
+                    })?;
+                }
+                seq.end()
+            }
+            _ => self.value.serialize(serializer),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+
+/// Operating system selecting one of VSCode's per-OS override blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetOs {
+    Windows,
+    Linux,
+    Osx,
+}
+
+/// Keys of the per-OS override blocks in launch configurations
+pub(crate) const OS_KEYS: [&str; 3] = ["windows", "linux", "osx"];
+
+impl TargetOs {
+    /// The operating system mklaunch is running on, if VSCode has a block for it
+    pub fn host() -> Option<Self> {
+        match std::env::consts::OS {
+            "windows" => Some(TargetOs::Windows),
+            "linux" => Some(TargetOs::Linux),
+            "macos" => Some(TargetOs::Osx),
+            _ => None,
+        }
+    }
+
+    /// Key of this OS's override block
+    pub fn key(self) -> &'static str {
+        match self {
+            TargetOs::Windows => "windows",
+            TargetOs::Linux => "linux",
+            TargetOs::Osx => "osx",
+        }
+    }
+}
+
+impl fmt::Display for TargetOs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+/// Separators written in the path values of generated configurations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathStyle {
+    /// Forward slashes
+    Posix,
+    /// Backslashes
+    Windows,
+}
+
+/// Keys of launch configurations (besides `program`) that hold a single path
+pub(crate) const PATH_KEYS: [&str; 7] = [
+    "cwd",
+    "miDebuggerPath",
+    "debugServerPath",
+    "envFile",
+    "coreDumpPath",
+    "python",
+    "runtimeExecutable",
+];
+
+impl PathStyle {
+    /// Style of the operating system mklaunch is running on
+    pub fn host() -> Self {
+        if cfg!(windows) {
+            PathStyle::Windows
+        } else {
+            PathStyle::Posix
+        }
+    }
+
+    /// `path` with every separator written in this style
+    pub fn normalize(self, path: &str) -> String {
+        match self {
+            PathStyle::Posix => path.replace('\\', "/"),
+            PathStyle::Windows => path.replace('/', "\\"),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::generator::LaunchConfig;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Where each value of a resolved configuration came from
+#[derive(Debug, Clone, Default)]
+pub struct Provenance {
+    keys: BTreeMap<String, String>,
+    args: Vec<(String, String)>,
+}
+
+impl Provenance {
+    /// Source of every top-level key, in key order
+    pub fn keys(&self) -> &BTreeMap<String, String> {
+        &self.keys
+    }
+
+    /// Each resolved argument paired with its source, in argument order
+    pub fn args(&self) -> &[(String, String)] {
+        &self.args
+    }
+
+    pub(crate) fn set_key(&mut self, key: &str, source: impl Into<String>) {
+        self.keys.insert(key.to_string(), source.into());
+    }
+
+    pub(crate) fn remove_key(&mut self, key: &str) {
+        self.keys.remove(key);
+    }
+
+    /// Records `source` for `key` unless a later step already claimed it
+    pub(crate) fn default_key(&mut self, key: &str, source: &str) {
+        self.keys
+            .entry(key.to_string())
+            .or_insert_with(|| source.to_string());
+    }
+
+    pub(crate) fn push_args<'a>(
+        &mut self,
+        args: impl IntoIterator<Item = &'a String>,
+        source: &str,
+    ) {
+        self.args
+            .extend(args.into_iter().map(|a| (a.clone(), source.to_string())));
+    }
+
+    pub(crate) fn remove_args(&mut self, range: std::ops::Range<usize>) {
+        self.args.drain(range);
+    }
+}
+
+/// A resolved configuration together with the provenance of its values
+#[derive(Debug)]
+pub struct Explanation {
+    /// Config file declaring the entry
+    pub source: PathBuf,
+    pub config: LaunchConfig,
+    pub provenance: Provenance,
+}
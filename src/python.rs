@@ -0,0 +1,172 @@
+use crate::discover::DiscoveredTarget;
+use crate::encoding::read_text;
+use crate::generator::workspace_path;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories never searched for test files
+const SKIPPED_DIRS: [&str; 7] = [
+    "__pycache__",
+    "build",
+    "dist",
+    "node_modules",
+    "site-packages",
+    "venv",
+    "env",
+];
+
+/// Enumerates debugpy targets of the Python project at `path` (a directory or its
+/// `pyproject.toml`):
+///
+/// - each console script in `[project.scripts]` or `[tool.poetry.scripts]` runs its
+///   module in module mode (`"module": "pkg.cli"`)
+/// - each top-level `*.py` file with an `if __name__ == "__main__":` guard runs in
+///   program mode
+/// - each pytest file (`test_*.py`, `*_test.py`) runs `pytest` on that file
+pub(crate) fn discover(path: &Path) -> Result<Vec<DiscoveredTarget>> {
+    let root = if path.is_file() {
+        path.parent().unwrap_or(Path::new(""))
+    } else {
+        path
+    };
+    if !root.is_dir() {
+        anyhow::bail!("Python project directory not found: {}", root.display());
+    }
+
+    let mut targets = Vec::new();
+
+    let pyproject = root.join("pyproject.toml");
+    if pyproject.is_file() {
+        let content = read_text(&pyproject)
+            .with_context(|| format!("Failed to read {}", pyproject.display()))?;
+        for (name, entry_point) in console_scripts(&content) {
+            let module = entry_point.split(':').next().unwrap_or_default().trim();
+            targets.push(DiscoveredTarget {
+                name,
+                source: pyproject.clone(),
+                keys: vec![
+                    ("module", Some(Value::String(module.to_string()))),
+                    ("program", None),
+                ],
+                args: Vec::new(),
+            });
+        }
+    }
+
+    for script in sorted_entries(root)? {
+        let is_python = script.extension().and_then(|s| s.to_str()) == Some("py");
+        if !script.is_file() || !is_python || is_test_file(&script) {
+            continue;
+        }
+        let content =
+            read_text(&script).with_context(|| format!("Failed to read {}", script.display()))?;
+        if !has_main_guard(&content) {
+            continue;
+        }
+        targets.push(DiscoveredTarget {
+            name: file_name(&script),
+            keys: vec![
+                ("program", Some(Value::String(workspace_path(&script)))),
+                ("module", None),
+            ],
+            source: script,
+            args: Vec::new(),
+        });
+    }
+
+    let mut tests = Vec::new();
+    collect_test_files(root, &mut tests)?;
+    for test in tests {
+        let relative = test.strip_prefix(root).unwrap_or(&test);
+        targets.push(DiscoveredTarget {
+            name: format!("pytest {}", relative.display()),
+            keys: vec![
+                ("module", Some(Value::String("pytest".to_string()))),
+                ("program", None),
+            ],
+            args: vec![workspace_path(&test)],
+            source: test,
+        });
+    }
+
+    Ok(targets)
+}
+
+/// `(name, "module:function")` pairs from the console script tables of a pyproject.toml.
+/// Only the simple `name = "value"` form used by these tables is understood.
+fn console_scripts(pyproject: &str) -> Vec<(String, String)> {
+    let mut scripts = Vec::new();
+    let mut in_scripts = false;
+    for line in pyproject.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            let table = line.trim_matches(|c| c == '[' || c == ']').trim();
+            in_scripts = matches!(table, "project.scripts" | "tool.poetry.scripts");
+            continue;
+        }
+        if !in_scripts || line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().trim_matches('"');
+        let value = value.trim();
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            continue;
+        };
+        if let Some(value) = value[1..].split(quote).next() {
+            scripts.push((key.to_string(), value.to_string()));
+        }
+    }
+    scripts
+}
+
+fn has_main_guard(source: &str) -> bool {
+    source.lines().any(|line| {
+        let line = line.trim_end();
+        line.starts_with("if __name__ ==") && line.contains("__main__")
+    })
+}
+
+fn is_test_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .is_some_and(|name| {
+            name.ends_with(".py") && (name.starts_with("test_") || name.ends_with("_test.py"))
+        })
+}
+
+/// Test files below `dir`, depth first in name order; hidden and virtualenv-like
+/// directories are skipped
+fn collect_test_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for path in sorted_entries(dir)? {
+        let name = file_name(&path);
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect_test_files(&path, out)?;
+            }
+        } else if is_test_file(&path) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
@@ -0,0 +1,145 @@
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::doctor::ConfigHealth;
+use crate::schema::ConfigFile;
+use serde::{Deserialize, Serialize};
+
+/// Version of the `--json` report schemas below, bumped only when a field is removed or
+/// changes meaning; fields may be added within a version
+pub const REPORT_VERSION: u32 = 1;
+
+/// `mklaunch list --json`: every config entry, in file order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListReport {
+    pub version: u32,
+    pub entries: Vec<EntryReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryReport {
+    /// Name as written, before name tokens are expanded
+    pub name: String,
+    pub extends: String,
+    /// Whether the entry is generated, after the disabled list and tag filter
+    pub enabled: bool,
+    pub tags: Vec<String>,
+}
+
+/// `mklaunch validate --json`: every problem found in the config entries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateReport {
+    pub version: u32,
+    /// Whether there are no diagnostics
+    pub ok: bool,
+    pub diagnostics: Vec<DiagnosticReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticReport {
+    pub path: String,
+    /// JSON pointer of the offending value, e.g. `/3/args/0`
+    pub pointer: Option<String>,
+    /// 1-based
+    pub line: Option<usize>,
+    /// 1-based
+    pub column: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+    pub fix: Option<FixReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixReport {
+    pub title: String,
+    pub pointer: String,
+    /// JSON text of the new value
+    pub replacement: String,
+}
+
+/// `mklaunch doctor --json`: the environment checks of every generated configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorReport {
+    pub version: u32,
+    /// Whether every check of every configuration passed
+    pub ok: bool,
+    pub configurations: Vec<HealthReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub name: String,
+    pub ok: bool,
+    pub checked: Vec<String>,
+    pub problems: Vec<String>,
+}
+
+impl ListReport {
+    pub fn new(entries: &[ConfigFile]) -> Self {
+        let entries = entries
+            .iter()
+            .map(|entry| EntryReport {
+                name: entry.name.clone(),
+                extends: entry.extends.clone(),
+                enabled: entry.enabled,
+                tags: entry.tags.clone(),
+            })
+            .collect();
+        Self {
+            version: REPORT_VERSION,
+            entries,
+        }
+    }
+}
+
+impl ValidateReport {
+    pub fn new(diagnostics: &[Diagnostic]) -> Self {
+        Self {
+            version: REPORT_VERSION,
+            ok: diagnostics.is_empty(),
+            diagnostics: diagnostics.iter().map(DiagnosticReport::from).collect(),
+        }
+    }
+}
+
+impl From<&Diagnostic> for DiagnosticReport {
+    fn from(diagnostic: &Diagnostic) -> Self {
+        Self {
+            path: diagnostic.path.display().to_string(),
+            pointer: diagnostic.pointer.clone(),
+            line: diagnostic.position.map(|(line, _)| line),
+            column: diagnostic.position.map(|(_, column)| column),
+            severity: diagnostic.severity,
+            message: diagnostic.message.clone(),
+            fix: diagnostic.fix.as_ref().map(|fix| FixReport {
+                title: fix.title.clone(),
+                pointer: fix.pointer.clone(),
+                replacement: fix.replacement.clone(),
+            }),
+        }
+    }
+}
+
+impl DoctorReport {
+    pub fn new(report: &[ConfigHealth]) -> Self {
+        let configurations: Vec<HealthReport> = report
+            .iter()
+            .map(|health| HealthReport {
+                name: health.name.clone(),
+                ok: health.is_ok(),
+                checked: health.checked.clone(),
+                problems: health.problems.clone(),
+            })
+            .collect();
+        Self {
+            version: REPORT_VERSION,
+            ok: configurations.iter().all(|c| c.ok),
+            configurations,
+        }
+    }
+}
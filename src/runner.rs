@@ -0,0 +1,146 @@
+use anyhow::{Context, Result};
+use std::fmt;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// External command run during generation: the shell of an `argsFromCommand`, a
+/// `cargo build`, or a discovery tool such as `go list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandSpec {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Directory to run in; empty for the current directory
+    pub cwd: PathBuf,
+    /// How long the command may run before it is killed; `None` waits indefinitely
+    pub timeout: Option<Duration>,
+}
+
+impl CommandSpec {
+    pub fn new(program: &str, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.into_iter().map(Into::into).collect(),
+            cwd: PathBuf::new(),
+            timeout: None,
+        }
+    }
+
+    pub fn cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.cwd = cwd.into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl fmt::Display for CommandSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+/// Output of a command that exited successfully
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// Runs the external commands of a generation. The default runs them as child processes;
+/// library users can supply their own to mock or sandbox them.
+pub trait CommandRunner: fmt::Debug + Send + Sync {
+    /// Runs `command` to completion. Fails when it cannot start, outlives its timeout or
+    /// exits unsuccessfully, with its stderr in the error.
+    fn run(&self, command: &CommandSpec) -> Result<CommandOutput>;
+}
+
+/// Runs commands as child processes, killing those that outlive their timeout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessRunner;
+
+impl CommandRunner for ProcessRunner {
+    fn run(&self, spec: &CommandSpec) -> Result<CommandOutput> {
+        let mut command = Command::new(&spec.program);
+        command
+            .args(&spec.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if !spec.cwd.as_os_str().is_empty() {
+            command.current_dir(&spec.cwd);
+        }
+        let mut child = command.spawn().with_context(|| {
+            format!(
+                "Failed to run `{}`; is {} installed and on PATH?",
+                spec, spec.program
+            )
+        })?;
+        // Read both pipes while waiting so a chatty command cannot fill one and stall
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+
+        let status = match spec.timeout {
+            None => child.wait()?,
+            Some(timeout) => {
+                let start = Instant::now();
+                loop {
+                    if let Some(status) = child.try_wait()? {
+                        break status;
+                    }
+                    if start.elapsed() >= timeout {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        // Processes the command started may keep the pipes open, so the
+                        // readers are left behind rather than joined
+                        anyhow::bail!("`{}` timed out after {} ms", spec, timeout.as_millis());
+                    }
+                    thread::sleep(Duration::from_millis(10));
+                }
+            }
+        };
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        if !status.success() {
+            anyhow::bail!(
+                "`{}` failed ({}):\n{}",
+                spec,
+                status,
+                String::from_utf8_lossy(&stderr).trim_end()
+            );
+        }
+        Ok(CommandOutput { stdout, stderr })
+    }
+}
+
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+/// Refuses every command, for `--no-exec`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoExec;
+
+impl CommandRunner for NoExec {
+    fn run(&self, command: &CommandSpec) -> Result<CommandOutput> {
+        anyhow::bail!(
+            "`{}` was not run: external commands are disabled (--no-exec)",
+            command
+        )
+    }
+}
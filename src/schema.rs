@@ -1,19 +1,277 @@
+use crate::args::{deserialize_args, deserialize_optional_args};
+use crate::atomic::write_atomic;
+use crate::cargo::{CargoBlock, LldbCargo};
+use crate::diagnostic::{Diagnostic, key_layout};
+use crate::encoding::read_text;
+use crate::format::format_config_file;
+use crate::include::{RawEntry, resolve_includes};
+use crate::order::KeyLayout;
+use crate::os::OS_KEYS;
+use crate::table::{is_table, read_table};
 use anyhow::{Context, Result};
-use serde::Deserialize;
-use serde_json::{Map, Value};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value, json};
 use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Current `schemaVersion` of the templates manifest and config files.
+/// Version 1 is the legacy layout: one template per file and single-object config files.
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// Keys a configuration entry may contain; `$schema` lets editors validate entries
+pub(crate) const CONFIG_KEYS: [&str; 42] = [
+    "$schema",
+    "name",
+    "extends",
+    "enabled",
+    "tags",
+    "perFile",
+    "include",
+    "variants",
+    "baseArgs",
+    "baseArgsOptional",
+    "argSets",
+    "args",
+    "argsString",
+    "argsFromCommand",
+    "timeoutMs",
+    "arrayMerge",
+    "attach",
+    "coreDump",
+    "cargo",
+    "container",
+    "envFile",
+    "program",
+    "cwd",
+    "preLaunchTask",
+    "postDebugTask",
+    "console",
+    "externalConsole",
+    "justMyCode",
+    "stopAtEntry",
+    "MIMode",
+    "miDebuggerPath",
+    "launchAfter",
+    "unset",
+    "group",
+    "order",
+    "hidden",
+    "env",
+    "presentation",
+    "sourceFileMap",
+    "windows",
+    "linux",
+    "osx",
+];
+
+/// Config keys that replace or extend template values, as named in `allowOverrides`
+pub(crate) const OVERRIDE_KEYS: [&str; 23] = [
+    "program",
+    "cwd",
+    "preLaunchTask",
+    "postDebugTask",
+    "console",
+    "externalConsole",
+    "justMyCode",
+    "stopAtEntry",
+    "MIMode",
+    "miDebuggerPath",
+    "unset",
+    "envFile",
+    "attach",
+    "coreDump",
+    "cargo",
+    "container",
+    "arrayMerge",
+    "env",
+    "presentation",
+    "sourceFileMap",
+    "windows",
+    "linux",
+    "osx",
+];
+
+/// Config keys that carry settings rather than identify the entry
+fn setting_keys() -> impl Iterator<Item = &'static str> {
+    CONFIG_KEYS.into_iter().filter(|k| {
+        !matches!(
+            *k,
+            "$schema" | "name" | "extends" | "enabled" | "tags" | "include" | "variants"
+        )
+    })
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// `deserialize_with` helper telling an explicit `null` (`Some(None)`) from a missing key (`None`)
+fn deserialize_nullable<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    d: D,
+) -> Result<Option<Option<T>>, D::Error> {
+    Option::<T>::deserialize(d).map(Some)
+}
+
+/// Keys a variant's `overrides` cannot replace
+const NOT_OVERRIDABLE: [&str; 3] = ["name", "include", "variants"];
+
+/// Splits an entry into itself and one copy per item of its `variants`, each named with the
+/// item's `nameSuffix` appended and with the keys of its `overrides` replaced
+fn expand_variants(mut value: Value, entry: &RawEntry) -> Result<Vec<Value>> {
+    let Some(variants) = value.as_object_mut().and_then(|o| o.remove("variants")) else {
+        return Ok(vec![value]);
+    };
+    let path = &entry.path;
+    let pointer = format!("{}/variants", entry.pointer);
+    let Value::Array(variants) = variants else {
+        return Err(Diagnostic::at_pointer(
+            path,
+            &pointer,
+            "'variants' must be an array of { \"nameSuffix\", \"overrides\" } objects",
+        )
+        .into());
+    };
+
+    let mut values = vec![value.clone()];
+    for (idx, variant) in variants.into_iter().enumerate() {
+        let pointer = format!("{}/{}", pointer, idx);
+        let Value::Object(mut variant) = variant else {
+            return Err(
+                Diagnostic::at_pointer(path, &pointer, "A variant must be an object").into(),
+            );
+        };
+        let suffix = match variant.remove("nameSuffix") {
+            Some(Value::String(suffix)) if !suffix.is_empty() => suffix,
+            _ => {
+                return Err(Diagnostic::at_pointer(
+                    path,
+                    &pointer,
+                    "A variant needs a non-empty 'nameSuffix' string",
+                )
+                .into());
+            }
+        };
+        let overrides = match variant.remove("overrides") {
+            None => Map::new(),
+            Some(Value::Object(overrides)) => overrides,
+            Some(_) => {
+                return Err(Diagnostic::at_pointer(
+                    path,
+                    &format!("{}/overrides", pointer),
+                    "'overrides' must be an object of config entry keys",
+                )
+                .into());
+            }
+        };
+        if let Some(key) = variant.keys().next() {
+            return Err(Diagnostic::at_pointer(
+                path,
+                &format!("{}/{}", pointer, key),
+                format!(
+                    "Unknown key '{}' in variant; expected nameSuffix or overrides",
+                    key
+                ),
+            )
+            .into());
+        }
+        if let Some(key) = overrides
+            .keys()
+            .find(|k| NOT_OVERRIDABLE.contains(&k.as_str()))
+        {
+            return Err(Diagnostic::at_pointer(
+                path,
+                &format!("{}/overrides/{}", pointer, key),
+                format!("A variant cannot override '{}'", key),
+            )
+            .into());
+        }
+
+        let mut copy = value.clone();
+        if let Value::Object(object) = &mut copy {
+            if let Some(Value::String(name)) = object.get_mut("name") {
+                name.push_str(&suffix);
+            }
+            object.extend(overrides);
+        }
+        values.push(copy);
+    }
+    Ok(values)
+}
+
+/// Closest of `candidates` to a mistyped `key`: a case-insensitive match or one at most
+/// two single-character edits away
+pub(crate) fn suggest<'a>(key: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    if let Some(found) = candidates.iter().find(|c| c.eq_ignore_ascii_case(key)) {
+        return Some(found);
+    }
+    candidates
+        .iter()
+        .map(|c| (edit_distance(key, c), *c))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
+
+/// Levenshtein distance between `a` and `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Checks the optional `schemaVersion` of a templates manifest or config file object
+fn check_schema_version(object: &Map<String, Value>, path: &Path) -> Result<()> {
+    let Some(value) = object.get("schemaVersion") else {
+        return Ok(());
+    };
+    match value.as_u64() {
+        Some(version) if (1..=SCHEMA_VERSION).contains(&version) => Ok(()),
+        Some(version) if version > SCHEMA_VERSION => Err(Diagnostic::at_pointer(
+            path,
+            "/schemaVersion",
+            format!(
+                "schemaVersion {} is newer than this mklaunch supports ({}); upgrade mklaunch",
+                version, SCHEMA_VERSION
+            ),
+        )
+        .into()),
+        _ => Err(Diagnostic::at_pointer(
+            path,
+            "/schemaVersion",
+            format!(
+                "schemaVersion must be an integer from 1 to {}",
+                SCHEMA_VERSION
+            ),
+        )
+        .into()),
+    }
+}
 
 /// Base arguments file structure: { "args": ["..."] }
 #[derive(Debug, Deserialize)]
 pub(crate) struct BaseArgsFile {
+    #[serde(deserialize_with = "deserialize_args")]
     pub args: Vec<String>,
 }
 
 impl BaseArgsFile {
     pub fn from_path(path: &Path) -> Result<Self> {
-        let content = fs::read_to_string(path)
+        let content = read_text(path)
             .with_context(|| format!("Failed to read baseArgs file: {}", path.display()))?;
         let parsed: BaseArgsFile = serde_json::from_str(&content)
             .with_context(|| format!("Failed to parse baseArgs JSON: {}", path.display()))?;
@@ -21,38 +279,533 @@ impl BaseArgsFile {
     }
 }
 
+/// Dotenv file contents as ordered `(name, value)` pairs
+#[derive(Debug, Default)]
+pub(crate) struct EnvFile {
+    pub vars: Vec<(String, String)>,
+}
+
+impl EnvFile {
+    /// Parses `KEY=VALUE` lines; blank lines, `#` comments and an `export ` prefix are ignored
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let content = read_text(path)
+            .with_context(|| format!("Failed to read envFile: {}", path.display()))?;
+
+        let mut vars = Vec::new();
+        for (idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Invalid envFile line {} in {}: expected KEY=VALUE",
+                    idx + 1,
+                    path.display()
+                )
+            })?;
+            let value = value.trim();
+            let value = ['"', '\'']
+                .iter()
+                .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+                .unwrap_or(value);
+            vars.push((key.trim().to_string(), value.to_string()));
+        }
+        Ok(Self { vars })
+    }
+}
+
+/// Library of named argument sets loaded from `<dir>/*.json`, keyed by file stem
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ArgSetLibrary {
+    dir: PathBuf,
+    sets: BTreeMap<String, Vec<String>>,
+    files: Vec<PathBuf>,
+}
+
+impl ArgSetLibrary {
+    /// Loads every arg set in `dir`. A missing directory yields an empty library.
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        let mut sets = BTreeMap::new();
+        let mut files = Vec::new();
+        if dir.exists() {
+            for entry in fs::read_dir(dir)
+                .with_context(|| format!("Failed to read args directory: {}", dir.display()))?
+            {
+                let path = entry?.path();
+                if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("json") {
+                    continue;
+                }
+                let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                sets.insert(name.to_string(), BaseArgsFile::from_path(&path)?.args);
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            sets,
+            files,
+        })
+    }
+
+    /// Arg set files in path order
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    pub fn get(&self, name: &str) -> Result<&[String]> {
+        self.sets.get(name).map(Vec::as_slice).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Arg set '{}' not found in args directory: {}",
+                name,
+                self.dir.display()
+            )
+        })
+    }
+}
+
+/// Keys merged into every configuration unless its template sets them, loaded from
+/// `defaults.json` next to the templates manifest
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Defaults {
+    pub path: PathBuf,
+    pub values: Map<String, Value>,
+}
+
+impl Defaults {
+    /// Loads a defaults file; a missing file yields no defaults
+    pub fn from_path(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self {
+                path: path.to_path_buf(),
+                values: Map::new(),
+            });
+        }
+        let content = read_text(path)
+            .with_context(|| format!("Failed to read defaults file: {}", path.display()))?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|e| Diagnostic::from_json_error(path, &e))
+            .context("Failed to parse defaults file")?;
+        let Value::Object(mut values) = value else {
+            return Err(Diagnostic::at_pointer(path, "", "Defaults must be a JSON object").into());
+        };
+        values.remove("$schema");
+        for key in ["type", "name", "args"] {
+            if values.contains_key(key) {
+                return Err(Diagnostic::at_pointer(
+                    path,
+                    &format!("/{}", key),
+                    format!("Defaults must not set '{}'", key),
+                )
+                .into());
+            }
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            values,
+        })
+    }
+}
+
+/// Configuration names (or `*`/`?` globs) to disable without editing their config files,
+/// loaded from `disabled.json` next to the templates manifest
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DisabledList {
+    patterns: Vec<String>,
+}
+
+impl DisabledList {
+    /// Loads a disabled list; a missing file disables nothing
+    pub fn from_path(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = read_text(path)
+            .with_context(|| format!("Failed to read disabled list: {}", path.display()))?;
+        let patterns: Vec<String> = serde_json::from_str(&content)
+            .map_err(|e| Diagnostic::from_json_error(path, &e))
+            .context("Failed to parse disabled list; expected an array of configuration names")?;
+        Ok(Self { patterns })
+    }
+
+    /// Turns `config` off when its name (as written, before token expansion) matches
+    pub fn apply(&self, config: &mut ConfigFile) {
+        if self.patterns.iter().any(|p| glob_match(p, &config.name)) {
+            config.enabled = false;
+        }
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters and `?`
+/// any single character
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    // Position after the last `*` seen, and the text position it was tried at
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// How config values are combined with a template array, e.g. `{ "append": [...] }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArrayMerge {
+    /// Template items followed by these values
+    Append(Vec<Value>),
+    /// These values followed by template items
+    Prepend(Vec<Value>),
+    /// These values instead of the template items
+    Replace(Vec<Value>),
+}
+
+/// Base directory for a relative `envFile` path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvFileBase {
+    /// Relative to the workspace folder (the current directory)
+    #[default]
+    Workspace,
+    /// Relative to the directory of the config file declaring it
+    Config,
+}
+
+/// `envFile` value: a path string, or `{ "path": "...", "relativeTo": "config" }`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnvFileSpec {
+    Path(PathBuf),
+    Detailed {
+        path: PathBuf,
+        #[serde(rename = "relativeTo", default)]
+        relative_to: EnvFileBase,
+    },
+}
+
+impl EnvFileSpec {
+    /// Path as written; relative paths are relative to the workspace once loaded
+    pub fn path(&self) -> &Path {
+        match self {
+            EnvFileSpec::Path(path) | EnvFileSpec::Detailed { path, .. } => path,
+        }
+    }
+}
+
+/// `launchAfter` value: the configuration to start first, by name, or
+/// `{ "name": "...", "pattern": "..." }` to start this one once the other's output matches
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LaunchAfter {
+    Name(String),
+    Ready { name: String, pattern: String },
+}
+
+impl LaunchAfter {
+    /// Name of the configuration started first
+    pub fn name(&self) -> &str {
+        match self {
+            LaunchAfter::Name(name) | LaunchAfter::Ready { name, .. } => name,
+        }
+    }
+}
+
+/// Process selection for `request: "attach"` configurations
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttachOptions {
+    /// Process name to attach to (used by debuggers that can attach by name)
+    #[serde(rename = "processName", skip_serializing_if = "Option::is_none")]
+    pub process_name: Option<String>,
+    /// Fixed process id; when omitted a process picker is generated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u64>,
+}
+
+/// Source path mapping between the workspace and a container, written in the form the
+/// template's debugger type expects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContainerMapping {
+    /// Source root on this machine [default: `${workspaceFolder}`]
+    #[serde(rename = "localRoot", default = "workspace_folder")]
+    pub local_root: String,
+    /// Source root inside the container, as the debuggee's debug info records it
+    #[serde(rename = "remoteRoot")]
+    pub remote_root: String,
+}
+
+/// `console` value: where the debuggee's input and output go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Console {
+    /// VSCode's Debug Console
+    InternalConsole,
+    /// VSCode's integrated terminal
+    IntegratedTerminal,
+    /// A terminal window outside VSCode
+    ExternalTerminal,
+}
+
+/// `MIMode` value: the debugger cppdbg drives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MiMode {
+    Gdb,
+    Lldb,
+}
+
+fn workspace_folder() -> String {
+    "${workspaceFolder}".to_string()
+}
+
 /// Individual configuration entry with template reference and overrides
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ConfigFile {
     /// Unique configuration name displayed in VSCode
     pub name: String,
+    /// JSON pointer of the entry in its config file, for error messages
+    #[serde(skip)]
+    pub(crate) pointer: String,
     /// Template name to extend (without .json extension)
     pub extends: String,
     /// Whether this configuration is enabled
     pub enabled: bool,
+    /// Free-form labels for `mklaunch list` and `--tag` filtering
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Debugs whichever file is open in the editor through `${file}` and related variables;
+    /// skips program checks and duplicate detection and is listed after the other configurations
+    #[serde(rename = "perFile", default, skip_serializing_if = "is_false")]
+    pub per_file: bool,
     /// Optional path to a JSON file containing base args, e.g., { "args": ["..."] }
-    #[serde(rename = "baseArgs")]
+    #[serde(rename = "baseArgs", skip_serializing_if = "Option::is_none")]
     pub base_args: Option<PathBuf>,
-    /// Additional args to append after base args
+    /// Treat a missing `baseArgs` file as an empty arg list instead of an error
+    #[serde(rename = "baseArgsOptional", default, skip_serializing_if = "is_false")]
+    pub base_args_optional: bool,
+    /// Named arg sets from the args library, appended after base args in order
+    #[serde(rename = "argSets", skip_serializing_if = "Option::is_none")]
+    pub arg_sets: Option<Vec<String>>,
+    /// Additional args to append after base args and arg sets.
+    /// Numbers, booleans and `{ "flag", "value" }` objects are normalized to strings.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_args",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub args: Option<Vec<String>>,
+    /// `args` written as one command line, split with shell quoting rules
+    #[serde(rename = "argsString", skip_serializing_if = "Option::is_none")]
+    pub args_string: Option<String>,
+    /// Shell command whose output is appended to args; runs only with `--allow-exec`
+    #[serde(rename = "argsFromCommand", skip_serializing_if = "Option::is_none")]
+    pub args_from_command: Option<String>,
+    /// Milliseconds the entry's `argsFromCommand` and cargo build may run before they are killed
+    #[serde(rename = "timeoutMs", skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Per-key merge of array values into template arrays such as `skipFiles`
+    #[serde(rename = "arrayMerge", skip_serializing_if = "Option::is_none")]
+    pub array_merge: Option<BTreeMap<String, ArrayMerge>>,
+    /// Turns the configuration into an attach request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attach: Option<AttachOptions>,
+    /// Core file for post-mortem debugging; a `*`/`?` pattern in the file name picks the
+    /// most recently modified match at generation time
+    #[serde(rename = "coreDump", skip_serializing_if = "Option::is_none")]
+    pub core_dump: Option<String>,
+    /// Cargo target built at generation time whose executable becomes `program`, or
+    /// CodeLLDB's own `cargo` launch block (with `args`), merged into the template's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cargo: Option<CargoBlock>,
+    /// Source path mapping for a debuggee running in a container
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerMapping>,
+    /// Dotenv file passed through as `envFile`, or expanded into `environment` for cppdbg
+    #[serde(rename = "envFile", skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<EnvFileSpec>,
+    /// Program to debug, replacing the template's `program`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program: Option<String>,
+    /// Working directory, replacing the template's `cwd`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Task run before debugging, replacing the template's; `Some(None)` (JSON `null`) removes it
+    #[serde(
+        rename = "preLaunchTask",
+        default,
+        deserialize_with = "deserialize_nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pre_launch_task: Option<Option<String>>,
+    /// Task run after debugging, replacing the template's; `Some(None)` (JSON `null`) removes it
+    #[serde(
+        rename = "postDebugTask",
+        default,
+        deserialize_with = "deserialize_nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub post_debug_task: Option<Option<String>>,
+    /// Where the debuggee's input and output go, written in the debugger type's terms
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub console: Option<Console>,
+    /// Runs the debuggee in an external terminal (`true`) or the integrated one (`false`);
+    /// shorthand for `console`
+    #[serde(rename = "externalConsole", skip_serializing_if = "Option::is_none")]
+    pub external_console: Option<bool>,
+    /// Steps into user code only (debugpy and coreclr)
+    #[serde(rename = "justMyCode", skip_serializing_if = "Option::is_none")]
+    pub just_my_code: Option<bool>,
+    /// Stops at the program's entry point (cppdbg and cppvsdbg)
+    #[serde(rename = "stopAtEntry", skip_serializing_if = "Option::is_none")]
+    pub stop_at_entry: Option<bool>,
+    /// Debugger cppdbg drives
+    #[serde(rename = "MIMode", skip_serializing_if = "Option::is_none")]
+    pub mi_mode: Option<MiMode>,
+    /// Path of the gdb or lldb executable cppdbg runs, replacing the template's
+    #[serde(rename = "miDebuggerPath", skip_serializing_if = "Option::is_none")]
+    pub mi_debugger_path: Option<String>,
+    /// Configuration that must be running before this one, joined in a compound or
+    /// started through its `serverReadyAction`
+    #[serde(rename = "launchAfter", skip_serializing_if = "Option::is_none")]
+    pub launch_after: Option<LaunchAfter>,
+    /// Keys inherited from the template (or the defaults file) to leave out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unset: Option<Vec<String>>,
+    /// Picker group, written to `presentation.group`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Position within the picker group, written to `presentation.order`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<i64>,
+    /// Hides the configuration from the picker, written to `presentation.hidden`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hidden: Option<bool>,
+    /// Environment variables, merged key by key into the template's `env`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<Map<String, Value>>,
+    /// Picker presentation, merged key by key into the template's `presentation`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation: Option<Map<String, Value>>,
+    /// Source path mapping, merged key by key into the template's `sourceFileMap`
+    #[serde(rename = "sourceFileMap", skip_serializing_if = "Option::is_none")]
+    pub source_file_map: Option<Map<String, Value>>,
+    /// Windows override block, merged over the template's block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windows: Option<Map<String, Value>>,
+    /// Linux override block, merged over the template's block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linux: Option<Map<String, Value>>,
+    /// macOS override block, merged over the template's block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub osx: Option<Map<String, Value>>,
 }
 
 impl ConfigFile {
     /// Loads and validates configuration entries from a path. Returns one entry per JSON object.
+    /// `include`s may name other entries of the same file or fragment files.
     pub fn from_path(config_path: &Path) -> Result<Vec<Self>> {
-        let content = fs::read_to_string(config_path)
+        let entries = Self::read_entries(config_path, &mut Vec::new())?;
+        let resolved = resolve_includes(&entries)?;
+        resolved
+            .into_iter()
+            .zip(&entries)
+            .map(|(value, entry)| Self::from_entry(value, entry, false))
+            .collect::<Result<Vec<_>>>()
+            .map(|configs| configs.into_iter().flatten().collect())
+    }
+
+    /// Text of a config file holding `configs`, laid out the way `mklaunch fmt` writes it.
+    /// Keys left at their default are omitted; entries loaded with `from_path` are written
+    /// with their `envFile` relative to the workspace.
+    pub fn to_file_string(configs: &[ConfigFile]) -> Result<String> {
+        let value = serde_json::to_value(configs).context("Failed to serialize config entries")?;
+        Ok(format_config_file(&value))
+    }
+
+    /// Writes `configs` to `config_path` as a config file, replacing it atomically
+    pub fn write_file(config_path: &Path, configs: &[ConfigFile]) -> Result<()> {
+        let content = Self::to_file_string(configs)?;
+        write_atomic(config_path, content.as_bytes())
+            .with_context(|| format!("Failed to write config file: {}", config_path.display()))
+    }
+
+    /// Reads the raw entries of a config file: a JSON array of entries, or an object with
+    /// `schemaVersion` and a `configurations` array. Legacy single-object files
+    /// (schema version 1) are read as one entry, with an upgrade message in `warnings`.
+    pub(crate) fn read_entries(
+        config_path: &Path,
+        warnings: &mut Vec<String>,
+    ) -> Result<Vec<RawEntry>> {
+        let content = read_text(config_path)
             .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
 
+        if is_table(config_path) {
+            return Ok(read_table(config_path, &content)?
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| RawEntry {
+                    path: config_path.to_path_buf(),
+                    index,
+                    pointer: format!("/{}", index),
+                    value,
+                })
+                .collect());
+        }
+
         let raw: Value = serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse config JSON: {}", config_path.display()))?;
+            .map_err(|e| Diagnostic::from_json_error(config_path, &e))
+            .context("Failed to parse config JSON")?;
 
-        let entries = match raw {
-            Value::Array(items) => items,
-            Value::Object(_) => {
-                anyhow::bail!(
-                    "{} must be a JSON array of configuration objects. Legacy single-object configs are no longer supported.",
+        let (entries, pointer) = match raw {
+            Value::Array(items) => (items, ""),
+            Value::Object(mut object) if object.contains_key("configurations") => {
+                check_schema_version(&object, config_path)?;
+                match object.remove("configurations") {
+                    Some(Value::Array(items)) => (items, "/configurations"),
+                    _ => {
+                        return Err(Diagnostic::at_pointer(
+                            config_path,
+                            "/configurations",
+                            "'configurations' must be an array of configuration objects",
+                        )
+                        .into());
+                    }
+                }
+            }
+            Value::Object(object) => {
+                warnings.push(format!(
+                    "{} uses the legacy single-object format (schemaVersion 1); run `mklaunch migrate` or wrap the object in an array",
                     config_path.display()
-                );
+                ));
+                return Ok(vec![RawEntry {
+                    path: config_path.to_path_buf(),
+                    index: 0,
+                    pointer: String::new(),
+                    value: Value::Object(object),
+                }]);
             }
             other => {
                 let type_name = match other {
@@ -71,31 +824,198 @@ impl ConfigFile {
             }
         };
 
-        entries
+        Ok(entries
             .into_iter()
             .enumerate()
-            .map(|(idx, entry)| -> Result<_> {
-                let config: ConfigFile = serde_json::from_value(entry).with_context(|| {
-                    format!(
-                        "Failed to parse config JSON entry at index {} in {}",
-                        idx,
-                        config_path.display()
-                    )
-                })?;
-
-                config.validate_extends(config_path)?;
-                Ok(config)
+            .map(|(index, value)| RawEntry {
+                path: config_path.to_path_buf(),
+                index,
+                pointer: format!("{}/{}", pointer, index),
+                value,
             })
+            .collect())
+    }
+
+    /// Parses and validates an entry (with includes already merged) into the entry itself
+    /// followed by one configuration per item of its `variants`.
+    /// With `strict`, keys mklaunch does not know are errors instead of being ignored.
+    pub(crate) fn from_entry(value: Value, entry: &RawEntry, strict: bool) -> Result<Vec<Self>> {
+        expand_variants(value, entry)?
+            .into_iter()
+            .map(|value| Self::from_value(value, entry, strict))
             .collect()
     }
 
-    fn validate_extends(&self, config_path: &Path) -> Result<()> {
+    fn from_value(value: Value, entry: &RawEntry, strict: bool) -> Result<Self> {
+        let config_path = &entry.path;
+        if strict && let Value::Object(object) = &value {
+            for key in object.keys() {
+                if CONFIG_KEYS.contains(&key.as_str()) {
+                    continue;
+                }
+                let hint = match suggest(key, &CONFIG_KEYS) {
+                    Some(known) => format!("; did you mean '{}'?", known),
+                    None => String::new(),
+                };
+                return Err(Diagnostic::at_pointer(
+                    config_path,
+                    &format!("{}/{}", entry.pointer, key),
+                    format!("Unknown key '{}' in configuration entry{}", key, hint),
+                )
+                .into());
+            }
+        }
+        let mut config: ConfigFile = serde_json::from_value(value)
+            .map_err(|e| {
+                Diagnostic::from_value_error::<ConfigFile>(config_path, &entry.pointer, &e)
+            })
+            .with_context(|| {
+                format!("Failed to parse config JSON entry at index {}", entry.index)
+            })?;
+
+        config.pointer = entry.pointer.clone();
+        config.validate_name(config_path, &entry.pointer)?;
+        config.validate_extends(config_path, &entry.pointer)?;
+        config.validate_arg_sets(config_path, &entry.pointer)?;
+        config.validate_array_merge(config_path, &entry.pointer)?;
+        config.resolve_env_file(config_path);
+        Ok(config)
+    }
+
+    fn validate_name(&self, config_path: &Path, pointer: &str) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Diagnostic::at_pointer(
+                config_path,
+                &format!("{}/name", pointer),
+                "Configuration name must not be empty or whitespace only",
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    fn validate_extends(&self, config_path: &Path, pointer: &str) -> Result<()> {
         if self.extends.contains('/') || self.extends.contains('\\') {
-            anyhow::bail!(
-                "Invalid extends value '{}' in {}\nOnly template names are allowed (e.g., 'cpp', 'lldb')",
-                self.extends,
-                config_path.display()
-            );
+            return Err(Diagnostic::at_pointer(
+                config_path,
+                &format!("{}/extends", pointer),
+                format!(
+                    "Invalid extends value '{}'\nOnly template names are allowed (e.g., 'cpp', 'lldb')",
+                    self.extends
+                ),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Setting keys present on this entry, in `CONFIG_KEYS` order
+    pub(crate) fn provided_keys(&self) -> Vec<&'static str> {
+        let present = |key: &str| match key {
+            "perFile" => self.per_file,
+            "baseArgs" => self.base_args.is_some(),
+            "baseArgsOptional" => self.base_args_optional,
+            "argSets" => self.arg_sets.is_some(),
+            "args" => self.args.is_some(),
+            "argsString" => self.args_string.is_some(),
+            "argsFromCommand" => self.args_from_command.is_some(),
+            "timeoutMs" => self.timeout_ms.is_some(),
+            "arrayMerge" => self.array_merge.is_some(),
+            "attach" => self.attach.is_some(),
+            "coreDump" => self.core_dump.is_some(),
+            "cargo" => self.cargo.is_some(),
+            "container" => self.container.is_some(),
+            "envFile" => self.env_file.is_some(),
+            "program" => self.program.is_some(),
+            "cwd" => self.cwd.is_some(),
+            "preLaunchTask" => self.pre_launch_task.is_some(),
+            "postDebugTask" => self.post_debug_task.is_some(),
+            "console" => self.console.is_some(),
+            "externalConsole" => self.external_console.is_some(),
+            "justMyCode" => self.just_my_code.is_some(),
+            "stopAtEntry" => self.stop_at_entry.is_some(),
+            "MIMode" => self.mi_mode.is_some(),
+            "miDebuggerPath" => self.mi_debugger_path.is_some(),
+            "launchAfter" => self.launch_after.is_some(),
+            "unset" => self.unset.is_some(),
+            "group" => self.group.is_some(),
+            "order" => self.order.is_some(),
+            "hidden" => self.hidden.is_some(),
+            "env" => self.env.is_some(),
+            "presentation" => self.presentation.is_some(),
+            "sourceFileMap" => self.source_file_map.is_some(),
+            "windows" => self.windows.is_some(),
+            "linux" => self.linux.is_some(),
+            "osx" => self.osx.is_some(),
+            _ => false,
+        };
+        setting_keys().filter(|k| present(k)).collect()
+    }
+
+    /// Object values this entry merges into the template's, keyed by `env`/`presentation`/
+    /// `sourceFileMap`
+    pub fn object_overrides(&self) -> impl Iterator<Item = (&'static str, &Map<String, Value>)> {
+        ["env", "presentation", "sourceFileMap"]
+            .into_iter()
+            .zip([&self.env, &self.presentation, &self.source_file_map])
+            .filter_map(|(key, object)| object.as_ref().map(|o| (key, o)))
+    }
+
+    /// Override blocks declared by this entry, keyed by `windows`/`linux`/`osx`
+    pub fn os_blocks(&self) -> impl Iterator<Item = (&'static str, &Map<String, Value>)> {
+        OS_KEYS
+            .into_iter()
+            .zip([&self.windows, &self.linux, &self.osx])
+            .filter_map(|(key, block)| block.as_ref().map(|b| (key, b)))
+    }
+
+    /// Rebases a config-relative `envFile` onto the config file's directory
+    fn resolve_env_file(&mut self, config_path: &Path) {
+        if let Some(EnvFileSpec::Detailed {
+            path,
+            relative_to: EnvFileBase::Config,
+        }) = &self.env_file
+            && path.is_relative()
+            && !path.starts_with("${workspaceFolder}")
+        {
+            let base = config_path.parent().unwrap_or(Path::new(""));
+            self.env_file = Some(EnvFileSpec::Path(base.join(path)));
+        }
+    }
+
+    fn validate_array_merge(&self, config_path: &Path, pointer: &str) -> Result<()> {
+        for key in self.array_merge.iter().flat_map(|m| m.keys()) {
+            if matches!(
+                key.as_str(),
+                "type" | "request" | "name" | "program" | "stopAtEntry" | "args"
+            ) {
+                return Err(Diagnostic::at_pointer(
+                    config_path,
+                    &format!("{}/arrayMerge/{}", pointer, key),
+                    format!(
+                        "arrayMerge cannot target '{}'\nUse 'args'/'baseArgs' for arguments; other reserved keys are not arrays",
+                        key
+                    ),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_arg_sets(&self, config_path: &Path, pointer: &str) -> Result<()> {
+        for (i, name) in self.arg_sets.iter().flatten().enumerate() {
+            if name.contains('/') || name.contains('\\') {
+                return Err(Diagnostic::at_pointer(
+                    config_path,
+                    &format!("{}/argSets/{}", pointer, i),
+                    format!(
+                        "Invalid argSets value '{}'\nOnly arg set names are allowed (e.g., 'common')",
+                        name
+                    ),
+                )
+                .into());
+            }
         }
         Ok(())
     }
@@ -108,7 +1028,14 @@ pub(crate) struct Template {
     pub request: Option<String>,
     pub program: Option<String>,
     pub stop_at_entry: Option<bool>,
-    pub rest: Map<String, Value>,
+    /// Config keys (from `OVERRIDE_KEYS`) entries may use; `None` allows all
+    pub allow_overrides: Option<Vec<String>>,
+    /// Config keys every entry extending this template must set
+    pub require_from_config: Vec<String>,
+    /// Other keys, shared with every configuration resolved from the template
+    pub rest: Arc<Map<String, Value>>,
+    /// Key order as written in the manifest, when read from one
+    pub layout: Option<Arc<KeyLayout>>,
 }
 
 impl Template {
@@ -123,6 +1050,14 @@ impl Template {
             anyhow::bail!("Template must not define 'args'; use config files to set args");
         }
 
+        for key in OS_KEYS {
+            if let Some(block) = template_obj.get(key)
+                && !block.is_object()
+            {
+                anyhow::bail!("Template '{}' override must be a JSON object", key);
+            }
+        }
+
         let type_field = template_obj
             .get("type")
             .and_then(|v| v.as_str())
@@ -141,41 +1076,405 @@ impl Template {
 
         let stop_at_entry = template_obj.get("stopAtEntry").and_then(|v| v.as_bool());
 
+        let allow_overrides = template_obj
+            .get("allowOverrides")
+            .map(|v| key_list(v, "allowOverrides", &OVERRIDE_KEYS))
+            .transpose()?;
+        let settings: Vec<&str> = setting_keys().collect();
+        let require_from_config = template_obj
+            .get("requireFromConfig")
+            .map(|v| key_list(v, "requireFromConfig", &settings))
+            .transpose()?
+            .unwrap_or_default();
+
         let mut rest: Map<String, Value> = Map::with_capacity(template_obj.len());
         for (k, v) in template_obj.iter() {
-            if k == "type" || k == "request" || k == "program" || k == "stopAtEntry" {
+            if matches!(
+                k.as_str(),
+                "type"
+                    | "request"
+                    | "program"
+                    | "stopAtEntry"
+                    | "allowOverrides"
+                    | "requireFromConfig"
+                    | "rust"
+            ) {
                 continue;
             }
             rest.insert(k.clone(), v.clone());
         }
+        if let Some(rust) = template_obj.get("rust") {
+            for (key, value) in rust_settings(rust, &type_field, program.is_some())? {
+                rest.entry(key).or_insert(value);
+            }
+        }
+        if let Some(cargo) = rest.get("cargo") {
+            if type_field != "lldb" {
+                anyhow::bail!(
+                    "'cargo' only applies to CodeLLDB templates (type \"lldb\"), not '{}'",
+                    type_field
+                );
+            }
+            if program.is_some() {
+                anyhow::bail!(
+                    "Template sets both 'program' and 'cargo'; CodeLLDB debugs the executable cargo builds"
+                );
+            }
+            LldbCargo::check_template(cargo)?;
+        }
+        check_adapter_keys(&mut rest)?;
+        for key in OS_KEYS {
+            if let Some(Value::Object(block)) = rest.get_mut(key) {
+                check_adapter_keys(block).with_context(|| format!("In the '{}' block", key))?;
+            }
+        }
 
         Ok(Self {
             type_field,
             request,
             program,
             stop_at_entry,
-            rest,
+            allow_overrides,
+            require_from_config,
+            rest: Arc::new(rest),
+            layout: None,
         })
     }
+
+    /// Checks `config` against the template's `allowOverrides` and `requireFromConfig`
+    pub fn check_contract(&self, config: &ConfigFile, template_label: &str) -> Result<()> {
+        let provided = config.provided_keys();
+        if let Some(allowed) = &self.allow_overrides
+            && let Some(key) = provided
+                .iter()
+                .find(|k| OVERRIDE_KEYS.contains(k) && !allowed.iter().any(|a| a == *k))
+        {
+            anyhow::bail!(
+                "'{}' sets '{}', which {} does not allow (allowOverrides: [{}])",
+                config.name,
+                key,
+                template_label,
+                allowed.join(", ")
+            );
+        }
+        let missing: Vec<&str> = self
+            .require_from_config
+            .iter()
+            .map(String::as_str)
+            .filter(|k| !provided.contains(k))
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "'{}' must set {} required by {} (requireFromConfig)",
+                config.name,
+                missing.join(", "),
+                template_label
+            );
+        }
+        Ok(())
+    }
 }
 
-/// Manifest containing multiple templates indexed by name
+/// CodeLLDB settings implied by a template's `rust` helper: `true`, or an object naming
+/// the cargo `bin` and `package` to build. Keys the template sets itself win; `cargo`
+/// is only added when the template has no `program`.
+fn rust_settings(value: &Value, type_field: &str, has_program: bool) -> Result<Map<String, Value>> {
+    let (bin, package) = match value {
+        Value::Bool(false) => return Ok(Map::new()),
+        Value::Bool(true) => (None, None),
+        Value::Object(obj) => {
+            if let Some(key) = obj
+                .keys()
+                .find(|k| !matches!(k.as_str(), "bin" | "package"))
+            {
+                anyhow::bail!(
+                    "Unknown key '{}' in 'rust'; expected 'bin' or 'package'",
+                    key
+                );
+            }
+            let field = |key: &str| match obj.get(key) {
+                None => Ok(None),
+                Some(Value::String(s)) => Ok(Some(s.as_str())),
+                Some(_) => Err(anyhow::anyhow!("'rust.{}' must be a string", key)),
+            };
+            (field("bin")?, field("package")?)
+        }
+        _ => anyhow::bail!("'rust' must be true or an object with 'bin' and 'package'"),
+    };
+    if type_field != "lldb" {
+        anyhow::bail!(
+            "'rust' only applies to CodeLLDB templates (type \"lldb\"), not '{}'",
+            type_field
+        );
+    }
+
+    let mut settings = Map::new();
+    settings.insert("sourceLanguages".to_string(), json!(["rust"]));
+    if !has_program {
+        let mut args = vec!["build".to_string()];
+        args.extend(bin.map(|bin| format!("--bin={}", bin)));
+        args.extend(package.map(|package| format!("--package={}", package)));
+        let mut cargo = json!({ "args": args });
+        if let Some(bin) = bin {
+            cargo["filter"] = json!({ "name": bin, "kind": "bin" });
+        }
+        settings.insert("cargo".to_string(), cargo);
+    }
+    Ok(settings)
+}
+
+/// `action` values of a `serverReadyAction`
+const SERVER_READY_ACTIONS: [&str; 4] = [
+    "openExternally",
+    "debugWithChrome",
+    "debugWithEdge",
+    "startDebugging",
+];
+
+/// Keys of a `serverReadyAction`
+const SERVER_READY_KEYS: [&str; 7] = [
+    "action",
+    "pattern",
+    "uriFormat",
+    "webRoot",
+    "name",
+    "config",
+    "killOnServerStop",
+];
+
+/// Checks and normalizes the adapter keys VSCode ignores silently when malformed:
+/// `serverReadyAction` must be a well-formed block and `debugServer` a port number, which
+/// may be written as a string of digits
+fn check_adapter_keys(object: &mut Map<String, Value>) -> Result<()> {
+    if let Some(action) = object.get("serverReadyAction") {
+        check_server_ready_action(action)?;
+    }
+    if let Some(port) = object.get_mut("debugServer") {
+        *port = debug_server_port(port)?;
+    }
+    Ok(())
+}
+
+fn check_server_ready_action(value: &Value) -> Result<()> {
+    let Value::Object(block) = value else {
+        anyhow::bail!("'serverReadyAction' must be an object");
+    };
+    if let Some(key) = block
+        .keys()
+        .find(|k| !SERVER_READY_KEYS.contains(&k.as_str()))
+    {
+        let hint = match suggest(key, &SERVER_READY_KEYS) {
+            Some(known) => format!("; did you mean '{}'?", known),
+            None => String::new(),
+        };
+        anyhow::bail!("Unknown key '{}' in serverReadyAction{}", key, hint);
+    }
+    for key in ["pattern", "uriFormat", "webRoot", "name"] {
+        if block.get(key).is_some_and(|v| !v.is_string()) {
+            anyhow::bail!("'serverReadyAction.{}' must be a string", key);
+        }
+    }
+    if block.get("config").is_some_and(|v| !v.is_object()) {
+        anyhow::bail!("'serverReadyAction.config' must be a launch configuration object");
+    }
+    if block
+        .get("killOnServerStop")
+        .is_some_and(|v| !v.is_boolean())
+    {
+        anyhow::bail!("'serverReadyAction.killOnServerStop' must be a boolean");
+    }
+
+    let action = match block.get("action") {
+        None => "openExternally",
+        Some(Value::String(action)) if SERVER_READY_ACTIONS.contains(&action.as_str()) => action,
+        Some(other) => anyhow::bail!(
+            "'serverReadyAction.action' must be one of {}, found {}",
+            SERVER_READY_ACTIONS.join(", "),
+            other
+        ),
+    };
+    if action == "startDebugging" && !block.contains_key("name") && !block.contains_key("config") {
+        anyhow::bail!("serverReadyAction 'startDebugging' needs the 'name' or 'config' to start");
+    }
+    let pattern = block.get("pattern").and_then(Value::as_str);
+    if pattern == Some("") {
+        anyhow::bail!("'serverReadyAction.pattern' must not be empty");
+    }
+    if let Some(uri_format) = block.get("uriFormat").and_then(Value::as_str) {
+        let slots = uri_format.matches("%s").count();
+        if slots > 1 {
+            anyhow::bail!("'serverReadyAction.uriFormat' may contain '%s' only once");
+        }
+        if slots == 1 && !pattern.is_none_or(has_capture_group) {
+            anyhow::bail!(
+                "'serverReadyAction.uriFormat' uses '%s', but pattern '{}' has no capture group to fill it",
+                pattern.unwrap_or_default()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Whether the regular expression `pattern` has a capturing group; the default pattern
+/// (`listening on port ([0-9]+)`) has one
+fn has_capture_group(pattern: &str) -> bool {
+    let bytes = pattern.as_bytes();
+    let mut in_class = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => in_class = true,
+            b']' => in_class = false,
+            b'(' if !in_class => {
+                let rest = &pattern[i + 1..];
+                let named = (rest.starts_with("?<") && !rest[2..].starts_with(['=', '!']))
+                    || rest.starts_with("?P<");
+                if !rest.starts_with('?') || named {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    false
+}
+
+/// `debugServer` as a port number
+fn debug_server_port(value: &Value) -> Result<Value> {
+    let port = match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse::<u64>().ok(),
+        _ => None,
+    };
+    match port {
+        Some(port @ 1..=65535) => Ok(Value::from(port)),
+        _ => anyhow::bail!(
+            "'debugServer' must be a port number between 1 and 65535, found {}",
+            value
+        ),
+    }
+}
+
+/// Parses a template's list of config keys, each of which must be one of `known`
+fn key_list(value: &Value, field: &str, known: &[&str]) -> Result<Vec<String>> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("'{}' must be an array of config keys", field))?;
+    items
+        .iter()
+        .map(|item| {
+            let key = item
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("'{}' items must be strings", field))?;
+            if !known.contains(&key) {
+                let hint = match suggest(key, known) {
+                    Some(k) => format!("did you mean '{}'?", k),
+                    None => format!("expected one of {}", known.join(", ")),
+                };
+                anyhow::bail!("Unknown key '{}' in '{}'; {}", key, field, hint);
+            }
+            Ok(key.to_string())
+        })
+        .collect()
+}
+
+/// Templates indexed by name, read from one or more manifests
 #[derive(Debug, Clone, Default)]
 pub(crate) struct TemplateFile {
-    templates: BTreeMap<String, Template>,
+    /// Shared with every configuration resolved from them, so resolving does not copy them
+    templates: BTreeMap<String, Arc<Template>>,
+    /// Each template object as written in its manifest
+    values: BTreeMap<String, Value>,
+    /// Manifest each template was read from
+    origins: BTreeMap<String, PathBuf>,
+    /// JSON pointer of each template's entry in its manifest, e.g. `/templates/2`
+    pointers: BTreeMap<String, String>,
+}
+
+/// Expands `path` into the manifests it names: the file itself, or the
+/// `*.templates.json` files of a directory in alphabetical order
+pub(crate) fn manifest_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(path)
+        .with_context(|| format!("Failed to read templates directory: {}", path.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.ends_with(".templates.json"))
+        })
+        .collect();
+    if files.is_empty() {
+        anyhow::bail!("No *.templates.json manifests found in {}", path.display());
+    }
+    files.sort();
+    Ok(files)
 }
 
 impl TemplateFile {
+    /// Reads a templates manifest, or every `*.templates.json` manifest in a directory
     pub fn from_path(path: &Path) -> Result<Self> {
+        Self::from_paths(&[path.to_path_buf()])
+    }
+
+    /// Merges the templates of several manifests or manifest directories. A template name
+    /// defined in more than one manifest is an error.
+    pub fn from_paths(paths: &[PathBuf]) -> Result<Self> {
+        let mut merged = Self::default();
+        for path in paths {
+            for manifest in manifest_files(path)? {
+                let mut file = Self::from_manifest(&manifest)?;
+                for (name, template) in file.templates {
+                    if let Some(first) = merged.origins.get(&name) {
+                        anyhow::bail!(
+                            "Duplicate template name '{}' found in {} and {}",
+                            name,
+                            first.display(),
+                            manifest.display()
+                        );
+                    }
+                    merged.origins.insert(name.clone(), manifest.clone());
+                    if let Some(value) = file.values.remove(&name) {
+                        merged.values.insert(name.clone(), value);
+                    }
+                    if let Some(pointer) = file.pointers.remove(&name) {
+                        merged.pointers.insert(name.clone(), pointer);
+                    }
+                    merged.templates.insert(name, template);
+                }
+            }
+        }
+        Ok(merged)
+    }
+
+    fn from_manifest(path: &Path) -> Result<Self> {
         if !path.exists() {
+            let legacy_dir = path.with_file_name("templates");
+            if legacy_dir.is_dir() {
+                anyhow::bail!(
+                    "Templates manifest does not exist: {}\nFound per-file templates in {} (schemaVersion 1); run `mklaunch migrate` to build the manifest",
+                    path.display(),
+                    legacy_dir.display()
+                );
+            }
             anyhow::bail!("Templates manifest does not exist: {}", path.display());
         }
 
-        let content = fs::read_to_string(path)
+        let content = read_text(path)
             .with_context(|| format!("Failed to read templates manifest: {}", path.display()))?;
 
         let root: Value = serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse templates manifest: {}", path.display()))?;
+            .map_err(|e| Diagnostic::from_json_error(path, &e))
+            .context("Failed to parse templates manifest")?;
+
+        if let Value::Object(object) = &root {
+            check_schema_version(object, path)?;
+        }
 
         let templates_value = root.get("templates").ok_or_else(|| {
             anyhow::anyhow!("Templates manifest must contain a 'templates' array")
@@ -186,6 +1485,8 @@ impl TemplateFile {
             .ok_or_else(|| anyhow::anyhow!("'templates' must be an array in {}", path.display()))?;
 
         let mut templates = BTreeMap::new();
+        let mut values = BTreeMap::new();
+        let mut pointers = BTreeMap::new();
         for (idx, entry) in templates_array.iter().enumerate() {
             let mut object = entry.as_object().cloned().ok_or_else(|| {
                 anyhow::anyhow!("Template entry at index {} must be a JSON object", idx)
@@ -213,10 +1514,16 @@ impl TemplateFile {
                 );
             }
 
-            let template = Template::from_value(Value::Object(object))
+            let mut template = Template::from_value(Value::Object(object))
+                .map_err(|e| {
+                    Diagnostic::at_pointer(path, &format!("/templates/{}", idx), format!("{:#}", e))
+                })
                 .with_context(|| format!("Invalid template '{}'", name))?;
 
-            templates.insert(name.to_string(), template);
+            template.layout = key_layout(&content, &format!("/templates/{}", idx)).map(Arc::new);
+            templates.insert(name.to_string(), Arc::new(template));
+            values.insert(name.to_string(), entry.clone());
+            pointers.insert(name.to_string(), format!("/templates/{}", idx));
         }
 
         if templates.is_empty() {
@@ -226,12 +1533,144 @@ impl TemplateFile {
             );
         }
 
-        Ok(Self { templates })
+        let origins = templates
+            .keys()
+            .map(|name| (name.clone(), path.to_path_buf()))
+            .collect();
+        Ok(Self {
+            templates,
+            values,
+            origins,
+            pointers,
+        })
     }
 
-    pub fn get(&self, name: &str) -> Result<&Template> {
+    pub fn get(&self, name: &str) -> Result<&Arc<Template>> {
         self.templates
             .get(name)
             .ok_or_else(|| anyhow::anyhow!("Template '{}' not found in templates manifest", name))
     }
+
+    /// Template names in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    /// Each template object as written, with the manifest it was read from, by name
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (&str, &Value, &Path)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value, self.origins[name].as_path()))
+    }
+
+    /// JSON pointer of a template's entry in its manifest, e.g. `/templates/2`, as recorded
+    /// when the manifest was read; the root pointer for templates not read from one
+    pub(crate) fn pointer(&self, name: &str) -> &str {
+        self.pointers.get(name).map_or("", String::as_str)
+    }
+
+    /// Warnings for templates whose name is not a valid template name, pointing at their
+    /// entry in the manifest
+    pub(crate) fn check_names(&self) -> Vec<Diagnostic> {
+        self.names()
+            .filter(|name| !is_valid_template_name(name))
+            .map(|name| {
+                let origin = &self.origins[name];
+                let message = format!(
+                    "Template name '{}' should only use lowercase letters, digits, '-' and '_' \
+                     (allowAnyTemplateName in settings.json turns this check off)",
+                    name
+                );
+                Diagnostic::at_pointer(origin, &format!("{}/name", self.pointer(name)), message)
+                    .warning()
+            })
+            .collect()
+    }
+
+    /// Fails when a template defines one of `forbidden` (`forbidTemplateKeys` in
+    /// settings.json), at its top level or in a per-OS block
+    pub(crate) fn check_forbidden_keys(&self, forbidden: &[String]) -> Result<()> {
+        for (name, value) in &self.values {
+            let blocks = OS_KEYS
+                .iter()
+                .filter_map(|os| value.get(os).map(|b| (*os, b)));
+            for (block, object) in std::iter::once(("", value)).chain(blocks) {
+                if let Some(key) = forbidden.iter().find(|k| object.get(k.as_str()).is_some()) {
+                    let key = match block {
+                        "" => key.clone(),
+                        block => format!("{}.{}", block, key),
+                    };
+                    anyhow::bail!(
+                        "Template '{}' in {} must not define '{}' (forbidTemplateKeys in settings.json)",
+                        name,
+                        self.origins[name].display(),
+                        key
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `name` is a valid template name: lowercase letters, digits, `-` and `_`, plus the
+/// `.devcontainer` suffix of a dev container variant such as `cpp.devcontainer`
+pub(crate) fn is_valid_template_name(name: &str) -> bool {
+    let base = name.strip_suffix(".devcontainer").unwrap_or(name);
+    !base.is_empty()
+        && base
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_'))
+}
+
+/// Templates available to config entries, for tools that list or inspect them without
+/// parsing manifests themselves
+#[derive(Debug, Clone)]
+pub struct Templates {
+    file: TemplateFile,
+}
+
+impl Templates {
+    /// Reads a templates manifest, or every `*.templates.json` manifest in a directory
+    pub fn from_path(path: &Path) -> Result<Self> {
+        Self::from_paths(&[path.to_path_buf()])
+    }
+
+    /// Merges several manifests or manifest directories, as repeated `--templates` do
+    pub fn from_paths(paths: &[PathBuf]) -> Result<Self> {
+        Ok(Self {
+            file: TemplateFile::from_paths(paths)?,
+        })
+    }
+
+    /// Template names in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.file.names()
+    }
+
+    /// The template object named `name` as written in its manifest, including `name`
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.file.values.get(name)
+    }
+
+    /// Every template with its name, in alphabetical order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.file
+            .values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Manifest the template named `name` was read from
+    pub fn origin(&self, name: &str) -> Option<&Path> {
+        self.file.origins.get(name).map(PathBuf::as_path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.file.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.file.values.is_empty()
+    }
 }
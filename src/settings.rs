@@ -0,0 +1,227 @@
+use crate::devcontainer::Environment;
+use crate::diagnostic::Diagnostic;
+use crate::emit::OutputFormat;
+use crate::encoding::read_text;
+use crate::generator::{DuplicateNames, KeyOrder, TagMatch};
+use crate::git::GitPolicy;
+use crate::sources::TemplateSource;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Repository-wide mklaunch settings read from `settings.json` next to the templates manifest
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    #[serde(rename = "$schema", default)]
+    _schema: Option<String>,
+    /// Templates manifests fetched by `mklaunch sync-templates`
+    #[serde(rename = "templateSources", default)]
+    pub(crate) template_sources: Vec<TemplateSource>,
+    /// `version` written to launch.json [default: 0.2.0]
+    #[serde(rename = "launchVersion")]
+    pub(crate) launch_version: Option<String>,
+    /// Other top-level launch.json keys, such as `compounds`, `inputs` or keys added by
+    /// newer VSCode versions
+    #[serde(rename = "launchKeys", default)]
+    pub(crate) launch_keys: Map<String, Value>,
+    /// Whether launch.json is committed or gitignored, checked after each generation
+    #[serde(rename = "gitPolicy")]
+    pub(crate) git_policy: Option<GitPolicy>,
+    /// Keys templates must not define, in addition to `args`
+    #[serde(rename = "forbidTemplateKeys", default)]
+    pub(crate) forbid_template_keys: Vec<String>,
+    /// Accepts any template name instead of lowercase letters, digits, `-` and `_`
+    #[serde(rename = "allowAnyTemplateName", default)]
+    pub(crate) allow_any_template_name: bool,
+    /// Treats configuration names differing only in case as different names
+    #[serde(rename = "caseSensitiveNames", default)]
+    pub(crate) case_sensitive_names: bool,
+    /// Decoration of every configuration name, e.g. `[{template}] {name}`
+    #[serde(rename = "nameFormat")]
+    pub(crate) name_format: Option<String>,
+    /// Command line options used when the matching flag is not given
+    #[serde(default)]
+    pub options: OptionDefaults,
+    /// Files written by one run when `--output` is not given, each with its own filters
+    #[serde(default)]
+    pub outputs: Vec<OutputTarget>,
+    /// Named filters that `outputs` refer to with `profile`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, OutputFilter>,
+}
+
+/// One file under `outputs` in settings.json
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct OutputTarget {
+    /// Relative to the folder containing `.mklaunch/`
+    pub path: PathBuf,
+    /// Profile whose filters apply where the target sets none itself
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub tag_match: Option<TagMatch>,
+    pub output_format: Option<OutputFormat>,
+    pub env: Option<Environment>,
+}
+
+/// Filters of an output, replacing the matching command line options
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct OutputFilter {
+    /// `--tag`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// `--tag-match`
+    pub tag_match: Option<TagMatch>,
+    /// `--output-format`
+    pub output_format: Option<OutputFormat>,
+    /// `--env`
+    pub env: Option<Environment>,
+}
+
+/// Defaults for command line options, under `options` in settings.json. A flag given on
+/// the command line wins over the value set here.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct OptionDefaults {
+    /// `--output`, relative to the folder containing `.mklaunch/`
+    pub output: Option<PathBuf>,
+    /// `--output-format`
+    pub output_format: Option<OutputFormat>,
+    /// `--key-order`
+    pub key_order: Option<KeyOrder>,
+    /// `--on-duplicate`
+    pub on_duplicate: Option<DuplicateNames>,
+    /// `--strict-config`
+    #[serde(default)]
+    pub strict_config: bool,
+    /// `--check-paths`
+    #[serde(default)]
+    pub check_paths: bool,
+    /// `--check-programs`
+    #[serde(default)]
+    pub check_programs: bool,
+    /// `--keep-going`
+    #[serde(default)]
+    pub keep_going: bool,
+    /// `--dedupe`
+    #[serde(default)]
+    pub dedupe: bool,
+    /// `--preserve-keys`
+    #[serde(default)]
+    pub preserve_keys: bool,
+    /// `--lint`
+    #[serde(default)]
+    pub lint: bool,
+}
+
+impl Settings {
+    /// Location of the settings file for the templates manifest at `templates_path`
+    pub fn path_for(templates_path: &Path) -> PathBuf {
+        templates_path
+            .parent()
+            .map(|p| p.join("settings.json"))
+            .unwrap_or_else(|| PathBuf::from("settings.json"))
+    }
+
+    /// Reads the settings file; a missing file yields the defaults
+    pub fn from_path(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = read_text(path)
+            .with_context(|| format!("Failed to read settings file: {}", path.display()))?;
+        let settings: Self = serde_json::from_str(&content)
+            .map_err(|e| Diagnostic::from_json_error(path, &e))
+            .context("Failed to parse settings file")?;
+        for source in &settings.template_sources {
+            source
+                .validate()
+                .with_context(|| format!("Invalid template source in {}", path.display()))?;
+        }
+        for key in ["version", "configurations"] {
+            if settings.launch_keys.contains_key(key) {
+                return Err(Diagnostic::at_pointer(
+                    path,
+                    &format!("/launchKeys/{}", key),
+                    format!("'launchKeys' must not set '{}'", key),
+                ))
+                .context("Failed to parse settings file");
+            }
+        }
+        if let Some(idx) = settings
+            .forbid_template_keys
+            .iter()
+            .position(|k| matches!(k.as_str(), "name" | "type"))
+        {
+            return Err(Diagnostic::at_pointer(
+                path,
+                &format!("/forbidTemplateKeys/{}", idx),
+                format!(
+                    "'{}' is required in every template and cannot be forbidden",
+                    settings.forbid_template_keys[idx]
+                ),
+            ))
+            .context("Failed to parse settings file");
+        }
+        if let Some(format) = &settings.name_format
+            && !format.contains("{name}")
+        {
+            return Err(Diagnostic::at_pointer(
+                path,
+                "/nameFormat",
+                format!("'nameFormat' must contain {{name}}, found '{}'", format),
+            ))
+            .context("Failed to parse settings file");
+        }
+        for (idx, target) in settings.outputs.iter().enumerate() {
+            let problem = if target.path.as_os_str().is_empty() {
+                Some(("path", "'path' must not be empty".to_string()))
+            } else if settings.outputs[..idx]
+                .iter()
+                .any(|t| t.path == target.path)
+            {
+                Some(("path", format!("{} is listed twice", target.path.display())))
+            } else {
+                target
+                    .profile
+                    .as_ref()
+                    .filter(|name| !settings.profiles.contains_key(*name))
+                    .map(|name| ("profile", format!("Unknown profile '{}'", name)))
+            };
+            if let Some((key, message)) = problem {
+                return Err(Diagnostic::at_pointer(
+                    path,
+                    &format!("/outputs/{}/{}", idx, key),
+                    message,
+                ))
+                .context("Failed to parse settings file");
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Filters of `target`: its own, falling back to those of its profile
+    pub fn output_filter(&self, target: &OutputTarget) -> OutputFilter {
+        let profile = target
+            .profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+            .cloned()
+            .unwrap_or_default();
+        OutputFilter {
+            tags: if target.tags.is_empty() {
+                profile.tags
+            } else {
+                target.tags.clone()
+            },
+            tag_match: target.tag_match.or(profile.tag_match),
+            output_format: target.output_format.or(profile.output_format),
+            env: target.env.or(profile.env),
+        }
+    }
+}
@@ -0,0 +1,349 @@
+use crate::atomic::write_atomic;
+use crate::lock::hash_bytes;
+use crate::runner::{CommandRunner, CommandSpec};
+use crate::schema::TemplateFile;
+use crate::settings::Settings;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const SOURCES_LOCK_VERSION: u32 = 1;
+
+/// Shared templates manifest in a git repository or at an HTTPS URL, listed under
+/// `templateSources` in the settings file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TemplateSource {
+    /// Local name; the fetched manifest is stored as `sources/<name>.templates.json`
+    pub name: String,
+    /// Repository to clone
+    pub git: Option<String>,
+    /// Branch or tag of `git` to follow [default: the remote's default branch]
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    /// Manifest path inside the repository [default: `templates.json`]
+    pub path: Option<String>,
+    /// Manifest to download
+    pub url: Option<String>,
+}
+
+impl TemplateSource {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            anyhow::bail!(
+                "Template source name '{}' may only contain letters, digits, '-', '_' and '.'",
+                self.name
+            );
+        }
+        if let Some(url) = &self.url
+            && !url.starts_with("https://")
+        {
+            anyhow::bail!(
+                "Template source '{}' must be downloaded over https",
+                self.name
+            );
+        }
+        match (&self.git, &self.url) {
+            (Some(_), None) => Ok(()),
+            (None, Some(_)) if self.git_ref.is_none() && self.path.is_none() => Ok(()),
+            (None, Some(_)) => anyhow::bail!(
+                "Template source '{}': 'ref' and 'path' only apply to git sources",
+                self.name
+            ),
+            _ => anyhow::bail!(
+                "Template source '{}' must set exactly one of 'git' and 'url'",
+                self.name
+            ),
+        }
+    }
+
+    /// Repository or URL the source is fetched from, as recorded in the lock file
+    fn location(&self) -> String {
+        match (&self.git, &self.url) {
+            (Some(repo), _) => format!(
+                "git {} {} {}",
+                repo,
+                self.git_ref.as_deref().unwrap_or("HEAD"),
+                self.manifest_path()
+            ),
+            (None, Some(url)) => url.clone(),
+            (None, None) => String::new(),
+        }
+    }
+
+    fn manifest_path(&self) -> &str {
+        self.path.as_deref().unwrap_or("templates.json")
+    }
+}
+
+/// Lock file pinning each fetched source to a commit (git) and manifest hash
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SourcesLock {
+    version: u32,
+    sources: BTreeMap<String, PinnedSource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PinnedSource {
+    source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rev: Option<String>,
+    hash: String,
+}
+
+impl SourcesLock {
+    fn from_path(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self {
+                version: SOURCES_LOCK_VERSION,
+                sources: BTreeMap::new(),
+            });
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let lock: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if lock.version != SOURCES_LOCK_VERSION {
+            anyhow::bail!(
+                "Unsupported version {} in {}; run `mklaunch sync-templates --update`",
+                lock.version,
+                path.display()
+            );
+        }
+        Ok(lock)
+    }
+}
+
+/// Template source written by [`sync_templates`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncedSource {
+    pub name: String,
+    /// Local copy of the manifest
+    pub path: PathBuf,
+    /// Commit the manifest was read from, for git sources
+    pub rev: Option<String>,
+}
+
+/// Where fetched manifests and their lock file live for the templates manifest at
+/// `templates_path`
+fn sources_dir(templates_path: &Path) -> PathBuf {
+    templates_path
+        .parent()
+        .map(|p| p.join("sources"))
+        .unwrap_or_else(|| PathBuf::from("sources"))
+}
+
+fn lock_path(templates_path: &Path) -> PathBuf {
+    sources_dir(templates_path).with_file_name("sources.lock.json")
+}
+
+/// Fetches the `templateSources` of the settings file next to `templates_path` into
+/// `sources/`. Git sources are checked out at the commit pinned in `sources.lock.json`,
+/// and URL sources must still have the pinned content; `update` fetches the latest
+/// revisions instead and re-pins them. git and curl run through `runner`.
+pub fn sync_templates(
+    templates_path: &Path,
+    update: bool,
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> Result<Vec<SyncedSource>> {
+    let settings = Settings::from_path(&Settings::path_for(templates_path))?;
+    let dir = sources_dir(templates_path);
+    let lock_path = lock_path(templates_path);
+    let mut lock = SourcesLock::from_path(&lock_path)?;
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+
+    let mut synced = Vec::new();
+    let mut pins = BTreeMap::new();
+    for source in &settings.template_sources {
+        let pinned = lock
+            .sources
+            .get(&source.name)
+            .filter(|p| !update && p.source == source.location());
+        let (content, rev) = match (&source.git, &source.url) {
+            (Some(repo), _) => {
+                let rev = pinned.and_then(|p| p.rev.as_deref());
+                fetch_git(source, repo, rev, &dir, timeout, runner)?
+            }
+            (None, Some(url)) => (fetch_url(url, timeout, runner)?, None),
+            (None, None) => unreachable!("validated when loading settings"),
+        };
+        let hash = hash_bytes(&content);
+        if let Some(pinned) = pinned
+            && pinned.hash != hash
+        {
+            anyhow::bail!(
+                "Template source '{}' changed since it was pinned; run `mklaunch sync-templates --update` to accept the new version",
+                source.name
+            );
+        }
+
+        let path = dir.join(format!("{}.templates.json", source.name));
+        let staged = dir.join(format!(".{}.download", source.name));
+        fs::write(&staged, &content)
+            .with_context(|| format!("Failed to write {}", staged.display()))?;
+        if let Err(err) = TemplateFile::from_path(&staged) {
+            let _ = fs::remove_file(&staged);
+            return Err(err).with_context(|| {
+                format!(
+                    "Template source '{}' is not a valid templates manifest",
+                    source.name
+                )
+            });
+        }
+        fs::rename(&staged, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        pins.insert(
+            source.name.clone(),
+            PinnedSource {
+                source: source.location(),
+                rev: rev.clone(),
+                hash,
+            },
+        );
+        synced.push(SyncedSource {
+            name: source.name.clone(),
+            path,
+            rev,
+        });
+    }
+
+    lock.sources = pins;
+    write_atomic(
+        &lock_path,
+        (serde_json::to_string_pretty(&lock)? + "\n").as_bytes(),
+    )?;
+    Ok(synced)
+}
+
+/// Local copies of the `templateSources` listed in `settings`, checked against the lock file
+pub(crate) fn source_manifests(templates_path: &Path, settings: &Settings) -> Result<Vec<PathBuf>> {
+    if settings.template_sources.is_empty() {
+        return Ok(Vec::new());
+    }
+    let dir = sources_dir(templates_path);
+    let lock = SourcesLock::from_path(&lock_path(templates_path))?;
+    let mut manifests = Vec::new();
+    for source in &settings.template_sources {
+        let path = dir.join(format!("{}.templates.json", source.name));
+        let pinned = lock
+            .sources
+            .get(&source.name)
+            .filter(|p| p.source == source.location());
+        let Some(pinned) = pinned.filter(|_| path.exists()) else {
+            anyhow::bail!(
+                "Template source '{}' has not been fetched; run `mklaunch sync-templates`",
+                source.name
+            );
+        };
+        let content =
+            fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        if hash_bytes(&content) != pinned.hash {
+            anyhow::bail!(
+                "{} does not match its pinned hash; run `mklaunch sync-templates` to restore it",
+                path.display()
+            );
+        }
+        manifests.push(path);
+    }
+    Ok(manifests)
+}
+
+/// Reads the manifest of a git source from a fresh clone, at `rev` when pinned
+fn fetch_git(
+    source: &TemplateSource,
+    repo: &str,
+    rev: Option<&str>,
+    dir: &Path,
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> Result<(Vec<u8>, Option<String>)> {
+    let git = |args: &[&str], cwd: &Path| git(args, cwd, timeout, runner);
+    let checkout = dir.join(format!(".{}.checkout", source.name));
+    if checkout.exists() {
+        fs::remove_dir_all(&checkout)
+            .with_context(|| format!("Failed to remove {}", checkout.display()))?;
+    }
+
+    let mut clone = vec!["clone", "--quiet"];
+    if rev.is_none() {
+        clone.extend(["--depth", "1"]);
+        if let Some(git_ref) = &source.git_ref {
+            clone.extend(["--branch", git_ref]);
+        }
+    }
+    let checkout_arg = checkout.to_string_lossy().into_owned();
+    // `--` keeps a repo from settings.json from being read as a git option
+    clone.extend(["--", repo, checkout_arg.as_str()]);
+
+    let result = (|| {
+        git(&clone, Path::new(""))?;
+        if let Some(rev) = rev {
+            git(&["checkout", "--quiet", rev], &checkout)?;
+        }
+        let head = git(&["rev-parse", "HEAD"], &checkout)?;
+        let manifest = checkout.join(source.manifest_path());
+        let content = fs::read(&manifest).with_context(|| {
+            format!(
+                "Template source '{}' has no {} in {}",
+                source.name,
+                source.manifest_path(),
+                repo
+            )
+        })?;
+        Ok((content, Some(head)))
+    })();
+    let _ = fs::remove_dir_all(&checkout);
+    result
+}
+
+/// Runs git in `cwd` (empty for the current directory) and returns its trimmed stdout
+fn git(
+    args: &[&str],
+    cwd: &Path,
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> Result<String> {
+    let command = CommandSpec::new("git", args.iter().copied())
+        .cwd(cwd)
+        .timeout(timeout);
+    let output = runner.run(&command)?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Downloads `url` with curl, refusing to follow redirects to anything but https
+pub(crate) fn fetch_url(
+    url: &str,
+    timeout: Option<Duration>,
+    runner: &dyn CommandRunner,
+) -> Result<Vec<u8>> {
+    let command = CommandSpec::new(
+        "curl",
+        [
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--proto",
+            "=https",
+            "--proto-redir",
+            "=https",
+            url,
+        ],
+    )
+    .timeout(timeout);
+    let output = runner
+        .run(&command)
+        .with_context(|| format!("Failed to download {}", url))?;
+    Ok(output.stdout)
+}
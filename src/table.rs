@@ -0,0 +1,217 @@
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::schema::suggest;
+use anyhow::Result;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// Columns holding a single string value
+const STRING_COLUMNS: [&str; 17] = [
+    "name",
+    "extends",
+    "program",
+    "cwd",
+    "coreDump",
+    "preLaunchTask",
+    "postDebugTask",
+    "console",
+    "MIMode",
+    "miDebuggerPath",
+    "launchAfter",
+    "group",
+    "baseArgs",
+    "envFile",
+    "argsString",
+    "argsFromCommand",
+    "include",
+];
+
+/// Columns holding `true`/`false` (also `yes`/`no`, `1`/`0`)
+const BOOL_COLUMNS: [&str; 7] = [
+    "enabled",
+    "hidden",
+    "baseArgsOptional",
+    "externalConsole",
+    "justMyCode",
+    "stopAtEntry",
+    "perFile",
+];
+
+/// Columns whose cells are split on whitespace into a list; repeated columns are concatenated
+const LIST_COLUMNS: [&str; 4] = ["args", "argSets", "unset", "tags"];
+
+/// Whether `path` is a table of config entries (`.csv` or `.tsv`)
+pub(crate) fn is_table(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|s| s.to_str()),
+        Some("csv" | "tsv")
+    )
+}
+
+/// Converts a CSV (or, for `.tsv` files, tab-separated) table into config entries.
+/// The first row names the columns; every following non-blank row becomes one entry.
+/// Empty cells are left out, and `enabled` defaults to true.
+pub(crate) fn read_table(path: &Path, content: &str) -> Result<Vec<Value>> {
+    let delimiter = if path.extension().and_then(|s| s.to_str()) == Some("tsv") {
+        '\t'
+    } else {
+        ','
+    };
+    let mut rows = parse_rows(path, content, delimiter)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Ok(Vec::new());
+    };
+
+    let columns: Vec<&str> = header.iter().map(|c| c.trim()).collect();
+    let known: Vec<&str> = STRING_COLUMNS
+        .iter()
+        .chain(&BOOL_COLUMNS)
+        .chain(&LIST_COLUMNS)
+        .chain(&["order"])
+        .copied()
+        .collect();
+    for (idx, column) in columns.iter().enumerate() {
+        if !known.contains(column) {
+            let hint = match suggest(column, &known) {
+                Some(found) => format!(" (did you mean '{}'?)", found),
+                None => String::new(),
+            };
+            return Err(at_line(path, 1, format!("Unknown column '{}'{}", column, hint)).into());
+        }
+        if !LIST_COLUMNS.contains(column) && columns[..idx].contains(column) {
+            return Err(at_line(path, 1, format!("Column '{}' appears twice", column)).into());
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (line, cells) in rows {
+        if cells.iter().all(|c| c.trim().is_empty()) {
+            continue;
+        }
+        if cells.len() > columns.len() {
+            return Err(at_line(
+                path,
+                line,
+                format!(
+                    "Row has {} cells but the header names {} columns",
+                    cells.len(),
+                    columns.len()
+                ),
+            )
+            .into());
+        }
+
+        let mut entry = Map::new();
+        entry.insert("enabled".to_string(), Value::Bool(true));
+        for (column, cell) in columns.iter().zip(&cells) {
+            let cell = cell.trim();
+            if cell.is_empty() {
+                continue;
+            }
+            let value = if LIST_COLUMNS.contains(column) {
+                let words = cell
+                    .split_whitespace()
+                    .map(|w| Value::String(w.to_string()));
+                match entry.get_mut(*column) {
+                    Some(Value::Array(items)) => {
+                        items.extend(words);
+                        continue;
+                    }
+                    _ => Value::Array(words.collect()),
+                }
+            } else if BOOL_COLUMNS.contains(column) {
+                match cell.to_ascii_lowercase().as_str() {
+                    "true" | "yes" | "1" => Value::Bool(true),
+                    "false" | "no" | "0" => Value::Bool(false),
+                    _ => {
+                        return Err(at_line(
+                            path,
+                            line,
+                            format!("'{}' must be true or false, found '{}'", column, cell),
+                        )
+                        .into());
+                    }
+                }
+            } else if *column == "order" {
+                match cell.parse::<i64>() {
+                    Ok(order) => Value::from(order),
+                    Err(_) => {
+                        return Err(at_line(
+                            path,
+                            line,
+                            format!("'order' must be an integer, found '{}'", cell),
+                        )
+                        .into());
+                    }
+                }
+            } else {
+                Value::String(cell.to_string())
+            };
+            entry.insert(column.to_string(), value);
+        }
+        entries.push(Value::Object(entry));
+    }
+    Ok(entries)
+}
+
+fn at_line(path: &Path, line: usize, message: String) -> Diagnostic {
+    Diagnostic {
+        path: path.to_path_buf(),
+        pointer: None,
+        position: Some((line, 1)),
+        message,
+        severity: Severity::Error,
+        fix: None,
+    }
+}
+
+/// Splits `content` into rows of cells, each tagged with its 1-based starting line.
+/// Cells may be quoted with `"` (doubled to escape), which lets them hold delimiters
+/// and line breaks.
+fn parse_rows(path: &Path, content: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut cell = String::new();
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            line += 1;
+        }
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    cell.push('"');
+                }
+                '"' => quoted = false,
+                _ => cell.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if cell.trim().is_empty() => {
+                cell.clear();
+                quoted = true;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut cell));
+                rows.push((row_line, std::mem::take(&mut row)));
+                row_line = line;
+            }
+            c if c == delimiter => row.push(std::mem::take(&mut cell)),
+            _ => cell.push(c),
+        }
+    }
+    if quoted {
+        return Err(at_line(path, row_line, "Unterminated quoted cell".to_string()).into());
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
@@ -0,0 +1,154 @@
+use crate::atomic::write_atomic;
+use crate::diagnostic::span;
+use crate::encoding::read_text;
+use crate::generator::list_config_dir;
+use crate::schema::ConfigFile;
+use crate::table::is_table;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Config entry listed by `toggle_enabled`
+struct Toggle {
+    path: PathBuf,
+    /// JSON pointer of the entry's `enabled` value in its file
+    pointer: String,
+    name: String,
+    enabled: bool,
+    /// Value in the file, to tell which entries were flipped
+    original: bool,
+}
+
+/// Interactively flips the `enabled` flags of the config entries in `configs_dirs`.
+/// The entries are listed with checkboxes on `output`; each line read from `input` toggles
+/// the entries it names by number or range (`3`, `2-5`, `1 4`), `a` enables and `n`
+/// disables every entry, `s` saves and `q` quits without saving.
+/// Saving rewrites only the `enabled` values, leaving the rest of each file untouched.
+/// Returns the files that were rewritten.
+pub fn toggle_enabled<R: BufRead, W: Write>(
+    configs_dirs: &[PathBuf],
+    input: &mut R,
+    output: &mut W,
+) -> Result<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    for dir in configs_dirs {
+        for path in list_config_dir(dir, false)?.0 {
+            // Tables have no JSON text to edit in place
+            if is_table(&path) {
+                continue;
+            }
+            for entry in ConfigFile::read_entries(&path, &mut Vec::new())? {
+                let name = entry.value.get("name").and_then(Value::as_str);
+                match (name, entry.value.get("enabled")) {
+                    (Some(name), Some(Value::Bool(enabled))) => entries.push(Toggle {
+                        path: path.clone(),
+                        pointer: format!("{}/enabled", entry.pointer),
+                        name: name.to_string(),
+                        enabled: *enabled,
+                        original: *enabled,
+                    }),
+                    // Names or flags inherited through `include`
+                    _ => skipped += 1,
+                }
+            }
+        }
+    }
+    if entries.is_empty() {
+        anyhow::bail!("No config entries with their own 'name' and 'enabled' to toggle");
+    }
+    if skipped > 0 {
+        writeln!(
+            output,
+            "{} entries without their own 'name' and 'enabled' are not listed",
+            skipped
+        )?;
+    }
+
+    loop {
+        let width = entries.iter().map(|e| e.name.len()).max().unwrap_or(0);
+        for (idx, entry) in entries.iter().enumerate() {
+            writeln!(
+                output,
+                "{:>3}) [{}] {:<width$}  {}",
+                idx + 1,
+                if entry.enabled { 'x' } else { ' ' },
+                entry.name,
+                entry.path.display(),
+            )?;
+        }
+        write!(
+            output,
+            "Toggle [numbers or ranges, a = all, n = none, s = save, q = quit]: "
+        )?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            anyhow::bail!("Unexpected end of input");
+        }
+        match line.trim() {
+            "s" => break,
+            "q" => return Ok(Vec::new()),
+            "a" => entries.iter_mut().for_each(|e| e.enabled = true),
+            "n" => entries.iter_mut().for_each(|e| e.enabled = false),
+            answer => match parse_selection(answer, entries.len()) {
+                Some(selected) => {
+                    for idx in selected {
+                        entries[idx].enabled = !entries[idx].enabled;
+                    }
+                }
+                None => writeln!(output, "Unknown selection '{}'", answer)?,
+            },
+        }
+    }
+
+    let mut changes: BTreeMap<&Path, Vec<&Toggle>> = BTreeMap::new();
+    for entry in entries.iter().filter(|e| e.enabled != e.original) {
+        changes.entry(&entry.path).or_default().push(entry);
+    }
+    let mut written = Vec::new();
+    for (path, toggles) in changes {
+        let mut content = read_text(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        let mut spans: Vec<((usize, usize), bool)> = toggles
+            .iter()
+            .map(|t| {
+                span(&content, &t.pointer)
+                    .map(|range| (range, t.enabled))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Failed to locate {} in {}", t.pointer, path.display())
+                    })
+            })
+            .collect::<Result<_>>()?;
+        // From the end of the file so earlier offsets stay valid
+        spans.sort_by_key(|((start, _), _)| std::cmp::Reverse(*start));
+        for ((start, end), enabled) in spans {
+            content.replace_range(start..end, if enabled { "true" } else { "false" });
+        }
+        write_atomic(path, content.as_bytes())?;
+        written.push(path.to_path_buf());
+    }
+    Ok(written)
+}
+
+/// Zero-based indices named by `answer`, e.g. `1 3-4`; `None` when a part is not a number
+/// or range between 1 and `len`
+fn parse_selection(answer: &str, len: usize) -> Option<Vec<usize>> {
+    let mut selected = Vec::new();
+    for part in answer.split([' ', ',']).filter(|p| !p.is_empty()) {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (first.parse().ok()?, last.parse().ok()?),
+            None => {
+                let n: usize = part.parse().ok()?;
+                (n, n)
+            }
+        };
+        if first < 1 || last > len || first > last {
+            return None;
+        }
+        selected.extend(first - 1..last);
+    }
+    (!selected.is_empty()).then_some(selected)
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// Expands `{token}` placeholders in a configuration name.
+/// Supported tokens are the keys of `vars` and `{args[N]}`; `{{` and `}}` are literal braces.
+pub(crate) fn expand_name(
+    name: &str,
+    vars: &BTreeMap<&str, String>,
+    args: &[String],
+) -> Result<String> {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                let rest = chars.as_str();
+                if let Some(stripped) = rest.strip_prefix('{') {
+                    out.push('{');
+                    chars = stripped.chars();
+                    continue;
+                }
+                let Some(end) = rest.find('}') else {
+                    anyhow::bail!("Unterminated token in name '{}'", name);
+                };
+                out.push_str(&lookup(&rest[..end], name, vars, args)?);
+                chars = rest[end + 1..].chars();
+            }
+            '}' => {
+                if let Some(stripped) = chars.as_str().strip_prefix('}') {
+                    chars = stripped.chars();
+                }
+                out.push('}');
+            }
+            _ => out.push(c),
+        }
+    }
+
+    Ok(out)
+}
+
+fn lookup(
+    token: &str,
+    name: &str,
+    vars: &BTreeMap<&str, String>,
+    args: &[String],
+) -> Result<String> {
+    if let Some(value) = vars.get(token) {
+        return Ok(value.clone());
+    }
+
+    if let Some(index) = token
+        .strip_prefix("args[")
+        .and_then(|s| s.strip_suffix(']'))
+    {
+        let index: usize = index
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid args index '{}' in name '{}'", index, name))?;
+        return args.get(index).cloned().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Token '{{args[{}]}}' in name '{}' is out of range ({} args)",
+                index,
+                name,
+                args.len()
+            )
+        });
+    }
+
+    let known: Vec<&str> = vars.keys().copied().collect();
+    anyhow::bail!(
+        "Unknown token '{{{}}}' in name '{}'. Supported tokens: {}, args[N]",
+        token,
+        name,
+        known.join(", ")
+    )
+}
@@ -1,5 +1,10 @@
 use anyhow::Result;
-use mklaunch::Generator;
+use mklaunch::{
+    ArgsDedupe, CommandOutput, CommandRunner, CommandSpec, ConfigChange, Diagnostics, DoctorReport,
+    DuplicateNames, Environment, Fix, GenerationSummary, Generator, GeneratorOptions, KeyOrder,
+    LintWarning, ListReport, NoExec, OptionDefaults, OutputFilter, OutputFormat, PathStyle,
+    ProcessRunner, REPORT_VERSION, Settings, Severity, TagMatch, ValidateReport,
+};
 use serde_json::json;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -395,3 +400,4700 @@ fn test_empty_configs_directory() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_attach_configs_per_debugger_type() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({
+            "templates": [
+                { "name": "cpp", "type": "cppdbg", "request": "launch", "program": "${workspaceFolder}/app" },
+                { "name": "lldb", "type": "lldb", "request": "launch", "program": "${workspaceFolder}/app" }
+            ]
+        }),
+    )?;
+
+    let configs = json!([
+        { "name": "Attach cpp", "extends": "cpp", "enabled": true, "attach": {} },
+        { "name": "Attach cpp pid", "extends": "cpp", "enabled": true, "attach": { "pid": 4242 } },
+        { "name": "Attach lldb", "extends": "lldb", "enabled": true, "attach": { "processName": "myapp" } },
+        { "name": "Attach lldb picker", "extends": "lldb", "enabled": true, "attach": {} }
+    ]);
+    write_json(configs_dir.join("attach.json"), &configs)?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let generator = Generator::new(base.join("templates.json"), base.join("configs"));
+    let v = serde_json::to_value(generator.generate()?)?;
+    let configurations = v["configurations"].as_array().unwrap();
+    let find_by_name = |n: &str| configurations.iter().find(|c| c["name"] == n).unwrap();
+
+    let cpp = find_by_name("Attach cpp");
+    assert_eq!(cpp["request"], "attach");
+    assert_eq!(cpp["processId"], "${command:pickProcess}");
+
+    assert_eq!(find_by_name("Attach cpp pid")["processId"], 4242);
+
+    let lldb = find_by_name("Attach lldb");
+    assert_eq!(lldb["request"], "attach");
+    assert_eq!(lldb["program"], "myapp");
+    assert!(lldb.get("pid").is_none());
+
+    assert_eq!(
+        find_by_name("Attach lldb picker")["pid"],
+        "${command:pickMyProcess}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_arg_sets_compose_in_order() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    let args_dir = temp_dir.path().join(".mklaunch/args");
+    fs::create_dir_all(&args_dir)?;
+
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(args_dir.join("common.json"), &json!({ "args": ["-v"] }))?;
+    write_json(
+        args_dir.join("dataset-small.json"),
+        &json!({ "args": ["--data", "small.bin"] }),
+    )?;
+    let base_args_path = temp_dir.path().join("baseargs.json");
+    write_json(&base_args_path, &json!({ "args": ["--base"] }))?;
+
+    write_json(
+        configs_dir.join("sets.json"),
+        &json!([
+            {
+                "name": "With Sets",
+                "extends": "cpp",
+                "enabled": true,
+                "baseArgs": base_args_path.to_string_lossy(),
+                "argSets": ["common", "dataset-small"],
+                "args": ["--extra"]
+            }
+        ]),
+    )?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let generator = Generator::new(base.join("templates.json"), base.join("configs"));
+    let v = serde_json::to_value(generator.generate()?)?;
+    assert_eq!(
+        v["configurations"][0]["args"],
+        json!(["--base", "-v", "--data", "small.bin", "--extra"])
+    );
+
+    // Unknown arg set names are reported
+    write_json(
+        configs_dir.join("sets.json"),
+        &json!([
+            { "name": "Missing Set", "extends": "cpp", "enabled": true, "argSets": ["nope"] }
+        ]),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("Arg set 'nope' not found"));
+
+    let other_dir = temp_dir.path().join("other-args");
+    fs::create_dir_all(&other_dir)?;
+    write_json(other_dir.join("nope.json"), &json!({ "args": ["-q"] }))?;
+    #[allow(deprecated)]
+    let generator = generator.with_args_dir(other_dir);
+    let v = serde_json::to_value(generator.generate()?)?;
+    assert_eq!(v["configurations"][0]["args"], json!(["-q"]));
+
+    Ok(())
+}
+
+#[test]
+fn test_env_file_expanded_or_passed_through() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({
+            "templates": [
+                {
+                    "name": "cpp",
+                    "type": "cppdbg",
+                    "environment": [
+                        { "name": "LOG", "value": "info" },
+                        { "name": "KEEP", "value": "1" }
+                    ]
+                },
+                { "name": "lldb", "type": "lldb" }
+            ]
+        }),
+    )?;
+    fs::write(
+        configs_dir.join(".env.debug"),
+        "# comment\nexport LOG=debug\nDATA_DIR=\"/tmp/data\"\n",
+    )?;
+    let absolute_env = configs_dir.join(".env.debug");
+
+    write_json(
+        configs_dir.join("env.json"),
+        &json!([
+            {
+                "name": "Cpp Env",
+                "extends": "cpp",
+                "enabled": true,
+                "envFile": { "path": ".env.debug", "relativeTo": "config" }
+            },
+            {
+                "name": "Lldb Env",
+                "extends": "lldb",
+                "enabled": true,
+                "envFile": absolute_env.to_string_lossy()
+            }
+        ]),
+    )?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let generator = Generator::new(base.join("templates.json"), base.join("configs"));
+    let v = serde_json::to_value(generator.generate()?)?;
+    let configurations = v["configurations"].as_array().unwrap();
+
+    let cpp = configurations
+        .iter()
+        .find(|c| c["name"] == "Cpp Env")
+        .unwrap();
+    assert!(cpp.get("envFile").is_none());
+    assert_eq!(
+        cpp["environment"],
+        json!([
+            { "name": "KEEP", "value": "1" },
+            { "name": "LOG", "value": "debug" },
+            { "name": "DATA_DIR", "value": "/tmp/data" }
+        ])
+    );
+
+    let lldb = configurations
+        .iter()
+        .find(|c| c["name"] == "Lldb Env")
+        .unwrap();
+    assert_eq!(lldb["envFile"], absolute_env.to_string_lossy().as_ref());
+
+    // Missing envFile is reported
+    write_json(
+        configs_dir.join("env.json"),
+        &json!([
+            {
+                "name": "Missing Env",
+                "extends": "lldb",
+                "enabled": true,
+                "envFile": { "path": "missing.env", "relativeTo": "config" }
+            }
+        ]),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("envFile does not exist"));
+
+    Ok(())
+}
+
+#[test]
+fn test_lint_reports_unused_templates_and_orphan_args() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    create_test_files(temp_dir.path())?;
+    let base = temp_dir.path().join(".mklaunch");
+    let args_dir = base.join("args");
+    fs::create_dir_all(&args_dir)?;
+
+    write_json(
+        base.join("templates.json"),
+        &json!({
+            "templates": [
+                { "name": "cpp", "type": "cppdbg" },
+                { "name": "lldb", "type": "lldb" },
+                { "name": "legacy", "type": "cppdbg" }
+            ]
+        }),
+    )?;
+    write_json(args_dir.join("used.json"), &json!({ "args": ["-a"] }))?;
+    write_json(args_dir.join("by-path.json"), &json!({ "args": ["-b"] }))?;
+    write_json(args_dir.join("stale.json"), &json!({ "args": ["-c"] }))?;
+    write_json(
+        base.join("configs/05-sets.json"),
+        &json!([
+            {
+                "name": "Sets",
+                "extends": "cpp",
+                "enabled": true,
+                "argSets": ["used"],
+                "baseArgs": args_dir.join("by-path.json").to_string_lossy()
+            },
+            {
+                "name": "Disabled",
+                "extends": "cpp",
+                "enabled": false,
+                "argSets": ["stale"]
+            }
+        ]),
+    )?;
+
+    let generator = Generator::new(base.join("templates.json"), base.join("configs"));
+    let warnings = generator.lint()?;
+    assert_eq!(
+        warnings,
+        vec![
+            LintWarning::UnusedTemplate("legacy".to_string()),
+            LintWarning::OrphanArgsFile(args_dir.join("stale.json")),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_name_tokens_expanded() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("bench.json"),
+        &json!([
+            {
+                "name": "{file}: {args[1]} ({template})",
+                "extends": "cpp",
+                "enabled": true,
+                "args": ["--mode", "fast"]
+            },
+            {
+                "name": "{file}: {args[1]} ({template})",
+                "extends": "cpp",
+                "enabled": true,
+                "args": ["--mode", "slow"]
+            },
+            {
+                "name": "{{literal}}",
+                "extends": "cpp",
+                "enabled": true
+            }
+        ]),
+    )?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let generator = Generator::new(base.join("templates.json"), base.join("configs"));
+    let v = serde_json::to_value(generator.generate()?)?;
+    let names: Vec<&str> = v["configurations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec!["bench: fast (cpp)", "bench: slow (cpp)", "{literal}"]
+    );
+
+    write_json(
+        configs_dir.join("bench.json"),
+        &json!([{ "name": "{nope}", "extends": "cpp", "enabled": true }]),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("Unknown token '{nope}'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_array_merge_modes() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({
+            "templates": [
+                {
+                    "name": "node",
+                    "type": "node",
+                    "request": "launch",
+                    "program": "${workspaceFolder}/index.js",
+                    "skipFiles": ["<node_internals>/**"],
+                    "runtimeArgs": ["--inspect"],
+                    "outFiles": ["${workspaceFolder}/dist/**/*.js"]
+                }
+            ]
+        }),
+    )?;
+    write_json(
+        configs_dir.join("node.json"),
+        &json!([
+            {
+                "name": "Node",
+                "extends": "node",
+                "enabled": true,
+                "arrayMerge": {
+                    "skipFiles": { "append": ["${workspaceFolder}/node_modules/**"] },
+                    "runtimeArgs": { "prepend": ["--enable-source-maps"] },
+                    "outFiles": { "replace": [] },
+                    "resolveSourceMapLocations": { "append": ["!**/node_modules/**"] }
+                }
+            }
+        ]),
+    )?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let generator = Generator::new(base.join("templates.json"), base.join("configs"));
+    let v = serde_json::to_value(generator.generate()?)?;
+    let node = &v["configurations"][0];
+    assert_eq!(
+        node["skipFiles"],
+        json!(["<node_internals>/**", "${workspaceFolder}/node_modules/**"])
+    );
+    assert_eq!(
+        node["runtimeArgs"],
+        json!(["--enable-source-maps", "--inspect"])
+    );
+    assert_eq!(node["outFiles"], json!([]));
+    assert_eq!(
+        node["resolveSourceMapLocations"],
+        json!(["!**/node_modules/**"])
+    );
+
+    // Reserved keys cannot be merged
+    write_json(
+        configs_dir.join("node.json"),
+        &json!([
+            {
+                "name": "Node",
+                "extends": "node",
+                "enabled": true,
+                "arrayMerge": { "args": { "append": ["x"] } }
+            }
+        ]),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("arrayMerge cannot target 'args'"));
+
+    Ok(())
+}
+
+/// Writes a minimal CMake File API reply describing `targets` as (name, type, artifact)
+fn write_cmake_reply(build_dir: &Path, targets: &[(&str, &str, &str)]) -> Result<()> {
+    let reply_dir = build_dir.join(".cmake/api/v1/reply");
+    fs::create_dir_all(&reply_dir)?;
+
+    let mut target_refs = Vec::new();
+    for (name, target_type, artifact) in targets {
+        let json_file = format!("target-{}-Debug.json", name);
+        write_json(
+            reply_dir.join(&json_file),
+            &json!({ "name": name, "type": target_type, "artifacts": [{ "path": artifact }] }),
+        )?;
+        target_refs.push(json!({ "name": name, "jsonFile": json_file }));
+    }
+    write_json(
+        reply_dir.join("codemodel-v2-abc.json"),
+        &json!({ "configurations": [{ "name": "Debug", "targets": target_refs }] }),
+    )?;
+    // Older index files are ignored in favour of the newest
+    write_json(
+        reply_dir.join("index-2020-01-01T00-00-00-0000.json"),
+        &json!({ "objects": [] }),
+    )?;
+    write_json(
+        reply_dir.join("index-2024-01-01T00-00-00-0000.json"),
+        &json!({ "objects": [{ "kind": "codemodel", "jsonFile": "codemodel-v2-abc.json" }] }),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_configs_from_cmake_presets() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, _configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg", "request": "launch" }] }),
+    )?;
+
+    let presets = temp_dir.path().join("CMakePresets.json");
+    write_json(
+        &presets,
+        &json!({
+            "version": 3,
+            "configurePresets": [
+                { "name": "base", "hidden": true },
+                { "name": "debug", "binaryDir": "${sourceDir}/build/${presetName}" },
+                { "name": "release", "binaryDir": "${sourceDir}/build/${presetName}" }
+            ]
+        }),
+    )?;
+    let build_dir = temp_dir.path().join("build/debug");
+    write_cmake_reply(
+        &build_dir,
+        &[
+            ("app", "EXECUTABLE", "bin/app"),
+            ("core", "STATIC_LIBRARY", "lib/libcore.a"),
+        ],
+    )?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let options = GeneratorOptions::new(base.join("templates.json"), base.join("configs"))
+        .from_cmake(presets, "cpp".to_string());
+    let launch = Generator::with_options(options).generate()?;
+    // The release preset was never configured
+    assert_eq!(launch.warnings().len(), 1, "{:?}", launch.warnings());
+    assert!(launch.warnings()[0].contains("Skipped CMake preset 'release'"));
+    assert!(launch.warnings()[0].contains("cmake --preset release"));
+    let v = serde_json::to_value(launch)?;
+    let configurations = v["configurations"].as_array().unwrap();
+    assert_eq!(configurations.len(), 1);
+    assert_eq!(configurations[0]["name"], "app (debug)");
+    assert_eq!(configurations[0]["type"], "cppdbg");
+    assert_eq!(
+        configurations[0]["program"],
+        build_dir.join("bin/app").to_string_lossy().as_ref()
+    );
+
+    // A build directory without File API replies explains how to create them
+    let options = GeneratorOptions::new(base.join("templates.json"), base.join("configs"))
+        .from_cmake(temp_dir.path().to_path_buf(), "cpp".to_string());
+    let err = Generator::with_options(options).generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("query/codemodel-v2"));
+
+    Ok(())
+}
+
+#[test]
+fn test_configs_from_python_project() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, _configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "python", "type": "debugpy", "program": "${file}" }] }),
+    )?;
+
+    let project = temp_dir.path().join("py");
+    fs::create_dir_all(project.join("tests"))?;
+    fs::create_dir_all(project.join(".venv/lib"))?;
+    fs::write(
+        project.join("pyproject.toml"),
+        "[project]\nname = \"tool\"\n\n[project.scripts]\ntool-cli = \"tool.cli:main\"\n\n[build-system]\nrequires = []\n",
+    )?;
+    fs::write(
+        project.join("serve.py"),
+        "def main():\n    pass\n\nif __name__ == \"__main__\":\n    main()\n",
+    )?;
+    fs::write(project.join("helpers.py"), "X = 1\n")?;
+    fs::write(
+        project.join("tests/test_cli.py"),
+        "def test_x():\n    pass\n",
+    )?;
+    fs::write(project.join(".venv/lib/test_ignored.py"), "")?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let options = GeneratorOptions::new(base.join("templates.json"), base.join("configs"))
+        .from_python(project.clone(), "python".to_string());
+    let v = serde_json::to_value(Generator::with_options(options).generate()?)?;
+    let configurations = v["configurations"].as_array().unwrap();
+    let names: Vec<&str> = configurations
+        .iter()
+        .map(|c| c["name"].as_str().unwrap())
+        .collect();
+    let test_name = format!(
+        "pytest {}",
+        Path::new("tests").join("test_cli.py").display()
+    );
+    assert_eq!(names, [test_name.as_str(), "serve.py", "tool-cli"]);
+
+    assert_eq!(configurations[0]["module"], "pytest");
+    assert_eq!(
+        configurations[0]["args"],
+        json!([project.join("tests/test_cli.py").to_string_lossy()])
+    );
+    assert!(configurations[0].get("program").is_none());
+    assert_eq!(
+        configurations[1]["program"],
+        project.join("serve.py").to_string_lossy().as_ref()
+    );
+    assert_eq!(configurations[2]["module"], "tool.cli");
+    assert!(configurations[2].get("program").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_configs_from_npm_scripts() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, _configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "node", "type": "node", "program": "index.js" }] }),
+    )?;
+
+    let project = temp_dir.path().join("web");
+    fs::create_dir_all(project.join("packages/api"))?;
+    fs::create_dir_all(project.join("packages/docs"))?;
+    write_json(
+        project.join("package.json"),
+        &json!({
+            "name": "root",
+            "scripts": { "dev": "vite", "lint": "eslint ." },
+            "workspaces": ["packages/*"]
+        }),
+    )?;
+    write_json(
+        project.join("packages/api/package.json"),
+        &json!({ "name": "@app/api", "scripts": { "dev": "node server.js" } }),
+    )?;
+    write_json(
+        project.join("packages/docs/package.json"),
+        &json!({ "name": "@app/docs", "scripts": { "build": "docs" } }),
+    )?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let options = GeneratorOptions::new(base.join("templates.json"), base.join("configs"))
+        .from_npm(project.clone(), "node".to_string(), vec!["dev".to_string()]);
+    let v = serde_json::to_value(Generator::with_options(options).generate()?)?;
+    let configurations = v["configurations"].as_array().unwrap();
+    assert_eq!(configurations.len(), 2);
+    assert_eq!(configurations[0]["name"], "npm run dev");
+    assert_eq!(configurations[0]["runtimeExecutable"], "npm");
+    assert_eq!(configurations[0]["runtimeArgs"], json!(["run", "dev"]));
+    assert!(configurations[0].get("program").is_none());
+    assert!(configurations[0].get("cwd").is_none());
+    assert_eq!(configurations[1]["name"], "npm run dev (@app/api)");
+    assert_eq!(
+        configurations[1]["cwd"],
+        project.join("packages/api").to_string_lossy().as_ref()
+    );
+
+    let options = GeneratorOptions::new(base.join("templates.json"), base.join("configs"))
+        .from_npm(project, "node".to_string(), vec!["start".to_string()]);
+    let err = Generator::with_options(options).generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("npm script 'start' not found"));
+
+    Ok(())
+}
+
+#[test]
+fn test_os_override_blocks() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({
+            "templates": [
+                {
+                    "name": "cpp",
+                    "type": "cppdbg",
+                    "program": "${workspaceFolder}/app",
+                    "MIMode": "gdb",
+                    "windows": { "program": "${workspaceFolder}/app.exe" },
+                    "osx": { "MIMode": "lldb" }
+                }
+            ]
+        }),
+    )?;
+    write_json(
+        configs_dir.join("os.json"),
+        &json!([
+            {
+                "name": "OS",
+                "extends": "cpp",
+                "enabled": true,
+                "args": ["--all"],
+                "windows": { "args": ["--win"] },
+                "linux": { "miDebuggerPath": "/usr/bin/gdb" }
+            }
+        ]),
+    )?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let options = GeneratorOptions::new(base.join("templates.json"), base.join("configs"));
+
+    // Passed through verbatim, with config blocks merged over template blocks
+    let v = serde_json::to_value(Generator::with_options(options.clone()).generate()?)?;
+    let config = &v["configurations"][0];
+    assert_eq!(
+        config["windows"],
+        json!({ "program": "${workspaceFolder}/app.exe", "args": ["--win"] })
+    );
+    assert_eq!(config["linux"], json!({ "miDebuggerPath": "/usr/bin/gdb" }));
+    assert_eq!(config["osx"], json!({ "MIMode": "lldb" }));
+
+    // Flattened for a chosen OS
+    let flattened = options.flatten_os(mklaunch::TargetOs::Windows);
+    let v = serde_json::to_value(Generator::with_options(flattened).generate()?)?;
+    let config = &v["configurations"][0];
+    assert_eq!(config["program"], "${workspaceFolder}/app.exe");
+    assert_eq!(config["args"], json!(["--win"]));
+    assert_eq!(config["MIMode"], "gdb");
+    assert!(config.get("windows").is_none());
+    assert!(config.get("osx").is_none());
+
+    // Template blocks must be objects
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg", "linux": "gdb" }] }),
+    )?;
+    let err = Generator::new(base.join("templates.json"), base.join("configs"))
+        .generate()
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("'linux' override must be a JSON object"));
+
+    Ok(())
+}
+
+#[test]
+fn test_config_includes_entries_and_fragments() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }, { "name": "lldb", "type": "lldb" }] }),
+    )?;
+    // Fragments start with '_' and are not config files themselves
+    write_json(
+        configs_dir.join("_base-debug.json"),
+        &json!({ "extends": "cpp", "enabled": true, "args": ["--debug"], "argSets": [] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Base", "include": "_base-debug.json", "enabled": false, "args": ["--base"] },
+            { "name": "Derived", "include": ["Base"], "enabled": true }
+        ]),
+    )?;
+    write_json(
+        configs_dir.join("b.json"),
+        &json!([
+            { "name": "Other File", "include": ["Base", "_base-debug.json"], "enabled": true, "extends": "lldb" }
+        ]),
+    )?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let generator = Generator::new(base.join("templates.json"), base.join("configs"));
+    let v = serde_json::to_value(generator.generate()?)?;
+    let configurations = v["configurations"].as_array().unwrap();
+    // "Base" is disabled and `enabled` is not inherited
+    assert_eq!(configurations.len(), 2);
+
+    let derived = &configurations[0];
+    assert_eq!(derived["name"], "Derived");
+    assert_eq!(derived["type"], "cppdbg");
+    assert_eq!(derived["args"], json!(["--base"]));
+
+    // Later includes and own keys win
+    let other = &configurations[1];
+    assert_eq!(other["name"], "Other File");
+    assert_eq!(other["type"], "lldb");
+    assert_eq!(other["args"], json!(["--debug"]));
+
+    write_json(
+        configs_dir.join("b.json"),
+        &json!([
+            { "name": "Loop A", "include": "Loop B", "enabled": true },
+            { "name": "Loop B", "include": "Loop A", "enabled": true }
+        ]),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("Include cycle: 'Loop A' -> 'Loop B' -> 'Loop A'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_emit_settings_and_snippets() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    create_test_files(temp_dir.path())?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let launch = Generator::new(base.join("templates.json"), base.join("configs")).generate()?;
+
+    let settings: serde_json::Value = serde_json::from_str(&mklaunch::settings_json(
+        &launch,
+        Some(r#"{ "editor.tabSize": 2, "launch": {} }"#),
+    )?)?;
+    assert_eq!(settings["editor.tabSize"], 2);
+    assert_eq!(settings["launch"]["version"], "0.2.0");
+    assert_eq!(
+        settings["launch"]["configurations"]
+            .as_array()
+            .unwrap()
+            .len(),
+        4
+    );
+    assert!(mklaunch::settings_json(&launch, Some("[]")).is_err());
+
+    let snippets: serde_json::Value = serde_json::from_str(&mklaunch::snippets(&launch)?)?;
+    let snippet = &snippets["Debug Basic"];
+    assert_eq!(snippet["prefix"], "launch: Debug Basic");
+    let body: Vec<&str> = snippet["body"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| l.as_str().unwrap())
+        .collect();
+    assert_eq!(body.first(), Some(&"{"));
+    assert!(body.contains(&r#"  "cwd": "\${workspaceFolder}","#));
+
+    Ok(())
+}
+
+#[test]
+fn test_cwd_override_and_check_paths() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({
+            "templates": [
+                { "name": "cpp", "type": "cppdbg", "cwd": "${workspaceFolder}" }
+            ]
+        }),
+    )?;
+    let missing = temp_dir.path().join("missing");
+    write_json(
+        configs_dir.join("cwd.json"),
+        &json!([
+            {
+                "name": "Custom Cwd",
+                "extends": "cpp",
+                "enabled": true,
+                "cwd": missing.to_string_lossy()
+            }
+        ]),
+    )?;
+
+    let launch = Generator::new(templates_manifest.clone(), configs_dir.clone()).generate()?;
+    let v = serde_json::to_value(&launch.configurations()[0])?;
+    assert_eq!(v["cwd"], missing.to_string_lossy().as_ref());
+
+    let checked =
+        GeneratorOptions::new(templates_manifest.clone(), configs_dir.clone()).check_paths(true);
+    let err = Generator::with_options(checked.clone())
+        .generate()
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("cwd does not exist"));
+
+    fs::create_dir(&missing)?;
+    Generator::with_options(checked).generate()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_duplicate_content_lint_and_dedupe() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg", "program": "a.out" }] }),
+    )?;
+    write_json(
+        configs_dir.join("dups.json"),
+        &json!([
+            { "name": "B Copy", "extends": "cpp", "enabled": true, "args": ["-x"] },
+            { "name": "A Original", "extends": "cpp", "enabled": true, "args": ["-x"] },
+            { "name": "C Other", "extends": "cpp", "enabled": true, "args": ["-y"] }
+        ]),
+    )?;
+
+    let warnings = Generator::new(templates_manifest.clone(), configs_dir.clone()).lint()?;
+    assert_eq!(
+        warnings,
+        vec![LintWarning::DuplicateContent {
+            name: "B Copy".to_string(),
+            duplicate_of: "A Original".to_string(),
+        }]
+    );
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir).dedupe(true);
+    let launch = Generator::with_options(options).generate()?;
+    let names: Vec<String> = launch
+        .configurations()
+        .iter()
+        .map(|c| {
+            serde_json::to_value(c).unwrap()["name"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(names, vec!["A Original", "C Other"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_missing_base_args_optional() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    let missing = temp_dir.path().join("local-args.json");
+    write_json(
+        configs_dir.join("local.json"),
+        &json!([
+            {
+                "name": "Local",
+                "extends": "cpp",
+                "enabled": true,
+                "baseArgs": missing.to_string_lossy(),
+                "args": ["--x"]
+            }
+        ]),
+    )?;
+
+    let generator = Generator::new(templates_manifest.clone(), configs_dir.clone());
+    assert!(generator.generate().is_err());
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir.clone())
+        .allow_missing_base_args(true);
+    let launch = Generator::with_options(options).generate()?;
+    let v = serde_json::to_value(&launch.configurations()[0])?;
+    assert_eq!(v["args"], json!(["--x"]));
+    assert_eq!(launch.warnings().len(), 1);
+    assert!(launch.warnings()[0].contains("local-args.json"));
+
+    write_json(
+        configs_dir.join("local.json"),
+        &json!([
+            {
+                "name": "Local",
+                "extends": "cpp",
+                "enabled": true,
+                "baseArgs": missing.to_string_lossy(),
+                "baseArgsOptional": true
+            }
+        ]),
+    )?;
+    let launch = generator.generate()?;
+    assert_eq!(launch.warnings().len(), 1);
+    assert_eq!(generator.input_files()?.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_schema_versions_and_legacy_configs() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({ "schemaVersion": 2, "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("legacy.json"),
+        &json!({ "name": "Legacy", "extends": "cpp", "enabled": true }),
+    )?;
+    write_json(
+        configs_dir.join("versioned.json"),
+        &json!({
+            "schemaVersion": 2,
+            "configurations": [{ "name": "Versioned", "extends": "cpp", "enabled": true }]
+        }),
+    )?;
+
+    let generator = Generator::new(templates_manifest.clone(), configs_dir.clone());
+    let launch = generator.generate()?;
+    assert_eq!(launch.configurations().len(), 2);
+    assert_eq!(launch.warnings().len(), 1);
+    assert!(launch.warnings()[0].contains("legacy single-object format"));
+
+    write_json(
+        configs_dir.join("versioned.json"),
+        &json!({ "schemaVersion": 9, "configurations": [] }),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("upgrade mklaunch"));
+
+    fs::remove_file(&templates_manifest)?;
+    fs::create_dir(templates_manifest.with_file_name("templates"))?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("per-file templates"));
+
+    Ok(())
+}
+
+#[test]
+fn test_migrate_legacy_layout() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let base = temp_dir.path().join(".vscode-debug");
+    let legacy_templates = base.join("templates");
+    let configs_dir = base.join("configs");
+    fs::create_dir_all(&legacy_templates)?;
+    fs::create_dir_all(&configs_dir)?;
+
+    write_json(
+        legacy_templates.join("cpp.json"),
+        &json!({ "type": "cppdbg", "request": "launch" }),
+    )?;
+    write_json(
+        configs_dir.join("old.json"),
+        &json!({ "name": "Old", "extends": "cpp", "enabled": true }),
+    )?;
+    write_json(
+        configs_dir.join("new.json"),
+        &json!([{ "name": "New", "extends": "cpp", "enabled": true }]),
+    )?;
+
+    let templates_manifest = base.join("templates.json");
+    let migrations = mklaunch::migrate(&templates_manifest, &configs_dir)?;
+    assert_eq!(migrations.len(), 2);
+    assert_eq!(migrations[0].backup, base.join("templates.bak"));
+    assert_eq!(migrations[1].path, configs_dir.join("old.json"));
+    assert!(configs_dir.join("old.json.bak").is_file());
+
+    let launch = Generator::new(templates_manifest.clone(), configs_dir.clone()).generate()?;
+    assert_eq!(launch.configurations().len(), 2);
+    assert!(launch.warnings().is_empty());
+
+    assert!(mklaunch::migrate(&templates_manifest, &configs_dir)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_import_launch_json_round_trips() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let configurations = json!([
+        {
+            "name": "App",
+            "type": "cppdbg",
+            "request": "launch",
+            "program": "${workspaceFolder}/build/app",
+            "MIMode": "gdb",
+            "args": ["--fast"]
+        },
+        {
+            "name": "App (slow)",
+            "type": "cppdbg",
+            "request": "launch",
+            "program": "${workspaceFolder}/build/app",
+            "MIMode": "gdb",
+            "cwd": "/tmp",
+            "args": ["--slow"]
+        },
+        {
+            "name": "Tool",
+            "type": "cppdbg",
+            "request": "launch",
+            "program": "${workspaceFolder}/build/tool",
+            "MIMode": "gdb",
+            "args": []
+        },
+        { "name": "Attach", "type": "lldb", "request": "attach", "pid": 1, "args": [] }
+    ]);
+    let compounds = json!([{ "name": "Both", "configurations": ["App", "Tool"] }]);
+    let launch_path = temp_dir.path().join("launch.json");
+    fs::write(
+        &launch_path,
+        format!(
+            "{{\n  // Hand-written\n  \"version\": \"0.2.0\",\n  \"configurations\": {},\n  \"compounds\": {},\n}}",
+            serde_json::to_string_pretty(&configurations)?,
+            serde_json::to_string(&compounds)?
+        ),
+    )?;
+
+    let base = temp_dir.path().join(".mklaunch");
+    let templates_manifest = base.join("templates.json");
+    let configs_dir = base.join("configs");
+    let import = mklaunch::import_launch(&launch_path, &templates_manifest, &configs_dir)?;
+    assert_eq!(import.configurations, 4);
+    assert_eq!(
+        import.templates,
+        vec!["cppdbg-app", "cppdbg-tool", "lldb-attach"]
+    );
+    assert_eq!(import.settings_file, Some(base.join("settings.json")));
+    assert!(import.warnings.is_empty());
+    // Written in the layout `mklaunch fmt` keeps
+    assert!(
+        mklaunch::format_inputs(
+            std::slice::from_ref(&templates_manifest),
+            std::slice::from_ref(&configs_dir),
+            true
+        )?
+        .is_empty()
+    );
+
+    let launch = Generator::new(templates_manifest.clone(), configs_dir.clone()).generate()?;
+    let generated = serde_json::to_value(&launch)?;
+    for expected in configurations.as_array().unwrap() {
+        let found = generated["configurations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|c| c["name"] == expected["name"])
+            .unwrap();
+        assert_eq!(found, expected);
+    }
+    assert_eq!(generated["compounds"], compounds);
+
+    assert!(mklaunch::import_launch(&launch_path, &templates_manifest, &configs_dir).is_err());
+
+    // An existing settings.json is left alone
+    fs::remove_file(&templates_manifest)?;
+    fs::remove_file(configs_dir.join("imported.json"))?;
+    let import = mklaunch::import_launch(&launch_path, &templates_manifest, &configs_dir)?;
+    assert_eq!(import.settings_file, None);
+    assert_eq!(import.warnings.len(), 1);
+    assert!(import.warnings[0].contains("'launchKeys.compounds'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_strict_config_rejects_unknown_keys() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("typo.json"),
+        &json!([
+            {
+                "$schema": "../configs.schema.json",
+                "name": "Typo",
+                "extends": "cpp",
+                "enabled": true,
+                "arg": ["--x"]
+            }
+        ]),
+    )?;
+
+    // Unknown keys are ignored by default
+    Generator::new(templates_manifest.clone(), configs_dir.clone()).generate()?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir).strict_config(true);
+    let err = Generator::with_options(options).generate().unwrap_err();
+    let diagnostic = &err.downcast_ref::<Diagnostics>().unwrap()[0];
+    assert_eq!(
+        diagnostic.message,
+        "Unknown key 'arg' in configuration entry; did you mean 'args'?"
+    );
+    assert_eq!(diagnostic.pointer.as_deref(), Some("/0/arg"));
+
+    Ok(())
+}
+
+#[test]
+fn test_structured_args_are_normalized() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    let base_args = temp_dir.path().join("base.json");
+    write_json(
+        &base_args,
+        &json!({ "args": [{ "flag": "-j", "value": 4 }] }),
+    )?;
+    write_json(
+        configs_dir.join("mixed.json"),
+        &json!([
+            {
+                "name": "Mixed",
+                "extends": "cpp",
+                "enabled": true,
+                "baseArgs": base_args.to_string_lossy(),
+                "args": [
+                    "--plain",
+                    3,
+                    true,
+                    { "flag": "--input", "value": "${env:DATA}" },
+                    { "flag": "--level", "value": 2, "separator": "=" },
+                    { "flag": "--verbose" }
+                ]
+            }
+        ]),
+    )?;
+
+    let generator = Generator::new(templates_manifest, configs_dir.clone());
+    let launch = generator.generate()?;
+    let v = serde_json::to_value(&launch.configurations()[0])?;
+    assert_eq!(
+        v["args"],
+        json!([
+            "-j",
+            "4",
+            "--plain",
+            "3",
+            "true",
+            "--input",
+            "${env:DATA}",
+            "--level=2",
+            "--verbose"
+        ])
+    );
+
+    write_json(
+        configs_dir.join("mixed.json"),
+        &json!([{ "name": "Bad", "extends": "cpp", "enabled": true, "args": ["ok", null] }]),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("args[1]: unsupported arg null"));
+
+    Ok(())
+}
+
+#[test]
+fn test_template_contracts() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+
+    write_json(
+        &templates_manifest,
+        &json!({
+            "templates": [
+                {
+                    "name": "cpp",
+                    "type": "cppdbg",
+                    "allowOverrides": ["envFile"],
+                    "requireFromConfig": ["args"]
+                }
+            ]
+        }),
+    )?;
+    let config = |extra: serde_json::Value| {
+        let mut entry = json!({ "name": "Guarded", "extends": "cpp", "enabled": true });
+        entry
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        json!([entry])
+    };
+    let generator = Generator::new(templates_manifest.clone(), configs_dir.clone());
+
+    write_json(
+        configs_dir.join("c.json"),
+        &config(json!({ "args": ["-x"] })),
+    )?;
+    let launch = generator.generate()?;
+    let v = serde_json::to_value(&launch.configurations()[0])?;
+    assert!(v.get("allowOverrides").is_none());
+
+    write_json(configs_dir.join("c.json"), &config(json!({})))?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("'Guarded' must set args required by template 'cpp'"));
+
+    write_json(
+        configs_dir.join("c.json"),
+        &config(json!({ "args": [], "cwd": "/tmp" })),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("sets 'cwd', which template 'cpp' does not allow"));
+
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg", "allowOverrides": ["cdw"] }] }),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("Unknown key 'cdw' in 'allowOverrides'; did you mean 'cwd'?")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_cache_reuses_unchanged_files() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    let base_args = temp_dir.path().join("base.json");
+    write_json(&base_args, &json!({ "args": ["--base"] }))?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true, "args": ["-a"] }]),
+    )?;
+    write_json(
+        configs_dir.join("b.json"),
+        &json!([{ "name": "B", "extends": "cpp", "enabled": true, "baseArgs": base_args }]),
+    )?;
+
+    let generator = Generator::with_options(
+        GeneratorOptions::new(templates_manifest.clone(), configs_dir.clone()).cache(true),
+    );
+    let args = |launch: &mklaunch::LaunchJson| -> Result<Vec<serde_json::Value>> {
+        Ok(serde_json::to_value(launch.configurations())?
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["args"].clone())
+            .collect())
+    };
+    let first = generator.generate()?;
+    assert_eq!(args(&first)?, vec![json!(["-a"]), json!(["--base"])]);
+
+    // Tamper with the cached result of b.json to observe whether it is reused
+    let cache_path = generator.cache_path();
+    let cache = fs::read_to_string(&cache_path)?.replace("--base", "--cached");
+    fs::write(&cache_path, cache)?;
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true, "args": ["-A"] }]),
+    )?;
+    let second = generator.generate()?;
+    assert_eq!(args(&second)?, vec![json!(["-A"]), json!(["--cached"])]);
+
+    // A changed baseArgs file invalidates the files that read it
+    write_json(&base_args, &json!({ "args": ["--base2"] }))?;
+    let third = generator.generate()?;
+    assert_eq!(args(&third)?, vec![json!(["-A"]), json!(["--base2"])]);
+
+    // So does a changed templates manifest, for every file
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "lldb" }] }),
+    )?;
+    let fourth = generator.generate()?;
+    assert!(
+        fourth
+            .configurations()
+            .iter()
+            .all(|c| serde_json::to_value(c).unwrap()["type"] == "lldb")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_root_resolves_relative_paths() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg", "environment": [] }] }),
+    )?;
+    fs::create_dir_all(temp_dir.path().join("args"))?;
+    write_json(
+        temp_dir.path().join("args/base.json"),
+        &json!({ "args": ["--base"] }),
+    )?;
+    fs::write(temp_dir.path().join(".env"), "MODE=debug\n")?;
+    fs::create_dir_all(temp_dir.path().join("run"))?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{
+            "name": "A",
+            "extends": "cpp",
+            "enabled": true,
+            "baseArgs": "args/base.json",
+            "envFile": "${workspaceFolder}/.env",
+            "cwd": "${workspaceFolder}/run"
+        }]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir).check_paths(true);
+    assert!(Generator::with_options(options.clone()).generate().is_err());
+
+    let generator = Generator::with_options(options.root(temp_dir.path().to_path_buf()));
+    let launch = generator.generate()?;
+    let config = &launch.configurations()[0];
+    assert_eq!(config.args(), ["--base"]);
+    assert_eq!(
+        config.get("environment"),
+        Some(json!([{ "name": "MODE", "value": "debug" }]))
+    );
+    assert_eq!(config.get("cwd"), Some(json!("${workspaceFolder}/run")));
+    assert!(
+        generator
+            .input_files()?
+            .contains(&temp_dir.path().join("args/base.json"))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_duplicate_name_policies() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    let entry = |name: &str, arg: &str| json!({ "name": name, "extends": "cpp", "enabled": true, "args": [arg] });
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([entry("Debug", "a"), entry("Debug (2)", "b")]),
+    )?;
+    write_json(
+        configs_dir.join("b.json"),
+        &json!([entry("Debug", "c"), entry("Debug", "d")]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir);
+    let err = Generator::with_options(options.clone())
+        .generate()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Duplicate configuration name 'Debug'")
+    );
+
+    let launch = Generator::with_options(options.clone().duplicate_names(DuplicateNames::Suffix))
+        .generate()?;
+    let named: Vec<(&str, &str)> = launch
+        .configurations()
+        .iter()
+        .map(|c| (c.name(), c.args()[0].as_str()))
+        .collect();
+    assert_eq!(
+        named,
+        [
+            ("Debug", "a"),
+            ("Debug (2)", "b"),
+            ("Debug (3)", "c"),
+            ("Debug (4)", "d")
+        ]
+    );
+    assert_eq!(launch.warnings().len(), 2);
+
+    let launch =
+        Generator::with_options(options.duplicate_names(DuplicateNames::Skip)).generate()?;
+    let names: Vec<&str> = launch.configurations().iter().map(|c| c.name()).collect();
+    assert_eq!(names, ["Debug", "Debug (2)"]);
+    assert!(launch.warnings()[0].contains("Skipped duplicate configuration 'Debug'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_key_order_follows_template() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    fs::write(
+        &templates_manifest,
+        r#"{
+  "templates": [
+    {
+      "name": "cpp",
+      "type": "cppdbg",
+      "preLaunchTask": "build",
+      "MIMode": "gdb",
+      "setupCommands": [{ "text": "-enable-pretty-printing", "ignoreFailures": true }],
+      "cwd": "${workspaceFolder}"
+    }
+  ]
+}"#,
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true, "linux": { "MIMode": "gdb" } }]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir);
+    let keys = |text: &str, keys: &[&str]| -> Vec<usize> {
+        keys.iter()
+            .map(|k| text.find(&format!("\"{}\"", k)).unwrap())
+            .collect()
+    };
+    let order = ["preLaunchTask", "MIMode", "setupCommands", "cwd", "linux"];
+
+    let alphabetical = Generator::with_options(options.clone())
+        .generate()?
+        .to_pretty_string()?;
+    let positions = keys(&alphabetical, &order);
+    assert!(positions[1] < positions[3] && positions[3] < positions[0]);
+
+    let generator = Generator::with_options(options.key_order(KeyOrder::Template));
+    let templated = generator.generate()?.to_pretty_string()?;
+    let positions = keys(&templated, &order);
+    assert!(positions.windows(2).all(|w| w[0] < w[1]), "{}", templated);
+    let nested = keys(&templated, &["text", "ignoreFailures"]);
+    assert!(nested[0] < nested[1]);
+
+    // The same JSON either way
+    let a: serde_json::Value = serde_json::from_str(&alphabetical)?;
+    let t: serde_json::Value = serde_json::from_str(&templated)?;
+    assert_eq!(a, t);
+
+    Ok(())
+}
+
+#[test]
+fn test_defaults_file_applies_to_every_configuration() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg", "program": "${workspaceFolder}/a.out" },
+            { "name": "node", "type": "node", "internalConsoleOptions": "neverOpen" }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "A", "extends": "cpp", "enabled": true },
+            { "name": "B", "extends": "node", "enabled": true }
+        ]),
+    )?;
+    let defaults_path = templates_manifest.parent().unwrap().join("defaults.json");
+    write_json(
+        &defaults_path,
+        &json!({
+            "internalConsoleOptions": "openOnSessionStart",
+            "presentation": { "group": "app" },
+            "stopAtEntry": true
+        }),
+    )?;
+
+    let generator = Generator::new(templates_manifest.clone(), configs_dir.clone());
+    let launch = generator.generate()?;
+    let [a, b] = launch.configurations() else {
+        panic!("expected two configurations");
+    };
+    assert_eq!(
+        a.get("internalConsoleOptions"),
+        Some(json!("openOnSessionStart"))
+    );
+    assert_eq!(a.get("presentation"), Some(json!({ "group": "app" })));
+    assert_eq!(a.get("stopAtEntry"), Some(json!(true)));
+    assert_eq!(b.get("internalConsoleOptions"), Some(json!("neverOpen")));
+    assert!(generator.input_files()?.contains(&defaults_path));
+
+    write_json(&defaults_path, &json!({ "args": ["-v"] }))?;
+    let err = Generator::new(templates_manifest, configs_dir)
+        .generate()
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("must not set 'args'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_presentation_group_order_hidden() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{
+            "name": "cpp",
+            "type": "cppdbg",
+            "presentation": { "group": "default", "panel": "new" }
+        }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "A", "extends": "cpp", "enabled": true, "group": "app", "order": 2 },
+            { "name": "B", "extends": "cpp", "enabled": true, "hidden": true }
+        ]),
+    )?;
+    fs::create_dir_all(configs_dir.join("tools/bench"))?;
+    write_json(
+        configs_dir.join("tools/bench/perf.json"),
+        &json!([{ "name": "C", "extends": "cpp", "enabled": true }]),
+    )?;
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&configs_dir, configs_dir.join("tools/loop"))?;
+
+    // Subdirectories are only read with --group-by-dir
+    let options = GeneratorOptions::new(templates_manifest, configs_dir);
+    let launch = Generator::with_options(options.clone()).generate()?;
+    let [a, b] = launch.configurations() else {
+        panic!("expected two configurations");
+    };
+    assert_eq!(
+        a.get("presentation"),
+        Some(json!({ "group": "app", "order": 2, "panel": "new" }))
+    );
+    assert_eq!(
+        b.get("presentation"),
+        Some(json!({ "group": "default", "hidden": true, "panel": "new" }))
+    );
+
+    // The symlink back to the configs directory is not followed
+    let launch = Generator::with_options(options.group_by_dir(true)).generate()?;
+    let [a, b, c] = launch.configurations() else {
+        panic!("expected three configurations");
+    };
+    assert_eq!(a.get("presentation").unwrap()["group"], "app");
+    assert_eq!(b.get("presentation").unwrap()["group"], "default");
+    assert_eq!(c.get("presentation").unwrap()["group"], "tools/bench");
+
+    Ok(())
+}
+
+#[test]
+fn test_doctor_reports_missing_paths_and_tasks() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{
+            "name": "cpp",
+            "type": "cppdbg",
+            "MIMode": "gdb",
+            "miDebuggerPath": "${workspaceFolder}/tools/gdb"
+        }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Good", "extends": "cpp", "enabled": true, "linux": { "program": "${workspaceFolder}/app" }, "osx": { "program": "${workspaceFolder}/app" }, "windows": { "program": "${workspaceFolder}/app" } },
+            { "name": "Bad", "extends": "cpp", "enabled": true, "envFile": { "path": "${workspaceFolder}/.env" } }
+        ]),
+    )?;
+    fs::create_dir_all(temp_dir.path().join("tools"))?;
+    fs::write(temp_dir.path().join("tools/gdb"), "")?;
+    fs::write(temp_dir.path().join("app"), "")?;
+    fs::write(temp_dir.path().join(".env"), "A=1\n")?;
+    fs::create_dir_all(temp_dir.path().join(".vscode"))?;
+    fs::write(
+        temp_dir.path().join(".vscode/tasks.json"),
+        "{ // build tasks\n \"tasks\": [{ \"label\": \"build\" },] }",
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest.clone(), configs_dir.clone())
+        .root(temp_dir.path().to_path_buf());
+    let report = Generator::with_options(options).doctor()?;
+    let [bad, good] = report.as_slice() else {
+        panic!("expected two configurations");
+    };
+    assert_eq!(good.name, "Good");
+    assert!(good.is_ok(), "{:?}", good.problems);
+    assert_eq!(good.checked.len(), 2);
+    assert!(bad.is_ok(), "{:?}", bad.problems);
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{
+            "name": "Bad",
+            "extends": "cpp",
+            "enabled": true,
+            "linux": { "program": "${workspaceFolder}/missing", "preLaunchTask": "compile" },
+            "osx": { "program": "${workspaceFolder}/missing", "preLaunchTask": "compile" },
+            "windows": { "program": "${workspaceFolder}/missing", "preLaunchTask": "compile" }
+        }]),
+    )?;
+    let options =
+        GeneratorOptions::new(templates_manifest, configs_dir).root(temp_dir.path().to_path_buf());
+    let report = Generator::with_options(options).doctor()?;
+    assert_eq!(report[0].problems.len(), 2);
+    assert!(report[0].problems[0].starts_with("program not found"));
+    assert!(report[0].problems[1].contains("preLaunchTask 'compile' is not defined"));
+
+    Ok(())
+}
+
+#[test]
+fn test_templates_from_multiple_manifests() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    let shared_dir = temp_dir.path().join("shared");
+    fs::create_dir_all(&shared_dir)?;
+    write_json(
+        shared_dir.join("node.templates.json"),
+        &json!({ "templates": [{ "name": "node", "type": "node" }] }),
+    )?;
+    write_json(
+        shared_dir.join("python.templates.json"),
+        &json!({ "templates": [{ "name": "python", "type": "debugpy" }] }),
+    )?;
+    fs::write(shared_dir.join("notes.json"), "not a manifest")?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "A", "extends": "cpp", "enabled": true },
+            { "name": "B", "extends": "node", "enabled": true },
+            { "name": "C", "extends": "python", "enabled": true }
+        ]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest.clone(), configs_dir.clone())
+        .templates(shared_dir.clone());
+    let generator = Generator::with_options(options.clone());
+    let launch = generator.generate()?;
+    let types: Vec<&str> = launch
+        .configurations()
+        .iter()
+        .map(|c| c.debug_type())
+        .collect();
+    assert_eq!(types, ["cppdbg", "node", "debugpy"]);
+    assert!(
+        generator
+            .input_files()?
+            .contains(&shared_dir.join("python.templates.json"))
+    );
+
+    write_json(
+        shared_dir.join("cpp.templates.json"),
+        &json!({ "templates": [{ "name": "cpp", "type": "lldb" }] }),
+    )?;
+    let err = Generator::with_options(options).generate().unwrap_err();
+    let message = format!("{:#}", err);
+    assert!(message.contains("Duplicate template name 'cpp'"));
+    assert!(message.contains("cpp.templates.json"));
+
+    Ok(())
+}
+
+#[test]
+fn test_sync_templates_rejects_non_https_urls() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, _) = create_dirs(temp_dir.path())?;
+    let settings_path = templates_manifest.parent().unwrap().join("settings.json");
+    for url in [
+        "http://example.com/t.json",
+        "file:///etc/t.json",
+        "ftp://example.com/t.json",
+    ] {
+        write_json(
+            &settings_path,
+            &json!({ "templateSources": [{ "name": "shared", "url": url }] }),
+        )?;
+        let err =
+            mklaunch::sync_templates(&templates_manifest, false, None, &ProcessRunner).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("must be downloaded over https"),
+            "{:#}",
+            err
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_sync_templates_from_git() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let upstream = temp_dir.path().join("upstream");
+    fs::create_dir_all(&upstream)?;
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(&upstream)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    };
+    if !git(&["init", "-q"]) {
+        // git is not installed
+        return Ok(());
+    }
+    write_json(
+        upstream.join("shared.json"),
+        &json!({ "templates": [{ "name": "node", "type": "node" }] }),
+    )?;
+    assert!(git(&["add", "."]));
+    assert!(git(&["commit", "-q", "-m", "first"]));
+
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    let mklaunch_dir = templates_manifest.parent().unwrap();
+    write_json(
+        mklaunch_dir.join("settings.json"),
+        &json!({ "templateSources": [
+            { "name": "shared", "git": upstream.display().to_string(), "path": "shared.json" }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "node", "enabled": true }]),
+    )?;
+
+    let generator = Generator::new(templates_manifest.clone(), configs_dir.clone());
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("has not been fetched"));
+
+    let synced = mklaunch::sync_templates(&templates_manifest, false, None, &ProcessRunner)?;
+    assert_eq!(synced.len(), 1);
+    assert_eq!(
+        synced[0].path,
+        mklaunch_dir.join("sources/shared.templates.json")
+    );
+    let first = synced[0]
+        .rev
+        .clone()
+        .expect("git sources are pinned by commit");
+    assert!(mklaunch_dir.join("sources.lock.json").exists());
+    let launch = generator.generate()?;
+    assert_eq!(launch.configurations()[0].debug_type(), "node");
+
+    // A new upstream commit is only picked up with --update
+    write_json(
+        upstream.join("shared.json"),
+        &json!({ "templates": [{ "name": "node", "type": "pwa-node" }] }),
+    )?;
+    assert!(git(&["commit", "-q", "-am", "second"]));
+    let synced = mklaunch::sync_templates(&templates_manifest, false, None, &ProcessRunner)?;
+    assert_eq!(synced[0].rev.as_deref(), Some(first.as_str()));
+    assert_eq!(
+        generator.generate()?.configurations()[0].debug_type(),
+        "node"
+    );
+    let synced = mklaunch::sync_templates(&templates_manifest, true, None, &ProcessRunner)?;
+    assert_ne!(synced[0].rev.as_deref(), Some(first.as_str()));
+    assert_eq!(
+        generator.generate()?.configurations()[0].debug_type(),
+        "pwa-node"
+    );
+
+    // Local edits to the fetched copy are detected
+    write_json(
+        mklaunch_dir.join("sources/shared.templates.json"),
+        &json!({ "templates": [{ "name": "node", "type": "edited" }] }),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("does not match its pinned hash"));
+
+    // A repo that looks like an option is passed to git as a repository
+    write_json(
+        mklaunch_dir.join("settings.json"),
+        &json!({ "templateSources": [{ "name": "shared", "git": "--upload-pack=true" }] }),
+    )?;
+    let err =
+        mklaunch::sync_templates(&templates_manifest, true, None, &ProcessRunner).unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("-- --upload-pack=true"),
+        "{:#}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_reports_every_unknown_extends() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp-gdb", "type": "cppdbg" },
+            { "name": "node", "type": "node" }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "A", "extends": "cpp-gdb", "enabled": true },
+            { "name": "B", "extends": "cpp-gbd", "enabled": true },
+            { "name": "C", "extends": "missing", "enabled": false }
+        ]),
+    )?;
+    write_json(
+        configs_dir.join("b.json"),
+        &json!([
+            { "name": "D", "extends": "python", "enabled": true },
+            { "name": "E", "enabled": true }
+        ]),
+    )?;
+
+    let diagnostics = Generator::new(templates_manifest, configs_dir.clone()).validate()?;
+    assert_eq!(diagnostics.len(), 3, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].path, configs_dir.join("a.json"));
+    assert_eq!(diagnostics[0].pointer.as_deref(), Some("/1/extends"));
+    assert_eq!(
+        diagnostics[0].message,
+        "'B' extends unknown template 'cpp-gbd'; did you mean 'cpp-gdb'?"
+    );
+    assert_eq!(diagnostics[1].pointer.as_deref(), Some("/0/extends"));
+    assert_eq!(
+        diagnostics[1].message,
+        "'D' extends unknown template 'python'"
+    );
+    assert_eq!(diagnostics[2].path, configs_dir.join("b.json"));
+    assert!(diagnostics[2].message.contains("extends"));
+
+    Ok(())
+}
+
+#[test]
+fn test_output_formats() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{
+            "name": "cpp",
+            "type": "cppdbg",
+            "setupCommands": [{ "text": "-enable-pretty-printing" }],
+            "env-vars": { "A": "x \"quoted\": y" },
+            "environment": []
+        }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true }]),
+    )?;
+
+    let render = |format| -> Result<String> {
+        let options = GeneratorOptions::new(templates_manifest.clone(), configs_dir.clone())
+            .output_format(format);
+        let mut out = Vec::new();
+        Generator::with_options(options).generate_to(&mut out)?;
+        Ok(String::from_utf8(out)?)
+    };
+
+    let json = render(OutputFormat::Json)?;
+    assert!(json.starts_with('{'));
+    let jsonc = render(OutputFormat::Jsonc)?;
+    assert_eq!(
+        jsonc,
+        format!(
+            "// Generated by mklaunch; do not edit. Changes are overwritten on the next run.\n{}",
+            json
+        )
+    );
+
+    let json5 = render(OutputFormat::Json5)?;
+    assert!(json5.starts_with("// Generated by mklaunch"));
+    // VSCode rejects unquoted keys, so they stay quoted
+    assert!(json5.contains("\n  \"version\": \"0.2.0\",\n"));
+    assert!(json5.contains("\"env-vars\": {\n        \"A\": \"x \\\"quoted\\\": y\",\n      },"));
+    assert!(json5.contains("\"args\": [],"));
+    assert!(json5.contains("\"environment\": [],"));
+    assert!(json5.contains("\"text\": \"-enable-pretty-printing\",\n        },\n      ],"));
+    assert!(json5.trim_end().ends_with("],\n}"));
+
+    Ok(())
+}
+
+#[test]
+fn test_generation_guard_and_atomic_write() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    let generator = Generator::new(templates_manifest, configs_dir);
+
+    let guard = generator.guard()?;
+    let acquired = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let waiter = {
+        let acquired = acquired.clone();
+        let generator = Generator::new(
+            temp_dir.path().join(".mklaunch/templates.json"),
+            temp_dir.path().join(".mklaunch/configs"),
+        );
+        std::thread::spawn(move || -> Result<()> {
+            let _guard = generator.guard()?;
+            acquired.store(true, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        })
+    };
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert!(!acquired.load(std::sync::atomic::Ordering::SeqCst));
+    drop(guard);
+    waiter.join().unwrap()?;
+    assert!(acquired.load(std::sync::atomic::Ordering::SeqCst));
+
+    let output = temp_dir.path().join(".vscode/launch.json");
+    mklaunch::write_atomic(&output, b"first")?;
+    mklaunch::write_atomic(&output, b"second")?;
+    assert_eq!(fs::read_to_string(&output)?, "second");
+    let leftovers: Vec<_> = fs::read_dir(temp_dir.path().join(".vscode"))?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name() != "launch.json")
+        .collect();
+    assert!(leftovers.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_preserve_top_level_keys() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true }]),
+    )?;
+    let existing = r#"{
+  // maintained by hand
+  "version": "0.2.0",
+  "configurations": [{ "name": "Old", "type": "node" }],
+  "inputs": [{ "id": "port", "type": "promptString" },],
+  "compounds": []
+}"#;
+
+    let mut launch = Generator::new(templates_manifest.clone(), configs_dir.clone()).generate()?;
+    let kept = launch.preserve_keys_from(existing)?;
+    assert_eq!(kept, ["compounds", "inputs"]);
+    let written: serde_json::Value = serde_json::from_str(&launch.to_pretty_string()?)?;
+    assert_eq!(written["configurations"][0]["name"], "A");
+    assert_eq!(written["configurations"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        written["inputs"],
+        json!([{ "id": "port", "type": "promptString" }])
+    );
+    assert_eq!(written["compounds"], json!([]));
+
+    // The next run reads back the JSON5 it wrote
+    let json5 = launch.to_string_as(OutputFormat::Json5)?;
+    let mut next = Generator::new(templates_manifest, configs_dir).generate()?;
+    assert_eq!(next.preserve_keys_from(&json5)?, ["compounds", "inputs"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_unmanaged_configurations() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true }]),
+    )?;
+    let launch = Generator::new(templates_manifest, configs_dir).generate()?;
+
+    let existing = r#"{
+  // maintained by hand
+  "version": "0.2.0",
+  "configurations": [
+    { "name": "Attach", "type": "node" },
+    { "name": "A", "type": "cppdbg" },
+    { "type": "python" },
+  ]
+}"#;
+    assert_eq!(launch.unmanaged_in(existing)?, ["Attach", "(unnamed)"]);
+    assert_eq!(
+        launch.unmanaged_in(r#"{ "launch": { "configurations": [{ "name": "B" }] } }"#)?,
+        ["B"]
+    );
+    assert!(launch.unmanaged_in("")?.is_empty());
+    assert!(launch.unmanaged_in("not json").is_err());
+
+    // JSON5 output has trailing commas, and hand-edited files may drop the quotes of keys
+    assert!(
+        launch
+            .unmanaged_in(&launch.to_string_as(OutputFormat::Json5)?)?
+            .is_empty()
+    );
+    assert_eq!(
+        launch.unmanaged_in("{ configurations: [{ name: \"Hand\", stopAtEntry: true },], }")?,
+        ["Hand"]
+    );
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_args_from_command() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    fs::write(temp_dir.path().join("case.txt"), "smoke")?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            {
+                "name": "A",
+                "extends": "cpp",
+                "enabled": true,
+                "args": ["-v"],
+                "argsFromCommand": "printf '[\"--case\", \"%s\", 3]' \"$(cat case.txt)\""
+            },
+            { "name": "B", "extends": "cpp", "enabled": true, "argsFromCommand": "echo one  two" }
+        ]),
+    )?;
+
+    let options =
+        GeneratorOptions::new(templates_manifest, configs_dir).root(temp_dir.path().to_path_buf());
+    let err = Generator::with_options(options.clone())
+        .generate()
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("pass --allow-exec"));
+
+    let launch = Generator::with_options(options.clone().allow_exec(true)).generate()?;
+    assert_eq!(
+        launch.configurations()[0].args(),
+        ["-v", "--case", "smoke", "3"]
+    );
+    assert_eq!(launch.configurations()[1].args(), ["one", "two"]);
+
+    // Command output is re-read on every cached run
+    let cached = options.allow_exec(true).cache(true);
+    Generator::with_options(cached.clone()).generate()?;
+    fs::write(temp_dir.path().join("case.txt"), "full")?;
+    let launch = Generator::with_options(cached).generate()?;
+    assert_eq!(launch.configurations()[0].args()[2], "full");
+
+    Ok(())
+}
+
+#[test]
+fn test_bom_and_utf16_inputs() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    let manifest = r#"{ "templates": [{ "name": "cpp", "type": "cppdbg" }] }"#;
+    fs::write(
+        &templates_manifest,
+        [b"\xEF\xBB\xBF", manifest.as_bytes()].concat(),
+    )?;
+
+    let config = r#"[{ "name": "Débogage", "extends": "cpp", "enabled": true, "args": ["-v"] }]"#;
+    let mut utf16le = vec![0xFF, 0xFE];
+    utf16le.extend(config.encode_utf16().flat_map(u16::to_le_bytes));
+    fs::write(configs_dir.join("a.json"), utf16le)?;
+    let utf16be: Vec<u8> = config
+        .replace("Débogage", "B")
+        .encode_utf16()
+        .flat_map(u16::to_be_bytes)
+        .collect();
+    fs::write(configs_dir.join("b.json"), utf16be)?;
+
+    let launch = Generator::new(templates_manifest, configs_dir).generate()?;
+    let names: Vec<_> = launch.configurations().iter().map(|c| c.name()).collect();
+    assert_eq!(names, ["B", "Débogage"]);
+    assert_eq!(launch.configurations()[1].args(), ["-v"]);
+
+    // VSCode on Windows may save launch.json with a BOM
+    let launch_path = temp_dir.path().join("launch.json");
+    let launch_json = r#"{ "version": "0.2.0", "configurations": [{ "name": "Run", "type": "node", "request": "launch" }] }"#;
+    fs::write(
+        &launch_path,
+        [b"\xEF\xBB\xBF", launch_json.as_bytes()].concat(),
+    )?;
+    let imported = temp_dir.path().join("imported");
+    let import = mklaunch::import_launch(
+        &launch_path,
+        &imported.join("templates.json"),
+        &imported.join("configs"),
+    )?;
+    assert_eq!(import.configurations, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_generation_summary() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg" },
+            { "name": "node", "type": "node" }
+        ] }),
+    )?;
+    let base_args_path = temp_dir.path().join("base.json");
+    write_json(&base_args_path, &json!({ "args": ["--base"] }))?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "A", "extends": "cpp", "enabled": true, "baseArgs": base_args_path },
+            { "name": "A", "extends": "cpp", "enabled": true },
+            { "name": "B", "extends": "cpp", "enabled": false }
+        ]),
+    )?;
+    write_json(configs_dir.join("b.json"), &json!([]))?;
+
+    let expected = GenerationSummary {
+        templates: 2,
+        files: 2,
+        enabled: 2,
+        disabled: 1,
+        discovered: 0,
+        skipped: 1,
+        base_args: 1,
+        warnings: 1,
+        failed: 0,
+    };
+    let options = GeneratorOptions::new(templates_manifest, configs_dir)
+        .duplicate_names(DuplicateNames::Skip);
+    for options in [
+        options.clone(),
+        options.clone().cache(true),
+        options.cache(true),
+    ] {
+        let launch = Generator::with_options(options).generate()?;
+        assert_eq!(launch.configurations().len(), 1);
+        assert_eq!(launch.summary(), &expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_unset_inherited_keys() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{
+            "name": "cpp",
+            "type": "cppdbg",
+            "program": "${workspaceFolder}/a.out",
+            "preLaunchTask": "build",
+            "externalConsole": true,
+            "cwd": "${workspaceFolder}"
+        }] }),
+    )?;
+    write_json(
+        templates_manifest.parent().unwrap().join("defaults.json"),
+        &json!({ "stopAtEntry": true }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{
+            "name": "A",
+            "extends": "cpp",
+            "enabled": true,
+            "unset": ["preLaunchTask", "externalConsole", "program", "stopAtEntry", "cwd"],
+            "cwd": "build"
+        }]),
+    )?;
+
+    let launch = Generator::new(templates_manifest.clone(), configs_dir.clone()).generate()?;
+    let config = &launch.configurations()[0];
+    for key in ["preLaunchTask", "externalConsole", "program", "stopAtEntry"] {
+        assert_eq!(config.get(key), None, "{} should be unset", key);
+    }
+    assert_eq!(config.get("cwd"), Some(json!("build")));
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true, "unset": ["type"] }]),
+    )?;
+    let err = Generator::new(templates_manifest, configs_dir)
+        .generate()
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("'A' cannot unset 'type'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_find_workspaces() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    for dir in [
+        "",
+        "packages/a",
+        "packages/b/nested",
+        "node_modules/c",
+        ".git/d",
+    ] {
+        fs::create_dir_all(temp_dir.path().join(dir).join(".mklaunch"))?;
+    }
+    fs::create_dir_all(temp_dir.path().join("packages/empty"))?;
+
+    let found = mklaunch::find_workspaces(temp_dir.path())?;
+    assert_eq!(
+        found,
+        [
+            temp_dir.path().to_path_buf(),
+            temp_dir.path().join("packages/a"),
+            temp_dir.path().join("packages/b/nested")
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_find_workspace_root() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path().join("repo");
+    fs::create_dir_all(root.join(".mklaunch"))?;
+    fs::create_dir_all(root.join("src/deep"))?;
+    fs::create_dir_all(root.join("packages/a/.mklaunch"))?;
+    fs::create_dir_all(root.join("packages/a/src"))?;
+
+    assert_eq!(mklaunch::find_workspace_root(&root), Some(root.clone()));
+    assert_eq!(
+        mklaunch::find_workspace_root(&root.join("src/deep")),
+        Some(root.clone())
+    );
+    assert_eq!(
+        mklaunch::find_workspace_root(&root.join("packages/a/src")),
+        Some(root.join("packages/a"))
+    );
+    assert_eq!(mklaunch::find_workspace_root(temp_dir.path()), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_templates_introspection() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    let cpp = json!({ "name": "cpp", "type": "cppdbg", "allowOverrides": ["cwd"] });
+    write_json(&templates_manifest, &json!({ "templates": [cpp] }))?;
+    let extra = temp_dir.path().join("node.templates.json");
+    write_json(
+        &extra,
+        &json!({ "templates": [{ "name": "node", "type": "node" }] }),
+    )?;
+
+    let generator = Generator::with_options(
+        GeneratorOptions::new(templates_manifest.clone(), configs_dir).templates(extra.clone()),
+    );
+    let templates = generator.templates()?;
+    assert_eq!(templates.names().collect::<Vec<_>>(), ["cpp", "node"]);
+    assert_eq!(templates.len(), 2);
+    assert_eq!(templates.get("cpp"), Some(&cpp));
+    assert_eq!(templates.get("rust"), None);
+    assert_eq!(templates.origin("node"), Some(extra.as_path()));
+    let types: Vec<_> = templates.iter().map(|(_, t)| t["type"].clone()).collect();
+    assert_eq!(types, [json!("cppdbg"), json!("node")]);
+
+    let manifest_only = mklaunch::Templates::from_path(&templates_manifest)?;
+    assert_eq!(manifest_only.names().collect::<Vec<_>>(), ["cpp"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_launch_version_and_keys_from_settings() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true }]),
+    )?;
+    let settings_path = templates_manifest.parent().unwrap().join("settings.json");
+    write_json(
+        &settings_path,
+        &json!({
+            "launchVersion": "0.3.0",
+            "launchKeys": {
+                "compounds": [{ "name": "Both", "configurations": ["A"] }],
+                "futureKey": { "enabled": true }
+            }
+        }),
+    )?;
+
+    let generator = Generator::new(templates_manifest.clone(), configs_dir.clone());
+    let mut launch = generator.generate()?;
+    let kept = launch.preserve_keys_from(r#"{ "compounds": [], "inputs": [] }"#)?;
+    assert_eq!(kept, ["inputs"]);
+    let written: serde_json::Value = serde_json::from_str(&launch.to_pretty_string()?)?;
+    assert_eq!(written["version"], "0.3.0");
+    assert_eq!(written["compounds"][0]["name"], "Both");
+    assert_eq!(written["futureKey"], json!({ "enabled": true }));
+    assert_eq!(written["inputs"], json!([]));
+
+    write_json(
+        &settings_path,
+        &json!({ "launchKeys": { "configurations": [] } }),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(format!("{:#}", err).contains("'launchKeys' must not set 'configurations'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_name_format_from_settings() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg", "request": "launch" },
+            { "name": "lldb", "type": "lldb", "request": "launch" }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("basic.json"),
+        &json!([
+            { "name": "Debug {args[0]}", "extends": "cpp", "enabled": true, "args": ["Basic"] },
+            { "name": "Debug Basic", "extends": "lldb", "enabled": true }
+        ]),
+    )?;
+    let options =
+        GeneratorOptions::new(templates_manifest.clone(), configs_dir.clone()).cache(true);
+    let names = |options: &GeneratorOptions| -> Result<Vec<String>> {
+        let launch = Generator::with_options(options.clone()).generate()?;
+        Ok(launch
+            .configurations()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect())
+    };
+
+    let err = Generator::with_options(options.clone())
+        .generate()
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("Duplicate"), "{:#}", err);
+
+    let settings = templates_manifest.with_file_name("settings.json");
+    write_json(&settings, &json!({ "nameFormat": "[{template}] {name}" }))?;
+    assert_eq!(
+        names(&options)?,
+        ["[cpp] Debug Basic", "[lldb] Debug Basic"]
+    );
+    let explanation = Generator::with_options(options.clone()).explain("[cpp] Debug Basic")?;
+    assert!(explanation.provenance.keys()["name"].ends_with("(nameFormat)"));
+
+    // The cache is keyed on settings.json as well
+    write_json(&settings, &json!({ "nameFormat": "{name} ({file})" }))?;
+    let err = Generator::with_options(options.clone())
+        .generate()
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("Duplicate"), "{:#}", err);
+
+    // launchAfter names configurations as written, before nameFormat
+    write_json(&settings, &json!({ "nameFormat": "[{template}] {name}" }))?;
+    write_json(
+        configs_dir.join("basic.json"),
+        &json!([
+            { "name": "Server", "extends": "cpp", "enabled": true },
+            { "name": "Client", "extends": "cpp", "enabled": true, "launchAfter": "Server" },
+            { "name": "Tool", "extends": "lldb", "enabled": true, "launchAfter": "[cpp] Server" }
+        ]),
+    )?;
+    for _ in 0..2 {
+        let launch = serde_json::to_value(Generator::with_options(options.clone()).generate()?)?;
+        assert_eq!(
+            launch["compounds"],
+            json!([
+                {
+                    "name": "[cpp] Server + [cpp] Client",
+                    "configurations": ["[cpp] Server", "[cpp] Client"],
+                    "stopAll": true
+                },
+                {
+                    "name": "[cpp] Server + [lldb] Tool",
+                    "configurations": ["[cpp] Server", "[lldb] Tool"],
+                    "stopAll": true
+                }
+            ])
+        );
+    }
+    write_json(
+        configs_dir.join("basic.json"),
+        &json!([
+            { "name": "Server", "extends": "cpp", "enabled": true },
+            { "name": "Server", "extends": "lldb", "enabled": true },
+            { "name": "Client", "extends": "cpp", "enabled": true, "launchAfter": "Server" }
+        ]),
+    )?;
+    let err = Generator::with_options(options.clone())
+        .generate()
+        .unwrap_err();
+    assert!(
+        format!("{:#}", err).contains(
+            "'[cpp] Client' has launchAfter 'Server', which nameFormat turned into '[cpp] Server', '[lldb] Server'"
+        ),
+        "{:#}",
+        err
+    );
+
+    write_json(&settings, &json!({ "nameFormat": "[{template}]" }))?;
+    let err = Generator::with_options(options).generate().unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("must contain {name}"),
+        "{:#}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_option_defaults_from_settings() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, _) = create_dirs(temp_dir.path())?;
+    let settings_path = Settings::path_for(&templates_manifest);
+    assert_eq!(
+        Settings::from_path(&settings_path)?.options,
+        OptionDefaults::default()
+    );
+
+    write_json(
+        &settings_path,
+        &json!({
+            "options": {
+                "output": "out/launch.json",
+                "outputFormat": "jsonc",
+                "keyOrder": "template",
+                "onDuplicate": "suffix",
+                "strictConfig": true
+            }
+        }),
+    )?;
+    let options = Settings::from_path(&settings_path)?.options;
+    assert_eq!(options.output, Some(PathBuf::from("out/launch.json")));
+    assert_eq!(options.output_format, Some(OutputFormat::Jsonc));
+    assert_eq!(options.key_order, Some(KeyOrder::Template));
+    assert_eq!(options.on_duplicate, Some(DuplicateNames::Suffix));
+    assert!(options.strict_config && !options.lint);
+
+    write_json(&settings_path, &json!({ "options": { "sortKeys": true } }))?;
+    let err = Settings::from_path(&settings_path).unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("unknown field `sortKeys`"),
+        "{:#}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_output_targets_from_settings() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, _) = create_dirs(temp_dir.path())?;
+    let settings_path = Settings::path_for(&templates_manifest);
+    write_json(
+        &settings_path,
+        &json!({
+            "outputs": [
+                { "path": ".vscode/launch.json", "tags": ["local"] },
+                { "path": "ci/launch.json", "profile": "ci" },
+                { "path": "ci/container.json", "profile": "ci", "env": "devcontainer", "tags": ["slow"] }
+            ],
+            "profiles": {
+                "ci": { "tags": ["ci", "smoke"], "tagMatch": "all", "outputFormat": "jsonc" }
+            }
+        }),
+    )?;
+    let settings = Settings::from_path(&settings_path)?;
+    let filters: Vec<OutputFilter> = settings
+        .outputs
+        .iter()
+        .map(|target| settings.output_filter(target))
+        .collect();
+    assert_eq!(
+        filters[0],
+        OutputFilter {
+            tags: vec!["local".to_string()],
+            ..Default::default()
+        }
+    );
+    assert_eq!(filters[1].tags, ["ci", "smoke"]);
+    assert_eq!(filters[1].tag_match, Some(TagMatch::All));
+    assert_eq!(filters[1].output_format, Some(OutputFormat::Jsonc));
+    assert_eq!(filters[2].tags, ["slow"]);
+    assert_eq!(filters[2].tag_match, Some(TagMatch::All));
+    assert_eq!(filters[2].env, Some(Environment::Devcontainer));
+
+    write_json(
+        &settings_path,
+        &json!({ "outputs": [{ "path": "a.json", "profile": "cl" }] }),
+    )?;
+    let err = format!("{:#}", Settings::from_path(&settings_path).unwrap_err());
+    assert!(err.contains("Unknown profile 'cl'"), "{}", err);
+    assert!(err.contains("(at /outputs/0/profile)"), "{}", err);
+
+    write_json(
+        &settings_path,
+        &json!({ "outputs": [{ "path": "a.json" }, { "path": "a.json", "tags": ["x"] }] }),
+    )?;
+    let err = format!("{:#}", Settings::from_path(&settings_path).unwrap_err());
+    assert!(err.contains("a.json is listed twice"), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_lint_templates_against_adapter_schemas() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg", "MIMode": "windbg", "externalConsole": "yes",
+              "linux": { "stopOnEntry": true }, "allowOverrides": ["cwd"] },
+            { "name": "rs", "type": "lldb", "terminal": "integrated",
+              "pid": "${command:pickProcess}", "request": "attach" },
+            { "name": "web", "type": "node", "anything": 1 }
+        ] }),
+    )?;
+    write_json(configs_dir.join("a.json"), &json!([]))?;
+
+    let generator = Generator::new(templates_manifest.clone(), configs_dir);
+    let diagnostics = generator.lint_templates(&[])?;
+    let found: Vec<(&str, Severity)> = diagnostics
+        .iter()
+        .map(|d| (d.pointer.as_deref().unwrap_or(""), d.severity))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("/templates/0/MIMode", Severity::Error),
+            ("/templates/0/externalConsole", Severity::Error),
+            ("/templates/0/linux/stopOnEntry", Severity::Warning),
+            ("/templates/1/terminal", Severity::Warning),
+            // No built-in schema describes node, so the template is reported as unchecked
+            ("/templates/2", Severity::Info),
+        ]
+    );
+    assert!(
+        diagnostics[4]
+            .message
+            .contains("no schema is known for 'node' launch configurations")
+    );
+    assert!(
+        diagnostics[1]
+            .message
+            .contains("cppdbg launch attribute 'externalConsole' must be boolean, found string")
+    );
+    assert!(
+        diagnostics[2]
+            .message
+            .contains("did you mean 'stopAtEntry'?")
+    );
+
+    // A package.json replaces the built-in attributes of the debugger types it describes
+    let package = temp_dir.path().join("package.json");
+    write_json(
+        &package,
+        &json!({ "contributes": { "debuggers": [{
+            "type": "node",
+            "configurationAttributes": { "launch": { "properties": { "program": { "type": "string" } } } }
+        }] } }),
+    )?;
+    let diagnostics = generator.lint_templates(&[package.display().to_string()])?;
+    assert_eq!(diagnostics.len(), 5);
+    assert!(
+        diagnostics[4]
+            .message
+            .contains("'anything' is not a node launch attribute")
+    );
+
+    // Packages are only downloaded over https
+    let err = generator
+        .lint_templates(&["http://example.com/package.json".to_string()])
+        .unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("must be downloaded over https"),
+        "{:#}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_lint_unknown_variables() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{
+            "name": "cpp",
+            "type": "cppdbg",
+            "program": "${workspaceFoldr}/a.out",
+            "cwd": "${fileDirname}",
+            "environment": [{ "name": "HOME", "value": "${env:HOME}${/}x" }]
+        }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{
+            "name": "A",
+            "extends": "cpp",
+            "enabled": true,
+            "args": ["${input:port}", "${input:host}", "${inptu:host}", "${command:pickProcess}"]
+        }]),
+    )?;
+    write_json(
+        templates_manifest.parent().unwrap().join("settings.json"),
+        &json!({ "launchKeys": { "inputs": [{ "id": "port", "type": "promptString" }] } }),
+    )?;
+
+    let warnings = Generator::new(templates_manifest, configs_dir).lint()?;
+    assert_eq!(
+        warnings,
+        [
+            LintWarning::UnknownInput {
+                name: "A".to_string(),
+                input: "host".to_string()
+            },
+            LintWarning::UnknownVariable {
+                name: "A".to_string(),
+                variable: "inptu:host".to_string(),
+                suggestion: Some("input:host".to_string())
+            },
+            LintWarning::UnknownVariable {
+                name: "A".to_string(),
+                variable: "workspaceFoldr".to_string(),
+                suggestion: Some("workspaceFolder".to_string())
+            },
+        ]
+    );
+    assert_eq!(
+        warnings[2].to_string(),
+        "Configuration 'A' uses unknown variable '${workspaceFoldr}'; did you mean '${workspaceFolder}'?"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_disabled_list() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Unit", "extends": "cpp", "enabled": true },
+            { "name": "Integration fast", "extends": "cpp", "enabled": true },
+            { "name": "Integration slow", "extends": "cpp", "enabled": true },
+            { "name": "Bench 1", "extends": "cpp", "enabled": true }
+        ]),
+    )?;
+    let disabled_path = templates_manifest.parent().unwrap().join("disabled.json");
+    write_json(&disabled_path, &json!(["Integration *", "Bench ?"]))?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir);
+    for options in [options.clone(), options.clone().cache(true)] {
+        let generator = Generator::with_options(options);
+        let launch = generator.generate()?;
+        let names: Vec<_> = launch.configurations().iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["Unit"]);
+        assert_eq!(launch.summary().disabled, 3);
+        assert!(generator.input_files()?.contains(&disabled_path));
+    }
+
+    write_json(&disabled_path, &json!(["Bench ??"]))?;
+    let launch = Generator::with_options(options.cache(true)).generate()?;
+    assert_eq!(launch.configurations().len(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_stamp_records_inputs_hash() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true }]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir.clone());
+    let stamp = |options: &GeneratorOptions| -> Result<serde_json::Value> {
+        let launch = Generator::with_options(options.clone()).generate()?;
+        let written: serde_json::Value = serde_json::from_str(&launch.to_pretty_string()?)?;
+        Ok(written["generatedBy"].clone())
+    };
+    assert_eq!(stamp(&options)?, serde_json::Value::Null);
+
+    let options = options.stamp(true);
+    let first = stamp(&options)?;
+    assert_eq!(first["tool"], "mklaunch");
+    assert_eq!(first["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(stamp(&options)?, first);
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true, "args": ["-v"] }]),
+    )?;
+    assert_ne!(stamp(&options)?["inputsHash"], first["inputsHash"]);
+
+    let mut launch = Generator::with_options(options.stamp(false)).generate()?;
+    let kept = launch.preserve_keys_from(r#"{ "generatedBy": {}, "inputs": [] }"#)?;
+    assert_eq!(kept, ["inputs"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_configs_from_csv_table() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    fs::write(
+        configs_dir.join("matrix.csv"),
+        "name,extends,args,args,enabled,order\r\n\
+         Small,cpp,--input small.bin,--threads 1,,2\r\n\
+         \"Large, slow\",cpp,\"--input \"\"large.bin\"\"\",,no,\r\n\
+         \r\n",
+    )?;
+    fs::write(configs_dir.join("more.tsv"), "name\textends\nTab\tcpp\n")?;
+
+    let generator = Generator::new(templates_manifest, configs_dir.clone());
+    let launch = generator.generate()?;
+    let configs = launch.configurations();
+    let names: Vec<_> = configs.iter().map(|c| c.name()).collect();
+    assert_eq!(names, ["Small", "Tab"]);
+    let small = serde_json::to_value(&configs[0])?;
+    assert_eq!(
+        small["args"],
+        json!(["--input", "small.bin", "--threads", "1"])
+    );
+    assert_eq!(small["presentation"]["order"], json!(2));
+    assert_eq!(launch.summary().disabled, 1);
+
+    fs::write(configs_dir.join("bad.csv"), "name,extend\nX,cpp\n")?;
+    let err = format!("{:#}", generator.generate().unwrap_err());
+    assert!(err.contains("bad.csv:1:1"), "{}", err);
+    assert!(err.contains("did you mean 'extends'?"), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_args_string() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{
+            "name": "Pasted",
+            "extends": "cpp",
+            "enabled": true,
+            "argSets": [],
+            "argsString": "--input 'my data.txt' --label \"a \\\"b\\\"\" --threads 4"
+        }]),
+    )?;
+    fs::write(
+        configs_dir.join("b.csv"),
+        "name,extends,argsString\nTable,cpp,\"--input 'x y' -v\"\n",
+    )?;
+
+    let generator = Generator::new(templates_manifest.clone(), configs_dir.clone());
+    let launch = serde_json::to_value(generator.generate()?)?;
+    assert_eq!(
+        launch["configurations"][0]["args"],
+        json!([
+            "--input",
+            "my data.txt",
+            "--label",
+            "a \"b\"",
+            "--threads",
+            "4"
+        ])
+    );
+    assert_eq!(
+        launch["configurations"][1]["args"],
+        json!(["--input", "x y", "-v"])
+    );
+    let explanation = generator.explain("Pasted")?;
+    assert!(
+        explanation.provenance.args()[1]
+            .1
+            .starts_with("argsString in config")
+    );
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "Both", "extends": "cpp", "enabled": true, "args": ["-v"], "argsString": "-q" }]),
+    )?;
+    let err = format!("{:#}", generator.generate().unwrap_err());
+    assert!(
+        err.contains("'Both' cannot set both args and argsString in config"),
+        "{}",
+        err
+    );
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "base", "extends": "cpp", "enabled": false, "args": ["-v"] },
+            { "name": "Mixed", "include": "base", "enabled": true, "argsString": "-q" }
+        ]),
+    )?;
+    let err = format!("{:#}", generator.generate().unwrap_err());
+    assert!(
+        err.contains("'Mixed' sets argsString, but include 'base' sets args"),
+        "{}",
+        err
+    );
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "Layered", "extends": "cpp", "enabled": true, "args": ["-v"] }]),
+    )?;
+    let local = temp_dir.path().join(".mklaunch").join("configs.local");
+    fs::create_dir_all(&local)?;
+    write_json(
+        local.join("mine.json"),
+        &json!([{ "name": "Layered", "argsString": "-q" }]),
+    )?;
+    let options = GeneratorOptions::new(templates_manifest, configs_dir.clone()).configs(local);
+    let err = format!(
+        "{:#}",
+        Generator::with_options(options).generate().unwrap_err()
+    );
+    assert!(
+        err.contains("sets argsString, but ") && err.contains("a.json sets args"),
+        "{}",
+        err
+    );
+    assert!(
+        err.contains("overlay ") && err.contains("mine.json"),
+        "{}",
+        err
+    );
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "Open", "extends": "cpp", "enabled": true, "argsString": "--input 'data" }]),
+    )?;
+    let err = format!("{:#}", generator.generate().unwrap_err());
+    assert!(
+        err.contains("'Open' has an invalid argsString: unterminated quote or trailing backslash"),
+        "{}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_tag_filtering() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Unit", "extends": "cpp", "enabled": true, "tags": ["asan", "unit"] },
+            { "name": "Soak", "extends": "cpp", "enabled": true, "tags": ["slow"] },
+            { "name": "Soak ASan", "extends": "cpp", "enabled": true, "tags": ["asan", "slow"] },
+            { "name": "Plain", "extends": "cpp", "enabled": true }
+        ]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir)
+        .tags(vec!["asan".to_string(), "slow".to_string()]);
+    let names = |options: GeneratorOptions| -> Result<Vec<String>> {
+        let launch = Generator::with_options(options).generate()?;
+        Ok(launch
+            .configurations()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect())
+    };
+    assert_eq!(names(options.clone())?, ["Soak", "Soak ASan", "Unit"]);
+    let all = options.clone().tag_match(TagMatch::All);
+    assert_eq!(names(all.clone())?, ["Soak ASan"]);
+    assert_eq!(names(all.clone().cache(true))?, ["Soak ASan"]);
+    assert_eq!(
+        names(options.clone().cache(true))?,
+        ["Soak", "Soak ASan", "Unit"]
+    );
+
+    let entries = Generator::with_options(all).entries()?;
+    let listed: Vec<_> = entries
+        .iter()
+        .map(|e| (e.name.as_str(), e.enabled))
+        .collect();
+    assert_eq!(
+        listed,
+        [
+            ("Unit", false),
+            ("Soak", false),
+            ("Soak ASan", true),
+            ("Plain", false)
+        ]
+    );
+    assert_eq!(entries[0].tags, ["asan", "unit"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_task_overrides() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{
+            "name": "cpp",
+            "type": "cppdbg",
+            "preLaunchTask": "build",
+            "postDebugTask": "cleanup"
+        }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "A", "extends": "cpp", "enabled": true, "preLaunchTask": "build-asan" },
+            { "name": "B", "extends": "cpp", "enabled": true, "preLaunchTask": null },
+            { "name": "C", "extends": "cpp", "enabled": true }
+        ]),
+    )?;
+
+    let launch = Generator::new(templates_manifest, configs_dir).generate()?;
+    let tasks: Vec<_> = launch
+        .configurations()
+        .iter()
+        .map(|c| (c.get("preLaunchTask"), c.get("postDebugTask")))
+        .collect();
+    assert_eq!(
+        tasks,
+        [
+            (Some(json!("build-asan")), Some(json!("cleanup"))),
+            (None, Some(json!("cleanup"))),
+            (Some(json!("build")), Some(json!("cleanup"))),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_resolve_iter() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Zeta", "extends": "cpp", "enabled": true, "args": ["1"] },
+            { "name": "Broken", "extends": "cpp", "enabled": true, "baseArgs": "missing.json" },
+            { "name": "Off", "extends": "cpp", "enabled": false },
+            { "name": "Alpha", "extends": "cpp", "enabled": true }
+        ]),
+    )?;
+
+    let generator = Generator::with_options(
+        GeneratorOptions::new(templates_manifest, configs_dir).root(temp_dir.path().to_path_buf()),
+    );
+    let mut configs = generator.resolve_iter()?;
+    let first = configs.next().unwrap()?;
+    assert_eq!(
+        (first.name(), first.args()),
+        ("Zeta", &["1".to_string()][..])
+    );
+    let err = configs.next().unwrap().unwrap_err();
+    assert!(format!("{:#}", err).contains("missing.json"), "{:#}", err);
+    let rest: Vec<_> = configs
+        .map(|c| c.map(|c| c.name().to_string()))
+        .collect::<Result<_>>()?;
+    assert_eq!(rest, ["Alpha"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_path_style() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{
+            "name": "cpp",
+            "type": "cppdbg",
+            "program": "${workspaceFolder}\\build/app",
+            "miDebuggerPath": "C:/msys64/bin/gdb.exe",
+            "windows": { "program": "a/b" }
+        }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true, "cwd": "out/x", "args": ["a/b"] }]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir);
+    let windows =
+        Generator::with_options(options.clone().path_style(PathStyle::Windows)).generate()?;
+    let config = &windows.configurations()[0];
+    assert_eq!(config.program(), Some("${workspaceFolder}\\build\\app"));
+    assert_eq!(config.get("cwd"), Some(json!("out\\x")));
+    assert_eq!(
+        config.get("miDebuggerPath"),
+        Some(json!("C:\\msys64\\bin\\gdb.exe"))
+    );
+    assert_eq!(config.args(), ["a/b"]);
+    assert_eq!(config.get("windows"), Some(json!({ "program": "a/b" })));
+
+    let posix = Generator::with_options(options.path_style(PathStyle::Posix));
+    let config = posix.resolve_iter()?.next().unwrap()?;
+    assert_eq!(config.program(), Some("${workspaceFolder}/build/app"));
+    assert_eq!(
+        config.get("miDebuggerPath"),
+        Some(json!("C:/msys64/bin/gdb.exe"))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_entry_variants() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg", "cwd": "debug" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{
+            "name": "Server",
+            "extends": "cpp",
+            "enabled": true,
+            "args": ["--port", "8080"],
+            "variants": [
+                { "nameSuffix": " (Release)", "overrides": { "cwd": "release", "args": ["--fast"] } },
+                { "nameSuffix": " (Off)", "overrides": { "enabled": false } }
+            ]
+        }]),
+    )?;
+
+    let generator = Generator::new(templates_manifest, configs_dir.clone());
+    let launch = generator.generate()?;
+    let configs: Vec<_> = launch
+        .configurations()
+        .iter()
+        .map(|c| (c.name(), c.args().to_vec(), c.get("cwd")))
+        .collect();
+    assert_eq!(
+        configs,
+        [
+            (
+                "Server",
+                vec!["--port".to_string(), "8080".to_string()],
+                Some(json!("debug"))
+            ),
+            (
+                "Server (Release)",
+                vec!["--fast".to_string()],
+                Some(json!("release"))
+            ),
+        ]
+    );
+    assert_eq!(launch.summary().disabled, 1);
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{
+            "name": "Server",
+            "extends": "cpp",
+            "enabled": true,
+            "variants": [{ "nameSuffix": " (R)", "overrides": { "name": "Other" } }]
+        }]),
+    )?;
+    let err = format!("{:#}", generator.generate().unwrap_err());
+    assert!(err.contains("cannot override 'name'"), "{}", err);
+    assert!(err.contains("/0/variants/0/overrides/name"), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_group_by_template() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg" },
+            { "name": "py", "type": "debugpy" }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "C", "extends": "cpp", "enabled": true },
+            { "name": "Off", "extends": "cpp", "enabled": false },
+            { "name": "P", "extends": "py", "enabled": true, "group": "tools" }
+        ]),
+    )?;
+    write_json(
+        configs_dir.join("b.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true, "order": 9 }]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir).group_by_template(true);
+    for options in [
+        options.clone(),
+        options.clone().cache(true),
+        options.cache(true),
+    ] {
+        let launch = Generator::with_options(options).generate()?;
+        let presentation: Vec<_> = launch
+            .configurations()
+            .iter()
+            .map(|c| (c.name(), c.get("presentation")))
+            .collect();
+        assert_eq!(
+            presentation,
+            [
+                ("A", Some(json!({ "group": "cpp", "order": 9 }))),
+                ("C", Some(json!({ "group": "cpp", "order": 1 }))),
+                ("P", Some(json!({ "group": "tools", "order": 1 }))),
+            ]
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_core_dump() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg", "program": "app" },
+            { "name": "lldb", "type": "lldb", "program": "app" }
+        ] }),
+    )?;
+    let build = temp_dir.path().join("build");
+    fs::create_dir(&build)?;
+    fs::write(build.join("core.200"), "")?;
+    fs::write(build.join("core.100"), "")?;
+    // The older core file sorts last, so only its modification time can rule it out
+    let old = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+    fs::File::options()
+        .write(true)
+        .open(build.join("core.100"))?
+        .set_modified(old)?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Cpp", "extends": "cpp", "enabled": true, "coreDump": "build/core.*" },
+            { "name": "Lldb", "extends": "lldb", "enabled": true, "coreDump": "/tmp/core" }
+        ]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir.clone())
+        .root(temp_dir.path().to_path_buf());
+    let launch = Generator::with_options(options.clone()).generate()?;
+    let cpp = &launch.configurations()[0];
+    assert_eq!(
+        cpp.get("coreDumpPath"),
+        Some(json!("${workspaceFolder}/build/core.200"))
+    );
+    let lldb = &launch.configurations()[1];
+    assert_eq!(lldb.request(), Some("custom"));
+    assert_eq!(
+        lldb.get("targetCreateCommands"),
+        Some(json!(["target create -c \"/tmp/core\" \"app\""]))
+    );
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "Cpp", "extends": "cpp", "enabled": true, "coreDump": "build/vg.*" }]),
+    )?;
+    let err = format!(
+        "{:#}",
+        Generator::with_options(options).generate().unwrap_err()
+    );
+    assert!(err.contains("No core file matches 'vg.*'"), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_format_inputs() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    fs::write(
+        &templates_manifest,
+        "\u{feff}{\"templates\":[{\"type\":\"cppdbg\",\"name\":\"cpp\",\"request\":\"launch\",\"MIMode\":\"gdb\"}]}\r\n",
+    )?;
+    fs::write(
+        configs_dir.join("a.json"),
+        r#"[{"args":["--verbose","--port","8080"],"enabled":true,"extends":"cpp","name":"Debug"}]"#,
+    )?;
+
+    let templates = [templates_manifest.clone()];
+    let configs = [configs_dir.clone()];
+    let changed = mklaunch::format_inputs(&templates, &configs, true)?;
+    assert_eq!(changed.len(), 2);
+    assert!(fs::read_to_string(&templates_manifest)?.contains("\r\n"));
+
+    mklaunch::format_inputs(&templates, &configs, false)?;
+    assert_eq!(
+        fs::read_to_string(&templates_manifest)?,
+        "{\n  \"templates\": [\n    {\n      \"name\": \"cpp\",\n      \"type\": \"cppdbg\",\n      \"request\": \"launch\",\n      \"MIMode\": \"gdb\"\n    }\n  ]\n}\n"
+    );
+    assert_eq!(
+        fs::read_to_string(configs_dir.join("a.json"))?,
+        "[\n  {\n    \"name\": \"Debug\",\n    \"extends\": \"cpp\",\n    \"enabled\": true,\n    \"args\": [\"--verbose\", \"--port\", \"8080\"]\n  }\n]\n"
+    );
+    assert!(mklaunch::format_inputs(&templates, &configs, true)?.is_empty());
+    Generator::new(templates_manifest, configs_dir).generate()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_launch_after() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "node", "type": "node" },
+            { "name": "cpp", "type": "cppdbg" }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Server", "extends": "node", "enabled": true },
+            { "name": "CLI", "extends": "cpp", "enabled": true, "launchAfter": "Server" },
+            {
+                "name": "Client",
+                "extends": "cpp",
+                "enabled": true,
+                "launchAfter": { "name": "Server", "pattern": "listening on ([0-9]+)" }
+            }
+        ]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir.clone());
+    for options in [options.clone(), options.clone().cache(true)] {
+        let mut launch = Generator::with_options(options).generate()?;
+        launch.preserve_keys_from(
+            r#"{ "compounds": [{ "name": "All", "configurations": ["CLI", "Client"] }] }"#,
+        )?;
+        let value: serde_json::Value = serde_json::from_str(&launch.to_pretty_string()?)?;
+        assert_eq!(
+            value["compounds"],
+            json!([
+                { "name": "Server + CLI", "configurations": ["Server", "CLI"], "stopAll": true },
+                { "name": "All", "configurations": ["CLI", "Client"] }
+            ])
+        );
+        let server = launch
+            .configurations()
+            .iter()
+            .find(|c| c.name() == "Server");
+        assert_eq!(
+            server.and_then(|c| c.get("serverReadyAction")),
+            Some(json!({
+                "action": "startDebugging",
+                "pattern": "listening on ([0-9]+)",
+                "name": "Client"
+            }))
+        );
+    }
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "CLI", "extends": "cpp", "enabled": true, "launchAfter": "Server" }]),
+    )?;
+    let err = Generator::with_options(options).generate().unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("launchAfter 'Server', which is not another enabled configuration"),
+        "{}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_git_policy_and_gitignore() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    };
+    if !git(&["init", "-q"]) {
+        // git is not installed
+        return Ok(());
+    }
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    create_test_files(temp_dir.path())?;
+    write_json(
+        templates_manifest.with_file_name("settings.json"),
+        &json!({ "gitPolicy": "ignore" }),
+    )?;
+    let output = temp_dir.path().join(".vscode").join("launch.json");
+    fs::create_dir_all(output.parent().unwrap())?;
+    fs::write(&output, "{}")?;
+
+    let generator = Generator::new(templates_manifest, configs_dir);
+    let warnings = generator.git_warnings(&output)?;
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("is not gitignored"), "{}", warnings[0]);
+
+    let err = mklaunch::gitignore_output(&output, None, &NoExec).unwrap_err();
+    assert!(format!("{:#}", err).contains("--no-exec"), "{:#}", err);
+    assert!(!temp_dir.path().join(".gitignore").exists());
+
+    let gitignore = mklaunch::gitignore_output(&output, None, &ProcessRunner)?;
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join(".gitignore"))?,
+        "/.vscode/launch.json\n"
+    );
+    assert!(gitignore.is_some());
+    assert_eq!(
+        mklaunch::gitignore_output(&output, None, &ProcessRunner)?,
+        None
+    );
+    assert!(generator.git_warnings(&output)?.is_empty());
+
+    assert!(git(&["add", "-f", ".vscode/launch.json"]));
+    let warnings = generator.git_warnings(&output)?;
+    assert!(warnings[0].contains("git rm --cached"), "{}", warnings[0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_configs_against_git_ref() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    };
+    if !git(&["init", "-q"]) {
+        // git is not installed
+        return Ok(());
+    }
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg", "request": "launch" }] }),
+    )?;
+    write_json(
+        configs_dir.join("app.json"),
+        &json!([
+            { "name": "Server", "extends": "cpp", "enabled": true, "args": ["--port", "80"] },
+            { "name": "Client", "extends": "cpp", "enabled": true }
+        ]),
+    )?;
+    assert!(git(&["add", "."]));
+    assert!(git(&[
+        "-c",
+        "user.name=test",
+        "-c",
+        "user.email=test@example.com",
+        "commit",
+        "-q",
+        "-m",
+        "initial"
+    ]));
+
+    let snapshot = temp_dir.path().join("snapshot");
+    let base = templates_manifest.parent().unwrap();
+    assert_eq!(
+        mklaunch::export_dir("HEAD", base, &snapshot, None, &ProcessRunner)?,
+        2
+    );
+    let old =
+        Generator::new(snapshot.join("templates.json"), snapshot.join("configs")).generate()?;
+
+    write_json(
+        configs_dir.join("app.json"),
+        &json!([
+            { "name": "Server", "extends": "cpp", "enabled": true, "args": ["--port", "8080"] },
+            { "name": "Tool", "extends": "cpp", "enabled": true }
+        ]),
+    )?;
+    let new = Generator::new(templates_manifest, configs_dir).generate()?;
+
+    let changes = mklaunch::diff_configs(&old, &new);
+    assert_eq!(changes.len(), 3, "{:?}", changes);
+    assert_eq!(changes[0], ConfigChange::Removed("Client".to_string()));
+    let ConfigChange::Changed { name, keys } = &changes[1] else {
+        panic!("{:?}", changes[1]);
+    };
+    assert_eq!(name, "Server");
+    assert_eq!(keys.len(), 1);
+    assert_eq!(
+        keys[0].to_string(),
+        r#"args: ["--port","80"] -> ["--port","8080"]"#
+    );
+    assert_eq!(changes[2], ConfigChange::Added("Tool".to_string()));
+    assert!(mklaunch::diff_configs(&new, &new).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_codelldb_cargo_blocks() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            {
+                "name": "rust",
+                "type": "lldb",
+                "request": "launch",
+                "cargo": { "args": ["build", "--bin=server"], "env": { "A": "1" } }
+            },
+            { "name": "lldb", "type": "lldb", "request": "launch", "program": "./app" },
+            { "name": "cpp", "type": "cppdbg", "request": "launch" }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            {
+                "name": "Features",
+                "extends": "rust",
+                "enabled": true,
+                "args": ["--port", "80"],
+                "cargo": { "args": ["--features=tls"], "env": { "B": "2" } }
+            },
+            {
+                "name": "Tests",
+                "extends": "lldb",
+                "enabled": true,
+                "cargo": { "args": ["test", "--no-run", "--lib"], "filter": { "kind": "lib" } }
+            }
+        ]),
+    )?;
+    let generator = Generator::new(templates_manifest.clone(), configs_dir.clone());
+    let launch = serde_json::to_value(generator.generate()?)?;
+    let configurations = launch["configurations"].as_array().unwrap();
+    let features = &configurations[0];
+    assert_eq!(
+        features["cargo"],
+        json!({
+            "args": ["build", "--bin=server", "--features=tls"],
+            "env": { "A": "1", "B": "2" }
+        })
+    );
+    assert_eq!(features["args"], json!(["--port", "80"]));
+    let tests = &configurations[1];
+    assert_eq!(
+        tests["cargo"],
+        json!({ "args": ["test", "--no-run", "--lib"], "filter": { "kind": "lib" } })
+    );
+    assert!(tests.get("program").is_none());
+
+    let explanation = generator.explain("Features")?;
+    let source = &explanation.provenance.keys()["cargo"];
+    assert!(
+        source.starts_with("template 'rust' + cargo in"),
+        "{}",
+        source
+    );
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "Flags", "extends": "lldb", "enabled": true, "cargo": { "args": ["--release"] } }]),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("no subcommand to extend"),
+        "{:#}",
+        err
+    );
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "C", "extends": "cpp", "enabled": true, "cargo": { "args": ["build"] } }]),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("has type 'cppdbg'"),
+        "{:#}",
+        err
+    );
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "Typo", "extends": "lldb", "enabled": true, "cargo": { "args": ["build"], "filtr": {} } }]),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("unknown field `filtr`"),
+        "{:#}",
+        err
+    );
+
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg", "cargo": { "args": ["build"] } }] }),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("only applies to CodeLLDB"),
+        "{:#}",
+        err
+    );
+
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "lldb", "type": "lldb", "cargo": { "args": "build" } }] }),
+    )?;
+    let err = generator.generate().unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("Invalid 'cargo' block"),
+        "{:#}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_json_reports() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg", "program": "./missing" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Server", "extends": "cpp", "enabled": true, "tags": ["net"] },
+            { "name": "Client", "extends": "cpp", "enabled": false }
+        ]),
+    )?;
+    let options = GeneratorOptions::new(templates_manifest, configs_dir.clone())
+        .root(temp_dir.path().to_path_buf());
+    let generator = Generator::with_options(options);
+
+    let list = serde_json::to_value(ListReport::new(&generator.entries()?))?;
+    assert_eq!(
+        list,
+        json!({
+            "version": REPORT_VERSION,
+            "entries": [
+                { "name": "Server", "extends": "cpp", "enabled": true, "tags": ["net"] },
+                { "name": "Client", "extends": "cpp", "enabled": false, "tags": [] }
+            ]
+        })
+    );
+
+    let doctor = DoctorReport::new(&generator.doctor()?);
+    assert!(!doctor.ok);
+    assert_eq!(doctor.configurations[0].name, "Server");
+    assert!(doctor.configurations[0].problems[0].contains("./missing"));
+
+    let validate = serde_json::to_value(ValidateReport::new(&generator.validate()?))?;
+    assert_eq!(
+        validate,
+        json!({ "version": 1, "ok": true, "diagnostics": [] })
+    );
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "Server", "extends": "cp", "enabled": true }]),
+    )?;
+    let validate = ValidateReport::new(&generator.validate()?);
+    let text = serde_json::to_string(&validate)?;
+    assert_eq!(serde_json::from_str::<ValidateReport>(&text)?, validate);
+    let diagnostic = serde_json::to_value(&validate.diagnostics[0])?;
+    assert_eq!(diagnostic["severity"], "error");
+    assert_eq!(diagnostic["pointer"], "/0/extends");
+    assert_eq!(diagnostic["line"], 4);
+    assert_eq!(diagnostic["fix"]["replacement"], "\"cpp\"");
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_config_file() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp-gdb", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("broken.json"),
+        &json!({ "not": "entries" }),
+    )?;
+    let path = configs_dir.join("server.json");
+    write_json(
+        &path,
+        &json!([{ "name": "Server", "extends": "cpp-gbd", "enabled": true, "argz": [] }]),
+    )?;
+
+    let diagnostics = mklaunch::validate_config_file(&path);
+    assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+    let unknown_key = &diagnostics[0];
+    assert_eq!(unknown_key.severity, Severity::Warning);
+    assert_eq!(unknown_key.pointer.as_deref(), Some("/0/argz"));
+    assert!(unknown_key.message.contains("did you mean 'args'?"));
+    let unknown_template = &diagnostics[1];
+    assert_eq!(unknown_template.severity, Severity::Error);
+    assert_eq!(
+        unknown_template.fix,
+        Some(Fix {
+            title: "Replace with 'cpp-gdb'".to_string(),
+            pointer: "/0/extends".to_string(),
+            replacement: "\"cpp-gdb\"".to_string(),
+        })
+    );
+    assert!(unknown_template.position.is_some());
+
+    fs::write(&path, "[{ \"name\": ")?;
+    let diagnostics = mklaunch::validate_config_file(&path);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].position, Some((1, 11)));
+
+    Ok(())
+}
+
+#[test]
+fn test_set_overrides() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg", "stopAtEntry": false, "env": { "A": "1" } }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Debug app", "extends": "cpp", "enabled": true },
+            { "name": "Release app", "extends": "cpp", "enabled": true }
+        ]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir)
+        .set("stopAtEntry".to_string(), json!(true))
+        .set("env.RUST_LOG".to_string(), json!("debug"))
+        .override_names(vec!["Debug *".to_string(), "Test *".to_string()]);
+    let launch = Generator::with_options(options.clone()).generate()?;
+    let debug = &launch.configurations()[0];
+    assert_eq!(debug.get("stopAtEntry"), Some(json!(true)));
+    assert_eq!(
+        debug.get("env"),
+        Some(json!({ "A": "1", "RUST_LOG": "debug" }))
+    );
+    let release = &launch.configurations()[1];
+    assert_eq!(release.get("stopAtEntry"), Some(json!(false)));
+    assert_eq!(
+        launch.warnings(),
+        ["--only 'Test *' matches no configuration"]
+    );
+
+    let err = Generator::with_options(options.set("env.A.B".to_string(), json!(1)))
+        .generate()
+        .unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("'env.A' is not an object"),
+        "{:#}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_extra_config_files() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "Shared", "extends": "cpp", "enabled": true, "args": ["--shared"] }]),
+    )?;
+    let private = temp_dir.path().join("private.json");
+    write_json(
+        &private,
+        &json!([{ "name": "Private", "include": "Shared", "enabled": true, "args": ["--token"] }]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest.clone(), configs_dir)
+        .extra_config(private.clone());
+    let launch = Generator::with_options(options).generate()?;
+    let names: Vec<&str> = launch.configurations().iter().map(|c| c.name()).collect();
+    assert_eq!(names, ["Private", "Shared"]);
+    assert_eq!(launch.summary().files, 2);
+
+    // The configs directory may be missing when extra files provide the entries
+    let options = GeneratorOptions::new(templates_manifest, temp_dir.path().join("none"))
+        .extra_config(temp_dir.path().join("missing.json"));
+    let err = Generator::with_options(options).generate().unwrap_err();
+    assert!(
+        err.to_string().contains("Extra config file does not exist"),
+        "{}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_layered_configs_dirs() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Server", "extends": "cpp", "enabled": true, "args": ["--port", "80"], "cwd": "srv" },
+            { "name": "Flaky", "extends": "cpp", "enabled": true }
+        ]),
+    )?;
+    let local = temp_dir.path().join(".mklaunch").join("configs.local");
+    fs::create_dir_all(&local)?;
+    write_json(
+        local.join("mine.json"),
+        &json!([
+            { "name": "Server", "args": ["--port", "9000"] },
+            { "name": "Flaky", "enabled": false },
+            { "name": "Mine", "extends": "cpp", "enabled": true }
+        ]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir)
+        .configs(local)
+        .configs(temp_dir.path().join("missing"));
+    for options in [options.clone(), options.cache(true)] {
+        let launch = Generator::with_options(options).generate()?;
+        let names: Vec<&str> = launch.configurations().iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["Mine", "Server"]);
+        let server = &launch.configurations()[1];
+        assert_eq!(server.args(), ["--port", "9000"]);
+        assert_eq!(server.get("cwd"), Some(json!("srv")));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_keep_going() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Good", "extends": "cpp", "enabled": true },
+            { "name": "Typo", "extends": "cxx", "enabled": true }
+        ]),
+    )?;
+    fs::write(configs_dir.join("broken.json"), "[{ \"name\": ")?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir.clone());
+    let err = Generator::with_options(options.clone())
+        .generate()
+        .unwrap_err();
+    let diagnostics = err.downcast_ref::<Diagnostics>().expect("diagnostics");
+    assert_eq!(diagnostics.len(), 2, "{}", diagnostics);
+    assert!(diagnostics.has_errors());
+
+    let options = options.keep_going(true);
+    for options in [options.clone(), options.clone().cache(true)] {
+        let launch = Generator::with_options(options).generate()?;
+        let names: Vec<&str> = launch.configurations().iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["Good"]);
+        assert_eq!(launch.errors().len(), 2, "{:?}", launch.errors());
+        assert_eq!(launch.errors()[0].path, configs_dir.join("broken.json"));
+        assert_eq!(launch.errors()[0].position, Some((1, 11)));
+        assert!(launch.errors()[1].message.contains("cxx"));
+        // The path is given once, by the diagnostic
+        assert!(!launch.errors()[1].message.contains("a.json"));
+        assert!(launch.errors()[1].to_string().contains("a.json"));
+        assert_eq!(launch.summary().failed, 1);
+        assert_eq!(launch.summary().disabled, 0);
+    }
+
+    // Discovered targets that fail to resolve are reported with the config entries
+    let project = temp_dir.path().join("py");
+    fs::create_dir_all(&project)?;
+    fs::write(
+        project.join("serve.py"),
+        "if __name__ == \"__main__\":\n    pass\n",
+    )?;
+    let options = options.from_python(project, "python".to_string());
+    let err = Generator::with_options(options.clone().keep_going(false))
+        .generate()
+        .unwrap_err();
+    let diagnostics = err.downcast_ref::<Diagnostics>().expect("diagnostics");
+    assert_eq!(diagnostics.len(), 3, "{}", diagnostics);
+    let launch = Generator::with_options(options).generate()?;
+    assert_eq!(launch.errors().len(), 3, "{:?}", launch.errors());
+    assert_eq!(launch.summary().failed, 2);
+    assert_eq!(launch.summary().disabled, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_cli_turns_off_settings_switch() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        templates_manifest.with_file_name("settings.json"),
+        &json!({ "options": { "keepGoing": true } }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Good", "extends": "cpp", "enabled": true },
+            { "name": "Typo", "extends": "cxx", "enabled": true }
+        ]),
+    )?;
+    let output = temp_dir.path().join(".vscode/launch.json");
+    let run = |args: &[&str]| -> Result<bool> {
+        let _ = fs::remove_file(&output);
+        std::process::Command::new(env!("CARGO_BIN_EXE_mklaunch"))
+            .arg("--no-discover")
+            .args(args)
+            .current_dir(temp_dir.path())
+            .env_remove("MKLAUNCH_EXTRA_CONFIG")
+            .output()?;
+        Ok(output.exists())
+    };
+
+    assert!(run(&[])?);
+    assert!(!run(&["--no-keep-going"])?);
+    // The last of the two flags wins
+    assert!(run(&["--no-keep-going", "--keep-going"])?);
+    assert!(!run(&["--keep-going", "--no-keep-going"])?);
+
+    Ok(())
+}
+
+#[test]
+fn test_keep_going_writes_every_output() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        templates_manifest.with_file_name("settings.json"),
+        &json!({ "outputs": [{ "path": "a/launch.json" }, { "path": "b/launch.json" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Good", "extends": "cpp", "enabled": true },
+            { "name": "Typo", "extends": "cxx", "enabled": true }
+        ]),
+    )?;
+
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_mklaunch"))
+        .args(["--no-discover", "--keep-going"])
+        .current_dir(temp_dir.path())
+        .env_remove("MKLAUNCH_EXTRA_CONFIG")
+        .output()?
+        .status;
+    assert!(!status.success());
+    for output in ["a/launch.json", "b/launch.json"] {
+        let launch = fs::read_to_string(temp_dir.path().join(output))?;
+        assert!(launch.contains("Good") && !launch.contains("Typo"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_forbid_template_keys() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg", "linux": { "program": "build/app" } }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "App", "extends": "cpp", "enabled": true }]),
+    )?;
+    let settings_path = templates_manifest.parent().unwrap().join("settings.json");
+    write_json(
+        &settings_path,
+        &json!({ "forbidTemplateKeys": ["program"] }),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir);
+    let err = Generator::with_options(options.clone())
+        .generate()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Template 'cpp' in")
+            && err.to_string().contains("must not define 'linux.program'"),
+        "{}",
+        err
+    );
+
+    write_json(&settings_path, &json!({ "forbidTemplateKeys": ["type"] }))?;
+    let err = Generator::with_options(options).generate().unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("'type' is required in every template"),
+        "{:#}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_program_override_and_check() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg", "program": "${workspaceFolder}/build/app" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "App", "extends": "cpp", "enabled": true },
+            { "name": "Tool", "extends": "cpp", "enabled": true, "program": "${workspaceFolder}/build/tool" }
+        ]),
+    )?;
+
+    let options =
+        GeneratorOptions::new(templates_manifest, configs_dir).root(temp_dir.path().to_path_buf());
+    let launch = Generator::with_options(options.clone()).generate()?;
+    let programs: Vec<Option<&str>> = launch
+        .configurations()
+        .iter()
+        .map(|c| c.program())
+        .collect();
+    assert_eq!(
+        programs,
+        [
+            Some("${workspaceFolder}/build/app"),
+            Some("${workspaceFolder}/build/tool")
+        ]
+    );
+
+    let checked = options.check_programs(true);
+    fs::create_dir(temp_dir.path().join("build"))?;
+    fs::write(temp_dir.path().join("build/app"), "")?;
+    let err = Generator::with_options(checked.clone())
+        .generate()
+        .unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("program does not exist")
+            && format!("{:#}", err).contains("tool"),
+        "{:#}",
+        err
+    );
+    fs::write(temp_dir.path().join("build/tool"), "")?;
+    for options in [checked.clone(), checked.cache(true)] {
+        Generator::with_options(options).generate()?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_deep_merge_os_overrides() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{
+            "name": "cpp",
+            "type": "cppdbg",
+            "env": { "LOG": "info", "HOME": "/home/dev" },
+            "linux": {
+                "env": { "LD_LIBRARY_PATH": "lib", "LOG": "warn" },
+                "sourceFileMap": { "/build": "${workspaceFolder}" }
+            }
+        }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Merged", "extends": "cpp", "enabled": true,
+              "linux": { "env": { "LOG": "debug" } } },
+            { "name": "Replaced", "extends": "cpp", "enabled": true,
+              "linux": { "env": { "$replace": true, "LOG": "trace" } } }
+        ]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir);
+    let launch = Generator::with_options(options.clone()).generate()?;
+    let merged = &launch.configurations()[0];
+    assert_eq!(
+        merged.get("linux"),
+        Some(json!({
+            "env": { "LD_LIBRARY_PATH": "lib", "LOG": "debug" },
+            "sourceFileMap": { "/build": "${workspaceFolder}" }
+        }))
+    );
+    let replaced = &launch.configurations()[1];
+    assert_eq!(
+        replaced.get("linux").unwrap()["env"],
+        json!({ "LOG": "trace" })
+    );
+
+    // Flattening merges the OS block into top-level objects the same way
+    let launch =
+        Generator::with_options(options.flatten_os(mklaunch::TargetOs::Linux)).generate()?;
+    assert_eq!(
+        launch.configurations()[0].get("env"),
+        Some(json!({ "HOME": "/home/dev", "LD_LIBRARY_PATH": "lib", "LOG": "debug" }))
+    );
+    // `$replace` applied to the template's block; the block still merges into `env`
+    assert_eq!(
+        launch.configurations()[1].get("env"),
+        Some(json!({ "HOME": "/home/dev", "LOG": "trace" }))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_deep_merge_top_level_overrides() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{
+            "name": "cpp",
+            "type": "cppdbg",
+            "env": { "A": "1", "NESTED": { "X": "1" } },
+            "presentation": { "group": "cpp", "order": 1 },
+            "sourceFileMap": { "/build": "${workspaceFolder}" }
+        }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Merged", "extends": "cpp", "enabled": true,
+              "env": { "B": "2", "NESTED": { "Y": "2" } },
+              "presentation": { "hidden": true },
+              "order": 3 },
+            { "name": "Replaced", "extends": "cpp", "enabled": true,
+              "env": { "$replace": true, "B": "2" },
+              "sourceFileMap": { "$replace": true, "/src": "${workspaceFolder}" } }
+        ]),
+    )?;
+
+    let generator = Generator::new(templates_manifest, configs_dir);
+    let launch = generator.generate()?;
+    let merged = &launch.configurations()[0];
+    assert_eq!(
+        merged.get("env"),
+        Some(json!({ "A": "1", "B": "2", "NESTED": { "X": "1", "Y": "2" } }))
+    );
+    // `order` still wins over the merged presentation
+    assert_eq!(
+        merged.get("presentation"),
+        Some(json!({ "group": "cpp", "hidden": true, "order": 3 }))
+    );
+    let replaced = &launch.configurations()[1];
+    assert_eq!(replaced.get("env"), Some(json!({ "B": "2" })));
+    assert_eq!(
+        replaced.get("sourceFileMap"),
+        Some(json!({ "/src": "${workspaceFolder}" }))
+    );
+    let explanation = generator.explain("Merged")?;
+    assert!(explanation.provenance.keys()["env"].contains("+ config"));
+
+    Ok(())
+}
+
+#[test]
+fn test_container_path_mappings() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg", "sourceFileMap": { "/usr/src/lib": "/opt/lib" } },
+            { "name": "py", "type": "debugpy", "request": "attach" },
+            { "name": "php", "type": "php" }
+        ] }),
+    )?;
+    let container = json!({ "remoteRoot": "/app" });
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "C", "extends": "cpp", "enabled": true, "container": container },
+            { "name": "Py", "extends": "py", "enabled": true,
+              "container": { "localRoot": "${workspaceFolder}/svc", "remoteRoot": "/app" } }
+        ]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir.clone());
+    let launch = Generator::with_options(options.clone()).generate()?;
+    assert_eq!(
+        launch.configurations()[0].get("sourceFileMap"),
+        Some(json!({ "/app": "${workspaceFolder}", "/usr/src/lib": "/opt/lib" }))
+    );
+    assert_eq!(
+        launch.configurations()[1].get("pathMappings"),
+        Some(json!([{ "localRoot": "${workspaceFolder}/svc", "remoteRoot": "/app" }]))
+    );
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "Php", "extends": "php", "enabled": true, "container": container }]),
+    )?;
+    let err = Generator::with_options(options).generate().unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("container is not supported for debugger type 'php'"),
+        "{:#}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_devcontainer_environment() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path().canonicalize()?;
+    let (templates_manifest, configs_dir) = create_dirs(&root)?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg", "miDebuggerPath": "/usr/bin/gdb" },
+            { "name": "cpp.devcontainer", "type": "cppdbg", "miDebuggerPath": "/usr/local/bin/gdb" }
+        ] }),
+    )?;
+    let host_build = root.join("build").join("app");
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "App", "extends": "cpp", "enabled": true,
+                  "program": host_build, "args": [format!("--data={}", root.join("data").display())] }]),
+    )?;
+
+    let options =
+        GeneratorOptions::new(templates_manifest.clone(), configs_dir.clone()).root(root.clone());
+    let err = Generator::with_options(
+        options
+            .clone()
+            .environment(mklaunch::Environment::Devcontainer),
+    )
+    .generate()
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("No .devcontainer/devcontainer.json"),
+        "{}",
+        err
+    );
+
+    fs::create_dir(root.join(".devcontainer"))?;
+    fs::write(
+        root.join(".devcontainer/devcontainer.json"),
+        "{\n  // JSONC, as VSCode writes it\n  \"workspaceFolder\": \"/src/${localWorkspaceFolderBasename}\",\n}\n",
+    )?;
+    let basename = root.file_name().unwrap().to_string_lossy().into_owned();
+
+    let host = Generator::with_options(options.clone()).generate()?;
+    assert_eq!(
+        host.configurations()[0].get("miDebuggerPath"),
+        Some(json!("/usr/bin/gdb"))
+    );
+    assert_eq!(host.configurations()[0].program(), host_build.to_str());
+
+    let container =
+        Generator::with_options(options.environment(mklaunch::Environment::Devcontainer))
+            .generate()?;
+    let app = &container.configurations()[0];
+    assert_eq!(app.get("miDebuggerPath"), Some(json!("/usr/local/bin/gdb")));
+    assert_eq!(
+        app.program(),
+        Some(format!("/src/{}/build/app", basename).as_str())
+    );
+    assert_eq!(app.args(), [format!("--data=/src/{}/data", basename)]);
+
+    // The variant is used through the template it is named after
+    let unused = Generator::new(templates_manifest, configs_dir).lint()?;
+    assert!(unused.is_empty(), "{:?}", unused);
+
+    Ok(())
+}
+
+#[test]
+fn test_console_toggles() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg", "externalConsole": true },
+            { "name": "lldb", "type": "lldb" },
+            { "name": "py", "type": "debugpy", "console": "internalConsole" }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "C", "extends": "cpp", "enabled": true, "console": "integratedTerminal" },
+            { "name": "L", "extends": "lldb", "enabled": true, "externalConsole": true },
+            { "name": "P", "extends": "py", "enabled": true,
+              "console": "externalTerminal", "justMyCode": false }
+        ]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir.clone());
+    let launch = Generator::with_options(options.clone()).generate()?;
+    let configs = launch.configurations();
+    assert_eq!(configs[0].get("externalConsole"), Some(json!(false)));
+    assert_eq!(configs[1].get("terminal"), Some(json!("external")));
+    assert_eq!(configs[2].get("console"), Some(json!("externalTerminal")));
+    assert_eq!(configs[2].get("justMyCode"), Some(json!(false)));
+
+    for (entry, message) in [
+        (
+            json!({ "name": "C", "extends": "cpp", "enabled": true, "justMyCode": true }),
+            "justMyCode is not supported for debugger type 'cppdbg'",
+        ),
+        (
+            json!({ "name": "P", "extends": "py", "enabled": true,
+                    "console": "integratedTerminal", "externalConsole": true }),
+            "cannot set both console and externalConsole",
+        ),
+        (
+            json!({ "name": "P", "extends": "py", "enabled": true, "console": "terminal" }),
+            "unknown variant `terminal`",
+        ),
+    ] {
+        write_json(configs_dir.join("a.json"), &json!([entry]))?;
+        let err = Generator::with_options(options.clone())
+            .generate()
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains(message), "{:#}", err);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_cppdbg_typed_overrides() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg", "MIMode": "gdb", "stopAtEntry": false },
+            { "name": "msvc", "type": "cppvsdbg" },
+            { "name": "py", "type": "debugpy" }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "C", "extends": "cpp", "enabled": true, "stopAtEntry": true,
+              "MIMode": "lldb", "miDebuggerPath": "/usr/bin/lldb-mi" },
+            { "name": "W", "extends": "msvc", "enabled": true, "stopAtEntry": true }
+        ]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir.clone());
+    let launch = Generator::with_options(options.clone()).generate()?;
+    let configs = launch.configurations();
+    assert_eq!(configs[0].get("stopAtEntry"), Some(json!(true)));
+    assert_eq!(configs[0].get("MIMode"), Some(json!("lldb")));
+    assert_eq!(
+        configs[0].get("miDebuggerPath"),
+        Some(json!("/usr/bin/lldb-mi"))
+    );
+    assert_eq!(configs[1].get("stopAtEntry"), Some(json!(true)));
+
+    for (entry, message) in [
+        (
+            json!({ "name": "W", "extends": "msvc", "enabled": true, "MIMode": "gdb" }),
+            "MIMode is not supported for debugger type 'cppvsdbg'",
+        ),
+        (
+            json!({ "name": "P", "extends": "py", "enabled": true, "stopAtEntry": true }),
+            "stopAtEntry is not supported for debugger type 'debugpy'",
+        ),
+        (
+            json!({ "name": "C", "extends": "cpp", "enabled": true, "MIMode": "windbg" }),
+            "unknown variant `windbg`, expected `gdb` or `lldb`",
+        ),
+    ] {
+        write_json(configs_dir.join("a.json"), &json!([entry]))?;
+        let err = Generator::with_options(options.clone())
+            .generate()
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains(message), "{:#}", err);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_per_file_entries() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "py", "type": "debugpy", "request": "launch", "program": "${file}" },
+            { "name": "cpp", "type": "cppdbg", "program": "build/app" }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([
+            { "name": "Current file", "extends": "py", "enabled": true, "perFile": true },
+            { "name": "Current file (copy)", "extends": "py", "enabled": true, "perFile": true },
+            { "name": "Z app", "extends": "cpp", "enabled": true, "program": "app" }
+        ]),
+    )?;
+    fs::write(temp_dir.path().join("app"), "")?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir.clone())
+        .root(temp_dir.path().to_path_buf())
+        .check_programs(true)
+        .dedupe(true);
+    let launch = Generator::with_options(options.clone()).generate()?;
+    let configs = launch.configurations();
+    let names: Vec<&str> = configs.iter().map(|c| c.name()).collect();
+    assert_eq!(names, ["Z app", "Current file", "Current file (copy)"]);
+    assert!(configs[1].is_per_file() && !configs[0].is_per_file());
+    assert_eq!(
+        configs[1].get("presentation"),
+        Some(json!({ "group": "Current file" }))
+    );
+    assert!(Generator::with_options(options.clone()).lint()?.is_empty());
+    for _ in 0..2 {
+        let cached = Generator::with_options(options.clone().cache(true)).generate()?;
+        let cached: Vec<&str> = cached.configurations().iter().map(|c| c.name()).collect();
+        assert_eq!(cached, names);
+    }
+
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "App", "extends": "cpp", "enabled": true, "perFile": true }]),
+    )?;
+    let err = Generator::with_options(options).generate().unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("'App' sets perFile but uses none of ${file}"),
+        "{:#}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_name_rules() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    let settings = templates_manifest.with_file_name("settings.json");
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [
+            { "name": "cpp", "type": "cppdbg" },
+            { "name": "Cpp Release", "type": "cppdbg" }
+        ] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "Debug", "extends": "cpp", "enabled": true }]),
+    )?;
+    write_json(
+        configs_dir.join("b.json"),
+        &json!([{ "name": "debug", "extends": "cpp", "enabled": true }]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest.clone(), configs_dir.clone());
+    let err = Generator::with_options(options.clone())
+        .generate()
+        .unwrap_err();
+    let message = format!("{:#}", err);
+    assert!(
+        message.contains("'Debug' and 'debug' differ only in case"),
+        "{}",
+        message
+    );
+    // The error names the entry of each file
+    assert!(message.contains("a.json (at /0)"), "{}", message);
+
+    write_json(&settings, &json!({ "caseSensitiveNames": true }))?;
+    let launch = Generator::with_options(options.clone()).generate()?;
+    // Template names are only warned about, so existing manifests keep working
+    let warnings: Vec<&String> = launch
+        .warnings()
+        .iter()
+        .filter(|w| w.contains("Template name"))
+        .collect();
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains("'Cpp Release'"), "{}", warnings[0]);
+    assert!(
+        Generator::with_options(options.clone())
+            .validate()?
+            .iter()
+            .any(|d| d.severity == Severity::Warning
+                && d.pointer.as_deref() == Some("/templates/1/name"))
+    );
+
+    write_json(
+        &settings,
+        &json!({ "allowAnyTemplateName": true, "caseSensitiveNames": true }),
+    )?;
+    let launch = Generator::with_options(options.clone()).generate()?;
+    assert_eq!(launch.configurations().len(), 2);
+    assert!(
+        !launch
+            .warnings()
+            .iter()
+            .any(|w| w.contains("Template name"))
+    );
+
+    write_json(
+        configs_dir.join("b.json"),
+        &json!([{ "name": "  ", "extends": "cpp", "enabled": true }]),
+    )?;
+    let err = Generator::with_options(options).generate().unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("must not be empty or whitespace only"),
+        "{:#}",
+        err
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_server_ready_action_and_debug_server_checks() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "Web", "extends": "web", "enabled": true }]),
+    )?;
+    let options = GeneratorOptions::new(templates_manifest.clone(), configs_dir);
+
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{
+            "name": "web", "type": "node", "debugServer": "4711",
+            "serverReadyAction": { "pattern": "listening on (?<port>\\d+)", "uriFormat": "http://localhost:%s" }
+        }] }),
+    )?;
+    let launch = Generator::with_options(options.clone()).generate()?;
+    assert_eq!(
+        launch.configurations()[0].get("debugServer"),
+        Some(json!(4711))
+    );
+
+    for (template, message) in [
+        (
+            json!({ "serverReadyAction": { "pattern": "ready", "uriFormat": "http://localhost:%s" } }),
+            "has no capture group",
+        ),
+        (
+            json!({ "serverReadyAction": { "action": "startDebugging" } }),
+            "needs the 'name' or 'config'",
+        ),
+        (
+            json!({ "linux": { "serverReadyAction": { "patern": "x" } } }),
+            "did you mean 'pattern'?",
+        ),
+        (
+            json!({ "debugServer": 70000 }),
+            "'debugServer' must be a port number",
+        ),
+    ] {
+        let mut entry = json!({ "name": "web", "type": "node" });
+        if let (Some(entry), serde_json::Value::Object(keys)) = (entry.as_object_mut(), template) {
+            entry.extend(keys);
+        }
+        write_json(&templates_manifest, &json!({ "templates": [entry] }))?;
+        let err = Generator::with_options(options.clone())
+            .generate()
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains(message), "{:#}", err);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_dedupe_args() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        templates_manifest.with_file_name("flags.json"),
+        &json!({ "--verbose": "once", "--port": "last", "-I": "repeat" }),
+    )?;
+    let base_args = temp_dir.path().join("base.json");
+    write_json(
+        &base_args,
+        &json!({ "args": ["--verbose", "--port", "80", "-I", "inc", "-D", "A"] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "App", "extends": "cpp", "enabled": true, "baseArgs": base_args,
+                  "args": ["--verbose", "--port=8080", "-I", "inc", "-D", "A", "-D", "B", "--", "--verbose"] }]),
+    )?;
+
+    let options = GeneratorOptions::new(templates_manifest, configs_dir);
+    let launch = Generator::with_options(options.clone()).generate()?;
+    assert_eq!(
+        launch.configurations()[0].args().len(),
+        17,
+        "args are kept without --dedupe-args"
+    );
+
+    let launch =
+        Generator::with_options(options.clone().dedupe_args(Some(ArgsDedupe::Warn))).generate()?;
+    assert!(
+        launch
+            .warnings()
+            .contains(&"'App' repeats --port, --verbose, -D in its args".to_string()),
+        "{:?}",
+        launch.warnings()
+    );
+
+    let launch =
+        Generator::with_options(options.dedupe_args(Some(ArgsDedupe::Remove))).generate()?;
+    assert_eq!(
+        launch.configurations()[0].args(),
+        [
+            "--verbose",
+            "-I",
+            "inc",
+            "-D",
+            "A",
+            "--port=8080",
+            "-I",
+            "inc",
+            "-D",
+            "B",
+            "--",
+            "--verbose"
+        ]
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct RecordingRunner {
+    commands: std::sync::Mutex<Vec<CommandSpec>>,
+}
+
+impl CommandRunner for RecordingRunner {
+    fn run(&self, command: &CommandSpec) -> Result<CommandOutput> {
+        self.commands.lock().unwrap().push(command.clone());
+        Ok(CommandOutput {
+            stdout: b"--from-mock".to_vec(),
+            stderr: Vec::new(),
+        })
+    }
+}
+
+#[test]
+fn test_command_runner_and_timeouts() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (templates_manifest, configs_dir) = create_dirs(temp_dir.path())?;
+    write_json(
+        &templates_manifest,
+        &json!({ "templates": [{ "name": "cpp", "type": "cppdbg" }] }),
+    )?;
+    write_json(
+        configs_dir.join("a.json"),
+        &json!([{ "name": "A", "extends": "cpp", "enabled": true,
+                  "argsFromCommand": "sleep 5", "timeoutMs": 200 }]),
+    )?;
+    let options = GeneratorOptions::new(templates_manifest, configs_dir)
+        .allow_exec(true)
+        .command_timeout(Some(std::time::Duration::from_secs(30)));
+
+    // Library users can replace the runner; the entry's timeout wins over the default
+    let runner = std::sync::Arc::new(RecordingRunner::default());
+    let launch =
+        Generator::with_options(options.clone().command_runner(runner.clone())).generate()?;
+    assert_eq!(launch.configurations()[0].args(), ["--from-mock"]);
+    let commands = runner.commands.lock().unwrap();
+    assert_eq!(commands[0].args.last().map(String::as_str), Some("sleep 5"));
+    assert_eq!(
+        commands[0].timeout,
+        Some(std::time::Duration::from_millis(200))
+    );
+
+    let err = Generator::with_options(options.clone().command_runner(std::sync::Arc::new(NoExec)))
+        .generate()
+        .unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("external commands are disabled (--no-exec)"),
+        "{:#}",
+        err
+    );
+
+    if cfg!(unix) {
+        let started = std::time::Instant::now();
+        let err = Generator::with_options(options).generate().unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("`sh -c sleep 5` timed out after 200 ms"),
+            "{:#}",
+            err
+        );
+        assert!(started.elapsed() < std::time::Duration::from_secs(4));
+    }
+
+    Ok(())
+}